@@ -0,0 +1,144 @@
+//! Talks to adventofcode.com as a logged in user, identified by the session cookie from their browser in
+//! [`SESSION_ENV`], e.g. to download each day's input in to its directory when it isn't there yet and to submit
+//! answers.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+
+pub mod submissions;
+
+pub use submissions::{Submissions, Verdict};
+
+type AError = anyhow::Error;
+
+/// Environment variable holding the value of a logged in user's `session` cookie
+pub const SESSION_ENV: &str = "ADVENT_OF_CODE_SESSION";
+pub const YEAR: u16 = 2023;
+/// What each day's input is saved as in its directory
+pub const INPUT_FILE: &str = "input.txt";
+
+const BASE_URL: &str = "https://adventofcode.com";
+/// As asked for by the site, so that automated requests can be traced back to their source
+const USER_AGENT: &str = "github.com/georgenicoll/advent-of-code-2023 aoc-client";
+
+pub struct AocClient {
+    session: String,
+    agent: ureq::Agent,
+}
+
+impl AocClient {
+    pub fn new(session: &str) -> Self {
+        AocClient {
+            session: session.trim().to_string(),
+            agent: ureq::AgentBuilder::new().user_agent(USER_AGENT).build(),
+        }
+    }
+
+    /// Client for the session in [`SESSION_ENV`]
+    pub fn from_env() -> Result<Self, AError> {
+        match std::env::var(SESSION_ENV) {
+            Ok(session) if !session.trim().is_empty() => Ok(AocClient::new(&session)),
+            _ => Err(AError::msg(format!(
+                "Set {SESSION_ENV} to the session cookie of a user logged in to adventofcode.com"
+            ))),
+        }
+    }
+
+    fn cookie(&self) -> String {
+        format!("session={}", self.session)
+    }
+
+    pub fn download_input(&self, day: u8) -> Result<String, AError> {
+        let url = input_url(day)?;
+        let response = self.agent.get(&url).set("Cookie", &self.cookie()).call();
+        read_response(&url, response)
+    }
+
+    /// Submit the answer to the part, giving what the site made of it
+    pub fn submit_answer(&self, day: u8, part: u8, answer: &str) -> Result<Verdict, AError> {
+        if !(1..=2).contains(&part) {
+            return Err(AError::msg(format!("No part {part}, only 1 or 2")));
+        }
+        let url = answer_url(day)?;
+        let response = self
+            .agent
+            .post(&url)
+            .set("Cookie", &self.cookie())
+            .send_form(&[("level", &part.to_string()), ("answer", answer)]);
+        Ok(submissions::parse_verdict(&read_response(&url, response)?))
+    }
+}
+
+fn read_response(
+    url: &str,
+    response: Result<ureq::Response, ureq::Error>,
+) -> Result<String, AError> {
+    match response {
+        Ok(response) => Ok(response.into_string()?),
+        Err(ureq::Error::Status(status, response)) => Err(AError::msg(format!(
+            "Requesting {url} failed with {status}: {}",
+            response.into_string().unwrap_or_default().trim()
+        ))),
+        Err(e) => Err(AError::new(e)),
+    }
+}
+
+fn day_url(day: u8) -> Result<String, AError> {
+    if !(1..=25).contains(&day) {
+        return Err(AError::msg(format!("No day {day}, only 1 to 25")));
+    }
+    Ok(format!("{BASE_URL}/{YEAR}/day/{day}"))
+}
+
+pub fn input_url(day: u8) -> Result<String, AError> {
+    Ok(format!("{}/input", day_url(day)?))
+}
+
+pub fn answer_url(day: u8) -> Result<String, AError> {
+    Ok(format!("{}/answer", day_url(day)?))
+}
+
+/// Whether the input has been saved yet, an empty file being a placeholder for it
+fn has_input(path: &Path) -> bool {
+    path.metadata().is_ok_and(|metadata| metadata.len() > 0)
+}
+
+/// The day's input file in its directory, downloaded with the client from [`AocClient::from_env`] first if
+/// it isn't there yet
+pub fn ensure_input(day_dir: &Path, day: u8) -> Result<PathBuf, AError> {
+    input_url(day)?;
+    let path = day_dir.join(INPUT_FILE);
+    if !has_input(&path) {
+        let input = AocClient::from_env()?.download_input(day)?;
+        std::fs::write(&path, input)
+            .with_context(|| format!("Failed writing {}", path.display()))?;
+    }
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inputs_found() {
+        assert_eq!(
+            input_url(17).unwrap(),
+            "https://adventofcode.com/2023/day/17/input"
+        );
+        assert!(input_url(0).is_err() && input_url(26).is_err());
+
+        let dir = std::env::temp_dir().join(format!("aoc-client-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(INPUT_FILE);
+        std::fs::write(&path, "").unwrap();
+        assert!(!has_input(&path));
+        std::fs::write(&path, "1abc2\n").unwrap();
+        assert!(has_input(&path));
+        // already there so nothing to download
+        assert_eq!(ensure_input(&dir, 1).unwrap(), path);
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(!has_input(&path));
+    }
+}
@@ -0,0 +1,55 @@
+use std::path::{Path, PathBuf};
+
+use aoc_client::{ensure_input, SESSION_ENV};
+
+type AError = anyhow::Error;
+
+/// The workspace holding the dayN directories
+fn workspace_dir() -> PathBuf {
+    let client_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    client_dir.parent().unwrap_or(client_dir).to_path_buf()
+}
+
+/// Days given as `--day N`
+fn parse_days(args: &[String]) -> Result<Vec<u8>, AError> {
+    args.windows(2)
+        .filter(|pair| pair[0] == "--day")
+        .map(|pair| {
+            pair[1]
+                .parse::<u8>()
+                .map_err(|_| AError::msg(format!("Invalid day: '{}'", pair[1])))
+        })
+        .collect()
+}
+
+fn usage() {
+    println!("Usage: aoc-client --day N...");
+    println!(
+        "Downloads the days' inputs that aren't in their directories yet as {SESSION_ENV}'s user"
+    );
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let days = match parse_days(&args) {
+        Ok(days) if !days.is_empty() => days,
+        Ok(_) => return usage(),
+        Err(e) => {
+            println!("Error: {e}");
+            std::process::exit(1);
+        }
+    };
+    let mut failed = false;
+    for day in days {
+        match ensure_input(&workspace_dir().join(format!("day{day}")), day) {
+            Ok(path) => println!("Day {day}: {}", path.display()),
+            Err(e) => {
+                println!("Day {day}: {e}");
+                failed = true;
+            }
+        }
+    }
+    if failed {
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,286 @@
+use std::{
+    fmt::Display,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+
+use crate::AError;
+
+/// What the site said about a submitted answer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    Right,
+    TooHigh,
+    TooLow,
+    Wrong,
+    /// Submitted too soon after the last wrong answer, with how long is left to wait
+    Wait(String),
+    /// The part has already been solved or isn't open yet
+    WrongLevel,
+    /// Anything else, as the text of the response
+    Unknown(String),
+}
+
+impl Verdict {
+    /// Whether the verdict is about the answer itself, so will be the same if it's submitted again
+    pub fn is_final(&self) -> bool {
+        matches!(
+            self,
+            Verdict::Right | Verdict::TooHigh | Verdict::TooLow | Verdict::Wrong
+        )
+    }
+
+    fn recorded(&self) -> Option<&'static str> {
+        match self {
+            Verdict::Right => Some("right"),
+            Verdict::TooHigh => Some("too high"),
+            Verdict::TooLow => Some("too low"),
+            Verdict::Wrong => Some("wrong"),
+            _ => None,
+        }
+    }
+
+    fn from_recorded(recorded: &str) -> Option<Verdict> {
+        match recorded {
+            "right" => Some(Verdict::Right),
+            "too high" => Some(Verdict::TooHigh),
+            "too low" => Some(Verdict::TooLow),
+            "wrong" => Some(Verdict::Wrong),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Verdict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Verdict::Right => write!(f, "right"),
+            Verdict::TooHigh => write!(f, "wrong, too high"),
+            Verdict::TooLow => write!(f, "wrong, too low"),
+            Verdict::Wrong => write!(f, "wrong"),
+            Verdict::Wait(left) => write!(f, "submitted too soon, {left} left to wait"),
+            Verdict::WrongLevel => write!(f, "for a part already solved or not open yet"),
+            Verdict::Unknown(text) => write!(f, "not understood: {text}"),
+        }
+    }
+}
+
+/// The text of the response page's article, which is where the site gives its verdict
+fn article_text(html: &str) -> String {
+    let article = html
+        .find("<article")
+        .and_then(|start| {
+            let end = html[start..].find("</article>")?;
+            Some(&html[start..start + end])
+        })
+        .unwrap_or(html);
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in article.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+pub fn parse_verdict(html: &str) -> Verdict {
+    let text = article_text(html);
+    if text.contains("That's the right answer") {
+        Verdict::Right
+    } else if text.contains("That's not the right answer") {
+        if text.contains("too high") {
+            Verdict::TooHigh
+        } else if text.contains("too low") {
+            Verdict::TooLow
+        } else {
+            Verdict::Wrong
+        }
+    } else if text.contains("You gave an answer too recently") {
+        let left = text
+            .split_once("You have ")
+            .and_then(|(_, rest)| rest.split_once(" left to wait"))
+            .map_or("some time", |(left, _)| left);
+        Verdict::Wait(left.to_string())
+    } else if text.contains("You don't seem to be solving the right level") {
+        Verdict::WrongLevel
+    } else {
+        Verdict::Unknown(text)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Submission {
+    pub day: u8,
+    pub part: u8,
+    pub answer: String,
+    pub verdict: Verdict,
+}
+
+/// Answers already submitted along with their (final) verdicts, so that none is submitted twice.  Stored as
+/// tab separated lines: `day  part  answer  verdict`
+#[derive(Debug, Default)]
+pub struct Submissions {
+    path: PathBuf,
+    submissions: Vec<Submission>,
+}
+
+impl Submissions {
+    /// Load the submissions from the path, starting afresh if it doesn't exist yet
+    pub fn load(path: &Path) -> Result<Self, AError> {
+        let mut submissions = Submissions {
+            path: path.to_path_buf(),
+            submissions: vec![],
+        };
+        if !path.exists() {
+            return Ok(submissions);
+        }
+        let contents = fs::read_to_string(path)?;
+        for line in contents.lines().filter(|line| !line.is_empty()) {
+            let fields: Vec<&str> = line.split('\t').collect();
+            let [day, part, answer, verdict] = fields[..] else {
+                return Err(AError::msg(format!("Malformed submission line: '{line}'")));
+            };
+            submissions.submissions.push(Submission {
+                day: day.parse()?,
+                part: part.parse()?,
+                answer: answer.to_string(),
+                verdict: Verdict::from_recorded(verdict).ok_or_else(|| {
+                    AError::msg(format!("Unrecognised verdict in submission: '{line}'"))
+                })?,
+            });
+        }
+        Ok(submissions)
+    }
+
+    pub fn save(&self) -> Result<(), AError> {
+        let contents: String = self
+            .submissions
+            .iter()
+            .filter_map(|submission| {
+                let verdict = submission.verdict.recorded()?;
+                Some(format!(
+                    "{}\t{}\t{}\t{verdict}\n",
+                    submission.day, submission.part, submission.answer
+                ))
+            })
+            .collect();
+        fs::write(&self.path, contents)
+            .with_context(|| format!("Failed writing submissions {}", self.path.display()))
+    }
+
+    /// Record the verdict, if it's final, i.e. the answer would get it again
+    pub fn record(&mut self, day: u8, part: u8, answer: &str, verdict: &Verdict) {
+        if verdict.is_final() {
+            self.submissions.push(Submission {
+                day,
+                part,
+                answer: answer.to_string(),
+                verdict: verdict.clone(),
+            });
+        }
+    }
+
+    /// The verdict the answer is already known to get from what's been submitted: the same as when it was
+    /// submitted before, wrong if another answer was right, or too high or low if it's past an answer that was
+    pub fn known_verdict(&self, day: u8, part: u8, answer: &str) -> Option<Verdict> {
+        let submitted: Vec<&Submission> = self
+            .submissions
+            .iter()
+            .filter(|submission| submission.day == day && submission.part == part)
+            .collect();
+        if let Some(submission) = submitted.iter().find(|s| s.answer == answer) {
+            return Some(submission.verdict.clone());
+        }
+        if submitted.iter().any(|s| s.verdict == Verdict::Right) {
+            return Some(Verdict::Wrong);
+        }
+        let value = answer.parse::<i128>().ok()?;
+        submitted.iter().find_map(|s| {
+            let submitted_value = s.answer.parse::<i128>().ok()?;
+            match s.verdict {
+                Verdict::TooHigh if value >= submitted_value => Some(Verdict::TooHigh),
+                Verdict::TooLow if value <= submitted_value => Some(Verdict::TooLow),
+                _ => None,
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verdicts_parsed() {
+        let page =
+            |text: &str| format!("<html><main><article><p>{text}</p></article></main></html>");
+        assert_eq!(
+            parse_verdict(&page(
+                "That's the right answer! You are <em>one gold star</em> closer."
+            )),
+            Verdict::Right
+        );
+        assert_eq!(
+            parse_verdict(&page(
+                "That's not the right answer; your answer is too high.  If you're stuck..."
+            )),
+            Verdict::TooHigh
+        );
+        assert_eq!(
+            parse_verdict(&page("That's not the right answer.  If you're stuck...")),
+            Verdict::Wrong
+        );
+        assert_eq!(
+            parse_verdict(&page(
+                "You gave an answer too recently; you have to wait after submitting an answer before \
+                 trying again.  You have 37s left to wait."
+            )),
+            Verdict::Wait("37s".to_string())
+        );
+        assert_eq!(
+            parse_verdict(&page(
+                "You don't seem to be solving the right level.  Did you already complete it?"
+            )),
+            Verdict::WrongLevel
+        );
+        assert_eq!(
+            parse_verdict(&page("Something <b>new</b>")),
+            Verdict::Unknown("Something new".to_string())
+        );
+    }
+
+    #[test]
+    fn guesses_not_repeated() {
+        let dir = std::env::temp_dir().join(format!("aoc-submissions-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("submissions.txt");
+        let mut submissions = Submissions::load(&path).unwrap();
+        submissions.record(1, 1, "100", &Verdict::TooHigh);
+        submissions.record(1, 1, "20", &Verdict::TooLow);
+        submissions.record(1, 1, "50", &Verdict::Wait("1m".to_string()));
+        submissions.record(1, 2, "abc", &Verdict::Right);
+        submissions.save().unwrap();
+
+        let submissions = Submissions::load(&path).unwrap();
+        assert_eq!(
+            submissions.known_verdict(1, 1, "100"),
+            Some(Verdict::TooHigh)
+        );
+        assert_eq!(
+            submissions.known_verdict(1, 1, "150"),
+            Some(Verdict::TooHigh)
+        );
+        assert_eq!(submissions.known_verdict(1, 1, "3"), Some(Verdict::TooLow));
+        assert_eq!(submissions.known_verdict(1, 1, "50"), None);
+        assert_eq!(submissions.known_verdict(2, 1, "100"), None);
+        assert_eq!(submissions.known_verdict(1, 2, "abc"), Some(Verdict::Right));
+        assert_eq!(submissions.known_verdict(1, 2, "abd"), Some(Verdict::Wrong));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
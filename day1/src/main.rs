@@ -1,44 +1,52 @@
 use once_cell::sync::Lazy;
-use processor::{ok_identity, process, reverse};
+use processor::{reverse, run_day, Answer, Day, PuzzleMetadata, Solver};
 use regex::Regex;
 
 type AError = anyhow::Error;
-type State = Vec<i64>;
-type FinalState = i64;
 
-fn main() {
-    //let file = "day1/test-input.txt";
-    //let file = "day1/test-input2.txt";
-    let file = "input.txt";
-
-    let result1 = process(
-        file,
-        Vec::new(),
-        parse_line_1,
-        ok_identity,
-        perform_processing,
-        ok_identity,
-    );
-    match result1 {
-        Ok(res) => println!("Result 1: {}", res),
-        Err(e) => println!("Error on 1: {}", e),
+struct Day1;
+
+impl Solver for Day1 {
+    fn metadata() -> PuzzleMetadata {
+        PuzzleMetadata::new(1, "Trebuchet?!")
+    }
+}
+
+impl Day for Day1 {
+    /// The lines, as each part reads the digits from them differently
+    type Parsed = Vec<String>;
+
+    fn parse(lines: impl Iterator<Item = String>) -> Result<Self::Parsed, AError> {
+        Ok(lines.collect())
+    }
+
+    fn part1(lines: &Self::Parsed) -> Result<Answer, AError> {
+        sum_calibrations(lines, calibration_1)
     }
 
-    let result2 = process(
-        file,
-        Vec::new(),
-        parse_line_2,
-        ok_identity,
-        perform_processing,
-        ok_identity,
-    );
-    match result2 {
-        Ok(res) => println!("Result 2: {}", res),
-        Err(e) => println!("Error on 2: {}", e),
+    fn part2(lines: &Self::Parsed) -> Result<Answer, AError> {
+        sum_calibrations(lines, calibration_2)
     }
 }
 
-fn parse_line_1(mut state: State, line: String) -> Result<State, AError> {
+fn main() {
+    //run_day::<Day1>("test-input.txt");
+    //run_day::<Day1>("test-input2.txt");
+    run_day::<Day1>("input.txt");
+}
+
+fn sum_calibrations(
+    lines: &[String],
+    calibration: fn(&str) -> Result<i64, AError>,
+) -> Result<Answer, AError> {
+    let calibrations = lines
+        .iter()
+        .map(|line| calibration(line))
+        .collect::<Result<Vec<i64>, AError>>()?;
+    Ok(calibrations.iter().sum::<i64>().into())
+}
+
+fn calibration_1(line: &str) -> Result<i64, AError> {
     let mut first: Option<i64> = None;
     let mut second: Option<i64> = None;
     for c in line.chars() {
@@ -55,8 +63,7 @@ fn parse_line_1(mut state: State, line: String) -> Result<State, AError> {
             "Didn't get the 2 numbers on line: {}",
             line
         )))?;
-    state.push(10 * a + b);
-    Ok(state)
+    Ok(10 * a + b)
 }
 
 static RE_FORWARDS: Lazy<Regex> =
@@ -64,7 +71,7 @@ static RE_FORWARDS: Lazy<Regex> =
 static RE_BACKWARDS: Lazy<Regex> =
     Lazy::new(|| Regex::new(r"([1-9]|eno|owt|eerht|ruof|evif|xis|neves|thgie|enin)").unwrap());
 
-fn parse_line_2(mut state: State, line: String) -> Result<State, AError> {
+fn calibration_2(line: &str) -> Result<i64, AError> {
     let mut first: Option<i64> = None;
     let mut second: Option<i64> = None;
 
@@ -83,11 +90,11 @@ fn parse_line_2(mut state: State, line: String) -> Result<State, AError> {
         }
     }
 
-    if let Some(m) = RE_FORWARDS.find(line.as_str()) {
+    if let Some(m) = RE_FORWARDS.find(line) {
         first = get_num(m.as_str())
     }
 
-    let backwards_line: String = reverse(&line);
+    let backwards_line: String = reverse(line);
     if let Some(m) = RE_BACKWARDS.find(backwards_line.as_str()) {
         second = get_num(reverse(m.as_str()).as_str());
     }
@@ -98,11 +105,17 @@ fn parse_line_2(mut state: State, line: String) -> Result<State, AError> {
             "Didn't get the 2 numbers on line: {}",
             line
         )))?;
-    state.push(10 * a + b);
-    Ok(state)
+    Ok(10 * a + b)
 }
 
-fn perform_processing(state: State) -> Result<FinalState, AError> {
-    //println!("State: {:?}", state);
-    Ok(state.iter().sum())
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use processor::assert_day;
+
+    #[test]
+    fn example_calibrations() {
+        assert_day!(Day1, part1, "test-input.txt", 142);
+        assert_day!(Day1, part2, "test-input2.txt", 354);
+    }
 }
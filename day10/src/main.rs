@@ -4,7 +4,10 @@ use std::{
 };
 
 use once_cell::sync::Lazy;
-use processor::{process, Cells, CellsBuilder};
+use processor::{
+    cell_chars, describe_if_requested, part_requested, process, CellChar, Cells, CellsBuilder,
+    Direction, PuzzleMetadata, Solver,
+};
 use strum_macros::EnumIter;
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -19,21 +22,16 @@ enum Pipe {
     Start,
 }
 
-impl Display for Pipe {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let c = match self {
-            Pipe::Vertical => '|',
-            Pipe::Horizontal => '-',
-            Pipe::NorthToEast => 'L',
-            Pipe::NorthToWest => 'J',
-            Pipe::SouthToWest => '7',
-            Pipe::SouthToEast => 'F',
-            Pipe::Ground => '.',
-            Pipe::Start => 'S',
-        };
-        write!(f, "{c}")
-    }
-}
+cell_chars!(Pipe {
+    '|' => Vertical,
+    '-' => Horizontal,
+    'L' => NorthToEast,
+    'J' => NorthToWest,
+    '7' => SouthToWest,
+    'F' => SouthToEast,
+    '.' | 'O' | 'I' => Ground,
+    'S' => Start,
+});
 
 type Coord = (usize, usize);
 
@@ -64,14 +62,6 @@ impl Display for State {
     }
 }
 
-#[derive(Debug, Clone)]
-enum Direction {
-    North,
-    East,
-    South,
-    West,
-}
-
 #[derive(Debug, Clone, EnumIter, PartialEq, Eq, Hash, PartialOrd, Ord)]
 enum PipeRunDirection {
     North,
@@ -97,33 +87,11 @@ type LoadedState = State;
 type ProcessedState = usize;
 type FinalResult = usize;
 
-fn add_next_pipe(c: char, pipes: &mut CellsBuilder<Pipe>) -> Result<bool, AError> {
-    let (is_start, pipe) = match c {
-        '|' => (false, Pipe::Vertical),
-        '-' => (false, Pipe::Horizontal),
-        'L' => (false, Pipe::NorthToEast),
-        'J' => (false, Pipe::NorthToWest),
-        '7' => (false, Pipe::SouthToWest),
-        'F' => (false, Pipe::SouthToEast),
-        '.' | 'O' | 'I' => (false, Pipe::Ground),
-        'S' => (true, Pipe::Start),
-        _ => panic!("Unrecognised pipe: {}", c),
-    };
-    pipes.add_cell(pipe)?;
-    Ok(is_start)
-}
-
 fn parse_line(mut state: InitialState, line: String) -> Result<InitialState, AError> {
-    state.pipes.new_line();
-    state.start = line.chars().fold(state.start, |start, c| {
-        let was_start = add_next_pipe(c, &mut state.pipes).ok()?;
-        if was_start {
-            let coords = state.pipes.current_cell().unwrap();
-            Some(coords)
-        } else {
-            start
-        }
-    });
+    state.pipes.add_line(&line)?;
+    if let Some(x) = line.find(Pipe::Start.to_char()) {
+        state.start = state.pipes.current_cell().map(|(_, y)| (x, y));
+    }
     Ok(state)
 }
 
@@ -156,26 +124,29 @@ fn finalise_state(mut state: InitialState) -> Result<LoadedState, AError> {
 }
 
 fn get_next_x_y_and_direction(
+    pipes: &Cells<Pipe>,
     x: usize,
     y: usize,
     pipe: &Pipe,
     direction: &Direction,
-) -> (usize, usize, Direction) {
-    match (pipe, direction) {
-        (Pipe::Vertical, Direction::North) => (x, y - 1, Direction::North),
-        (Pipe::Vertical, Direction::South) => (x, y + 1, Direction::South),
-        (Pipe::Horizontal, Direction::East) => (x + 1, y, Direction::East),
-        (Pipe::Horizontal, Direction::West) => (x - 1, y, Direction::West),
-        (Pipe::NorthToEast, Direction::South) => (x + 1, y, Direction::East),
-        (Pipe::NorthToEast, Direction::West) => (x, y - 1, Direction::North),
-        (Pipe::NorthToWest, Direction::South) => (x - 1, y, Direction::West),
-        (Pipe::NorthToWest, Direction::East) => (x, y - 1, Direction::North),
-        (Pipe::SouthToWest, Direction::North) => (x - 1, y, Direction::West),
-        (Pipe::SouthToWest, Direction::East) => (x, y + 1, Direction::South),
-        (Pipe::SouthToEast, Direction::North) => (x + 1, y, Direction::East),
-        (Pipe::SouthToEast, Direction::West) => (x, y + 1, Direction::South),
+) -> Result<(usize, usize, Direction), AError> {
+    let next_direction = match (pipe, direction) {
+        (Pipe::Vertical, Direction::North | Direction::South)
+        | (Pipe::Horizontal, Direction::East | Direction::West) => *direction,
+        (Pipe::NorthToEast, Direction::South)
+        | (Pipe::NorthToWest, Direction::East)
+        | (Pipe::SouthToWest, Direction::North)
+        | (Pipe::SouthToEast, Direction::West) => direction.turn_left(),
+        (Pipe::NorthToEast, Direction::West)
+        | (Pipe::NorthToWest, Direction::South)
+        | (Pipe::SouthToWest, Direction::East)
+        | (Pipe::SouthToEast, Direction::North) => direction.turn_right(),
         _ => panic!("Unrecognised pipe/direction: {}, {:?}", pipe, direction),
-    }
+    };
+    let (next_x, next_y) = pipes
+        .step((x, y), next_direction)
+        .ok_or_else(|| AError::msg(format!("Pipe at ({x}, {y}) leads off the grid")))?;
+    Ok((next_x, next_y, next_direction))
 }
 
 fn get_loop_tiles(state: &LoadedState) -> Result<HashSet<(usize, usize)>, AError> {
@@ -183,18 +154,22 @@ fn get_loop_tiles(state: &LoadedState) -> Result<HashSet<(usize, usize)>, AError
     let (start_x, start_y) = state.start;
     let pipe = state.pipes.get(start_x, start_y)?;
     let (n, e, s, w) = get_exits(pipe);
-    let (mut x, mut y, mut direction) = match (n, e, s, w) {
-        (true, _, _, _) => (start_x, start_y - 1, Direction::North),
-        (_, _, true, _) => (start_x, start_y + 1, Direction::South),
-        (_, true, _, _) => (start_x + 1, start_y, Direction::East),
-        (_, _, _, true) => (start_x - 1, start_y, Direction::West),
+    let mut direction = match (n, e, s, w) {
+        (true, _, _, _) => Direction::North,
+        (_, _, true, _) => Direction::South,
+        (_, true, _, _) => Direction::East,
+        (_, _, _, true) => Direction::West,
         _ => panic!("Can't get current direction"),
     };
+    let (mut x, mut y) = state
+        .pipes
+        .step(state.start, direction)
+        .ok_or_else(|| AError::msg("Start leads off the grid"))?;
     let mut loop_tiles = HashSet::from([(start_x, start_y)]);
     while !loop_tiles.contains(&(x, y)) {
         loop_tiles.insert((x, y));
         let pipe = state.pipes.get(x, y)?;
-        (x, y, direction) = get_next_x_y_and_direction(x, y, pipe, &direction);
+        (x, y, direction) = get_next_x_y_and_direction(&state.pipes, x, y, pipe, &direction)?;
     }
     Ok(loop_tiles)
 }
@@ -223,7 +198,7 @@ fn get_pipe_at<'a>(
     }
     let adjusted = (adjusted_x, adjusted_y);
     if loop_tiles.contains(&adjusted) {
-        Some((pipe_cells.get(adjusted_x, adjusted_y).unwrap(), adjusted))
+        Some((&pipe_cells[(adjusted_x, adjusted_y)], adjusted))
     } else {
         //not a loop tile, treat it as though it is a ground tile - we should search as though it it
         Some((&Pipe::Ground, adjusted))
@@ -436,44 +411,67 @@ fn calc_result(state: ProcessedState) -> Result<FinalResult, AError> {
     Ok(state)
 }
 
+struct Day10;
+
+impl Solver for Day10 {
+    fn metadata() -> PuzzleMetadata {
+        PuzzleMetadata::new(10, "Pipe Maze").with_parameter(
+            "start_pipe",
+            "The pipe under the start tile, as it's drawn in the input",
+            'F',
+        )
+    }
+}
+
 fn main() {
-    //let input = (Pipe::SouthToEast, "test-input.txt");
-    //let input = (Pipe::SouthToEast, "test-input2.txt");
-    //let input = (Pipe::SouthToEast, "test-input3.txt");
-    //let input = (Pipe::SouthToWest, "test-input4.txt");
-    let input = (Pipe::Vertical, "input.txt");
-
-    let result1 = process(
-        input.1,
-        LoadingState {
-            start: None,
-            start_pipe: input.0.clone(),
-            pipes: CellsBuilder::new_empty(),
-        },
-        parse_line,
-        finalise_state,
-        perform_processing_1,
-        calc_result,
-    );
-    match result1 {
-        Ok(res) => println!("Result 1: {:?}", res),
-        Err(e) => println!("Error on 1: {}", e),
+    describe_if_requested::<Day10>();
+    //let config = PuzzleConfig::new("test-input4.txt").with("start_pipe", '7');
+    let config = Day10::metadata()
+        .load_config("input.txt")
+        .expect("Invalid configuration");
+    let start_pipe = config
+        .get::<char>("start_pipe")
+        .ok()
+        .and_then(Pipe::from_char)
+        .filter(|pipe| !matches!(pipe, Pipe::Ground | Pipe::Start))
+        .expect("Invalid start_pipe");
+    let input = (start_pipe, config.file);
+
+    if part_requested(1) {
+        let result1 = process(
+            &input.1,
+            LoadingState {
+                start: None,
+                start_pipe: input.0.clone(),
+                pipes: CellsBuilder::new_empty(),
+            },
+            parse_line,
+            finalise_state,
+            perform_processing_1,
+            calc_result,
+        );
+        match result1 {
+            Ok(res) => println!("Result 1: {:?}", res),
+            Err(e) => println!("Error on 1: {}", e),
+        }
     }
 
-    let result2 = process(
-        input.1,
-        LoadingState {
-            start: None,
-            start_pipe: input.0,
-            pipes: CellsBuilder::new_empty(),
-        },
-        parse_line,
-        finalise_state,
-        perform_processing_2,
-        calc_result,
-    );
-    match result2 {
-        Ok(res) => println!("Result 2: {:?}", res),
-        Err(e) => println!("Error on 2: {}", e),
+    if part_requested(2) {
+        let result2 = process(
+            &input.1,
+            LoadingState {
+                start: None,
+                start_pipe: input.0,
+                pipes: CellsBuilder::new_empty(),
+            },
+            parse_line,
+            finalise_state,
+            perform_processing_2,
+            calc_result,
+        );
+        match result2 {
+            Ok(res) => println!("Result 2: {:?}", res),
+            Err(e) => println!("Error on 2: {}", e),
+        }
     }
 }
@@ -1,94 +1,69 @@
-use std::collections::HashSet;
-
-use processor::process;
+use processor::{
+    cell_chars, describe_if_requested, input_file, part_requested, process, Cells, CellsBuilder,
+    PuzzleMetadata, Solver,
+};
 
 type Int = u64;
 type Coord = (Int, Int);
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Space {
+    Empty,
+    Galaxy,
+}
+
+cell_chars!(Space {
+    '.' => Empty,
+    '#' => Galaxy,
+});
+
 #[derive(Debug)]
 struct Galaxy {
-    id: Int,
     coord: Coord,
 }
 
-#[derive(Debug, Default)]
-struct LoadingState {
-    unexpanded_galaxies: Vec<Galaxy>,
-    rows_with_galaxies: HashSet<Int>,
-    columns_with_galaxies: HashSet<Int>,
-    current_row: Int,
-    current_galaxy: Int,
-}
-
 struct LoadedState {
     galaxies: Vec<Galaxy>,
 }
 
 type AError = anyhow::Error;
-type InitialState = LoadingState;
+type InitialState = CellsBuilder<Space>;
 type ProcessedState = Vec<Int>;
 type FinalResult = Int;
 
 fn parse_line(mut state: InitialState, line: String) -> Result<InitialState, AError> {
-    for (index, char) in line.chars().enumerate() {
-        if char == '#' {
-            let coord = (index as Int, state.current_row as Int);
-            let galaxy = Galaxy {
-                id: state.current_galaxy,
-                coord,
-            };
-            state.unexpanded_galaxies.push(galaxy);
-            state.rows_with_galaxies.insert(state.current_row);
-            state.columns_with_galaxies.insert(index as Int);
-            state.current_galaxy += 1
-        }
+    if !line.is_empty() {
+        state.add_line(&line)?;
     }
-    state.current_row += 1;
     Ok(state)
 }
 
-fn expand_galaxy(galaxy: &Galaxy, state: &LoadingState, increment: u64) -> Galaxy {
-    let mut increment_x = 0u64;
-    for i in 0..galaxy.coord.0 {
-        if !state.columns_with_galaxies.contains(&i) {
-            increment_x += increment
-        }
-    }
-    let mut increment_y = 0u64;
-    for i in 0..galaxy.coord.1 {
-        if !state.rows_with_galaxies.contains(&i) {
-            increment_y += increment
-        }
-    }
-    let new_coord = (galaxy.coord.0 + increment_x, galaxy.coord.1 + increment_y);
-    Galaxy {
-        id: galaxy.id,
-        coord: new_coord,
-    }
-}
-
-fn expand_universe(state: &LoadingState, increment: u64) -> Vec<Galaxy> {
-    state
-        .unexpanded_galaxies
+/// The galaxies where they end up once each empty row and column has become factor of them
+fn expand_universe(cells: &Cells<Space>, factor: usize) -> Vec<Galaxy> {
+    let expansion = cells.expand_where(|space| *space == Space::Empty, factor);
+    cells
         .iter()
-        .fold(Vec::default(), |mut acc, galaxy| {
-            let expanded_galaxy = expand_galaxy(galaxy, state, increment);
-            acc.push(expanded_galaxy);
-            acc
+        .filter(|(_, space)| **space == Space::Galaxy)
+        .map(|(coord, _)| {
+            let (x, y) = expansion.map(coord).unwrap();
+            Galaxy {
+                coord: (x as Int, y as Int),
+            }
         })
+        .collect()
 }
 
-fn finalise_state_1(state: InitialState) -> Result<LoadedState, AError> {
-    let expanded_galaxies = expand_universe(&state, 1);
+fn finalise_state_1(mut state: InitialState) -> Result<LoadedState, AError> {
+    let cells = state.build_cells(Space::Empty)?;
     Ok(LoadedState {
-        galaxies: expanded_galaxies,
+        galaxies: expand_universe(&cells, 2),
     })
 }
 
-fn finalise_state_2(state: InitialState) -> Result<LoadedState, AError> {
-    let expanded_galaxies = expand_universe(&state, 1000000 - 1);
+fn finalise_state_2(mut state: InitialState) -> Result<LoadedState, AError> {
+    let cells = state.build_cells(Space::Empty)?;
     Ok(LoadedState {
-        galaxies: expanded_galaxies,
+        galaxies: expand_universe(&cells, 1000000),
     })
 }
 
@@ -111,34 +86,47 @@ fn calc_result(state: ProcessedState) -> Result<FinalResult, AError> {
     Ok(state.iter().sum())
 }
 
+struct Day11;
+
+impl Solver for Day11 {
+    fn metadata() -> PuzzleMetadata {
+        PuzzleMetadata::new(11, "Cosmic Expansion")
+    }
+}
+
 fn main() {
+    describe_if_requested::<Day11>();
     //let file = "test-input.txt";
     //let file = "test-input2.txt";
-    let file = "input.txt";
-
-    let result1 = process(
-        file,
-        LoadingState::default(),
-        parse_line,
-        finalise_state_1,
-        perform_processing,
-        calc_result,
-    );
-    match result1 {
-        Ok(res) => println!("Result 1: {:?}", res),
-        Err(e) => println!("Error on 1: {}", e),
+    let file = &input_file("input.txt");
+
+    if part_requested(1) {
+        let result1 = process(
+            file,
+            CellsBuilder::new_empty(),
+            parse_line,
+            finalise_state_1,
+            perform_processing,
+            calc_result,
+        );
+        match result1 {
+            Ok(res) => println!("Result 1: {:?}", res),
+            Err(e) => println!("Error on 1: {}", e),
+        }
     }
 
-    let result2 = process(
-        file,
-        LoadingState::default(),
-        parse_line,
-        finalise_state_2,
-        perform_processing,
-        calc_result,
-    );
-    match result2 {
-        Ok(res) => println!("Result 2: {:?}", res),
-        Err(e) => println!("Error on 2: {}", e),
+    if part_requested(2) {
+        let result2 = process(
+            file,
+            CellsBuilder::new_empty(),
+            parse_line,
+            finalise_state_2,
+            perform_processing,
+            calc_result,
+        );
+        match result2 {
+            Ok(res) => println!("Result 2: {:?}", res),
+            Err(e) => println!("Error on 2: {}", e),
+        }
     }
 }
@@ -1,11 +1,11 @@
-use std::{
-    collections::{HashMap, HashSet},
-    fmt::Display,
-};
+use std::collections::HashSet;
 
 use anyhow::anyhow;
 use once_cell::sync::Lazy;
-use processor::{process, read_next, read_word};
+use processor::{
+    cell_chars, describe_if_requested, diagnostic, input_file, memo::Memo, part_requested, process,
+    read_next, read_word, stats::Distribution, CellChar, PuzzleMetadata, Solver,
+};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 enum Condition {
@@ -14,21 +14,11 @@ enum Condition {
     Unknown,
 }
 
-impl Condition {
-    fn character_rep(&self) -> char {
-        match self {
-            Condition::Operational => '.',
-            Condition::Damaged => '#',
-            Condition::Unknown => '?',
-        }
-    }
-}
-
-impl Display for Condition {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.character_rep())
-    }
-}
+cell_chars!(Condition {
+    '.' => Operational,
+    '#' => Damaged,
+    '?' => Unknown,
+});
 
 #[derive(Debug)]
 struct Line {
@@ -50,13 +40,8 @@ fn parse_condition_line(line: &str) -> Result<Line, AError> {
         read_word(&mut chars, &DELIMITERS).ok_or_else(|| anyhow!("No Conditions"))?;
     let conditions = conditions
         .chars()
-        .map(|c| match c {
-            '.' => Condition::Operational,
-            '#' => Condition::Damaged,
-            '?' => Condition::Unknown,
-            _ => panic!("Unknown condition: {c}"),
-        })
-        .collect();
+        .map(|c| Condition::from_char(c).ok_or_else(|| anyhow!(format!("Unknown condition: {c}"))))
+        .collect::<Result<_, _>>()?;
     let mut group_lengths = Vec::default();
     while let Ok((group_length, _)) = read_next::<usize>(&mut chars, &DELIMITERS) {
         group_lengths.push(group_length);
@@ -154,15 +139,21 @@ fn construct_arrangement_key(to_arrange: &[Condition], group_lengths: &[usize])
 fn calculate_arrangements(
     to_arrange: &Vec<Condition>,
     group_lengths: &Vec<usize>,
-    memoized: &mut HashMap<ArrangementKey, usize>,
-) -> (bool, usize) {
+    memo: &mut Memo<ArrangementKey, usize>,
+) -> usize {
     let key = construct_arrangement_key(to_arrange, group_lengths);
-    if let Some(arrangements) = memoized.get(&key) {
-        return (true, *arrangements);
-    }
+    memo.get_or_compute(key, |memo| {
+        count_arrangements(to_arrange, group_lengths, memo)
+    })
+}
 
+fn count_arrangements(
+    to_arrange: &Vec<Condition>,
+    group_lengths: &Vec<usize>,
+    memo: &mut Memo<ArrangementKey, usize>,
+) -> usize {
     if !is_possible_arrangement(to_arrange, group_lengths, false) {
-        return (false, 0);
+        return 0;
     }
 
     //drop leading operationals
@@ -210,42 +201,27 @@ fn calculate_arrangements(
     {
         let mut to_arrange1 = to_arrange.to_vec();
         *to_arrange1.get_mut(index_of_unknown).unwrap() = Condition::Damaged;
-        let (was_cached, arrangements1) =
-            calculate_arrangements(&to_arrange1, &group_lengths, memoized);
-        if !was_cached {
-            let key = construct_arrangement_key(&to_arrange1, &group_lengths);
-            memoized.insert(key, arrangements1);
-        }
+        let arrangements1 = calculate_arrangements(&to_arrange1, &group_lengths, memo);
 
         let mut to_arrange2 = to_arrange.to_vec();
         *to_arrange2.get_mut(index_of_unknown).unwrap() = Condition::Operational;
-        let (was_cached, arrangements2) =
-            calculate_arrangements(&to_arrange2, &group_lengths, memoized);
-        if !was_cached {
-            let key = construct_arrangement_key(&to_arrange2, &group_lengths);
-            memoized.insert(key, arrangements2);
-        }
+        let arrangements2 = calculate_arrangements(&to_arrange2, &group_lengths, memo);
 
-        (false, arrangements1 + arrangements2)
+        arrangements1 + arrangements2
     } else if is_possible_arrangement(&to_arrange, &group_lengths, true) {
-        (false, 1)
+        1
     } else {
-        (false, 0)
+        0
     }
 }
 
 fn calculate_possible_arrangements(line: &Line) -> usize {
-    let (_, result) = calculate_arrangements(
-        &line.conditions,
-        &line.group_lengths,
-        &mut HashMap::default(),
-    );
-    result
+    calculate_arrangements(&line.conditions, &line.group_lengths, &mut Memo::new())
 }
 
 fn perform_processing(state: LoadedState) -> Result<ProcessedState, AError> {
     let mut line_num = 0;
-    Ok(state
+    let arrangements: ProcessedState = state
         .iter()
         .map(|line| {
             let result = calculate_possible_arrangements(line);
@@ -253,7 +229,11 @@ fn perform_processing(state: LoadedState) -> Result<ProcessedState, AError> {
             println!("processed line {line_num}: {result}");
             result
         })
-        .collect())
+        .collect();
+    if let Some(distribution) = Distribution::of(arrangements.iter().copied()) {
+        diagnostic("arrangements per line", distribution);
+    }
+    Ok(arrangements)
 }
 
 fn calc_result(state: ProcessedState) -> Result<FinalResult, AError> {
@@ -264,35 +244,48 @@ fn calc_result_2(state: ProcessedState) -> Result<FinalResult, AError> {
     Ok(state.iter().sum())
 }
 
+struct Day12;
+
+impl Solver for Day12 {
+    fn metadata() -> PuzzleMetadata {
+        PuzzleMetadata::new(12, "Hot Springs")
+    }
+}
+
 fn main() {
+    describe_if_requested::<Day12>();
     //let file = "test-input.txt";
     //let file = "test-input2.txt";
-    let file = "input.txt";
-
-    let result1 = process(
-        file,
-        Vec::new(),
-        parse_line,
-        finalise_state,
-        perform_processing,
-        calc_result,
-    );
-    match result1 {
-        Ok(res) => println!("Result 1: {:?}", res),
-        Err(e) => println!("Error on 1: {}", e),
+    let file = &input_file("input.txt");
+
+    if part_requested(1) {
+        let result1 = process(
+            file,
+            Vec::new(),
+            parse_line,
+            finalise_state,
+            perform_processing,
+            calc_result,
+        );
+        match result1 {
+            Ok(res) => println!("Result 1: {:?}", res),
+            Err(e) => println!("Error on 1: {}", e),
+        }
     }
 
-    let result2 = process(
-        file,
-        Vec::new(),
-        parse_line,
-        finalise_state_2,
-        perform_processing,
-        calc_result_2,
-    );
-    match result2 {
-        Ok(res) => println!("Result 2: {:?}", res),
-        Err(e) => println!("Error on 2: {}", e),
+    if part_requested(2) {
+        let result2 = process(
+            file,
+            Vec::new(),
+            parse_line,
+            finalise_state_2,
+            perform_processing,
+            calc_result_2,
+        );
+        match result2 {
+            Ok(res) => println!("Result 2: {:?}", res),
+            Err(e) => println!("Error on 2: {}", e),
+        }
     }
 }
 
@@ -3,29 +3,22 @@ use std::{
     fmt::Display,
 };
 
-use processor::{process, read_word, Cells, CellsBuilder, BLANK_DELIMITERS};
+use processor::{
+    cell_chars, describe_if_requested, parallel::par_map, read_word, Cells, CellsBuilder,
+    LineHashes, Pipeline, PuzzleMetadata, Solver, BLANK_DELIMITERS,
+};
 
-#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
 enum Cell {
     #[default]
     Ash,
     Rock,
 }
 
-impl Cell {
-    fn character_rep(&self) -> char {
-        match self {
-            Cell::Ash => '.',
-            Cell::Rock => '#',
-        }
-    }
-}
-
-impl Display for Cell {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.character_rep())
-    }
-}
+cell_chars!(Cell {
+    '.' => Ash,
+    '#' => Rock,
+});
 
 #[derive(Debug, Default)]
 struct LoadingState {
@@ -64,16 +57,7 @@ fn parse_line(mut state: InitialState, line: String) -> Result<InitialState, AEr
     match read_word(&mut line.chars(), &BLANK_DELIMITERS) {
         Some((line, _)) => {
             let current_builder = state.patterns.last_mut().unwrap();
-            current_builder.new_line();
-            line.chars().for_each(|c| match c {
-                '.' => current_builder
-                    .add_cell(Cell::Ash)
-                    .expect("Failed to add ash cell"),
-                '#' => current_builder
-                    .add_cell(Cell::Rock)
-                    .expect("Failed to add rock cell"),
-                _ => panic!("unrecognised cell: {c}"),
-            })
+            current_builder.add_line(&line)?;
         }
         None => state.patterns.push(CellsBuilder::default()),
     };
@@ -91,13 +75,13 @@ fn finalise_state(state: InitialState) -> Result<LoadedState, AError> {
 /// If a possible reflection is found, checks that the reflection gets all the way to an edge
 /// returns None if it doesn't otherwise returns Some and the number of cells reflected
 /// i.e. if returns 0, then there was no reflection all the way to an edge
-fn find_reflection_size(lines: &Vec<Vec<Cell>>, upper_index: usize) -> Option<usize> {
-    let max_repeats_upper = lines.len() - upper_index - 1;
+fn find_reflection_size(line_hashes: &[u64], upper_index: usize) -> Option<usize> {
+    let max_repeats_upper = line_hashes.len() - upper_index - 1;
     let max_repeats_lower = upper_index - 1;
     let required_repeats = max_repeats_lower.min(max_repeats_upper);
     for i in 0..(required_repeats + 1) {
-        let upper_line = &lines[upper_index + i];
-        let lower_line = &lines[upper_index - (i + 1)];
+        let upper_line = line_hashes[upper_index + i];
+        let lower_line = line_hashes[upper_index - (i + 1)];
         if upper_line != lower_line {
             return None;
         }
@@ -105,27 +89,14 @@ fn find_reflection_size(lines: &Vec<Vec<Cell>>, upper_index: usize) -> Option<us
     Some(required_repeats)
 }
 
-fn output_lines(_index: usize, _name: &str, _lines: &[Vec<Cell>]) {
-    // println!("{index} {name}");
-    // lines.iter().for_each(|line| {
-    //     println!("{}", line.iter().map(|c| c.character_rep()).collect::<String>());
-    // });
-    // println!();
-}
-
 /// Returns the upper_index and the size of the reflection
-fn find_reflection_indices(
-    index: usize,
-    name: &str,
-    lines: &Vec<Vec<Cell>>,
-) -> BTreeSet<UpperIndexAndSize> {
-    output_lines(index, name, lines);
+fn find_reflection_indices(line_hashes: &[u64]) -> BTreeSet<UpperIndexAndSize> {
     let mut reflections = BTreeSet::default();
-    for i in 1..lines.len() {
-        let lower_line = &lines[i - 1];
-        let upper_line = &lines[i];
+    for i in 1..line_hashes.len() {
+        let lower_line = line_hashes[i - 1];
+        let upper_line = line_hashes[i];
         if lower_line == upper_line {
-            if let Some(span) = find_reflection_size(lines, i) {
+            if let Some(span) = find_reflection_size(line_hashes, i) {
                 reflections.insert((i, span));
             }
         }
@@ -133,62 +104,43 @@ fn find_reflection_indices(
     reflections
 }
 
-fn get_mirrored_row_columns(index: usize, cells: &Cells<Cell>) -> Reflection {
-    //rows
-    let mut rows: Vec<Vec<Cell>> = Vec::default();
-    for row in 0..cells.side_lengths.1 {
-        let mut this_row = Vec::default();
-        for column in 0..cells.side_lengths.0 {
-            this_row.push(*cells.get(column, row).unwrap());
-        }
-        rows.push(this_row);
-    }
-    let found_rows = find_reflection_indices(index, "rows: ", &rows);
-    //columns
-    let mut cols: Vec<Vec<Cell>> = Vec::default();
-    for column in 0..cells.side_lengths.0 {
-        let mut this_column = Vec::default();
-        for row in 0..cells.side_lengths.1 {
-            this_column.push(*cells.get(column, row).unwrap());
-        }
-        cols.push(this_column);
-    }
-    let found_columns = find_reflection_indices(index, "columns: ", &cols);
+fn get_mirrored_row_columns(line_hashes: &LineHashes) -> Reflection {
     Reflection {
-        rows: found_rows,
-        columns: found_columns,
+        rows: find_reflection_indices(&line_hashes.rows),
+        columns: find_reflection_indices(&line_hashes.columns),
     }
 }
 
 fn perform_processing_1(state: LoadedState) -> Result<ProcessedState, AError> {
-    let row_columns = state
-        .patterns
-        .iter()
-        .enumerate()
-        .map(|(index, cells)| get_mirrored_row_columns(index, cells))
-        .collect();
+    // patterns are independent, results come back in pattern order for the final sum
+    let row_columns = par_map(state.patterns, |cells| {
+        get_mirrored_row_columns(&cells.line_hashes())
+    });
     Ok(row_columns)
 }
 
-fn flip_cell(cells: &mut Cells<Cell>, x: usize, y: usize) {
-    let cell = cells.get_mut(x, y).unwrap();
-    let flipped = match cell {
+fn flipped(cell: &Cell) -> Cell {
+    match cell {
         Cell::Ash => Cell::Rock,
         Cell::Rock => Cell::Ash,
-    };
-    *cell = flipped;
+    }
 }
 
 fn fix_smudge_and_get_mirrored_row_columns(index: usize, cells: &mut Cells<Cell>) -> Reflection {
-    let original = get_mirrored_row_columns(index, cells);
+    let line_hashes = cells.line_hashes();
+    let original = get_mirrored_row_columns(&line_hashes);
     let mut smudge_reflections: HashSet<Reflection> = HashSet::default();
     for x in 0..cells.side_lengths.0 {
         for y in 0..cells.side_lengths.1 {
-            //Flip it
-            flip_cell(cells, x, y);
-            let smudge_reflection = get_mirrored_row_columns(index, cells);
-            //Remember to flip it back
-            flip_cell(cells, x, y);
+            //Flip it, the patch is reverted once the reflection has been found
+            let flip = ((x, y), flipped(&cells[(x, y)]));
+            let smudge_reflection = cells
+                .with_patches([flip], |smudged| {
+                    let mut smudged_hashes = line_hashes.clone();
+                    smudged_hashes.rehash_cell(smudged, x, y).unwrap();
+                    get_mirrored_row_columns(&smudged_hashes)
+                })
+                .unwrap();
 
             // println!("{}, {} original: {}", x, y, original);
             // println!("{}, {} smudged: {}", x, y, original);
@@ -224,18 +176,16 @@ fn fix_smudge_and_get_mirrored_row_columns(index: usize, cells: &mut Cells<Cell>
     smudge_reflections.into_iter().next().unwrap()
 }
 
-fn perform_processing_2(mut state: LoadedState) -> Result<ProcessedState, AError> {
-    let row_columns = state
-        .patterns
-        .iter_mut()
-        .enumerate()
-        .map(|(index, cells)| fix_smudge_and_get_mirrored_row_columns(index, cells))
-        .collect();
+fn perform_processing_2(state: LoadedState) -> Result<ProcessedState, AError> {
+    let indexed_patterns = state.patterns.into_iter().enumerate().collect();
+    let row_columns = par_map(indexed_patterns, |(index, mut cells)| {
+        fix_smudge_and_get_mirrored_row_columns(index, &mut cells)
+    });
     Ok(row_columns)
 }
 
 fn calc_result(state: ProcessedState) -> Result<FinalResult, AError> {
-    let values = state.iter().enumerate().map(|(_index, reflection)| {
+    let values = state.iter().map(|reflection| {
         let (col_upper_index, _col_span) = reflection.columns.first().unwrap_or(&(0, 0));
         let (row_upper_index, _row_span) = reflection.rows.first().unwrap_or(&(0, 0));
         row_upper_index * 100 + col_upper_index
@@ -243,34 +193,24 @@ fn calc_result(state: ProcessedState) -> Result<FinalResult, AError> {
     Ok(values.sum())
 }
 
-fn main() {
-    //let file = "test-input.txt";
-    //let file = "test-input2.txt";
-    let file = "input.txt";
+struct Day13;
 
-    let result1 = process(
-        file,
-        LoadingState::default(),
-        parse_line,
-        finalise_state,
-        perform_processing_1,
-        calc_result,
-    );
-    match result1 {
-        Ok(res) => println!("Result 1: {:?}", res),
-        Err(e) => println!("Error on 1: {}", e),
+impl Solver for Day13 {
+    fn metadata() -> PuzzleMetadata {
+        PuzzleMetadata::new(13, "Point of Incidence")
     }
+}
 
-    let result2 = process(
-        file,
-        LoadingState::default(),
-        parse_line,
-        finalise_state,
-        perform_processing_2,
-        calc_result,
-    );
-    match result2 {
-        Ok(res) => println!("Result 2: {:?}", res),
-        Err(e) => println!("Error on 2: {}", e),
-    }
+fn main() {
+    describe_if_requested::<Day13>();
+    Pipeline::builder()
+        //.input("test-input.txt")
+        //.input("test-input2.txt")
+        .initial_state(LoadingState::default)
+        .parser(parse_line)
+        .finaliser(finalise_state)
+        .part1(|state| calc_result(perform_processing_1(state)?))
+        .part2(|state| calc_result(perform_processing_2(state)?))
+        .run()
+        .print();
 }
@@ -2,7 +2,10 @@ use std::collections::HashSet;
 
 use linked_hash_map::LinkedHashMap;
 use once_cell::sync::Lazy;
-use processor::{process, read_next, read_word};
+use processor::{
+    describe_if_requested, input_file, part_requested, process, read_next, read_word,
+    PuzzleMetadata, Solver,
+};
 
 type AError = anyhow::Error;
 
@@ -154,34 +157,47 @@ fn calc_result_2(state: ProcessedState2) -> Result<FinalResult2, AError> {
     Ok(result)
 }
 
+struct Day15;
+
+impl Solver for Day15 {
+    fn metadata() -> PuzzleMetadata {
+        PuzzleMetadata::new(15, "Lens Library")
+    }
+}
+
 fn main() {
+    describe_if_requested::<Day15>();
     //let file = "test-input.txt";
     //let file = "test-input2.txt";
-    let file = "input.txt";
-
-    let result1 = process(
-        file,
-        Vec::new(),
-        parse_line_1,
-        finalise_state_1,
-        perform_processing_1,
-        calc_result_1,
-    );
-    match result1 {
-        Ok(res) => println!("Result 1: {:?}", res),
-        Err(e) => println!("Error on 1: {}", e),
+    let file = &input_file("input.txt");
+
+    if part_requested(1) {
+        let result1 = process(
+            file,
+            Vec::new(),
+            parse_line_1,
+            finalise_state_1,
+            perform_processing_1,
+            calc_result_1,
+        );
+        match result1 {
+            Ok(res) => println!("Result 1: {:?}", res),
+            Err(e) => println!("Error on 1: {}", e),
+        }
     }
 
-    let result2 = process(
-        file,
-        Vec::new(),
-        parse_line_2,
-        finalise_state_2,
-        perform_processing_2,
-        calc_result_2,
-    );
-    match result2 {
-        Ok(res) => println!("Result 2: {:?}", res),
-        Err(e) => println!("Error on 2: {}", e),
+    if part_requested(2) {
+        let result2 = process(
+            file,
+            Vec::new(),
+            parse_line_2,
+            finalise_state_2,
+            perform_processing_2,
+            calc_result_2,
+        );
+        match result2 {
+            Ok(res) => println!("Result 2: {:?}", res),
+            Err(e) => println!("Error on 2: {}", e),
+        }
     }
 }
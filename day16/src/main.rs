@@ -1,59 +1,26 @@
-use std::{
-    collections::{HashSet, VecDeque},
-    fmt::Display,
+use std::collections::VecDeque;
+
+use processor::{
+    describe_if_requested, diagnostic,
+    history::Inspector,
+    part_requested, process,
+    tiles::{self, Tile},
+    CellChar, Cells, CellsBuilder, DirSet, Direction, InlineVec, PuzzleMetadata, Solver,
 };
 
-use processor::{process, Cells, CellsBuilder};
-
 type AError = anyhow::Error;
 
-#[derive(Debug, Clone, Copy, Default)]
-enum Tile {
-    #[default]
-    Space,
-    MirrorTopLeftBottomRight,
-    MirrorBottomLeftTopRight,
-    SplitterHorizontal,
-    SplitterVertical,
-}
-
-impl Tile {
-    fn char_rep(&self) -> char {
-        match self {
-            Tile::Space => '.',
-            Tile::MirrorTopLeftBottomRight => '\\',
-            Tile::MirrorBottomLeftTopRight => '/',
-            Tile::SplitterHorizontal => '-',
-            Tile::SplitterVertical => '|',
-        }
-    }
-}
-
-impl Display for Tile {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.char_rep())
-    }
-}
-
 type InitialState = CellsBuilder<Tile>;
 type LoadedState = Cells<Tile>;
 type ProcessedState = usize;
 type FinalResult = usize;
 
+/// The tiles the grid is drawn with
+const TILES: &str = r".|-/\";
+
 fn parse_line(mut state: InitialState, line: String) -> Result<InitialState, AError> {
     if !line.is_empty() {
-        state.new_line();
-        for c in line.chars() {
-            let tile = match c {
-                '.' => Tile::Space,
-                '\\' => Tile::MirrorTopLeftBottomRight,
-                '/' => Tile::MirrorBottomLeftTopRight,
-                '-' => Tile::SplitterHorizontal,
-                '|' => Tile::SplitterVertical,
-                _ => panic!("Unrecognised tile: {c}"),
-            };
-            state.add_cell(tile)?;
-        }
+        state.add_line_with(&line, |c| tiles::allowed_tile(c, TILES))?;
     }
     Ok(state)
 }
@@ -65,105 +32,42 @@ fn output_cells(_cells: &Cells<Tile>) {
 }
 
 fn finalise_state(mut state: InitialState) -> Result<LoadedState, AError> {
-    let cells = state.build_cells(Tile::Space)?;
+    let cells = state.build_cells(Tile::Empty)?;
+    diagnostic("tiles", cells.value_counts());
     output_cells(&cells);
     Ok(cells)
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-enum LightDirection {
-    Up,
-    Right,
-    Down,
-    Left,
-}
-
 type Coord = (usize, usize);
-type ProcessingDirection = (Coord, LightDirection);
-
-fn create_empty_light_directions(cells: &Cells<Tile>) -> Cells<HashSet<LightDirection>> {
-    let mut directions = CellsBuilder::new_empty();
-    for _y in 0..cells.side_lengths.1 {
-        directions.new_line();
-        for _x in 0..cells.side_lengths.0 {
-            directions.add_cell(HashSet::default()).unwrap();
-        }
-    }
+type ProcessingDirection = (Coord, Direction);
 
-    directions.build_cells(HashSet::default()).unwrap()
-}
-
-fn get_next_direction(
-    x: usize,
-    y: usize,
-    direction: LightDirection,
-) -> ((isize, isize), LightDirection) {
-    let x = x as isize;
-    let y = y as isize;
-    match direction {
-        LightDirection::Up => ((x, y - 1), direction),
-        LightDirection::Down => ((x, y + 1), direction),
-        LightDirection::Left => ((x - 1, y), direction),
-        LightDirection::Right => ((x + 1, y), direction),
-    }
+fn create_empty_light_directions(cells: &Cells<Tile>) -> Cells<DirSet> {
+    Cells::with_dimension(
+        cells.side_lengths.0,
+        cells.side_lengths.1,
+        DirSet::default(),
+    )
 }
 
 fn process_light_direction(
     tiles: &Cells<Tile>,
-    directions: &mut Cells<HashSet<LightDirection>>,
+    directions: &mut Cells<DirSet>,
     direction: &ProcessingDirection,
-) -> Vec<ProcessingDirection> {
+) -> InlineVec<ProcessingDirection, 2> {
     let ((x, y), direction) = direction;
-    let tile = tiles.get(*x, *y).unwrap();
-    let next_directions: Vec<((isize, isize), LightDirection)> = match (tile, direction) {
-        (Tile::MirrorTopLeftBottomRight, LightDirection::Up) => {
-            vec![get_next_direction(*x, *y, LightDirection::Left)]
-        }
-        (Tile::MirrorTopLeftBottomRight, LightDirection::Down) => {
-            vec![get_next_direction(*x, *y, LightDirection::Right)]
-        }
-        (Tile::MirrorTopLeftBottomRight, LightDirection::Left) => {
-            vec![get_next_direction(*x, *y, LightDirection::Up)]
-        }
-        (Tile::MirrorTopLeftBottomRight, LightDirection::Right) => {
-            vec![get_next_direction(*x, *y, LightDirection::Down)]
-        }
-        (Tile::MirrorBottomLeftTopRight, LightDirection::Up) => {
-            vec![get_next_direction(*x, *y, LightDirection::Right)]
-        }
-        (Tile::MirrorBottomLeftTopRight, LightDirection::Down) => {
-            vec![get_next_direction(*x, *y, LightDirection::Left)]
-        }
-        (Tile::MirrorBottomLeftTopRight, LightDirection::Left) => {
-            vec![get_next_direction(*x, *y, LightDirection::Down)]
-        }
-        (Tile::MirrorBottomLeftTopRight, LightDirection::Right) => {
-            vec![get_next_direction(*x, *y, LightDirection::Up)]
-        }
-        (Tile::SplitterHorizontal, LightDirection::Up)
-        | (Tile::SplitterHorizontal, LightDirection::Down) => vec![
-            get_next_direction(*x, *y, LightDirection::Left),
-            get_next_direction(*x, *y, LightDirection::Right),
-        ],
-        (Tile::SplitterVertical, LightDirection::Left)
-        | (Tile::SplitterVertical, LightDirection::Right) => vec![
-            get_next_direction(*x, *y, LightDirection::Up),
-            get_next_direction(*x, *y, LightDirection::Down),
-        ],
-        _ => vec![get_next_direction(*x, *y, *direction)],
+    let tile = &tiles[(*x, *y)];
+    let next_directions = match tile {
+        Tile::Mirror(mirror) => InlineVec::from_iter([mirror.reflect(*direction)]),
+        Tile::Splitter(splitter) => splitter.split(*direction),
+        _ => InlineVec::from_iter([*direction]),
     };
     //only keep directions that are in bounds and we didn't already process
-    let next_directions: Vec<ProcessingDirection> = next_directions
+    let next_directions: InlineVec<ProcessingDirection, 2> = next_directions
         .into_iter()
-        .filter_map(|candidate| {
-            let ((x, y), direction) = candidate;
-            if !directions.in_bounds(x, y) {
-                return None; //off the cells
-            };
-            let x = x as usize;
-            let y = y as usize;
-            let dirs = directions.get(x, y).unwrap();
-            if dirs.contains(&direction) {
+        .filter_map(|direction| {
+            let (x, y) = tiles.step((*x, *y), direction)?; //off the cells?
+            let dirs = &directions[(x, y)];
+            if dirs.contains(direction) {
                 return None; //already processed
             };
             Some(((x, y), direction))
@@ -172,23 +76,35 @@ fn process_light_direction(
     //mark the cells as visited...
     next_directions.iter().for_each(|dir| {
         let ((x, y), direction) = dir;
-        directions.get_mut(*x, *y).unwrap().insert(*direction);
+        directions[(*x, *y)].insert(*direction);
     });
     next_directions
 }
 
-fn number_of_energised_tiles(directions: &Cells<HashSet<LightDirection>>) -> usize {
+fn number_of_energised_tiles(directions: &Cells<DirSet>) -> usize {
     directions
         .iter()
         .map(|((_x, _y), directions)| if directions.is_empty() { 0 } else { 1 })
         .sum()
 }
 
+/// The tiles with those the light has passed through so far shown as `#`
+fn energised_view(tiles: &Cells<Tile>, directions: &Cells<DirSet>) -> Cells<char> {
+    let mut view = Cells::with_dimension(tiles.side_lengths.0, tiles.side_lengths.1, '.');
+    tiles.iter().for_each(|((x, y), tile)| {
+        let energised = !directions[(x, y)].is_empty();
+        view[(x, y)] = if energised { '#' } else { tile.to_char() };
+    });
+    view
+}
+
+/// Steps inspected are the number of beam moves processed
 fn process_from(
     tiles: &Cells<Tile>,
     start_x: usize,
     start_y: usize,
-    start_direction: LightDirection,
+    start_direction: Direction,
+    inspector: &mut Inspector<Cells<char>>,
 ) -> usize {
     let mut directions = create_empty_light_directions(tiles);
     let mut current_processing_directions: VecDeque<ProcessingDirection> = VecDeque::default();
@@ -200,27 +116,48 @@ fn process_from(
         .insert(start_direction);
 
     //process until we have no more beam locations to process
+    let mut step = 0;
+    inspector.record_with(step, || energised_view(tiles, &directions));
     while let Some(direction) = current_processing_directions.pop_front() {
-        let mut new_directions = process_light_direction(tiles, &mut directions, &direction);
-        new_directions
-            .drain(..)
-            .for_each(|dir| current_processing_directions.push_back(dir));
+        let new_directions = process_light_direction(tiles, &mut directions, &direction);
+        current_processing_directions.extend(new_directions);
+        step += 1;
+        inspector.record_with(step, || energised_view(tiles, &directions));
     }
     //calculate how many tiles
     number_of_energised_tiles(&directions)
 }
 
-fn perform_processing_1(state: LoadedState) -> Result<ProcessedState, AError> {
-    Ok(process_from(&state, 0, 0, LightDirection::Right))
+fn perform_processing_1(
+    state: LoadedState,
+    inspector: &mut Inspector<Cells<char>>,
+) -> Result<ProcessedState, AError> {
+    Ok(process_from(&state, 0, 0, Direction::East, inspector))
 }
 
 fn perform_processing_2(state: LoadedState) -> Result<ProcessedState, AError> {
-    let left = (0..state.side_lengths.1).map(|y| process_from(&state, 0, y, LightDirection::Right));
-    let top = (0..state.side_lengths.0).map(|x| process_from(&state, x, 0, LightDirection::Down));
-    let right = (0..state.side_lengths.1)
-        .map(|y| process_from(&state, state.side_lengths.0 - 1, y, LightDirection::Left));
-    let bottom = (0..state.side_lengths.0)
-        .map(|x| process_from(&state, x, state.side_lengths.1 - 1, LightDirection::Up));
+    let left = (0..state.side_lengths.1)
+        .map(|y| process_from(&state, 0, y, Direction::East, &mut Inspector::off()));
+    let top = (0..state.side_lengths.0)
+        .map(|x| process_from(&state, x, 0, Direction::South, &mut Inspector::off()));
+    let right = (0..state.side_lengths.1).map(|y| {
+        process_from(
+            &state,
+            state.side_lengths.0 - 1,
+            y,
+            Direction::West,
+            &mut Inspector::off(),
+        )
+    });
+    let bottom = (0..state.side_lengths.0).map(|x| {
+        process_from(
+            &state,
+            x,
+            state.side_lengths.1 - 1,
+            Direction::North,
+            &mut Inspector::off(),
+        )
+    });
     let result = left.chain(top).chain(right).chain(bottom).max();
     Ok(result.unwrap())
 }
@@ -229,34 +166,52 @@ fn calc_result(state: ProcessedState) -> Result<FinalResult, AError> {
     Ok(state)
 }
 
+struct Day16;
+
+impl Solver for Day16 {
+    fn metadata() -> PuzzleMetadata {
+        PuzzleMetadata::new(16, "The Floor Will Be Lava").with_inspection()
+    }
+}
+
 fn main() {
+    describe_if_requested::<Day16>();
     //let file = "test-input.txt";
     //let file = "test-input2.txt";
-    let file = "input.txt";
-
-    let result1 = process(
-        file,
-        CellsBuilder::default(),
-        parse_line,
-        finalise_state,
-        perform_processing_1,
-        calc_result,
-    );
-    match result1 {
-        Ok(res) => println!("Result 1: {:?}", res),
-        Err(e) => println!("Error on 1: {}", e),
+    let config = Day16::metadata()
+        .load_config("input.txt")
+        .expect("Invalid configuration");
+    let file = &config.file;
+    let mut inspector = Inspector::from_config(&config).expect("Invalid inspection");
+
+    if part_requested(1) {
+        let result1 = process(
+            file,
+            CellsBuilder::default(),
+            parse_line,
+            finalise_state,
+            |state| perform_processing_1(state, &mut inspector),
+            calc_result,
+        );
+        inspector.report();
+        match result1 {
+            Ok(res) => println!("Result 1: {:?}", res),
+            Err(e) => println!("Error on 1: {}", e),
+        }
     }
 
-    let result2 = process(
-        file,
-        CellsBuilder::default(),
-        parse_line,
-        finalise_state,
-        perform_processing_2,
-        calc_result,
-    );
-    match result2 {
-        Ok(res) => println!("Result 2: {:?}", res),
-        Err(e) => println!("Error on 2: {}", e),
+    if part_requested(2) {
+        let result2 = process(
+            file,
+            CellsBuilder::default(),
+            parse_line,
+            finalise_state,
+            perform_processing_2,
+            calc_result,
+        );
+        match result2 {
+            Ok(res) => println!("Result 2: {:?}", res),
+            Err(e) => println!("Error on 2: {}", e),
+        }
     }
 }
@@ -1,9 +1,9 @@
-use std::{
-    collections::{HashMap, VecDeque},
-    fmt::Display,
-};
+use std::fmt::Display;
 
-use processor::{process, Cells, CellsBuilder};
+use processor::{
+    describe_if_requested, diagnostic, dijkstra::dijkstra, input_file, part_requested, process,
+    profile_span, start_profiling, Cells, CellsBuilder, Direction, PuzzleMetadata, Solver,
+};
 
 type AError = anyhow::Error;
 
@@ -18,6 +18,12 @@ impl Display for HeatLoss {
     }
 }
 
+impl From<HeatLoss> for i64 {
+    fn from(heat_loss: HeatLoss) -> Self {
+        heat_loss.amount as i64
+    }
+}
+
 type InitialState = CellsBuilder<HeatLoss>;
 type LoadedState = Cells<HeatLoss>;
 type ProcessedState = usize;
@@ -25,23 +31,22 @@ type FinalResult = usize;
 
 fn parse_line(mut state: InitialState, line: String) -> Result<InitialState, AError> {
     if !line.is_empty() {
-        state.new_line();
-        line.chars().for_each(|c| {
-            if let Some(heat_loss) = c.to_digit(10) {
-                state
-                    .add_cell(HeatLoss {
-                        amount: heat_loss as usize,
-                    })
-                    .unwrap();
-            } else {
-                panic!("Non-number {} in line: {}", c, line);
-            }
-        })
+        state.add_line_with(&line, |c| {
+            let amount = c
+                .to_digit(10)
+                .ok_or_else(|| AError::msg(format!("Non-number {c}")))?;
+            Ok(HeatLoss {
+                amount: amount as usize,
+            })
+        })?;
     }
     Ok(state)
 }
 
-fn output_heat_loss_grid(_grid: &Cells<HeatLoss>) {
+fn output_heat_loss_grid(grid: &Cells<HeatLoss>) {
+    if let Some(stats) = grid.stats() {
+        diagnostic("heat loss", stats);
+    }
     // println!("Grid:");
     // println!("{grid}");
     // println!("")
@@ -53,49 +58,8 @@ fn finalise_state(mut state: InitialState) -> Result<LoadedState, AError> {
     Ok(grid)
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-enum Direction {
-    Up,
-    Down,
-    Left,
-    Right,
-}
-
-#[derive(Debug, Clone, Copy)]
-struct Move {
-    x: usize,
-    y: usize,
-    direction: Direction,
-    cost: usize,
-    turn_last_made: usize,
-}
-
-impl Move {
-    fn new(x: usize, y: usize, direction: Direction, cost: usize, turn_last_made: usize) -> Move {
-        Move {
-            x,
-            y,
-            direction,
-            cost,
-            turn_last_made,
-        }
-    }
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-struct BestSoFarKey {
-    direction: Direction,
-    turn_last_made: usize,
-}
-
-impl BestSoFarKey {
-    fn new(direction: Direction, turn_last_made: usize) -> BestSoFarKey {
-        BestSoFarKey {
-            direction,
-            turn_last_made,
-        }
-    }
-}
+/// Position, direction and moves made since the last turn
+type CrucibleState = ((usize, usize), Direction, usize);
 
 struct CrucibleParameters {
     min_in_straight_line: usize,
@@ -103,236 +67,74 @@ struct CrucibleParameters {
 }
 
 fn can_move_required_in_a_straight_line(
-    x_y_direction: (isize, isize, Direction),
-    turn_last_made: usize,
-    heat_loss_grid: &Cells<HeatLoss>,
-    crucible_parameters: &CrucibleParameters,
-) -> bool {
-    let (x, y, direction) = x_y_direction;
-    if turn_last_made < crucible_parameters.min_in_straight_line {
-        let (delta_x, delta_y) = match direction {
-            Direction::Up => (0isize, -1isize),
-            Direction::Down => (0, 1),
-            Direction::Left => (-1, 0),
-            Direction::Right => (1, 0),
-        };
-        let still_to_go = (crucible_parameters.min_in_straight_line - turn_last_made) as isize;
-        let (forced_x, forced_y) = (delta_x * still_to_go, delta_y * still_to_go);
-        if !heat_loss_grid.in_bounds(x + forced_x, y + forced_y) {
-            return false;
-        }
-    }
-    true
-}
-
-fn construct_move(
-    x_y_direction: (isize, isize, Direction),
+    coord: (usize, usize),
+    direction: Direction,
     turn_last_made: usize,
     heat_loss_grid: &Cells<HeatLoss>,
-    best_so_far: &mut Cells<HashMap<BestSoFarKey, usize>>,
-    previous_move: &Move,
-    crucible_parameters: &CrucibleParameters,
-) -> Option<Move> {
-    let (x, y, direction) = x_y_direction;
-    // println!("x={}, y={}, direction={:?}, turn_last_made={}", x, y, direction, turn_last_made);
-
-    //not in bounds?
-    if !heat_loss_grid.in_bounds(x, y) {
-        // println!("Not in bounds");
-        return None;
-    }
-    //Need to move a minium in this direction, can we do it?
-    if !can_move_required_in_a_straight_line(
-        x_y_direction,
-        turn_last_made,
-        heat_loss_grid,
-        crucible_parameters,
-    ) {
-        // println!("Unable to move all required in direction");
-        return None;
-    }
-
-    let (x, y) = (x as usize, y as usize);
-
-    let heat_loss = heat_loss_grid.get(x, y).unwrap().amount;
-    let cost_to_get_here = previous_move.cost + heat_loss;
-    //Did we already get to the position going in the same direction after the same number of moves after turning with a lower cost?
-    let best_costs_so_far = best_so_far.get_mut(x, y).unwrap();
-    let key = BestSoFarKey::new(direction, turn_last_made);
-    if let Some(best_cost_so_far) = best_costs_so_far.get(&key) {
-        if *best_cost_so_far <= cost_to_get_here {
-            //already done it as good or better, no point continuing
-            return None;
-        }
-    };
-    //not got here with a better cost with direction etc. Accept the move...
-    best_costs_so_far.insert(key, cost_to_get_here);
-    //...but if we are at bottom right, no point in continuing from here
-    if x == heat_loss_grid.side_lengths.0 - 1 && y == heat_loss_grid.side_lengths.1 - 1 {
-        // print!("Not best");
-        return None;
-    }
-    //better cost and not at final destination, we should process this move
-    Some(Move::new(x, y, direction, cost_to_get_here, turn_last_made))
-}
-
-fn turn_allowed(this_move: &Move, crucible_parameters: &CrucibleParameters) -> bool {
-    //can't turn unless we've been going straight for our minimum number
-    this_move.turn_last_made >= crucible_parameters.min_in_straight_line
-}
-
-fn turn_left(
-    heat_loss_grid: &Cells<HeatLoss>,
-    best_so_far: &mut Cells<HashMap<BestSoFarKey, usize>>,
-    this_move: &Move,
-    crucible_parameters: &CrucibleParameters,
-) -> Option<Move> {
-    if !turn_allowed(this_move, crucible_parameters) {
-        return None;
-    }
-
-    let (x, y) = (this_move.x, this_move.y);
-    let x_y_direction = match this_move.direction {
-        Direction::Up => (x as isize - 1, y as isize, Direction::Left),
-        Direction::Down => (x as isize + 1, y as isize, Direction::Right),
-        Direction::Left => (x as isize, y as isize + 1, Direction::Down),
-        Direction::Right => (x as isize, y as isize - 1, Direction::Up),
-    };
-    construct_move(
-        x_y_direction,
-        1,
-        heat_loss_grid,
-        best_so_far,
-        this_move,
-        crucible_parameters,
-    )
-}
-
-fn turn_right(
-    heat_loss_grid: &Cells<HeatLoss>,
-    best_so_far: &mut Cells<HashMap<BestSoFarKey, usize>>,
-    this_move: &Move,
-    crucible_parameters: &CrucibleParameters,
-) -> Option<Move> {
-    if !turn_allowed(this_move, crucible_parameters) {
-        return None;
-    }
-
-    let (x, y) = (this_move.x, this_move.y);
-    let x_y_direction = match this_move.direction {
-        Direction::Up => (x as isize + 1, y as isize, Direction::Right),
-        Direction::Down => (x as isize - 1, y as isize, Direction::Left),
-        Direction::Left => (x as isize, y as isize - 1, Direction::Up),
-        Direction::Right => (x as isize, y as isize + 1, Direction::Down),
-    };
-    construct_move(
-        x_y_direction,
-        1,
-        heat_loss_grid,
-        best_so_far,
-        this_move,
-        crucible_parameters,
-    )
-}
-
-fn continue_straight_on_allowed(
-    this_move: &Move,
-    crucible_parameters: &CrucibleParameters,
+    min_in_straight_line: usize,
 ) -> bool {
-    this_move.turn_last_made < crucible_parameters.max_in_straight_line
-}
-
-fn go_straight(
-    heat_loss_grid: &Cells<HeatLoss>,
-    best_so_far: &mut Cells<HashMap<BestSoFarKey, usize>>,
-    this_move: &Move,
+    let still_to_go = min_in_straight_line.saturating_sub(turn_last_made);
+    (0..still_to_go)
+        .try_fold(coord, |coord, _| heat_loss_grid.step(coord, direction))
+        .is_some()
+}
+
+/// The states the crucible can move on to, with the heat lost entering each.  It can turn 90 degrees left or
+/// right once it's been going straight for its minimum, or go straight on if it hasn't been for its maximum,
+/// but never in to a direction where it would run off the grid before it could turn again.
+fn next_moves<'a>(
+    heat_loss_grid: &'a Cells<HeatLoss>,
+    state: &CrucibleState,
     crucible_parameters: &CrucibleParameters,
-) -> Option<Move> {
-    //Only allowed to go a max number in a straight line before we have to turn
-    if !continue_straight_on_allowed(this_move, crucible_parameters) {
-        return None;
-    }
-
-    let (x, y) = (this_move.x, this_move.y);
-    let x_y_direction = match this_move.direction {
-        Direction::Up => (x as isize, y as isize - 1, Direction::Up),
-        Direction::Down => (x as isize, y as isize + 1, Direction::Down),
-        Direction::Left => (x as isize - 1, y as isize, Direction::Left),
-        Direction::Right => (x as isize + 1, y as isize, Direction::Right),
-    };
-    construct_move(
-        x_y_direction,
-        this_move.turn_last_made + 1,
-        heat_loss_grid,
-        best_so_far,
-        this_move,
-        crucible_parameters,
-    )
-}
-
-fn make_next_moves(
-    heat_loss_grid: &Cells<HeatLoss>,
-    best_so_far: &mut Cells<HashMap<BestSoFarKey, usize>>,
-    this_move: &Move,
-    current_moves: &mut VecDeque<Move>,
-    crucible_parameters: &CrucibleParameters,
-) {
-    //we can either turn 90 degrees left, turn 90 degrees right or go ahead (if we haven't been going straight for too long)
-    if let Some(turn_left) = turn_left(heat_loss_grid, best_so_far, this_move, crucible_parameters)
-    {
-        current_moves.push_back(turn_left);
-    };
-    if let Some(turn_right) =
-        turn_right(heat_loss_grid, best_so_far, this_move, crucible_parameters)
-    {
-        current_moves.push_back(turn_right);
-    }
-    if let Some(go_straight) =
-        go_straight(heat_loss_grid, best_so_far, this_move, crucible_parameters)
-    {
-        current_moves.push_back(go_straight);
-    };
+) -> impl Iterator<Item = (CrucibleState, usize)> + 'a {
+    let (coord, direction, turn_last_made) = *state;
+    let can_turn = turn_last_made >= crucible_parameters.min_in_straight_line;
+    let can_go_straight = turn_last_made < crucible_parameters.max_in_straight_line;
+    let min_in_straight_line = crucible_parameters.min_in_straight_line;
+    [
+        (can_turn, direction.turn_left(), 1),
+        (can_turn, direction.turn_right(), 1),
+        (can_go_straight, direction, turn_last_made + 1),
+    ]
+    .into_iter()
+    .filter(|(allowed, _, _)| *allowed)
+    .filter_map(move |(_, direction, turn_last_made)| {
+        let next = heat_loss_grid.step(coord, direction)?;
+        can_move_required_in_a_straight_line(
+            next,
+            direction,
+            turn_last_made,
+            heat_loss_grid,
+            min_in_straight_line,
+        )
+        .then(|| {
+            (
+                (next, direction, turn_last_made),
+                heat_loss_grid[next].amount,
+            )
+        })
+    })
 }
 
 fn perform(heat_loss_grid: &Cells<HeatLoss>, crucible_parameters: CrucibleParameters) -> usize {
-    let mut best_so_far: Cells<HashMap<BestSoFarKey, usize>> = Cells::with_dimension(
-        heat_loss_grid.side_lengths.0,
-        heat_loss_grid.side_lengths.1,
-        HashMap::default(),
+    profile_span!("dijkstra");
+    let bottom_right = (
+        heat_loss_grid.side_lengths.0 - 1,
+        heat_loss_grid.side_lengths.1 - 1,
     );
-    let mut current_moves: VecDeque<Move> = VecDeque::default();
-    //prime
-    current_moves.push_back(Move::new(0, 0, Direction::Right, 0, 0));
-    best_so_far
-        .get_mut(0, 0)
-        .unwrap()
-        .insert(BestSoFarKey::new(Direction::Right, 1), 0);
-    current_moves.push_back(Move::new(0, 0, Direction::Down, 0, 0));
-    best_so_far
-        .get_mut(0, 0)
-        .unwrap()
-        .insert(BestSoFarKey::new(Direction::Down, 1), 0);
-    //Run
-    while let Some(this_move) = current_moves.pop_front() {
-        make_next_moves(
-            heat_loss_grid,
-            &mut best_so_far,
-            &this_move,
-            &mut current_moves,
-            &crucible_parameters,
-        );
-    }
-    //look at the last square to see what the best was
-    let bottom_right = best_so_far
-        .get(
-            best_so_far.side_lengths.0 - 1,
-            best_so_far.side_lengths.1 - 1,
-        )
-        .unwrap();
-    *bottom_right
-        .values()
-        .min()
-        .expect("Didn't find a bottom right best")
+    let result = dijkstra(
+        (
+            heat_loss_grid.side_lengths,
+            crucible_parameters.max_in_straight_line,
+        ),
+        [((0, 0), Direction::East, 0), ((0, 0), Direction::South, 0)],
+        |state| next_moves(heat_loss_grid, state, &crucible_parameters),
+        |(coord, _, _)| *coord == bottom_right,
+    );
+    diagnostic("states expanded", result.stats.expanded);
+    diagnostic("stale states skipped", result.stats.stale);
+    let (_, heat_loss) = result.goal.expect("Didn't find a bottom right best");
+    heat_loss
 }
 
 fn perform_processing_1(state: LoadedState) -> Result<ProcessedState, AError> {
@@ -359,34 +161,48 @@ fn calc_result(state: ProcessedState) -> Result<FinalResult, AError> {
     Ok(state)
 }
 
+struct Day17;
+
+impl Solver for Day17 {
+    fn metadata() -> PuzzleMetadata {
+        PuzzleMetadata::new(17, "Clumsy Crucible")
+    }
+}
+
 fn main() {
+    describe_if_requested::<Day17>();
+    let _profiling = start_profiling();
     //let file = "test-input.txt";
     //let file = "test-input2.txt";
-    let file = "input.txt";
-
-    let result1 = process(
-        file,
-        InitialState::new_empty(),
-        parse_line,
-        finalise_state,
-        perform_processing_1,
-        calc_result,
-    );
-    match result1 {
-        Ok(res) => println!("Result 1: {:?}", res),
-        Err(e) => println!("Error on 1: {}", e),
+    let file = &input_file("input.txt");
+
+    if part_requested(1) {
+        let result1 = process(
+            file,
+            InitialState::new_empty(),
+            parse_line,
+            finalise_state,
+            perform_processing_1,
+            calc_result,
+        );
+        match result1 {
+            Ok(res) => println!("Result 1: {:?}", res),
+            Err(e) => println!("Error on 1: {}", e),
+        }
     }
 
-    let result2 = process(
-        file,
-        InitialState::new_empty(),
-        parse_line,
-        finalise_state,
-        perform_processing_2,
-        calc_result,
-    );
-    match result2 {
-        Ok(res) => println!("Result 2: {:?}", res),
-        Err(e) => println!("Error on 2: {}", e),
+    if part_requested(2) {
+        let result2 = process(
+            file,
+            InitialState::new_empty(),
+            parse_line,
+            finalise_state,
+            perform_processing_2,
+            calc_result,
+        );
+        match result2 {
+            Ok(res) => println!("Result 2: {:?}", res),
+            Err(e) => println!("Error on 2: {}", e),
+        }
     }
 }
@@ -1,11 +1,15 @@
-use std::{
-    collections::{HashMap, HashSet, VecDeque},
-    fmt::Display,
-};
+use std::{collections::HashSet, fmt::Display};
 
 use anyhow::anyhow;
 use once_cell::sync::Lazy;
-use processor::{process, read_next, read_word};
+use processor::{
+    branching::branch_and_fold,
+    collections::Map,
+    describe_if_requested, diagnostic,
+    graph::{Graph, NodeId},
+    input_file, part_requested, process_with_context, read_group, read_next, read_token, read_word,
+    LineContext, PuzzleMetadata, Solver, Token,
+};
 
 type AError = anyhow::Error;
 
@@ -49,8 +53,9 @@ struct Workflow {
 
 #[derive(Debug, Clone)]
 struct Part {
-    _index: usize,
-    attributes: HashMap<char, usize>,
+    /// Line the part was read from
+    _line_index: usize,
+    attributes: Map<char, usize>,
 }
 
 enum LoadingState {
@@ -60,7 +65,7 @@ enum LoadingState {
 
 #[derive(Debug, Default)]
 struct State {
-    workflows: HashMap<String, Workflow>,
+    workflows: Map<String, Workflow>,
     parts: Vec<Part>,
 }
 
@@ -69,8 +74,7 @@ type LoadedState = State;
 type ProcessedState = usize;
 type FinalResult = usize;
 
-static WORKFLOW_DELIMITERS: Lazy<HashSet<char>> =
-    Lazy::new(|| HashSet::from(['{', '}', ':', ',', '<', '>']));
+static WORKFLOW_DELIMITERS: Lazy<HashSet<char>> = Lazy::new(|| HashSet::from([':', ',', '<', '>']));
 
 fn parse_check(delimiter: char, amount: usize) -> Check {
     match delimiter {
@@ -91,7 +95,11 @@ fn parse_destination(s: String) -> Destination {
 fn load_worflow(line: String) -> Workflow {
     let mut chars = line.chars();
     //px{a<2006:qkq,m>2090:A,rfg}
-    let (name, _) = read_word(&mut chars, &WORKFLOW_DELIMITERS).expect("No name");
+    let Some((Token::Word(name), _)) = read_token(&mut chars, &WORKFLOW_DELIMITERS).unwrap() else {
+        panic!("No name: '{line}'");
+    };
+    let (rules_contents, _) = read_group(&mut chars, &WORKFLOW_DELIMITERS, '{').unwrap();
+    let mut chars = rules_contents.chars();
     let mut rules = Vec::default();
     let mut unmatched_destination = None;
     while let Some((attribute_or_destination, delimiter)) =
@@ -123,24 +131,26 @@ fn load_worflow(line: String) -> Workflow {
     }
 }
 
-static PART_DELIMITERS: Lazy<HashSet<char>> = Lazy::new(|| HashSet::from(['{', '}', '=', ',']));
+static PART_DELIMITERS: Lazy<HashSet<char>> = Lazy::new(|| HashSet::from(['=', ',']));
 
-fn load_part(part_index: usize, line: String) -> Part {
-    let mut chars = line.chars();
-    let mut attributes = HashMap::default();
+fn load_part(line_index: usize, line: &str) -> Result<Part, AError> {
+    //{x=787,m=2655,a=1222,s=2876}
+    let (attributes_contents, _) = read_group(&mut line.chars(), &PART_DELIMITERS, '{')?;
+    let mut chars = attributes_contents.chars();
+    let mut attributes = Map::default();
     while let Some((attribute, _)) = read_word(&mut chars, &PART_DELIMITERS) {
-        let (attribute_value, _) =
-            read_next::<usize>(&mut chars, &PART_DELIMITERS).expect("Reading part value");
+        let (attribute_value, _) = read_next::<usize>(&mut chars, &PART_DELIMITERS)?;
         attributes.insert(attribute.chars().next().unwrap(), attribute_value);
     }
-    Part {
-        _index: part_index,
+    Ok(Part {
+        _line_index: line_index,
         attributes,
-    }
+    })
 }
 
-fn parse_line(istate: InitialState, line: String) -> Result<InitialState, AError> {
+fn parse_line(istate: InitialState, context: LineContext) -> Result<InitialState, AError> {
     let (loading_state, mut state) = istate;
+    let line = context.raw;
     if line.is_empty() {
         return Ok((LoadingState::Parts, state));
     };
@@ -150,15 +160,57 @@ fn parse_line(istate: InitialState, line: String) -> Result<InitialState, AError
             state.workflows.insert(wf.name.clone(), wf);
         }
         LoadingState::Parts => {
-            let part = load_part(state.parts.len(), line);
+            let part = load_part(context.index, &line)?;
             state.parts.push(part);
         }
     }
     Ok((loading_state, state))
 }
 
+/// Workflows that send parts round in a loop, which would never accept or reject them
+fn workflow_cycles(workflows: &Map<String, Workflow>) -> Vec<Vec<String>> {
+    let mut graph = Graph::directed();
+    let names: Vec<&String> = workflows.keys().collect();
+    let ids: Vec<NodeId> = names.iter().map(|name| graph.add_node(*name)).collect();
+    for (from, workflow) in ids.into_iter().zip(names.iter()) {
+        let destinations = workflows[*workflow]
+            .rules
+            .iter()
+            .map(|rule| &rule.destination)
+            .chain([&workflows[*workflow].unmatched_destination]);
+        for destination in destinations {
+            if let Destination::Workflow { name } = destination {
+                if let Some(to) = graph.find_node(|node| *node == name) {
+                    graph.add_edge(from, to);
+                }
+            }
+        }
+    }
+    let mut cycles: Vec<Vec<String>> = graph
+        .strongly_connected_components()
+        .into_iter()
+        .filter(|members| {
+            members.len() > 1 || graph.neighbours(members[0]).any(|(to, _)| to == members[0])
+        })
+        .map(|members| {
+            let mut names: Vec<String> = members
+                .iter()
+                .map(|id| graph.node(*id).to_string())
+                .collect();
+            names.sort();
+            names
+        })
+        .collect();
+    cycles.sort();
+    cycles
+}
+
 fn finalise_state(istate: InitialState) -> Result<LoadedState, AError> {
     let (_, state) = istate;
+    let cycles = workflow_cycles(&state.workflows);
+    if !cycles.is_empty() {
+        return Err(anyhow!("Workflows loop: {cycles:?}"));
+    }
     Ok(state)
 }
 
@@ -215,6 +267,7 @@ fn perform_processing_1(state: LoadedState) -> Result<ProcessedState, AError> {
         }
     }
 
+    diagnostic("accepted parts", accepted_parts.len());
     let result = accepted_parts
         .iter()
         .map(|part| part.attributes.values().sum::<usize>())
@@ -224,14 +277,14 @@ fn perform_processing_1(state: LoadedState) -> Result<ProcessedState, AError> {
 
 type MinMax = (usize, usize);
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct PartPossibilities {
-    attributes: HashMap<char, MinMax>,
+    attributes: Map<char, MinMax>,
 }
 
 struct ToProcess {
     possibilities: PartPossibilities,
-    workflow: String,
+    destination: Destination,
 }
 
 /// Separates out a given MinMax into a part that matches a rule, and a part that doesn't match the rule
@@ -266,151 +319,133 @@ fn match_rule(rule: &Rule, min_max: &MinMax) -> (Option<MinMax>, Option<MinMax>)
     }
 }
 
-fn process_matched_part(
-    accepted: &mut Vec<PartPossibilities>,
-    to_process: &mut VecDeque<ToProcess>,
-    original_possibilities: &PartPossibilities,
-    rule: &Rule,
-    matched_min_max: MinMax,
-) {
-    match &rule.destination {
-        Destination::Accepted => {
-            let mut new_attributes = original_possibilities.attributes.clone();
-            new_attributes.insert(rule.attribute, matched_min_max);
-            accepted.push(PartPossibilities {
-                attributes: new_attributes,
-            });
-        }
-        Destination::Workflow { name } => {
-            let mut new_attributes = original_possibilities.attributes.clone();
-            new_attributes.insert(rule.attribute, matched_min_max);
-            to_process.push_back(ToProcess {
-                possibilities: PartPossibilities {
-                    attributes: new_attributes,
-                },
-                workflow: name.clone(),
-            });
-        }
-        Destination::Rejected => (), //drop it
-    }
-}
-
-fn process_unmatched_part(
+fn with_attribute(
     original_possibilities: &PartPossibilities,
-    rule: &Rule,
-    unmatched_min_max: MinMax,
+    attribute: char,
+    min_max: MinMax,
 ) -> PartPossibilities {
     let mut new_attributes = original_possibilities.attributes.clone();
-    new_attributes.insert(rule.attribute, unmatched_min_max);
+    new_attributes.insert(attribute, min_max);
     PartPossibilities {
         attributes: new_attributes,
     }
 }
 
-fn process_next(
-    workflows: &HashMap<String, Workflow>,
-    accepted: &mut Vec<PartPossibilities>,
-    to_process: &mut VecDeque<ToProcess>,
-    this_one: ToProcess,
-) {
-    // println!("Processing at {}: {:?}", this_one.workflow, this_one.possibilities);
-    let workflow = workflows.get(&this_one.workflow).unwrap();
-    let mut current_part_possibilities = Some(this_one.possibilities);
+/// Splits the possibilities up through the rules of the workflow they are at, giving the possibilities
+/// sent on to each destination.  Rejected possibilities are dropped.
+fn process_next(workflows: &Map<String, Workflow>, this_one: &ToProcess) -> Vec<ToProcess> {
+    let Destination::Workflow { name } = &this_one.destination else {
+        return vec![];
+    };
+    let workflow = workflows.get(name).unwrap();
+    let mut next = Vec::default();
+    let mut current_part_possibilities = Some(this_one.possibilities.clone());
     for rule in workflow.rules.iter() {
         if let Some(possibilities) = current_part_possibilities {
             let min_max = possibilities.attributes.get(&rule.attribute).unwrap();
 
             let (matched, unmatched) = match_rule(rule, min_max);
             if let Some(matched) = matched {
-                process_matched_part(accepted, to_process, &possibilities, rule, matched);
+                next.push(ToProcess {
+                    possibilities: with_attribute(&possibilities, rule.attribute, matched),
+                    destination: rule.destination.clone(),
+                });
             }
-            current_part_possibilities =
-                unmatched.map(|unmatched| process_unmatched_part(&possibilities, rule, unmatched));
+            current_part_possibilities = unmatched
+                .map(|unmatched| with_attribute(&possibilities, rule.attribute, unmatched));
         }
     }
     //default?
     if let Some(possibilities) = current_part_possibilities {
-        match &workflow.unmatched_destination {
-            Destination::Accepted => accepted.push(possibilities),
-            Destination::Workflow { name } => to_process.push_back(ToProcess {
-                possibilities,
-                workflow: name.clone(),
-            }),
-            Destination::Rejected => (), //drop it
-        }
+        next.push(ToProcess {
+            possibilities,
+            destination: workflow.unmatched_destination.clone(),
+        });
     }
+    next.retain(|to_process| !matches!(to_process.destination, Destination::Rejected));
+    next
 }
 
 fn perform_processing_2(state: LoadedState) -> Result<ProcessedState, AError> {
-    let mut accepted_possibilities: Vec<PartPossibilities> = Vec::default();
     //Push through the possibilities splitting them as required until they reach a final state (A or R)
-    let mut to_process: VecDeque<ToProcess> = VecDeque::default();
-    //prime
-    to_process.push_back(ToProcess {
+    let initial = ToProcess {
         possibilities: PartPossibilities {
-            attributes: HashMap::from([
+            attributes: Map::from([
                 ('x', (1, 4000)),
                 ('m', (1, 4000)),
                 ('a', (1, 4000)),
                 ('s', (1, 4000)),
             ]),
         },
-        workflow: INITIAL_WORKFLOW.to_string(),
-    });
-    //Pump
-    while let Some(next_to_process) = to_process.pop_front() {
-        process_next(
-            &state.workflows,
-            &mut accepted_possibilities,
-            &mut to_process,
-            next_to_process,
-        );
-    }
-    //Calculate the final combinations and sum
-    let result = accepted_possibilities
-        .iter()
-        .map(|possibility| {
-            possibility
-                .attributes
-                .values()
-                .fold(1usize, |acc, (min, max)| acc * (*max - *min + 1))
-        })
-        .sum();
-    Ok(result)
+        destination: Destination::Workflow {
+            name: INITIAL_WORKFLOW.to_string(),
+        },
+    };
+    //Calculate the final combinations of those accepted and sum
+    let result = branch_and_fold(
+        initial,
+        |to_process| process_next(&state.workflows, to_process),
+        |to_process| match to_process.destination {
+            Destination::Accepted => Some(
+                to_process
+                    .possibilities
+                    .attributes
+                    .values()
+                    .fold(1usize, |acc, (min, max)| acc * (*max - *min + 1)),
+            ),
+            _ => None,
+        },
+        0usize,
+        |acc, combinations| acc + combinations,
+    );
+    Ok(result.value)
 }
 
 fn calc_result(state: ProcessedState) -> Result<FinalResult, AError> {
     Ok(state)
 }
 
+struct Day19;
+
+impl Solver for Day19 {
+    fn metadata() -> PuzzleMetadata {
+        PuzzleMetadata::new(19, "Aplenty")
+    }
+}
+
 fn main() {
+    describe_if_requested::<Day19>();
     //let file = "test-input.txt";
     //let file = "test-input2.txt";
-    let file = "input.txt";
-
-    let result1 = process(
-        file,
-        (LoadingState::Workflows, State::default()),
-        parse_line,
-        finalise_state,
-        perform_processing_1,
-        calc_result,
-    );
-    match result1 {
-        Ok(res) => println!("Result 1: {:?}", res),
-        Err(e) => println!("Error on 1: {}", e),
+    let file = &input_file("input.txt");
+
+    if part_requested(1) {
+        let result1 = process_with_context(
+            file,
+            (LoadingState::Workflows, State::default()),
+            parse_line,
+            finalise_state,
+            perform_processing_1,
+            calc_result,
+        );
+        match result1 {
+            Ok(res) => println!("Result 1: {:?}", res),
+            Err(e) => println!("Error on 1: {}", e),
+        }
     }
 
-    let result2 = process(
-        file,
-        (LoadingState::Workflows, State::default()),
-        parse_line,
-        finalise_state,
-        perform_processing_2,
-        calc_result,
-    );
-    match result2 {
-        Ok(res) => println!("Result 2: {:?}", res),
-        Err(e) => println!("Error on 2: {}", e),
+    if part_requested(2) {
+        let result2 = process_with_context(
+            file,
+            (LoadingState::Workflows, State::default()),
+            parse_line,
+            finalise_state,
+            perform_processing_2,
+            calc_result,
+        );
+        match result2 {
+            Ok(res) => println!("Result 2: {:?}", res),
+            Err(e) => println!("Error on 2: {}", e),
+        }
     }
 }
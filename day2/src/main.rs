@@ -1,7 +1,10 @@
 use std::collections::{HashMap, HashSet};
 
 use once_cell::sync::Lazy;
-use processor::{ok_identity, process, read_next, read_word};
+use processor::{
+    describe_if_requested, input_file, ok_identity, part_requested, process, read_next, read_word,
+    PuzzleMetadata, Solver,
+};
 
 type AError = anyhow::Error;
 type InitialState = Vec<Game>;
@@ -14,35 +17,48 @@ struct Game {
     picks: Vec<HashMap<String, i64>>,
 }
 
+struct Day2;
+
+impl Solver for Day2 {
+    fn metadata() -> PuzzleMetadata {
+        PuzzleMetadata::new(2, "Cube Conundrum")
+    }
+}
+
 fn main() {
+    describe_if_requested::<Day2>();
     //let file = "test-input.txt";
     //let file = "test-input2.txt";
-    let file = "input.txt";
-
-    let result1 = process(
-        file,
-        Vec::new(),
-        parse_line,
-        ok_identity,
-        perform_processing_1,
-        ok_identity,
-    );
-    match result1 {
-        Ok(res) => println!("Result 1: {:?}", res),
-        Err(e) => println!("Error on 1: {}", e),
+    let file = &input_file("input.txt");
+
+    if part_requested(1) {
+        let result1 = process(
+            file,
+            Vec::new(),
+            parse_line,
+            ok_identity,
+            perform_processing_1,
+            ok_identity,
+        );
+        match result1 {
+            Ok(res) => println!("Result 1: {:?}", res),
+            Err(e) => println!("Error on 1: {}", e),
+        }
     }
 
-    let result2 = process(
-        file,
-        Vec::new(),
-        parse_line,
-        ok_identity,
-        perform_processing_2,
-        ok_identity,
-    );
-    match result2 {
-        Ok(res) => println!("Result 2: {:?}", res),
-        Err(e) => println!("Error on 2: {}", e),
+    if part_requested(2) {
+        let result2 = process(
+            file,
+            Vec::new(),
+            parse_line,
+            ok_identity,
+            perform_processing_2,
+            ok_identity,
+        );
+        match result2 {
+            Ok(res) => println!("Result 2: {:?}", res),
+            Err(e) => println!("Error on 2: {}", e),
+        }
     }
 }
 
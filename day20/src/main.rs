@@ -1,82 +1,25 @@
-use std::{
-    collections::{HashMap, HashSet, VecDeque},
-    fmt::Display,
-};
+mod network;
+
+use std::collections::HashSet;
 
 use anyhow::anyhow;
 use itertools::Itertools;
 use num::Integer;
 use once_cell::sync::Lazy;
-use processor::{process, read_word};
-use substring::Substring;
-
-type AError = anyhow::Error;
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum Pulse {
-    High,
-    Low,
-    NotSeen,
-}
-
-#[derive(Debug)]
-enum Module {
-    FlipFlop {
-        on: bool,
-        inputs: HashMap<String, Pulse>,
-        outputs: Vec<String>,
-    }, //'%', ignores high, flips on low,
-    Conjunction {
-        inputs: HashMap<String, Pulse>,
-        outputs: Vec<String>,
-    }, //'&', starts low on all
-    Broadcast {
-        inputs: HashMap<String, Pulse>,
-        outputs: Vec<String>,
-    }, //Single one 'broadcaster'
-}
-
-impl Module {
-    fn inputs_string(inputs: &HashMap<String, Pulse>) -> String {
-        inputs
-            .iter()
-            .map(|(name, pulse)| format!("{}={:?}", name, pulse))
-            .join(",")
-    }
+use processor::{
+    collections::Map,
+    describe_if_requested, diagnostic,
+    graph::{self, Graph, Highlight, NodeId},
+    input_file, part_requested, process, start_profiling, Cursor, PuzzleMetadata, Solver,
+};
 
-    fn outputs_string(outputs: &[String]) -> String {
-        outputs.iter().join(",")
-    }
-}
+use network::{Module, Network, Pulse};
 
-impl Display for Module {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let (prefix, inputs, outputs) = match self {
-            Module::FlipFlop {
-                on,
-                inputs,
-                outputs,
-            } => {
-                let on = if *on { "on" } else { "off" };
-                (format!("FlipFlop {} ", on), inputs, outputs)
-            }
-            Module::Conjunction { inputs, outputs } => {
-                ("Conjunction ".to_string(), inputs, outputs)
-            }
-            Module::Broadcast { inputs, outputs } => ("Broadcast ".to_string(), inputs, outputs),
-        };
-        write!(
-            f,
-            "{prefix} -> ({}) -> ({})",
-            Module::inputs_string(inputs),
-            Module::outputs_string(outputs)
-        )
-    }
-}
+type AError = anyhow::Error;
 
-type InitialState = (String, HashMap<String, Module>);
+type InitialState = (String, Map<String, Module>);
 
-type LoadedState = (String, HashMap<String, Module>);
+type LoadedState = (String, Network);
 type ProcessedState = usize;
 type FinalResult = usize;
 
@@ -84,280 +27,213 @@ static DELIMITERS: Lazy<HashSet<char>> = Lazy::new(|| HashSet::from([' ', '-', '
 
 fn parse_line(istate: InitialState, line: String) -> Result<InitialState, AError> {
     let (output, mut state) = istate;
-    let mut chars = line.chars();
-    if let Some((module_type_and_name, _)) = read_word(&mut chars, &DELIMITERS) {
+    let mut cursor = Cursor::new(&line);
+    let module_type = cursor.next_if(|c| c == '%' || c == '&');
+    if let Some((name, _)) = cursor.read_word(&DELIMITERS) {
         //read in the outputs
-        let inputs: HashMap<String, Pulse> = HashMap::default();
         let mut outputs: Vec<String> = Vec::default();
-        while let Some((output_name, _)) = read_word(&mut chars, &DELIMITERS) {
+        while let Some((output_name, _)) = cursor.read_word(&DELIMITERS) {
             outputs.push(output_name);
         }
-        let possible_name = module_type_and_name.substring(1, module_type_and_name.len());
-        let (name, module) = match module_type_and_name.substring(0, 1) {
-            "b" => {
-                if module_type_and_name != "broadcaster" {
-                    return Err(anyhow!(format!(
-                        "Unexpected module name following 'b': {module_type_and_name}"
-                    )));
-                };
-                ("broadcaster", Module::Broadcast { inputs, outputs })
-            }
-            "%" => (
-                possible_name,
-                Module::FlipFlop {
-                    on: false,
-                    inputs,
-                    outputs,
-                },
-            ),
-            "&" => (possible_name, Module::Conjunction { inputs, outputs }),
-            _ => {
-                return Err(anyhow!(format!(
-                    "indecipherable module type/name: {module_type_and_name}"
-                )))
-            }
+        let module = match module_type {
+            Some('%') => Module::flip_flop(outputs),
+            Some('&') => Module::conjunction(outputs),
+            _ if name == "broadcaster" => Module::broadcast(outputs),
+            _ => return Err(anyhow!(format!("indecipherable module type/name: {line}"))),
         };
         state.insert(name.to_string(), module);
     }
     Ok((output, state))
 }
 
-fn get_outputs(module: &Module) -> &Vec<String> {
-    match module {
-        Module::Broadcast {
-            inputs: _input,
-            outputs,
-        } => outputs,
-        Module::Conjunction {
-            inputs: _input,
-            outputs,
-        } => outputs,
-        Module::FlipFlop {
-            on: _on,
-            inputs: _input,
-            outputs,
-        } => outputs,
-    }
+fn finalise_state(istate: InitialState) -> Result<LoadedState, AError> {
+    let (output, state) = istate;
+    let network = Network::new(state)?;
+    export_graph(&output, &network)?;
+    Ok((output, network))
 }
 
-fn finalise_state(istate: InitialState) -> Result<LoadedState, AError> {
-    let (output, mut state) = istate;
-    //Set up all of the Conjunction states - we need to prime them with the incoming conections (set them all to Pulse::Low)
-    let source_destinations: Vec<(String, String)> = state
-        .iter_mut()
+/// The modules and where they send their pulses, with the output and the two layers of modules feeding it
+/// picked out (part 2 watches the cadence of those), see [`graph::export_if_requested`]
+fn export_graph(output: &str, network: &Network) -> Result<(), AError> {
+    let mut names: Vec<&String> = network
+        .modules()
+        .flat_map(|(name, module)| std::iter::once(name).chain(module.outputs()))
+        .collect();
+    names.sort();
+    names.dedup();
+    let mut graph = Graph::directed();
+    names.iter().for_each(|name| {
+        graph.add_node(name.as_str());
+    });
+    let id_of = |name: &str| graph.find_node(|node| *node == name).unwrap();
+    let edges: Vec<(NodeId, NodeId)> = network
+        .modules()
         .flat_map(|(name, module)| {
-            let outputs = get_outputs(module);
-            outputs.iter().map(|output| (name.clone(), output.clone()))
+            module
+                .outputs()
+                .iter()
+                .map(|destination| (id_of(name), id_of(destination)))
         })
         .collect();
-    source_destinations
-        .iter()
-        .for_each(|(source, destination)| {
-            let module = state.get_mut(destination);
-            match module {
-                Some(Module::FlipFlop {
-                    on: _on,
-                    inputs,
-                    outputs: _outputs,
-                }) => {
-                    inputs.insert(source.clone(), Pulse::NotSeen);
-                }
-                Some(Module::Broadcast {
-                    inputs,
-                    outputs: _outputs,
-                }) => {
-                    inputs.insert(source.clone(), Pulse::NotSeen);
-                }
-                Some(Module::Conjunction {
-                    inputs,
-                    outputs: _outputs,
-                }) => {
-                    inputs.insert(source.clone(), Pulse::Low);
-                }
-                _ => (),
-            }
-        });
-    Ok((output, state))
-}
-
-/// Push the button, sending a low pulse into the broadcast.
-///
-/// Each pulse to a destination will be passed to the observation function along with a value of type T (starting
-/// with the initial_value).  The observation function then returns another (or the same) value of type T which will be
-/// passed to the observation function the next time it's called, similar to a fold.
-///
-fn push_button<T, F>(
-    state: &mut HashMap<String, Module>,
-    initial_value: T,
-    observation_function: F,
-) -> (usize, usize, T)
-where
-    F: Fn(T, &Pulse, &String) -> T,
-{
-    let mut low_pulse_count = 0;
-    let mut high_pulse_count = 0;
-    let mut observation_value = initial_value;
-
-    //Queue of source, pulse_type and destination
-    let mut pulse_queue: VecDeque<(String, Pulse, String)> = VecDeque::default();
-    //First send a low pulse to 'broadcaster'
-    let button = "button".to_string();
-    let broadcaster = "broadcaster".to_string();
-    pulse_queue.push_back((button, Pulse::Low, broadcaster));
-
-    while let Some((source, pulse, destination)) = pulse_queue.pop_front() {
-        // println!("{source} -{pulse:?}-> {destination}");
-        match pulse {
-            Pulse::Low => {
-                low_pulse_count += 1;
-            }
-            Pulse::High => {
-                high_pulse_count += 1;
-            }
-            _ => (),
-        }
-        observation_value = observation_function(observation_value, &pulse, &destination);
-        if !state.contains_key(&destination) {
-            // println!("No destination '{destination}'");
-            continue;
-        }
-        state.entry(destination.clone()).and_modify(|module| {
-            match module {
-                Module::Broadcast { inputs, outputs } => {
-                    inputs.insert(source.clone(), pulse);
-                    //Same pulse to all outputs
-                    outputs.iter().for_each(|output| {
-                        pulse_queue.push_back((destination.clone(), pulse, output.clone()))
-                    });
-                }
-                Module::FlipFlop {
-                    on,
-                    inputs,
-                    outputs,
-                } => {
-                    inputs.insert(source.clone(), pulse);
-                    //Ignore high pulses, flip on low pulse and send high if now on, or low if now off
-                    if matches!(pulse, Pulse::Low) {
-                        *on = !*on;
-                        let next_pulse = if *on { Pulse::High } else { Pulse::Low };
-                        outputs.iter().for_each(|output| {
-                            pulse_queue.push_back((destination.clone(), next_pulse, output.clone()))
-                        });
-                    }
-                }
-                Module::Conjunction { inputs, outputs } => {
-                    //Update memory for the input
-                    inputs.insert(source.clone(), pulse);
-                    //If all inputs the same...
-                    let all_same = inputs.values().fold(inputs.values().next(), |acc, this| {
-                        if matches!(acc, Some(pulse) if pulse == this) {
-                            acc
-                        } else {
-                            None
-                        }
-                    });
-                    let pulse = match all_same {
-                        Some(Pulse::High) => Pulse::Low, //If all were the same and high, send a low
-                        _ => Pulse::High,                //otherwise send a high
-                    };
-                    outputs.iter().for_each(|output| {
-                        pulse_queue.push_back((destination.clone(), pulse, output.clone()))
-                    });
-                }
-            }
-        });
+    let feeding = |ids: &[NodeId]| -> Vec<NodeId> {
+        edges
+            .iter()
+            .filter(|(_, to)| ids.contains(to))
+            .map(|(from, _)| *from)
+            .collect()
+    };
+    let mut highlighted = graph
+        .find_node(|node| *node == output)
+        .into_iter()
+        .collect::<Vec<_>>();
+    for _ in 0..2 {
+        highlighted.extend(feeding(&highlighted));
     }
-    // println!("Done ({low_pulse_count}, {high_pulse_count})");
-    // println!();
-    (low_pulse_count, high_pulse_count, observation_value)
+    let highlight = Highlight::default()
+        .with_edges(
+            edges
+                .iter()
+                .filter(|(from, to)| highlighted.contains(from) && highlighted.contains(to))
+                .copied(),
+        )
+        .with_nodes(highlighted);
+    edges
+        .into_iter()
+        .for_each(|(from, to)| graph.add_edge(from, to));
+    graph::export_if_requested(
+        &graph,
+        |_, name| match network.module(name) {
+            Some(Module::FlipFlop { .. }) => format!("%{name}"),
+            Some(Module::Conjunction { .. }) => format!("&{name}"),
+            _ => name.to_string(),
+        },
+        &highlight,
+    )
 }
 
 const NUM_ITERATIONS: usize = 1000;
 
+/// How many presses to report the number of flip-flops flipped by, which for the flip-flops making up binary
+/// counters goes 1,2,1,3,1,2,1,4...
+const FLIPS_REPORTED: usize = 16;
+
 fn perform_processing_1(lstate: LoadedState) -> Result<ProcessedState, AError> {
-    let (_, mut state) = lstate;
+    let (_, mut network) = lstate;
     let mut low_pulse_count: usize = 0;
     let mut high_pulse_count: usize = 0;
-    (0..NUM_ITERATIONS).for_each(|_iteration| {
-        let (num_low, num_high, _) = push_button(&mut state, 0usize, |acc, _, _| acc);
+    let mut flipped: Vec<usize> = Vec::default();
+    (0..NUM_ITERATIONS).for_each(|iteration| {
+        let before = (iteration < FLIPS_REPORTED).then(|| network.snapshot());
+        let (num_low, num_high, _) = network.push_button(0usize, |acc, _, _, _| acc);
         low_pulse_count += num_low;
         high_pulse_count += num_high;
+        if let Some(before) = before {
+            flipped.push(before.diff(&network.snapshot()).len());
+        }
     });
+    diagnostic(
+        "flip-flops flipped by each of the first presses",
+        flipped.iter().join(","),
+    );
     Ok(low_pulse_count * high_pulse_count)
 }
 
+/// Gives up on finding the cycles after this many presses
+const MAX_PRESSES: usize = 1_000_000;
+
+/// The output only gets a low once the conjunction feeding it has had a high from all of its inputs on the same
+/// press.  Each of those inputs sends a high on a cycle of its own, so watch for the press each first does so on
+/// and the output gets a low on the lowest common multiple of them.
 fn perform_processing_2(lstate: LoadedState) -> Result<ProcessedState, AError> {
-    //Very specific to the main input :(...
-    //Watch the 4 inputs to rx and see what their cadences for receiving a Low are.
-    //&dr -> rx
-    //&mp -> dr
-    //&qt -> dr
-    //&qb -> dr
-    //&ng -> dr
-    let (_output, mut state) = lstate;
+    let (output, mut network) = lstate;
+    let feeder = match network.inputs_of(&output)[..] {
+        [feeder] if matches!(network.module(feeder), Some(Module::Conjunction { .. })) => {
+            feeder.clone()
+        }
+        ref feeders => {
+            return Err(anyhow!(
+                "Expected a single conjunction feeding '{output}' but found {feeders:?}"
+            ))
+        }
+    };
+    let watched: Vec<String> = network.inputs_of(&feeder).into_iter().cloned().collect();
+    println!("Watching the inputs to '{feeder}': {}", watched.join(","));
     let mut num_presses = 0;
-    let mut interesting_nums: HashMap<String, usize> = HashMap::default();
-    loop {
+    let mut first_highs: Map<String, usize> = Map::default();
+    while first_highs.len() < watched.len() {
         num_presses += 1;
-        let (_num_low, _num_high, (_, numbers)) = push_button(
-            &mut state,
-            (num_presses, interesting_nums),
-            |(num, mut acc), pulse, destination| {
-                if *pulse == Pulse::Low
-                    && matches!(destination.as_str(), "mp" | "qt" | "qb" | "ng")
-                    && !acc.contains_key(destination)
+        if num_presses > MAX_PRESSES {
+            return Err(anyhow!(
+                "Only found the cycles of {:?} after {MAX_PRESSES} presses",
+                first_highs.keys().collect::<Vec<_>>()
+            ));
+        }
+        let (_num_low, _num_high, (_, found)) = network.push_button(
+            (num_presses, first_highs),
+            |(num, mut acc), source, pulse, destination| {
+                if *pulse == Pulse::High
+                    && *destination == feeder
+                    && watched.contains(source)
+                    && !acc.contains_key(source)
                 {
-                    acc.insert(destination.clone(), num);
-                    println!("Found '{}' at {}", destination, num);
+                    acc.insert(source.clone(), num);
+                    println!("Found '{}' at {}", source, num);
                 };
                 (num, acc)
             },
         );
-        interesting_nums = numbers;
-        if interesting_nums.len() >= 4 {
-            break;
-        }
+        first_highs = found;
     }
-    let mp_num = interesting_nums.get("mp").unwrap();
-    let qt_num = interesting_nums.get("qt").unwrap();
-    let qb_num = interesting_nums.get("qb").unwrap();
-    let ng_num = interesting_nums.get("ng").unwrap();
-    let result = mp_num.lcm(qt_num).lcm(qb_num).lcm(ng_num);
-    Ok(result)
+    Ok(first_highs.values().fold(1, |result, num| result.lcm(num)))
 }
 
 fn calc_result(state: ProcessedState) -> Result<FinalResult, AError> {
     Ok(state)
 }
 
+struct Day20;
+
+impl Solver for Day20 {
+    fn metadata() -> PuzzleMetadata {
+        PuzzleMetadata::new(20, "Pulse Propagation")
+    }
+}
+
 fn main() {
+    describe_if_requested::<Day20>();
+    let _profiling = start_profiling();
     //let (output, file) = ("a", test-input.txt");
     //let (output, file) = ("outputxx", "test-input2.txt");
-    let (output, file) = ("rx", "input.txt");
-
-    let result1 = process(
-        file,
-        (output.to_string(), HashMap::default()),
-        parse_line,
-        finalise_state,
-        perform_processing_1,
-        calc_result,
-    );
-    match result1 {
-        Ok(res) => println!("Result 1: {:?}", res),
-        Err(e) => println!("Error on 1: {}", e),
+    let (output, file) = ("rx", &input_file("input.txt"));
+
+    if part_requested(1) {
+        let result1 = process(
+            file,
+            (output.to_string(), Map::default()),
+            parse_line,
+            finalise_state,
+            perform_processing_1,
+            calc_result,
+        );
+        match result1 {
+            Ok(res) => println!("Result 1: {:?}", res),
+            Err(e) => println!("Error on 1: {}", e),
+        }
     }
 
-    let result2 = process(
-        file,
-        (output.to_string(), HashMap::default()),
-        parse_line,
-        finalise_state,
-        perform_processing_2,
-        calc_result,
-    );
-    match result2 {
-        Ok(res) => println!("Result 2: {:?}", res),
-        Err(e) => println!("Error on 2: {}", e),
+    if part_requested(2) {
+        let result2 = process(
+            file,
+            (output.to_string(), Map::default()),
+            parse_line,
+            finalise_state,
+            perform_processing_2,
+            calc_result,
+        );
+        match result2 {
+            Ok(res) => println!("Result 2: {:?}", res),
+            Err(e) => println!("Error on 2: {}", e),
+        }
     }
 }
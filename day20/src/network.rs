@@ -0,0 +1,422 @@
+//! The network of modules passing pulses between themselves, and the simulation of what happens each time the
+//! button is pushed.  Conjunctions keep a count of how many of their inputs last sent a high pulse, so deciding
+//! what they send doesn't need a look at every input on every pulse.
+
+use std::{
+    collections::{BTreeMap, VecDeque},
+    fmt::Display,
+};
+
+use anyhow::anyhow;
+use itertools::Itertools;
+use processor::{collections::Map, profile_span};
+
+type AError = anyhow::Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pulse {
+    High,
+    Low,
+    NotSeen,
+}
+
+#[derive(Debug)]
+pub enum Module {
+    FlipFlop {
+        on: bool,
+        inputs: Map<String, Pulse>,
+        outputs: Vec<String>,
+    }, //'%', ignores high, flips on low,
+    Conjunction {
+        inputs: Map<String, Pulse>,
+        /// How many of the inputs are remembered as High
+        high_inputs: usize,
+        outputs: Vec<String>,
+    }, //'&', starts low on all
+    Broadcast {
+        inputs: Map<String, Pulse>,
+        outputs: Vec<String>,
+    }, //Single one 'broadcaster'
+}
+
+impl Module {
+    pub fn flip_flop(outputs: Vec<String>) -> Self {
+        Module::FlipFlop {
+            on: false,
+            inputs: Map::default(),
+            outputs,
+        }
+    }
+
+    pub fn conjunction(outputs: Vec<String>) -> Self {
+        Module::Conjunction {
+            inputs: Map::default(),
+            high_inputs: 0,
+            outputs,
+        }
+    }
+
+    pub fn broadcast(outputs: Vec<String>) -> Self {
+        Module::Broadcast {
+            inputs: Map::default(),
+            outputs,
+        }
+    }
+
+    pub fn outputs(&self) -> &Vec<String> {
+        match self {
+            Module::Broadcast { outputs, .. } => outputs,
+            Module::Conjunction { outputs, .. } => outputs,
+            Module::FlipFlop { outputs, .. } => outputs,
+        }
+    }
+
+    fn inputs_string(inputs: &Map<String, Pulse>) -> String {
+        inputs
+            .iter()
+            .map(|(name, pulse)| format!("{}={:?}", name, pulse))
+            .join(",")
+    }
+
+    fn outputs_string(outputs: &[String]) -> String {
+        outputs.iter().join(",")
+    }
+}
+
+impl Display for Module {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (prefix, inputs, outputs) = match self {
+            Module::FlipFlop {
+                on,
+                inputs,
+                outputs,
+            } => {
+                let on = if *on { "on" } else { "off" };
+                (format!("FlipFlop {} ", on), inputs, outputs)
+            }
+            Module::Conjunction {
+                inputs, outputs, ..
+            } => ("Conjunction ".to_string(), inputs, outputs),
+            Module::Broadcast { inputs, outputs } => ("Broadcast ".to_string(), inputs, outputs),
+        };
+        write!(
+            f,
+            "{prefix} -> ({}) -> ({})",
+            Module::inputs_string(inputs),
+            Module::outputs_string(outputs)
+        )
+    }
+}
+
+/// Whether each flip-flop is on at a moment (say between button presses), to compare with another moment
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NetworkState {
+    on: BTreeMap<String, bool>,
+}
+
+impl NetworkState {
+    /// The flip-flops that are different in the other state, in name order, with whether each is on in it.
+    /// Flip-flops in only one of the states are left out.
+    pub fn diff<'a>(&self, other: &'a NetworkState) -> Vec<(&'a str, bool)> {
+        other
+            .on
+            .iter()
+            .filter(|(name, on)| self.on.get(*name).is_some_and(|was_on| was_on != *on))
+            .map(|(name, on)| (name.as_str(), *on))
+            .collect()
+    }
+}
+
+/// Index of a module, or of an output only ever sent to, in its [`Network`] - in name order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ModuleId(u16);
+
+impl ModuleId {
+    fn index(&self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// A pulse on its way from one module to another, small enough to copy so that queueing it doesn't allocate
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct QueuedPulse {
+    source: ModuleId,
+    destination: ModuleId,
+    pulse: Pulse,
+}
+
+const BUTTON: &str = "button";
+const BROADCASTER: &str = "broadcaster";
+
+/// The modules, looked up by the ids their names are interned to
+#[derive(Debug)]
+pub struct Network {
+    /// Every module and output (and the button) by id
+    names: Vec<String>,
+    /// By id, None for outputs that aren't modules
+    modules: Vec<Option<Module>>,
+    /// The ids of each module's outputs, by id
+    outputs: Vec<Vec<ModuleId>>,
+}
+
+impl Network {
+    /// Connect up the modules, priming each with the modules sending to it - conjunctions remembering a Low
+    /// from each of them
+    pub fn new(mut modules: Map<String, Module>) -> Result<Self, AError> {
+        let source_destinations: Vec<(String, String)> = modules
+            .iter()
+            .flat_map(|(name, module)| {
+                module
+                    .outputs()
+                    .iter()
+                    .map(|output| (name.clone(), output.clone()))
+            })
+            .collect();
+        source_destinations
+            .iter()
+            .for_each(|(source, destination)| match modules.get_mut(destination) {
+                Some(Module::FlipFlop { inputs, .. }) | Some(Module::Broadcast { inputs, .. }) => {
+                    inputs.insert(source.clone(), Pulse::NotSeen);
+                }
+                Some(Module::Conjunction { inputs, .. }) => {
+                    inputs.insert(source.clone(), Pulse::Low);
+                }
+                None => (),
+            });
+        let names: Vec<String> = source_destinations
+            .into_iter()
+            .flat_map(|(source, destination)| [source, destination])
+            .chain(modules.keys().cloned())
+            .chain([BUTTON.to_string(), BROADCASTER.to_string()])
+            .sorted()
+            .dedup()
+            .collect();
+        if names.len() > u16::MAX as usize {
+            return Err(anyhow!("Too many modules to number: {}", names.len()));
+        }
+        let id_of = |name: &String| ModuleId(names.binary_search(name).unwrap() as u16);
+        let outputs = names
+            .iter()
+            .map(|name| {
+                modules.get(name).map_or(vec![], |module| {
+                    module.outputs().iter().map(id_of).collect()
+                })
+            })
+            .collect();
+        let modules = names.iter().map(|name| modules.remove(name)).collect();
+        Ok(Network {
+            names,
+            modules,
+            outputs,
+        })
+    }
+
+    fn id(&self, name: &str) -> Option<ModuleId> {
+        self.names
+            .binary_search_by(|other| other.as_str().cmp(name))
+            .ok()
+            .map(|index| ModuleId(index as u16))
+    }
+
+    /// The modules by name, in name order
+    pub fn modules(&self) -> impl Iterator<Item = (&String, &Module)> {
+        self.names
+            .iter()
+            .zip(self.modules.iter())
+            .filter_map(|(name, module)| module.as_ref().map(|module| (name, module)))
+    }
+
+    pub fn module(&self, name: &str) -> Option<&Module> {
+        self.modules[self.id(name)?.index()].as_ref()
+    }
+
+    /// The flip-flops as they are now
+    pub fn snapshot(&self) -> NetworkState {
+        NetworkState {
+            on: self
+                .modules()
+                .filter_map(|(name, module)| match module {
+                    Module::FlipFlop { on, .. } => Some((name.clone(), *on)),
+                    _ => None,
+                })
+                .collect(),
+        }
+    }
+
+    /// The names of the modules sending to the named one, in order
+    pub fn inputs_of(&self, name: &str) -> Vec<&String> {
+        let Some(id) = self.id(name) else {
+            return vec![];
+        };
+        self.outputs
+            .iter()
+            .zip(self.names.iter())
+            .filter(|(outputs, _)| outputs.contains(&id))
+            .map(|(_, source)| source)
+            .collect()
+    }
+
+    /// Push the button, sending a low pulse into the broadcast.
+    ///
+    /// Each pulse from a source to a destination will be passed to the observation function along with a value
+    /// of type T (starting with the initial_value).  The observation function then returns another (or the same)
+    /// value of type T which will be passed to the observation function the next time it's called, similar to
+    /// a fold.
+    ///
+    pub fn push_button<T, F>(
+        &mut self,
+        initial_value: T,
+        observation_function: F,
+    ) -> (usize, usize, T)
+    where
+        F: Fn(T, &String, &Pulse, &String) -> T,
+    {
+        profile_span!("push_button");
+        let mut low_pulse_count = 0;
+        let mut high_pulse_count = 0;
+        let mut observation_value = initial_value;
+
+        let mut pulse_queue: VecDeque<QueuedPulse> = VecDeque::default();
+        //First send a low pulse to 'broadcaster'
+        pulse_queue.push_back(QueuedPulse {
+            source: self.id(BUTTON).unwrap(),
+            destination: self.id(BROADCASTER).unwrap(),
+            pulse: Pulse::Low,
+        });
+
+        while let Some(QueuedPulse {
+            source,
+            destination,
+            pulse,
+        }) = pulse_queue.pop_front()
+        {
+            match pulse {
+                Pulse::Low => {
+                    low_pulse_count += 1;
+                }
+                Pulse::High => {
+                    high_pulse_count += 1;
+                }
+                _ => (),
+            }
+            let source_name = &self.names[source.index()];
+            observation_value = observation_function(
+                observation_value,
+                source_name,
+                &pulse,
+                &self.names[destination.index()],
+            );
+            let Some(module) = self.modules[destination.index()].as_mut() else {
+                continue;
+            };
+            let send = |pulse_queue: &mut VecDeque<QueuedPulse>, pulse: Pulse| {
+                self.outputs[destination.index()].iter().for_each(|output| {
+                    pulse_queue.push_back(QueuedPulse {
+                        source: destination,
+                        destination: *output,
+                        pulse,
+                    })
+                });
+            };
+            //Remember the pulse from the source, giving the one it last sent
+            let remember = |inputs: &mut Map<String, Pulse>| match inputs.get_mut(source_name) {
+                Some(last) => Some(std::mem::replace(last, pulse)),
+                None => inputs.insert(source_name.clone(), pulse),
+            };
+            match module {
+                Module::Broadcast { inputs, .. } => {
+                    remember(inputs);
+                    //Same pulse to all outputs
+                    send(&mut pulse_queue, pulse);
+                }
+                Module::FlipFlop { on, inputs, .. } => {
+                    remember(inputs);
+                    //Ignore high pulses, flip on low pulse and send high if now on, or low if now off
+                    if matches!(pulse, Pulse::Low) {
+                        *on = !*on;
+                        let next_pulse = if *on { Pulse::High } else { Pulse::Low };
+                        send(&mut pulse_queue, next_pulse);
+                    }
+                }
+                Module::Conjunction {
+                    inputs,
+                    high_inputs,
+                    ..
+                } => {
+                    //Update memory for the input, and the count of highs if it's changed
+                    let previous = remember(inputs);
+                    match (previous == Some(Pulse::High), pulse == Pulse::High) {
+                        (false, true) => *high_inputs += 1,
+                        (true, false) => *high_inputs -= 1,
+                        _ => (),
+                    }
+                    let pulse = if *high_inputs == inputs.len() {
+                        Pulse::Low //If all were high, send a low
+                    } else {
+                        Pulse::High //otherwise send a high
+                    };
+                    send(&mut pulse_queue, pulse);
+                }
+            }
+        }
+        (low_pulse_count, high_pulse_count, observation_value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The first example, broadcaster -> a, b, c; %a -> b; %b -> c; %c -> inv; &inv -> a
+    fn example() -> Network {
+        let names = |names: &[&str]| names.iter().map(|name| name.to_string()).collect();
+        Network::new(Map::from_iter([
+            (
+                "broadcaster".to_string(),
+                Module::broadcast(names(&["a", "b", "c"])),
+            ),
+            ("a".to_string(), Module::flip_flop(names(&["b"]))),
+            ("b".to_string(), Module::flip_flop(names(&["c"]))),
+            ("c".to_string(), Module::flip_flop(names(&["inv"]))),
+            ("inv".to_string(), Module::conjunction(names(&["a"]))),
+        ]))
+        .unwrap()
+    }
+
+    #[test]
+    fn pulses_counted() {
+        let mut network = example();
+        assert_eq!(network.push_button((), |_, _, _, _| ()), (8, 4, ()));
+        let Some(Module::Conjunction { high_inputs, .. }) = network.module("inv") else {
+            panic!("inv isn't a conjunction");
+        };
+        assert_eq!(*high_inputs, 0);
+        assert_eq!(network.inputs_of("b"), vec!["a", "broadcaster"]);
+        assert!(network.inputs_of("broadcaster").is_empty());
+        let (low, high) = (0..999).fold((8, 4), |(low, high), _| {
+            let (more_low, more_high, _) = network.push_button((), |_, _, _, _| ());
+            (low + more_low, high + more_high)
+        });
+        assert_eq!(low * high, 32000000);
+    }
+
+    #[test]
+    fn flip_flop_changes_found() {
+        let mut network = example();
+        let before = network.snapshot();
+        assert_eq!(before.on.get("a"), Some(&false));
+        assert_eq!(before.on.get("inv"), None);
+        network.push_button((), |_, _, _, _| ());
+        // a, b and c all flip on, inv then sends a high to a which is ignored, then the lows from them flip
+        // them all back off
+        let after = network.snapshot();
+        assert!(before.diff(&after).is_empty());
+        assert_eq!(after, before);
+
+        let mut changed = NetworkState::default();
+        changed.on.insert("a".to_string(), true);
+        changed.on.insert("z".to_string(), true);
+        assert_eq!(before.diff(&changed), vec![("a", true)]);
+        assert_eq!(changed.diff(&before), vec![("a", false)]);
+    }
+}
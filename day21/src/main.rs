@@ -1,40 +1,47 @@
 use std::{
     collections::{HashSet, VecDeque},
-    fmt::Display,
     mem::swap,
 };
 
 use anyhow::anyhow;
-use processor::{adjacent_coords_cartesian, process, Cells, CellsBuilder};
+use processor::{
+    describe_if_requested, diagnostic,
+    frontier::AdaptiveFrontier,
+    history::Inspector,
+    part_requested,
+    polynomial::fit_polynomial,
+    process,
+    reachability::count_reachable_by_parity,
+    rings::ring,
+    tiles::{self, Tile},
+    CellChar, Cells, CellsBuilder, Direction, MetaCells, PuzzleConfig, PuzzleMetadata, Solver,
+};
 
 type AError = anyhow::Error;
 
-#[derive(Debug, Clone, Copy)]
-enum Tile {
-    Plot,
-    Rock,
-}
+type Coord = (usize, usize);
 
-impl Tile {
-    fn char_rep(&self) -> char {
-        match self {
-            Self::Plot => '.',
-            Self::Rock => '#',
-        }
-    }
+/// How to walk, from the part's config
+struct Walk {
+    starts: String,
+    total_steps: usize,
+    total_to_calculate: usize,
+    track_tiles: bool,
 }
 
-impl Display for Tile {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.char_rep())
+impl Walk {
+    fn from_config(config: &PuzzleConfig) -> Result<Walk, AError> {
+        let total_steps = config.get("total_steps")?;
+        Ok(Walk {
+            starts: config.get("starts")?,
+            total_steps,
+            total_to_calculate: config.get_or("total_to_calculate", total_steps)?,
+            track_tiles: config.get_or("track_tiles", false)?,
+        })
     }
 }
 
-type Coord = (usize, usize);
-
 struct LoadingState {
-    total_steps: usize,
-    total_to_calculate: usize,
     start: Option<Coord>,
     tiles: CellsBuilder<Tile>,
 }
@@ -42,31 +49,23 @@ struct LoadingState {
 type InitialState = LoadingState;
 
 struct LoadedState {
-    total_steps: usize,
-    total_to_calculate: usize,
-    start: Coord,
+    starts: Vec<Coord>,
     tiles: Cells<Tile>,
 }
 
 type ProcessedState = usize;
 type FinalResult = usize;
 
+/// The tiles the grid is drawn with
+const TILES: &str = ".#S";
+
 fn parse_line(mut state: InitialState, line: String) -> Result<InitialState, AError> {
     if !line.is_empty() {
-        state.tiles.new_line();
-        for c in line.chars() {
-            let (tile, was_start) = match c {
-                '.' => (Tile::Plot, false),
-                '#' => (Tile::Rock, false),
-                'S' => (Tile::Plot, true),
-                _ => {
-                    return Err(anyhow!(format!("Unrecognised tile: {c}")));
-                }
-            };
-            state.tiles.add_cell(tile)?;
-            if was_start {
-                state.start = state.tiles.current_cell();
-            }
+        state
+            .tiles
+            .add_line_with(&line, |c| tiles::allowed_tile(c, TILES))?;
+        if let Some(x) = line.find('S') {
+            state.start = state.tiles.current_cell().map(|(_, y)| (x, y));
         }
     }
     Ok(state)
@@ -74,50 +73,121 @@ fn parse_line(mut state: InitialState, line: String) -> Result<InitialState, AEr
 
 fn output_state(_state: &LoadedState) {
     // println!("=== State ===:");
-    // println!("Start: {:?}", state.start);
+    // println!("Starts: {:?}", state.starts);
     // println!("{}", state.tiles);
 }
 
-fn finalise_state(mut state: InitialState) -> Result<LoadedState, AError> {
-    let loaded = LoadedState {
-        total_steps: state.total_steps,
-        total_to_calculate: state.total_to_calculate,
-        start: state.start.ok_or_else(|| anyhow!("No start found"))?,
-        tiles: state.tiles.build_cells(Tile::Plot)?,
+/// Starting coordinates from a ';' separated list of `S` (the marked start), `corners` (all four corners of
+/// the grid) or `x,y` coordinates
+fn parse_starts(
+    starts: &str,
+    marked_start: Option<Coord>,
+    side_lengths: &(usize, usize),
+) -> Result<Vec<Coord>, AError> {
+    let (Some(max_x), Some(max_y)) = (side_lengths.0.checked_sub(1), side_lengths.1.checked_sub(1))
+    else {
+        return Err(anyhow!("No starts on an empty grid"));
     };
+    let mut coords: Vec<Coord> = vec![];
+    for start in starts.split(';').map(str::trim) {
+        match start {
+            "S" => coords.push(marked_start.ok_or_else(|| anyhow!("No start found"))?),
+            "corners" => coords.extend([(0, 0), (max_x, 0), (0, max_y), (max_x, max_y)]),
+            _ => {
+                let (x, y) = start.split_once(',').ok_or_else(|| {
+                    anyhow!("Invalid start '{start}', expected S, corners or x,y")
+                })?;
+                let coord = (x.trim().parse()?, y.trim().parse()?);
+                if coord.0 > max_x || coord.1 > max_y {
+                    return Err(anyhow!("Start {coord:?} is outside of the grid"));
+                }
+                coords.push(coord);
+            }
+        }
+    }
+    Ok(coords)
+}
+
+fn finalise_state(mut state: InitialState, walk: &Walk) -> Result<LoadedState, AError> {
+    let tiles = state.tiles.build_cells(Tile::Empty)?;
+    let starts = parse_starts(&walk.starts, state.start, &tiles.side_lengths)?;
+    if let Some(rock) = starts
+        .iter()
+        .find(|(x, y)| matches!(tiles.get(*x, *y), Ok(Tile::Wall)))
+    {
+        return Err(anyhow!("Start {rock:?} is on a rock"));
+    }
+    diagnostic("tiles", tiles.value_counts());
+    let loaded = LoadedState { starts, tiles };
     output_state(&loaded);
     Ok(loaded)
 }
 
 fn make_step(tiles: &Cells<Tile>, current_position: &Coord, next_positions: &mut HashSet<Coord>) {
-    adjacent_coords_cartesian(current_position, &tiles.side_lengths)
+    Direction::ALL
         .iter()
+        .filter_map(|direction| tiles.step(*current_position, *direction))
         .for_each(|(candidate_x, candidate_y)| {
-            let tile = tiles.get(*candidate_x, *candidate_y).unwrap();
-            if matches!(tile, Tile::Plot) {
-                next_positions.insert((*candidate_x, *candidate_y));
+            let tile = &tiles[(candidate_x, candidate_y)];
+            if matches!(tile, Tile::Empty) {
+                next_positions.insert((candidate_x, candidate_y));
             }
         })
 }
 
-fn perform_walk(state: &LoadedState) -> usize {
+/// The garden with the positions reached shown as `O`
+fn positions_view(tiles: &Cells<Tile>, positions: &HashSet<Coord>) -> Cells<char> {
+    let mut view = Cells::with_dimension(tiles.side_lengths.0, tiles.side_lengths.1, '.');
+    tiles.iter().for_each(|((x, y), tile)| {
+        view[(x, y)] = if positions.contains(&(x, y)) {
+            'O'
+        } else {
+            tile.to_char()
+        };
+    });
+    view
+}
+
+fn perform_walk(
+    state: &LoadedState,
+    total_steps: usize,
+    inspector: &mut Inspector<Cells<char>>,
+) -> usize {
     let mut current_positions: HashSet<Coord> = HashSet::default();
     let mut next_positions: HashSet<Coord> = HashSet::default();
-    //start at start
-    current_positions.insert(state.start);
+    //start at the starts
+    current_positions.extend(state.starts.iter().copied());
+    inspector.record_with(0, || positions_view(&state.tiles, &current_positions));
     //make the steps
-    for _i in 0..state.total_steps {
+    for i in 0..total_steps {
         current_positions
             .iter()
             .for_each(|position| make_step(&state.tiles, position, &mut next_positions));
         swap(&mut current_positions, &mut next_positions);
         next_positions.clear();
+        inspector.record_with(i + 1, || positions_view(&state.tiles, &current_positions));
     }
     current_positions.len()
 }
 
-fn perform_processing(state: LoadedState) -> Result<ProcessedState, AError> {
-    Ok(perform_walk(&state))
+fn perform_processing(
+    state: LoadedState,
+    walk: &Walk,
+    inspector: &mut Inspector<Cells<char>>,
+) -> Result<ProcessedState, AError> {
+    //distances only tell us what is reachable if all of the starts are the same parity, otherwise walk it,
+    //as also when inspecting so that there are steps to look at
+    let mut start_parities = state.starts.iter().map(|(x, y)| (x + y) % 2);
+    let first_parity = start_parities.next();
+    if !inspector.is_on() && start_parities.all(|parity| Some(parity) == first_parity) {
+        let counts =
+            count_reachable_by_parity(&state.tiles, &state.starts, walk.total_steps, |tile| {
+                matches!(tile, Tile::Empty)
+            });
+        Ok(counts.reachable_in(walk.total_steps))
+    } else {
+        Ok(perform_walk(&state, walk.total_steps, inspector))
+    }
 }
 
 fn calc_result(state: ProcessedState) -> Result<FinalResult, AError> {
@@ -128,14 +198,14 @@ type Coord2 = (isize, isize);
 
 fn try_make_step(
     tiles: &Cells<Tile>,
-    next_positions: &mut HashSet<Coord2>,
+    next_positions: &mut AdaptiveFrontier,
     candidate_x: isize,
     candidate_y: isize,
 ) {
     //get the cell within the bounds of the tiles
     let (x, y) = tiles.get_position_in_bounds(candidate_x, candidate_y);
-    let tile = tiles.get(x, y).unwrap();
-    if matches!(tile, Tile::Plot) {
+    let tile = &tiles[(x, y)];
+    if matches!(tile, Tile::Empty) {
         next_positions.insert((candidate_x, candidate_y));
     }
 }
@@ -143,7 +213,7 @@ fn try_make_step(
 fn make_step_2(
     tiles: &Cells<Tile>,
     current_position: &Coord2,
-    next_positions: &mut HashSet<Coord2>,
+    next_positions: &mut AdaptiveFrontier,
 ) {
     let (current_x, current_y) = *current_position;
     //North
@@ -156,33 +226,125 @@ fn make_step_2(
     try_make_step(tiles, next_positions, current_x - 1, current_y);
 }
 
-fn perform_walk_2(state: &LoadedState) -> Vec<isize> {
-    let mut lengths = Vec::with_capacity(state.total_steps);
-    let mut current_positions: HashSet<Coord2> = HashSet::default();
-    let mut next_positions: HashSet<Coord2> = HashSet::default();
-    //start at start
-    let start = (state.start.0 as isize, state.start.1 as isize);
-    current_positions.insert(start);
+/// What happened within one copy of the garden during part 2's walk
+#[derive(Debug, Default)]
+struct TileSummary {
+    first_reached: Option<usize>,
+    last_newly_reached: usize,
+    /// Plots first reached on even and odd steps, i.e. those occupied on every even or odd step after
+    reached_by_parity: [usize; 2],
+}
+
+type TileTracker = MetaCells<bool, TileSummary>;
+
+fn track_step(tracker: &mut TileTracker, positions: &AdaptiveFrontier, step: usize) {
+    positions.iter().for_each(|position| {
+        let reached = tracker.get_mut(position);
+        if !*reached {
+            *reached = true;
+            let summary = tracker.summary_mut(position);
+            summary.first_reached.get_or_insert(step);
+            summary.last_newly_reached = step;
+            summary.reached_by_parity[step % 2] += 1;
+        }
+    });
+}
+
+fn output_tile_summaries(tracker: &TileTracker, total_steps: usize) {
+    let max_radius = tracker
+        .tiles()
+        .map(|((x, y), _)| x.unsigned_abs().max(y.unsigned_abs()))
+        .max()
+        .unwrap_or(0);
+    for radius in 0..=max_radius {
+        for tile_coord in ring((0, 0), radius) {
+            if let Some(tile) = tracker.tile(tile_coord) {
+                let summary = &tile.summary;
+                println!(
+                    "Tile {tile_coord:?}: {} plots reached, first at step {}, {}",
+                    summary.reached_by_parity[total_steps % 2],
+                    summary.first_reached.unwrap_or_default(),
+                    if summary.last_newly_reached + 1 < total_steps {
+                        format!("saturated at step {}", summary.last_newly_reached)
+                    } else {
+                        "still filling".to_string()
+                    }
+                );
+            }
+        }
+    }
+}
+
+fn perform_walk_2(state: &LoadedState, walk: &Walk) -> Vec<isize> {
+    let mut lengths = Vec::with_capacity(walk.total_steps);
+    // the frontiers swap every step, so each always holds the positions after an even or an odd number of steps
+    let mut current_positions = AdaptiveFrontier::new("even steps");
+    let mut next_positions = AdaptiveFrontier::new("odd steps");
+    //start at the starts
+    state.starts.iter().for_each(|(x, y)| {
+        current_positions.insert((*x as isize, *y as isize));
+    });
+    let mut tracker = walk.track_tiles.then(|| {
+        let (width, height) = state.tiles.side_lengths;
+        TileTracker::new(Cells::with_dimension(width, height, false))
+    });
+    if let Some(tracker) = tracker.as_mut() {
+        track_step(tracker, &current_positions, 0);
+    }
     //make the steps
-    for i in 0..state.total_steps {
+    for i in 0..walk.total_steps {
         current_positions
             .iter()
-            .for_each(|position| make_step_2(&state.tiles, position, &mut next_positions));
+            .for_each(|position| make_step_2(&state.tiles, &position, &mut next_positions));
+        next_positions.rebalance();
         swap(&mut current_positions, &mut next_positions);
         next_positions.clear();
         lengths.push(current_positions.len() as isize);
+        if let Some(tracker) = tracker.as_mut() {
+            track_step(tracker, &current_positions, i + 1);
+        }
         if (i + 1) % 50 == 0 {
             println!("Calculated to {} ({})", i + 1, current_positions.len())
         }
     }
+    for frontier in [&current_positions, &next_positions] {
+        diagnostic(
+            &format!("{} frontier stats", frontier.name()),
+            format!("{:?}", frontier.stats()),
+        );
+    }
+    if let Some(tracker) = tracker {
+        output_tile_summaries(&tracker, walk.total_steps);
+    }
     lengths
 }
 
-type ProcessedState2 = (usize, Vec<isize>);
+/// Steps to extrapolate to, width of the garden and the plots reachable after each step walked
+type ProcessedState2 = (usize, usize, Vec<isize>);
+
+fn perform_processing_2(state: LoadedState, walk: &Walk) -> Result<ProcessedState2, AError> {
+    let first_n = perform_walk_2(&state, walk);
+    Ok((walk.total_to_calculate, state.tiles.side_lengths.0, first_n))
+}
 
-fn perform_processing_2(state: LoadedState) -> Result<ProcessedState2, AError> {
-    let first_n = perform_walk_2(&state);
-    Ok((state.total_to_calculate, first_n))
+/// Reachable plots after the steps, from a quadratic in the number of gardens crossed, fitted to the plots
+/// reachable at the same point in each of the first few gardens (see [`fit_polynomial`]).  This needs a square
+/// garden and the walk to have gone far enough in to the gardens to both fit and check the quadratic.
+fn extrapolate_quadratic(
+    values: &[isize],
+    garden_width: usize,
+    num_required: usize,
+) -> Result<usize, AError> {
+    let offset = num_required % garden_width;
+    let samples: Vec<(i128, i128)> = (0..)
+        .map(|gardens| (gardens, offset + gardens * garden_width))
+        .take_while(|(_, steps)| *steps <= values.len())
+        .filter(|(_, steps)| *steps > 0)
+        .map(|(gardens, steps)| (gardens as i128, values[steps - 1] as i128))
+        .collect();
+    let quadratic = fit_polynomial(&samples, 2)?;
+    let plots = quadratic.evaluate_integer((num_required / garden_width) as i128)?;
+    Ok(plots as usize)
 }
 
 fn get_differences(nums: &[isize]) -> Vec<isize> {
@@ -197,7 +359,7 @@ fn get_differences(nums: &[isize]) -> Vec<isize> {
 
 const NUM_REPEAT_CHECKS: usize = 1;
 
-fn calculate_repeat_size(second_order_differences: &Vec<isize>) -> (usize, Vec<isize>) {
+fn calculate_repeat_size(second_order_differences: &[isize]) -> (usize, Vec<isize>) {
     let end_repeat_index = second_order_differences.len() - 1;
     let mut candidate_repeat_size = 0usize;
     'outer: loop {
@@ -281,46 +443,118 @@ fn calc_result_2_internal(values: Vec<isize>, num_required: usize) -> Result<Fin
 }
 
 fn calc_result_2(state: ProcessedState2) -> Result<FinalResult, AError> {
-    calc_result_2_internal(state.1, state.0)
+    let (num_required, garden_width, values) = state;
+    match extrapolate_quadratic(&values, garden_width, num_required) {
+        Ok(plots) => return Ok(plots),
+        Err(e) => println!("No quadratic fit ({e}), looking for a repeat instead"),
+    }
+    calc_result_2_internal(values, num_required)
 }
 
-fn main() {
-    //let (total_steps, total_steps_2, total_to_calculate_2, file) = (6, 100, 5000, "test-input.txt");
-    //let (total_steps, file) = ( "test-input2.txt");
-    let (total_steps, total_steps_2, total_to_calculate_2, file) = (64, 500, 26501365, "input.txt");
+struct Day21;
+
+impl Solver for Day21 {
+    fn metadata() -> PuzzleMetadata {
+        PuzzleMetadata::new(21, "Step Counter")
+            .with_parameter(
+                "starts",
+                "Where to start, ';' separated S (the marked start), corners or x,y",
+                "S",
+            )
+            .with_parameter("total_steps", "Steps to take from the starts", 64)
+            .with_parameter(
+                "part2.total_steps",
+                "Steps to simulate before extrapolating",
+                500,
+            )
+            .with_parameter(
+                "part2.total_to_calculate",
+                "Steps to extrapolate the reachable plots to",
+                26501365,
+            )
+            .with_parameter(
+                "track_tiles",
+                "Summarise each copy of the garden reached by part 2's walk",
+                false,
+            )
+            .with_inspection()
+    }
+}
 
-    fn initial_state(total_steps: usize, total_to_calculate: usize) -> LoadingState {
+fn main() {
+    describe_if_requested::<Day21>();
+    //let config = PuzzleConfig::new("test-input.txt")
+    //    .with("total_steps", 6)
+    //    .with("part2.total_steps", 100)
+    //    .with("part2.total_to_calculate", 5000);
+    let config = Day21::metadata()
+        .load_config("input.txt")
+        .expect("Invalid configuration");
+
+    fn initial_state() -> LoadingState {
         LoadingState {
-            total_steps,
-            total_to_calculate,
             start: None,
             tiles: CellsBuilder::new_empty(),
         }
     }
 
-    let result1 = process(
-        file,
-        initial_state(total_steps, total_steps),
-        parse_line,
-        finalise_state,
-        perform_processing,
-        calc_result,
-    );
-    match result1 {
-        Ok(res) => println!("Result 1: {:?}", res),
-        Err(e) => println!("Error on 1: {}", e),
+    if part_requested(1) {
+        let config1 = config.for_part(1);
+        let mut inspector = Inspector::from_config(&config1).expect("Invalid inspection");
+        let result1 = Walk::from_config(&config1).and_then(|walk| {
+            process(
+                &config1.file,
+                initial_state(),
+                parse_line,
+                |state| finalise_state(state, &walk),
+                |state| perform_processing(state, &walk, &mut inspector),
+                calc_result,
+            )
+        });
+        inspector.report();
+        match result1 {
+            Ok(res) => println!("Result 1: {:?}", res),
+            Err(e) => println!("Error on 1: {}", e),
+        }
     }
 
-    let result2 = process(
-        file,
-        initial_state(total_steps_2, total_to_calculate_2),
-        parse_line,
-        finalise_state,
-        perform_processing_2,
-        calc_result_2,
-    );
-    match result2 {
-        Ok(res) => println!("Result 2: {:?}", res),
-        Err(e) => println!("Error on 2: {}", e),
+    if part_requested(2) {
+        let config2 = config.for_part(2);
+        let result2 = Walk::from_config(&config2).and_then(|walk| {
+            process(
+                &config2.file,
+                initial_state(),
+                parse_line,
+                |state| finalise_state(state, &walk),
+                |state| perform_processing_2(state, &walk),
+                calc_result_2,
+            )
+        });
+        match result2 {
+            Ok(res) => println!("Result 2: {:?}", res),
+            Err(e) => println!("Error on 2: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn starts_parsed() {
+        let sides = (11, 11);
+        assert_eq!(
+            parse_starts("S", Some((5, 5)), &sides).unwrap(),
+            vec![(5, 5)]
+        );
+        assert_eq!(
+            parse_starts("corners; 3,4", None, &sides).unwrap(),
+            vec![(0, 0), (10, 0), (0, 10), (10, 10), (3, 4)]
+        );
+        assert!(parse_starts("S", None, &sides).is_err());
+        assert!(parse_starts("11,0", None, &sides).is_err());
+        assert!(parse_starts("middle", None, &sides).is_err());
+        assert!(parse_starts("corners", None, &(0, 0)).is_err());
     }
 }
@@ -1,17 +1,22 @@
 use std::{
     cmp::Ordering,
-    collections::{BTreeMap, HashSet, VecDeque},
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
     fmt::Display,
 };
 
-use once_cell::sync::Lazy;
-use processor::{process, read_next, Coord3};
+use processor::{
+    describe_if_requested,
+    parse::{integer, parse_complete, preceded, separated_list, tag},
+    part_requested, process_with_context, profile_span,
+    spatial_hash::{BBox, SpatialHash},
+    start_profiling, Coord3, Cursor, LineContext, PuzzleMetadata, Solver,
+};
 
 #[derive(Debug, Clone)]
 struct Brick {
     id: usize,
-    corner1: Coord3,
-    corner2: Coord3,
+    corner1: Coord3<usize>,
+    corner2: Coord3<usize>,
     supported_by_ids: HashSet<usize>, //ids of bricks that are supporting this
     supporting_ids: HashSet<usize>,   //ids of bricks that this is supporting
 }
@@ -41,12 +46,35 @@ impl Brick {
         self.corner1.z.max(self.corner2.z)
     }
 
-    fn overlaps_x_y(&self, other: &Brick) -> bool {
-        self.min_x() <= other.max_x()
-            && self.max_x() >= other.min_x()
-            && self.min_y() <= other.max_y()
-            && self.max_y() >= other.min_y()
+    fn bbox_x_y(&self) -> BBox {
+        (
+            (self.min_x() as f64, self.min_y() as f64),
+            (self.max_x() as f64, self.max_y() as f64),
+        )
+    }
+}
+
+/// A, B, ... Z, AA, AB, ... as the bricks are labelled in the puzzle's example, from the brick's id (the index
+/// of its line)
+fn label(id: usize) -> String {
+    let mut label = vec![];
+    let mut remaining = id + 1;
+    while remaining > 0 {
+        remaining -= 1;
+        label.push((b'A' + (remaining % 26) as u8) as char);
+        remaining /= 26;
+    }
+    label.iter().rev().collect()
+}
+
+fn id_of(label: &str) -> Option<usize> {
+    if label.is_empty() || !label.chars().all(|c| c.is_ascii_uppercase()) {
+        return None;
     }
+    let number = label
+        .bytes()
+        .fold(0, |number, c| number * 26 + (c - b'A') as usize + 1);
+    Some(number - 1)
 }
 
 impl Display for Brick {
@@ -63,21 +91,25 @@ type LoadedState = InitialState;
 type ProcessedState = BTreeMap<usize, Brick>;
 type FinalResult = usize;
 
-static DELIMITERS: Lazy<HashSet<char>> = Lazy::new(|| HashSet::from([',', '~']));
+/// x,y,z
+fn coord3(cursor: &mut Cursor<'_>) -> Result<Coord3<usize>, AError> {
+    let values = separated_list(integer::<usize>, tag(","))(cursor)?;
+    match values[..] {
+        [x, y, z] => Ok(Coord3::new(x, y, z)),
+        _ => Err(AError::msg(format!("Expected 3 values but got {values:?}"))),
+    }
+}
 
-fn parse_line(mut state: InitialState, line: String) -> Result<InitialState, AError> {
+fn parse_line(mut state: InitialState, context: LineContext) -> Result<InitialState, AError> {
+    let line = context.raw;
     if !line.is_empty() {
-        let mut chars = line.chars();
-        let (x1, _) = read_next::<usize>(&mut chars, &DELIMITERS)?;
-        let (y1, _) = read_next::<usize>(&mut chars, &DELIMITERS)?;
-        let (z1, _) = read_next::<usize>(&mut chars, &DELIMITERS)?;
-        let (x2, _) = read_next::<usize>(&mut chars, &DELIMITERS)?;
-        let (y2, _) = read_next::<usize>(&mut chars, &DELIMITERS)?;
-        let (z2, _) = read_next::<usize>(&mut chars, &DELIMITERS)?;
+        let (corner1, corner2) = parse_complete(&line, |cursor| {
+            Ok((coord3(cursor)?, preceded(tag("~"), coord3)(cursor)?))
+        })?;
         state.push(Brick {
-            id: state.len(),
-            corner1: Coord3::new(x1, y1, z1),
-            corner2: Coord3::new(x2, y2, z2),
+            id: context.index,
+            corner1,
+            corner2,
             supporting_ids: HashSet::default(),
             supported_by_ids: HashSet::default(),
         })
@@ -106,54 +138,60 @@ fn finalise_state(mut state: InitialState) -> Result<LoadedState, AError> {
     Ok(state)
 }
 
-fn place_brick(brick: &Brick, stacked: &mut BTreeMap<usize, Brick>) {
+/// Bricks are mostly a few cubes long
+const CELL_SIZE: f64 = 2.0;
+
+/// Ids of the bricks that each brick overlaps in x and y, i.e. those it could land on or be landed on by
+fn overlapping_x_y<'a>(bricks: impl IntoIterator<Item = &'a Brick>) -> HashMap<usize, Vec<usize>> {
+    let mut hash = SpatialHash::new(CELL_SIZE);
+    bricks
+        .into_iter()
+        .for_each(|brick| hash.insert(brick.bbox_x_y(), brick.id));
+    let mut overlapping: HashMap<usize, Vec<usize>> = HashMap::default();
+    for (first, second) in hash.candidate_pairs() {
+        overlapping.entry(first).or_default().push(second);
+        overlapping.entry(second).or_default().push(first);
+    }
+    overlapping
+}
+
+fn place_brick(brick: &Brick, overlapping: &[usize], stacked: &mut BTreeMap<usize, Brick>) {
     //previous bricks will be stacked 'lowest' to highest. See if we overlap any other bricks
     //if we overlap then we have to put our brick above the other brick (brick.max_z + 1)... otherwise
     //we can put the brick at the bottom (z=1)
-    let (max_z, supporting_bricks) = stacked
-        .values()
-        .filter(|other| brick.overlaps_x_y(other))
-        .fold(
-            (0, HashSet::default()),
-            |(max_z_so_far, mut supporting), other| {
-                match max_z_so_far.cmp(&other.max_z()) {
-                    Ordering::Equal => {
-                        //at the same level, this and others are supporting -> add to the supporting bricks
-                        supporting.insert(other.id);
-                        (max_z_so_far, supporting)
-                    }
-                    Ordering::Less => {
-                        //new one is higher -> this will be supporting instead of the other ones
-                        supporting.clear();
-                        supporting.insert(other.id);
-                        (other.max_z(), supporting)
-                    }
-                    Ordering::Greater => {
-                        //overlapping but another higher is supporting -> this one can be discounted
-                        (max_z_so_far, supporting)
-                    }
+    let (max_z, supporting_bricks) = overlapping.iter().filter_map(|id| stacked.get(id)).fold(
+        (0, HashSet::default()),
+        |(max_z_so_far, mut supporting), other| {
+            match max_z_so_far.cmp(&other.max_z()) {
+                Ordering::Equal => {
+                    //at the same level, this and others are supporting -> add to the supporting bricks
+                    supporting.insert(other.id);
+                    (max_z_so_far, supporting)
                 }
-            },
-        );
+                Ordering::Less => {
+                    //new one is higher -> this will be supporting instead of the other ones
+                    supporting.clear();
+                    supporting.insert(other.id);
+                    (other.max_z(), supporting)
+                }
+                Ordering::Greater => {
+                    //overlapping but another higher is supporting -> this one can be discounted
+                    (max_z_so_far, supporting)
+                }
+            }
+        },
+    );
     //update the supporting_ids on the bricks that are supporting this one
     supporting_bricks.iter().for_each(|id| {
         let other = stacked.get_mut(id).unwrap();
         other.supporting_ids.insert(brick.id);
     });
     //and add the new stacked brick at its new level
-    let z_adjustment = brick.min_z() - max_z - 1;
+    let z_adjustment = Coord3::new(0, 0, brick.min_z() - max_z - 1);
     let stacked_brick = Brick {
         id: brick.id,
-        corner1: Coord3::new(
-            brick.corner1.x,
-            brick.corner1.y,
-            brick.corner1.z - z_adjustment,
-        ),
-        corner2: Coord3::new(
-            brick.corner2.x,
-            brick.corner2.y,
-            brick.corner2.z - z_adjustment,
-        ),
+        corner1: brick.corner1 - z_adjustment,
+        corner2: brick.corner2 - z_adjustment,
         supported_by_ids: supporting_bricks,
         supporting_ids: HashSet::default(),
     };
@@ -161,16 +199,119 @@ fn place_brick(brick: &Brick, stacked: &mut BTreeMap<usize, Brick>) {
 }
 
 fn perform_processing(state: LoadedState) -> Result<ProcessedState, AError> {
+    profile_span!("settle_bricks");
     //take each brick (assuming that we are dealing with the lowest first)
     //and try to place them as close to the bottom as possible according to the floor (z > 0)
     //and any other bricks
+    let overlapping = overlapping_x_y(&state);
     let mut stacked: BTreeMap<usize, Brick> = BTreeMap::default();
     for brick in state {
-        place_brick(&brick, &mut stacked);
+        let others = overlapping.get(&brick.id).map_or(&[][..], Vec::as_slice);
+        place_brick(&brick, others, &mut stacked);
+    }
+    Ok(stacked)
+}
+
+/// Check that the bricks settled properly: none overlap, each is on the floor or on at least one brick exactly
+/// one below it, and each brick supports exactly those that it's supported by
+fn check_settled(stacked: &ProcessedState) -> Result<(), AError> {
+    let brick = |id: &usize| {
+        stacked
+            .get(id)
+            .ok_or_else(|| AError::msg(format!("No settled brick {}", label(*id))))
+    };
+    let overlapping = overlapping_x_y(stacked.values());
+    for this in stacked.values() {
+        let others = overlapping.get(&this.id).into_iter().flatten();
+        for other in others.filter(|id| **id > this.id).map(brick) {
+            let other = other?;
+            if this.min_z() <= other.max_z() && other.min_z() <= this.max_z() {
+                return Err(AError::msg(format!(
+                    "Bricks {} {this} and {} {other} overlap",
+                    label(this.id),
+                    label(other.id)
+                )));
+            }
+        }
+    }
+    for this in stacked.values() {
+        if this.min_z() > 1 && this.supported_by_ids.is_empty() {
+            return Err(AError::msg(format!(
+                "Brick {} {this} is floating",
+                label(this.id)
+            )));
+        }
+        for supporter in this.supported_by_ids.iter().map(brick) {
+            let supporter = supporter?;
+            if supporter.max_z() + 1 != this.min_z() {
+                return Err(AError::msg(format!(
+                    "Brick {} {this} is supported by {} {supporter} which isn't just below it",
+                    label(this.id),
+                    label(supporter.id)
+                )));
+            }
+            if !supporter.supporting_ids.contains(&this.id) {
+                return Err(AError::msg(format!(
+                    "Brick {} is supported by {} which doesn't support it",
+                    label(this.id),
+                    label(supporter.id)
+                )));
+            }
+        }
+        for supported in this.supporting_ids.iter().map(brick) {
+            let supported = supported?;
+            if !supported.supported_by_ids.contains(&this.id) {
+                return Err(AError::msg(format!(
+                    "Brick {} supports {} which isn't supported by it",
+                    label(this.id),
+                    label(supported.id)
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Settle the bricks, checking the result if asked to
+fn settle(state: LoadedState, check: bool) -> Result<ProcessedState, AError> {
+    let stacked = perform_processing(state)?;
+    if check {
+        check_settled(&stacked)?;
     }
     Ok(stacked)
 }
 
+fn labels(ids: &HashSet<usize>) -> String {
+    if ids.is_empty() {
+        return "none".to_string();
+    }
+    let mut ids: Vec<&usize> = ids.iter().collect();
+    ids.sort();
+    ids.iter()
+        .map(|id| label(**id))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// What's holding up and held up by each of the ',' separated brick labels once they've settled
+fn query_bricks(stacked: &ProcessedState, query: &str) -> Result<Vec<String>, AError> {
+    query
+        .split(',')
+        .map(str::trim)
+        .filter(|label| !label.is_empty())
+        .map(|queried| {
+            let brick = id_of(queried)
+                .and_then(|id| stacked.get(&id))
+                .ok_or_else(|| AError::msg(format!("No brick labelled '{queried}'")))?;
+            Ok(format!(
+                "{queried} {brick}: supported by {}, supporting {}",
+                labels(&brick.supported_by_ids),
+                labels(&brick.supporting_ids)
+            ))
+        })
+        .collect()
+}
+
 fn calc_result(state: ProcessedState) -> Result<FinalResult, AError> {
     let mut num_can_be_disintegrated = 0usize;
     'outer: for brick in state.values() {
@@ -222,34 +363,160 @@ fn calc_result_2(state: ProcessedState) -> Result<FinalResult, AError> {
     Ok(total_number)
 }
 
+struct Day22;
+
+impl Solver for Day22 {
+    fn metadata() -> PuzzleMetadata {
+        PuzzleMetadata::new(22, "Sand Slabs")
+            .with_parameter(
+                "query",
+                "',' separated labels (A, B, ... by line) of settled bricks to describe the supports of",
+                "",
+            )
+            .with_parameter(
+                "check",
+                "Check that the settled bricks don't overlap and that their supports are consistent",
+                false,
+            )
+    }
+}
+
 fn main() {
-    //let file = "test-input.txt";
-    //let file = "test-input2.txt";
-    let file = "input.txt";
-
-    let result1 = process(
-        file,
-        Vec::new(),
-        parse_line,
-        finalise_state,
-        perform_processing,
-        calc_result,
-    );
-    match result1 {
-        Ok(res) => println!("Result 1: {:?}", res),
-        Err(e) => println!("Error on 1: {}", e),
-    }
-
-    let result2 = process(
-        file,
-        Vec::new(),
-        parse_line,
-        finalise_state,
-        perform_processing,
-        calc_result_2,
-    );
-    match result2 {
-        Ok(res) => println!("Result 2: {:?}", res),
-        Err(e) => println!("Error on 2: {}", e),
+    describe_if_requested::<Day22>();
+    let _profiling = start_profiling();
+    //let config = PuzzleConfig::new("test-input.txt").with("query", "A,B,G");
+    let config = Day22::metadata()
+        .load_config("input.txt")
+        .expect("Invalid configuration");
+    let file = &config.file;
+    let query: String = config.get("query").expect("Invalid query");
+    let check: bool = config.get("check").expect("Invalid check");
+
+    if part_requested(1) {
+        let result1 = process_with_context(
+            file,
+            Vec::new(),
+            parse_line,
+            finalise_state,
+            |state| settle(state, check),
+            |stacked| {
+                query_bricks(&stacked, &query)?
+                    .iter()
+                    .for_each(|line| println!("{line}"));
+                calc_result(stacked)
+            },
+        );
+        match result1 {
+            Ok(res) => println!("Result 1: {:?}", res),
+            Err(e) => println!("Error on 1: {}", e),
+        }
+    }
+
+    if part_requested(2) {
+        let result2 = process_with_context(
+            file,
+            Vec::new(),
+            parse_line,
+            finalise_state,
+            |state| settle(state, check),
+            calc_result_2,
+        );
+        match result2 {
+            Ok(res) => println!("Result 2: {:?}", res),
+            Err(e) => println!("Error on 2: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn labels_round_trip() {
+        assert_eq!(label(0), "A");
+        assert_eq!(label(6), "G");
+        assert_eq!(label(25), "Z");
+        assert_eq!(label(26), "AA");
+        assert_eq!(label(27 * 26), "AAA");
+        (0..1000).for_each(|id| assert_eq!(id_of(&label(id)), Some(id)));
+        assert_eq!(id_of("a"), None);
+        assert_eq!(id_of(""), None);
+    }
+
+    fn settled_example() -> ProcessedState {
+        process_with_context(
+            "test-input.txt",
+            Vec::new(),
+            parse_line,
+            finalise_state,
+            |state| settle(state, true),
+            Ok,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn example_supports_by_label() {
+        let stacked = settled_example();
+        assert_eq!(
+            query_bricks(&stacked, "A, D,G").unwrap(),
+            vec![
+                "A (1,0,1)~(1,2,1): supported by none, supporting B, C",
+                "D (0,0,3)~(0,2,3): supported by B, C, supporting F",
+                "G (1,1,5)~(1,1,6): supported by F, supporting none",
+            ]
+        );
+        assert_eq!(
+            query_bricks(&stacked, "H").unwrap_err().to_string(),
+            "No brick labelled 'H'"
+        );
+    }
+
+    #[test]
+    fn broken_settling_caught() {
+        let check = |change: fn(&mut ProcessedState)| {
+            let mut stacked = settled_example();
+            change(&mut stacked);
+            check_settled(&stacked).unwrap_err().to_string()
+        };
+        // G dropped on to F
+        assert_eq!(
+            check(|stacked| {
+                let g = stacked.get_mut(&6).unwrap();
+                (g.corner1.z, g.corner2.z) = (4, 5);
+            }),
+            "Bricks F (0,1,4)~(2,1,4) and G (1,1,4)~(1,1,5) overlap"
+        );
+        // G lifted off F
+        assert_eq!(
+            check(|stacked| {
+                let g = stacked.get_mut(&6).unwrap();
+                (g.corner1.z, g.corner2.z) = (7, 8);
+            }),
+            "Brick G (1,1,7)~(1,1,8) is supported by F (0,1,4)~(2,1,4) which isn't just below it"
+        );
+        // G and F forgetting each other, leaving G in the air
+        assert_eq!(
+            check(|stacked| {
+                stacked.get_mut(&6).unwrap().supported_by_ids.clear();
+                stacked.get_mut(&5).unwrap().supporting_ids.clear();
+            }),
+            "Brick G (1,1,5)~(1,1,6) is floating"
+        );
+        // B forgetting that it's holding up D
+        assert_eq!(
+            check(|stacked| {
+                stacked.get_mut(&1).unwrap().supporting_ids.remove(&3);
+            }),
+            "Brick D is supported by B which doesn't support it"
+        );
+        // F claiming to hold up A
+        assert_eq!(
+            check(|stacked| {
+                stacked.get_mut(&5).unwrap().supporting_ids.insert(0);
+            }),
+            "Brick F supports A which isn't supported by it"
+        );
     }
 }
@@ -1,65 +1,15 @@
-use std::{
-    collections::{HashMap, HashSet, VecDeque},
-    fmt::Display,
-    mem::swap,
-    time,
-};
+use std::{collections::HashSet, mem::swap, time};
 
 use anyhow::anyhow;
-use processor::{process, Cells, CellsBuilder};
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-enum Direction {
-    North,
-    East,
-    South,
-    West,
-}
-
-impl Direction {
-    fn opposite(&self) -> Direction {
-        match self {
-            Direction::North => Direction::South,
-            Direction::East => Direction::West,
-            Direction::South => Direction::North,
-            Direction::West => Direction::East,
-        }
-    }
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum Tile {
-    Path,
-    Forest,
-    Slope { direction: Direction },
-}
-
-impl Tile {
-    fn char_rep(&self) -> char {
-        match self {
-            Tile::Path => '.',
-            Tile::Forest => '#',
-            Tile::Slope {
-                direction: Direction::North,
-            } => '^',
-            Tile::Slope {
-                direction: Direction::East,
-            } => '>',
-            Tile::Slope {
-                direction: Direction::South,
-            } => 'v',
-            Tile::Slope {
-                direction: Direction::West,
-            } => '<',
-        }
-    }
-}
-
-impl Display for Tile {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.char_rep())
-    }
-}
+use processor::{
+    describe_if_requested, diagnostic, diagnostics,
+    graph::{self, Graph, Highlight, SimplePath},
+    input_file, part_requested, process,
+    reachability::find_openings,
+    stats::Distribution,
+    tiles::{self, Tile},
+    Cells, CellsBuilder, Direction, InlineVec, PuzzleMetadata, Solver,
+};
 
 type AError = anyhow::Error;
 
@@ -68,29 +18,12 @@ type LoadedState = Cells<Tile>;
 type ProcessedState = usize;
 type FinalResult = ProcessedState;
 
+/// The tiles the grid is drawn with
+const TILES: &str = ".#^>v<";
+
 fn parse_line(mut state: InitialState, line: String) -> Result<InitialState, AError> {
     if !line.is_empty() {
-        state.new_line();
-        for c in line.chars() {
-            let tile = match c {
-                '.' => Tile::Path,
-                '#' => Tile::Forest,
-                '^' => Tile::Slope {
-                    direction: Direction::North,
-                },
-                '>' => Tile::Slope {
-                    direction: Direction::East,
-                },
-                'v' => Tile::Slope {
-                    direction: Direction::South,
-                },
-                '<' => Tile::Slope {
-                    direction: Direction::West,
-                },
-                _ => return Err(anyhow!(format!("Unrecognised tile: {c}"))),
-            };
-            state.add_cell(tile)?;
-        }
+        state.add_line_with(&line, |c| tiles::allowed_tile(c, TILES))?;
     }
     Ok(state)
 }
@@ -102,7 +35,8 @@ fn output_cells(cells: &Cells<Tile>) {
 }
 
 fn finalise_state(mut state: InitialState) -> Result<LoadedState, AError> {
-    let cells = state.build_cells(Tile::Forest)?;
+    let cells = state.build_cells(Tile::Wall)?;
+    diagnostic("tiles", cells.value_counts());
     output_cells(&cells);
     Ok(cells)
 }
@@ -145,9 +79,9 @@ fn calculate_next_steps<F>(
             });
             continue;
         };
-        let next_tile = cells.get(next_candidate.0, next_candidate.1).unwrap();
+        let next_tile = &cells[next_candidate];
         match next_tile {
-            Tile::Forest => (), //can't go here
+            Tile::Wall => (), //can't go here
             _ => {
                 let mut new_visited = walk.visited_cells.clone();
                 new_visited.insert(next_candidate);
@@ -198,28 +132,16 @@ where
     finished_walks
 }
 
-fn adjacent_coords_and_directions(tiles: &Cells<Tile>, coord: &Coord) -> Vec<(Coord, Direction)> {
-    [
-        (
-            get_next_coord(tiles, coord, &Direction::North),
-            Direction::North,
-        ),
-        (
-            get_next_coord(tiles, coord, &Direction::East),
-            Direction::East,
-        ),
-        (
-            get_next_coord(tiles, coord, &Direction::South),
-            Direction::South,
-        ),
-        (
-            get_next_coord(tiles, coord, &Direction::West),
-            Direction::West,
-        ),
-    ]
-    .iter()
-    .filter_map(|(coord, direction)| coord.map(|coord| (coord, *direction)))
-    .collect()
+fn adjacent_coords_and_directions(
+    tiles: &Cells<Tile>,
+    coord: &Coord,
+) -> InlineVec<(Coord, Direction), 4> {
+    Direction::ALL
+        .into_iter()
+        .filter_map(|direction| {
+            get_next_coord(tiles, coord, &direction).map(|next| (next, direction))
+        })
+        .collect()
 }
 
 fn walk_to_end_of_corridor<F>(
@@ -243,74 +165,121 @@ where
     Some((next_coord, last_direction, steps))
 }
 
+/// Whether the tile can be stepped on heading in the direction, slopes only being climbed if not followed
+fn can_enter(tile: &Tile, heading: Direction, follow_slopes: bool) -> bool {
+    match tile {
+        Tile::Wall => false,
+        Tile::Slope(direction) => !follow_slopes || *direction == heading,
+        _ => true,
+    }
+}
+
+/// The start, the end and every cell where paths branch, joined by the lengths of the corridors between them
+fn junction_graph(
+    cells: &Cells<Tile>,
+    start: &Coord,
+    end: &Coord,
+    follow_slopes: bool,
+) -> Graph<Coord> {
+    graph::contract_corridors(cells, &[*start, *end], |tile, heading| {
+        can_enter(tile, heading, follow_slopes)
+    })
+}
+
+/// Size of the junction graph, the sum of the shortest distances between junctions that can reach each other
+/// (a quick feel for how spread out the maze is) and the walks from start to end through it, exporting the
+/// graph with the longest walk picked out (see [`graph::export_if_requested`]).  Only worked out when the
+/// diagnostics or the export are asked for, as walking every path through the graph takes a while.
+fn report_junctions(cells: &Cells<Tile>, start: &Coord, end: &Coord) -> Result<(), AError> {
+    if !diagnostics::diagnostics_requested() && !graph::export_requested() {
+        return Ok(());
+    }
+    let graph = junction_graph(cells, start, end, true);
+    let distances = graph.floyd_warshall();
+    let total: usize = distances
+        .values()
+        .flat_map(|row| row.values())
+        .flatten()
+        .sum();
+    diagnostic("junctions", graph.node_count());
+    diagnostic("junction corridors", graph.edges().len());
+    diagnostic("sum of junction distances", total);
+
+    let (Some(from), Some(to)) = (
+        graph.find_node(|coord| coord == start),
+        graph.find_node(|coord| coord == end),
+    ) else {
+        return Ok(());
+    };
+    let mut walks = 0;
+    let mut longest: Option<SimplePath> = None;
+    for path in graph.simple_paths(from, to, None) {
+        walks += 1;
+        if longest
+            .as_ref()
+            .is_none_or(|longest| path.length > longest.length)
+        {
+            longest = Some(path);
+        }
+    }
+    diagnostic("junction walks", walks);
+    let highlight = longest.map_or(Highlight::default(), |longest| {
+        diagnostic("longest junction walk", longest.length);
+        Highlight::default().with_path(&longest.nodes)
+    });
+    graph::export_if_requested(&graph, |_, (x, y)| format!("{x},{y}"), &highlight)
+}
+
+/// The way in and out of the maze, the openings in its edge nearest the top and nearest the bottom
+fn find_start_and_end(cells: &Cells<Tile>) -> Result<(Coord, Coord), AError> {
+    match find_openings(cells, Tile::is_wall)[..] {
+        [start, end] => Ok((start, end)),
+        ref openings => Err(anyhow!(
+            "Expected 2 openings in the edge of the maze but found {}: {openings:?}",
+            openings.len()
+        )),
+    }
+}
+
 fn perform_processing(state: LoadedState) -> Result<ProcessedState, AError> {
-    let starting_point = (1, 0);
-    let ending_point = (state.side_lengths.0 - 2, state.side_lengths.1 - 1);
+    let (starting_point, ending_point) = find_start_and_end(&state)?;
+    report_junctions(&state, &starting_point, &ending_point)?;
     let walks = do_walks(&state, &starting_point, &ending_point, &|coord, tile| {
         let next_coords = match tile {
-            Tile::Path => adjacent_coords_and_directions(&state, coord),
-            Tile::Slope { direction } => {
-                let next_coord_and_direction = match direction {
-                    Direction::North => ((coord.0, coord.1 - 1), Direction::North),
-                    Direction::East => ((coord.0 + 1, coord.1), Direction::East),
-                    Direction::South => ((coord.0, coord.1 + 1), Direction::South),
-                    Direction::West => ((coord.0 - 1, coord.1), Direction::West),
-                };
-                let (next_coord, _) = next_coord_and_direction;
-                if state.in_bounds(next_coord.0, next_coord.1) {
-                    vec![next_coord_and_direction]
-                } else {
-                    vec![]
-                }
-            }
-            _ => vec![],
+            Tile::Empty => adjacent_coords_and_directions(&state, coord),
+            Tile::Slope(direction) => state
+                .step(*coord, *direction)
+                .map(|next_coord| (next_coord, *direction))
+                .into_iter()
+                .collect(),
+            _ => InlineVec::new(),
         };
         next_coords
             .iter()
             .filter_map(|(coord, direction)| {
                 walk_to_end_of_corridor(&state, coord, direction, &|tile| {
-                    matches!(tile, Tile::Path)
+                    matches!(tile, Tile::Empty)
                 })
             })
             .map(|(coord, _, steps)| (coord, steps))
             .collect()
     });
+    diagnostic("finished walks", walks.len());
+    if let Some(distribution) = Distribution::of(walks.iter().map(|walk| walk.steps)) {
+        diagnostic("finished walk lengths", distribution);
+    }
     Ok(walks.iter().map(|walk| walk.steps).max().unwrap())
 }
 
-struct Visit {
-    coord: Coord,
-    steps: usize,
-    visited: HashSet<Coord>,
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-struct Visited {
-    coord: Coord,
-    direction: Direction,
-}
-
 fn get_next_coord(cells: &Cells<Tile>, coord: &Coord, direction: &Direction) -> Option<Coord> {
-    let (next_x, next_y) = match direction {
-        Direction::North => (coord.0 as isize, coord.1 as isize - 1),
-        Direction::East => (coord.0 as isize + 1, coord.1 as isize),
-        Direction::South => (coord.0 as isize, coord.1 as isize + 1),
-        Direction::West => (coord.0 as isize - 1, coord.1 as isize),
-    };
-    if !cells.in_bounds(next_x, next_y) {
-        return None;
-    }
-    Some((next_x as usize, next_y as usize))
+    cells.step(*coord, *direction)
 }
 
-fn is_forest_or_edge(cells: &Cells<Tile>, coord: &Coord, delta_x: isize, delta_y: isize) -> bool {
-    let (x, y) = (coord.0 as isize + delta_x, coord.1 as isize + delta_y);
-    if !cells.in_bounds(x, y) {
-        return true;
+fn is_forest_or_edge(cells: &Cells<Tile>, coord: &Coord, direction: Direction) -> bool {
+    match get_next_coord(cells, coord, &direction) {
+        Some((x, y)) => matches!(cells[(x, y)], Tile::Wall),
+        None => true,
     }
-    let (x, y) = (x as usize, y as usize);
-    let tile = cells.get(x, y).unwrap();
-    matches!(tile, Tile::Forest)
 }
 
 fn is_corridor<F>(cells: &Cells<Tile>, coord: &Coord, is_corridor_tile: &F) -> bool
@@ -319,23 +288,23 @@ where
 {
     let mut count_walls = 0usize;
     //Firstly, This needs to be a Path
-    let tile = cells.get(coord.0, coord.1).unwrap();
+    let tile = &cells[*coord];
     if !is_corridor_tile(tile) {
         return false;
     }
-    if is_forest_or_edge(cells, coord, 0, -1) {
+    if is_forest_or_edge(cells, coord, Direction::North) {
         //above
         count_walls += 1;
     }
-    if is_forest_or_edge(cells, coord, 0, 1) {
+    if is_forest_or_edge(cells, coord, Direction::South) {
         //below
         count_walls += 1;
     }
-    if is_forest_or_edge(cells, coord, 1, 0) {
+    if is_forest_or_edge(cells, coord, Direction::East) {
         //right
         count_walls += 1;
     }
-    if is_forest_or_edge(cells, coord, -1, 0) {
+    if is_forest_or_edge(cells, coord, Direction::West) {
         //left
         count_walls += 1;
     }
@@ -348,19 +317,19 @@ fn get_next_in_corridor(
     direction: &Direction,
 ) -> Option<(Coord, Direction)> {
     let mut possible_direction: Vec<Direction> = Vec::default();
-    if !is_forest_or_edge(cells, coord, 0, -1) {
+    if !is_forest_or_edge(cells, coord, Direction::North) {
         //above
         possible_direction.push(Direction::North);
     }
-    if !is_forest_or_edge(cells, coord, 0, 1) {
+    if !is_forest_or_edge(cells, coord, Direction::South) {
         //below
         possible_direction.push(Direction::South);
     }
-    if !is_forest_or_edge(cells, coord, 1, 0) {
+    if !is_forest_or_edge(cells, coord, Direction::East) {
         //right
         possible_direction.push(Direction::East);
     }
-    if !is_forest_or_edge(cells, coord, -1, 0) {
+    if !is_forest_or_edge(cells, coord, Direction::West) {
         //left
         possible_direction.push(Direction::West);
     }
@@ -371,91 +340,21 @@ fn get_next_in_corridor(
     get_next_coord(cells, coord, new_direction).map(|coord| (coord, *new_direction))
 }
 
-fn go_to_next(
-    cells: &Cells<Tile>,
-    end_coord: &Coord,
-    visit: &Visit,
-    visited: &mut HashMap<Visited, usize>,
-    direction: Direction,
-    to_visit: &mut VecDeque<Visit>,
-) {
-    let next_coord = get_next_coord(cells, &visit.coord, &direction);
-    if next_coord.is_none() {
-        return;
-    }
-    let mut next_coord = next_coord.unwrap();
-    if visit.visited.contains(&next_coord) {
-        return;
-    }
-    let next_tile = cells.get(next_coord.0, next_coord.1).unwrap();
-    if matches!(next_tile, Tile::Forest) {
-        return;
-    }
-    let mut latest_direction = direction;
-    let mut steps = 1;
-    while is_corridor(cells, &next_coord, &|tile| !matches!(tile, Tile::Forest)) {
-        //new_visit_visited.insert(next_coord);
-        let next_and_direction = get_next_in_corridor(cells, &next_coord, &latest_direction);
-        if next_and_direction.is_none() {
-            return;
-        }
-        steps += 1;
-        if next_coord == *end_coord {
-            break;
-        }
-        (next_coord, latest_direction) = next_and_direction.unwrap();
-    }
-    if visit.visited.contains(&next_coord) {
-        return;
-    }
-    let mut new_visit_visited = visit.visited.clone();
-    new_visit_visited.insert(next_coord);
-    let new_visited = Visited {
-        coord: next_coord,
-        direction: latest_direction,
-    };
-    // let max_and_coords = visited.get(&new_visited);
-    // match max_and_coords {
-    //     None => (), //keep going
-    //     Some((max_steps_so_far, coords_so_far)) => {
-    //         //already got here with the same coords?
-    //         if new_visit_visited.is_subset(coords_so_far) && *max_steps_so_far < visit.steps + steps {
-    //             //stop searching now
-    //             return;
-    //         } else {
-    //             //this is more... don't stop
-    //         }
-    //     }
-    // }
-    to_visit.push_front(Visit {
-        coord: next_coord,
-        steps: visit.steps + steps,
-        visited: new_visit_visited,
-    });
-    //Update the visited to this new max
-    let new_max = visited
-        .get(&new_visited)
-        .copied()
-        .unwrap_or(0)
-        .max(visit.steps + steps);
-    visited.insert(new_visited, new_max);
-}
-
-/// Original 'breadth first' search.  It needs a *lot* of memory but does get there
-/// eventually, if it's available (~12G needed)
+// Original 'breadth first' search.  It needs a *lot* of memory but does get there
+// eventually, if it's available (~12G needed)
 // fn perform_processing_2(state: LoadedState) -> Result<ProcessedState, AError> {
 //     let starting_point = (1, 0);
 //     let ending_point = (state.side_lengths.0 - 2, state.side_lengths.1 - 1);
 //     let walks = do_walks(&state, &starting_point, &ending_point, &|coord, tile| {
 //         let next_coords = match tile {
-//             Tile::Path => adjacent_coords_and_directions(&state, coord),
-//             Tile::Slope { direction: _direction } => adjacent_coords_and_directions(&state, coord),
+//             Tile::Empty => adjacent_coords_and_directions(&state, coord),
+//             Tile::Slope(_direction) => adjacent_coords_and_directions(&state, coord),
 //             _ => vec![],
 //         };
 //         next_coords.iter()
 //             .filter_map(|(coord, direction)|
 //                 walk_to_end_of_corridor(&state, coord, direction, &|tile|
-//                     !matches!(tile, Tile::Forest)
+//                     !matches!(tile, Tile::Wall)
 //                 )
 //             )
 //             .map(|(coord, _, steps)| (coord, steps))
@@ -468,113 +367,79 @@ fn go_to_next(
 //         .unwrap())
 // }
 
-/// Alternative Depth first search - requires much less memory but similar time require (still super slow -
-/// takes ~10 mins on mini-pc)
+/// Longest walk over the junctions left after contracting the corridors, slopes being climbable.  Day 23's
+/// mazes only have a few dozen junctions, so the search over them takes under a second rather than the ~10 minutes
+/// the depth first search cell by cell did.
 fn perform_processing_2(state: LoadedState) -> Result<ProcessedState, AError> {
-    let starting_point = (1, 0);
-    let ending_point = (state.side_lengths.0 - 2, state.side_lengths.1 - 1);
-    //need to do a depth first search...  ?dropping any where we got to the point in more from the same direction already
-    let mut visited: HashMap<Visited, usize> = HashMap::default();
-    let mut to_visit: VecDeque<Visit> = VecDeque::default();
-    //Prime
-    to_visit.push_front(Visit {
-        coord: starting_point,
-        steps: 0,
-        visited: HashSet::from([starting_point]),
-    });
-    //Pump
-    let mut last_reported = 0;
-    while let Some(visit) = to_visit.pop_front() {
-        let the_len = to_visit.len();
-        if the_len % 10 == 0 && last_reported != the_len {
-            // println!("to_visit: {}", to_visit.len());
-            last_reported = the_len;
-        }
-        go_to_next(
-            &state,
-            &ending_point,
-            &visit,
-            &mut visited,
-            Direction::North,
-            &mut to_visit,
-        );
-        go_to_next(
-            &state,
-            &ending_point,
-            &visit,
-            &mut visited,
-            Direction::East,
-            &mut to_visit,
-        );
-        go_to_next(
-            &state,
-            &ending_point,
-            &visit,
-            &mut visited,
-            Direction::South,
-            &mut to_visit,
-        );
-        go_to_next(
-            &state,
-            &ending_point,
-            &visit,
-            &mut visited,
-            Direction::West,
-            &mut to_visit,
-        );
-    }
-    //get longest to end
-    let steps = visited
-        .get(&Visited {
-            coord: ending_point,
-            direction: Direction::South,
-        })
-        .expect("Didn't find end visit");
-    Ok(*steps)
+    let (starting_point, ending_point) = find_start_and_end(&state)?;
+    let graph = junction_graph(&state, &starting_point, &ending_point, false);
+    diagnostic("junctions", graph.node_count());
+    let (Some(from), Some(to)) = (
+        graph.find_node(|coord| *coord == starting_point),
+        graph.find_node(|coord| *coord == ending_point),
+    ) else {
+        return Err(anyhow!("Start or end missing from the junctions"));
+    };
+    graph
+        .longest_simple_path_length(from, to)
+        .ok_or_else(|| anyhow!("Didn't find end visit"))
 }
 
 fn calc_result(state: ProcessedState) -> Result<FinalResult, AError> {
     Ok(state)
 }
 
+struct Day23;
+
+impl Solver for Day23 {
+    fn metadata() -> PuzzleMetadata {
+        PuzzleMetadata::new(23, "A Long Walk")
+    }
+}
+
 fn main() {
+    describe_if_requested::<Day23>();
     //let file = "test-input.txt";
     //let file = "test-input2.txt";
-    let file = "input.txt";
-
-    let started1_at = time::Instant::now();
-    let result1 = process(
-        file,
-        CellsBuilder::new_empty(),
-        parse_line,
-        finalise_state,
-        perform_processing,
-        calc_result,
-    );
-    match result1 {
-        Ok(res) => println!(
-            "Result 1: {:?} (took: {}s)",
-            res,
-            started1_at.elapsed().as_secs_f32()
-        ),
-        Err(e) => println!("Error on 1: {}", e),
+    let file = &input_file("input.txt");
+
+    if part_requested(1) {
+        let started1_at = time::Instant::now();
+        let result1 = process(
+            file,
+            CellsBuilder::new_empty(),
+            parse_line,
+            finalise_state,
+            perform_processing,
+            calc_result,
+        );
+        match result1 {
+            Ok(res) => println!(
+                "Result 1: {:?} (took: {}s)",
+                res,
+                started1_at.elapsed().as_secs_f32()
+            ),
+            Err(e) => println!("Error on 1: {}", e),
+        }
     }
 
-    let started2_at = time::Instant::now();
-    let result2 = process(
-        file,
-        CellsBuilder::new_empty(),
-        parse_line,
-        finalise_state,
-        perform_processing_2,
-        calc_result,
-    );
-    match result2 {
-        Ok(res) => println!(
-            "Result 2: {:?} (took: {}s)",
-            res,
-            started2_at.elapsed().as_secs_f32()
-        ),
-        Err(e) => println!("Error on 2: {}", e),
+    if part_requested(2) {
+        let started2_at = time::Instant::now();
+        let result2 = process(
+            file,
+            CellsBuilder::new_empty(),
+            parse_line,
+            finalise_state,
+            perform_processing_2,
+            calc_result,
+        );
+        match result2 {
+            Ok(res) => println!(
+                "Result 2: {:?} (took: {}s)",
+                res,
+                started2_at.elapsed().as_secs_f32()
+            ),
+            Err(e) => println!("Error on 2: {}", e),
+        }
     }
 }
@@ -1,35 +1,20 @@
-use std::{collections::HashSet, fmt::Display, time};
+use std::{fmt::Display, time};
 
 use num_rational::Rational64;
-use once_cell::sync::Lazy;
-use processor::{process, read_next};
+use processor::{
+    describe_if_requested,
+    geometry::{intersections_within, intersections_within_naive, Ray, DEFAULT_EPSILON},
+    parse::{integer, parse_complete, preceded, separated_list, tag},
+    part_requested, process, Answer, Coord3, Cursor, PuzzleConfig, PuzzleMetadata, Solver,
+};
 
 type AError = anyhow::Error;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub struct ICoord3 {
-    pub x: isize,
-    pub y: isize,
-    pub z: isize,
-}
-
-impl ICoord3 {
-    pub fn new(x: isize, y: isize, z: isize) -> ICoord3 {
-        ICoord3 { x, y, z }
-    }
-}
-
-impl Display for ICoord3 {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "({},{},{})", self.x, self.y, self.z)
-    }
-}
-
 #[derive(Debug, Clone, Copy)]
 struct HailStone {
     id: usize,
-    position: ICoord3,
-    velocity: ICoord3,
+    position: Coord3<isize>,
+    velocity: Coord3<isize>,
 }
 
 impl Display for HailStone {
@@ -38,31 +23,65 @@ impl Display for HailStone {
     }
 }
 
+struct LoadingState {
+    hailstones: Vec<HailStone>,
+}
+
 struct State {
-    test_area: (isize, isize),
     hailstones: Vec<HailStone>,
 }
 
-type InitialState = State;
-type LoadedState = InitialState;
+/// Where and how part 1 looks for paths crossing, from its config
+struct TestArea {
+    bounds: (isize, isize),
+    epsilon: Float,
+    /// Only check the pairs of paths that come near each other, rather than every pair
+    spatial_hash: bool,
+    check_crossings: bool,
+}
+
+impl TestArea {
+    fn from_config(config: &PuzzleConfig) -> Result<TestArea, AError> {
+        Ok(TestArea {
+            bounds: (config.get("test_area_min")?, config.get("test_area_max")?),
+            epsilon: config.get("epsilon")?,
+            spatial_hash: match config.get::<String>("crossings")?.as_str() {
+                "pairs" => false,
+                "spatial_hash" => true,
+                other => {
+                    return Err(AError::msg(format!(
+                        "Unknown crossings '{other}', expected pairs or spatial_hash"
+                    )))
+                }
+            },
+            check_crossings: config.get("check_crossings")?,
+        })
+    }
+}
+
+type InitialState = LoadingState;
+type LoadedState = State;
 type ProcessedState = usize;
 type FinalResult = usize;
 
-static DELIMITERS: Lazy<HashSet<char>> = Lazy::new(|| HashSet::from([' ', ',', '@']));
+/// px, py, pz
+fn coord3(cursor: &mut Cursor<'_>) -> Result<Coord3<isize>, AError> {
+    let values = separated_list(integer::<isize>, tag(","))(cursor)?;
+    match values[..] {
+        [x, y, z] => Ok(Coord3::new(x, y, z)),
+        _ => Err(AError::msg(format!("Expected 3 values but got {values:?}"))),
+    }
+}
 
 fn parse_line(mut state: InitialState, line: String) -> Result<InitialState, AError> {
     if !line.is_empty() {
-        let mut chars = line.chars();
-        let (x, _) = read_next::<isize>(&mut chars, &DELIMITERS)?;
-        let (y, _) = read_next::<isize>(&mut chars, &DELIMITERS)?;
-        let (z, _) = read_next::<isize>(&mut chars, &DELIMITERS)?;
-        let (v_x, _) = read_next::<isize>(&mut chars, &DELIMITERS)?;
-        let (v_y, _) = read_next::<isize>(&mut chars, &DELIMITERS)?;
-        let (v_z, _) = read_next::<isize>(&mut chars, &DELIMITERS)?;
+        let (position, velocity) = parse_complete(&line, |cursor| {
+            Ok((coord3(cursor)?, preceded(tag("@"), coord3)(cursor)?))
+        })?;
         let hailstone = HailStone {
             id: state.hailstones.len() + 1,
-            position: ICoord3::new(x, y, z),
-            velocity: ICoord3::new(v_x, v_y, v_z),
+            position,
+            velocity,
         };
         state.hailstones.push(hailstone);
     };
@@ -76,101 +95,47 @@ fn parse_line(mut state: InitialState, line: String) -> Result<InitialState, AEr
 // }
 
 fn output_state(_state: &State) {
-    // println!("Bounds: {:?}", state.test_area);
     // output_hailstones(&state.hailstones);
 }
 
 fn finalise_state(state: InitialState) -> Result<LoadedState, AError> {
-    output_state(&state);
-    Ok(state)
+    let loaded = State {
+        hailstones: state.hailstones,
+    };
+    output_state(&loaded);
+    Ok(loaded)
 }
 
 type Float = f64;
 
-fn line_a_b_c_from_points(x1: isize, x2: isize, y1: isize, y2: isize) -> (Float, Float, Float) {
-    let x1 = x1 as Float;
-    let x2 = x2 as Float;
-    let y1 = y1 as Float;
-    let y2 = y2 as Float;
-
-    let a = y2 - y1;
-    let b = x1 - x2;
-    let c = a * x1 + b * y1;
-
-    (a, b, c)
-}
-
-fn line_a_b_c(stone: &HailStone) -> (Float, Float, Float) {
-    line_a_b_c_from_points(
-        stone.position.x,
-        stone.position.x + stone.velocity.x,
-        stone.position.y,
-        stone.position.y + stone.velocity.y,
+fn path_x_y(stone: &HailStone) -> Ray {
+    Ray::new(
+        (stone.position.x as Float, stone.position.y as Float),
+        (stone.velocity.x as Float, stone.velocity.y as Float),
     )
 }
 
-//https://www.topcoder.com/thrive/articles/Geometry%20Concepts%20part%202:%20%20Line%20Intersection%20and%20its%20Applications
-fn paths_intersect_x_y(
-    min: Float,
-    max: Float,
-    a: HailStone,
-    b: HailStone,
-) -> Option<(Float, Float)> {
-    let (a1, b1, c1) = line_a_b_c(&a);
-    let (a2, b2, c2) = line_a_b_c(&b);
-
-    let det = a1 * b2 - a2 * b1;
-    if det == 0.0 {
-        return None; //parallel
-    }
-    let intersection_x = (b2 * c1 - b1 * c2) / det;
-    let intersection_y = (a1 * c2 - a2 * c1) / det;
-
-    //Is the intersection within the bounds?
-    if intersection_x < min || intersection_x > max || intersection_y < min || intersection_y > max
-    {
-        return None; //out of bounds
-    }
-
-    //check time is positive for a
-    let x_0 = a.position.x as Float;
-    let v_x = a.velocity.x as Float;
-    let time_a = (intersection_x - x_0) / v_x;
-    if time_a < 0.0 {
-        return None;
-    }
-
-    //check time is positive for b
-    let x_0 = b.position.x as Float;
-    let v_x = b.velocity.x as Float;
-    let time_b = (intersection_x - x_0) / v_x;
-
-    if time_b >= 0.0 {
-        Some((time_a, time_b))
-    } else {
-        None
-    }
-}
-
-fn perform_processing(state: LoadedState) -> Result<ProcessedState, AError> {
-    let min = state.test_area.0 as Float;
-    let max = state.test_area.1 as Float;
-    let mut collisions = 0usize;
-    for i in 0..state.hailstones.len() {
-        for j in (i + 1)..state.hailstones.len() {
-            if i == j {
-                //shouldn't be necessary but just in case
-                continue;
-            }
-            if let Some((_time_1, _time_2)) =
-                paths_intersect_x_y(min, max, state.hailstones[i], state.hailstones[j])
-            {
-                // println!("{} {}", time_1, time_2);
-                collisions += 1
+fn perform_processing(state: LoadedState, area: &TestArea) -> Result<ProcessedState, AError> {
+    let min = (area.bounds.0 as Float, area.bounds.0 as Float);
+    let max = (area.bounds.1 as Float, area.bounds.1 as Float);
+    let paths: Vec<Ray> = state.hailstones.iter().map(path_x_y).collect();
+    let crossings = if area.spatial_hash {
+        let crossings = intersections_within(&paths, min, max, area.epsilon);
+        if area.check_crossings {
+            let naive = intersections_within_naive(&paths, min, max, area.epsilon);
+            if crossings != naive {
+                return Err(AError::msg(format!(
+                    "Spatial hash found {} crossings but checking every pair found {}",
+                    crossings.len(),
+                    naive.len()
+                )));
             }
         }
-    }
-    Ok(collisions)
+        crossings
+    } else {
+        intersections_within_naive(&paths, min, max, area.epsilon)
+    };
+    Ok(crossings.len())
 }
 
 fn calc_result(state: ProcessedState) -> Result<FinalResult, AError> {
@@ -178,7 +143,7 @@ fn calc_result(state: ProcessedState) -> Result<FinalResult, AError> {
 }
 
 type ProcessedState2 = Rational64;
-type FinalResult2 = Rational64;
+type FinalResult2 = Answer;
 
 #[inline]
 fn as_rational(i: isize) -> Rational64 {
@@ -197,14 +162,13 @@ fn get_intersect_pos_time(
     let zero = as_rational(0);
     let minus1 = as_rational(-1);
 
-    let pos_ax = as_rational(stone_a.position.x);
-    let pos_ay = as_rational(stone_a.position.y);
-    let pos_bx = as_rational(stone_b.position.x);
-    let pos_by = as_rational(stone_b.position.y);
-    let vel_ax = as_rational(stone_a.velocity.x + delta_x);
-    let vel_ay = as_rational(stone_a.velocity.y + delta_y);
-    let vel_bx = as_rational(stone_b.velocity.x + delta_x);
-    let vel_by = as_rational(stone_b.velocity.y + delta_y);
+    let shift = Coord3::new(delta_x, delta_y, 0);
+    let pos_a = stone_a.position.map(as_rational);
+    let pos_b = stone_b.position.map(as_rational);
+    let vel_a = (stone_a.velocity + shift).map(as_rational);
+    let vel_b = (stone_b.velocity + shift).map(as_rational);
+    let (pos_ax, pos_ay, pos_bx, pos_by) = (pos_a.x, pos_a.y, pos_b.x, pos_b.y);
+    let (vel_ax, vel_ay, vel_bx, vel_by) = (vel_a.x, vel_a.y, vel_b.x, vel_b.y);
 
     let det = (vel_ax * minus1 * vel_by) - (vel_ay * minus1 * vel_bx);
 
@@ -224,6 +188,95 @@ fn get_intersect_pos_time(
     Some(((px, py), t))
 }
 
+/// The time the rock hits the hailstone on one axis, `Ok(None)` if they move together so are always level
+fn axis_collision_time(
+    rock_position: isize,
+    rock_velocity: isize,
+    position: isize,
+    velocity: isize,
+) -> Result<Option<isize>, String> {
+    let distance = rock_position - position;
+    let closing = velocity - rock_velocity;
+    if closing == 0 {
+        return match distance {
+            0 => Ok(None),
+            _ => Err(format!("never level, {distance} apart at the same speed")),
+        };
+    }
+    match (distance % closing, distance / closing) {
+        (0, time) if time >= 0 => Ok(Some(time)),
+        (0, time) => Err(format!("level at time {time}, before the rock is thrown")),
+        _ => Err(format!(
+            "level at time {distance}/{closing}, not a whole number"
+        )),
+    }
+}
+
+/// Check the rock hits every hailstone, working out the (whole, non-negative) time it does on each axis
+/// separately and checking they agree.  The error is for the first hailstone missed.
+fn check_rock(
+    position: Coord3<isize>,
+    velocity: Coord3<isize>,
+    hailstones: &[HailStone],
+) -> Result<(), AError> {
+    for stone in hailstones {
+        let axes = [
+            (
+                "x",
+                position.x,
+                velocity.x,
+                stone.position.x,
+                stone.velocity.x,
+            ),
+            (
+                "y",
+                position.y,
+                velocity.y,
+                stone.position.y,
+                stone.velocity.y,
+            ),
+            (
+                "z",
+                position.z,
+                velocity.z,
+                stone.position.z,
+                stone.velocity.z,
+            ),
+        ];
+        let mut hit_at: Option<(&str, isize)> = None;
+        for (axis, rock_position, rock_velocity, stone_position, stone_velocity) in axes {
+            let miss = |reason: String| {
+                AError::msg(format!(
+                    "Rock {position} @ {velocity} misses hailstone {stone} on {axis}: {reason}"
+                ))
+            };
+            let time =
+                axis_collision_time(rock_position, rock_velocity, stone_position, stone_velocity)
+                    .map_err(miss)?;
+            match (hit_at, time) {
+                (Some((hit_axis, hit_time)), Some(time)) if hit_time != time => {
+                    return Err(miss(format!(
+                        "level at time {time} but on {hit_axis} at time {hit_time}"
+                    )))
+                }
+                (None, Some(time)) => hit_at = Some((axis, time)),
+                _ => {}
+            }
+        }
+    }
+    Ok(())
+}
+
+fn as_integer(r: Rational64) -> Result<isize, AError> {
+    if r.is_integer() {
+        Ok(r.to_integer() as isize)
+    } else {
+        Err(AError::msg(format!(
+            "Rock position {r} isn't a whole number"
+        )))
+    }
+}
+
 const RANGE: isize = 337; //Smallest that we can still find it
 
 fn perform_processing_2(state: LoadedState) -> Result<ProcessedState2, AError> {
@@ -232,7 +285,7 @@ fn perform_processing_2(state: LoadedState) -> Result<ProcessedState2, AError> {
     let stone_2 = state.hailstones[2];
     let stone_3 = state.hailstones[3];
 
-    let mut found_pos: Option<(Rational64, Rational64, Rational64)> = None;
+    let mut found: Option<((Rational64, Rational64, Rational64), Coord3<isize>)> = None;
     'outer: for x in -RANGE..RANGE + 1 {
         // if x % 1000 == 0 {
         //     println!("{x}");
@@ -265,65 +318,114 @@ fn perform_processing_2(state: LoadedState) -> Result<ProcessedState2, AError> {
 
                 if z_intersect1 == z_intersect2 && z_intersect1 == z_intersect3 {
                     //Found it
-                    found_pos = Some((coord.0, coord.1, z_intersect1));
+                    //the stones' velocities were shifted by the opposite of the rock's
+                    found = Some(((coord.0, coord.1, z_intersect1), -Coord3::new(x, y, z)));
                     break 'outer;
                 }
             }
         }
     }
-    let (x, y, z) = found_pos.expect("Didn't find it");
+    let ((x, y, z), velocity) =
+        found.ok_or_else(|| AError::msg("No rock velocity in range hits the first hailstones"))?;
+    let position = Coord3::new(as_integer(x)?, as_integer(y)?, as_integer(z)?);
+    check_rock(position, velocity, &state.hailstones)?;
     Ok(x + y + z)
 }
 
 fn calc_result_2(state: ProcessedState2) -> Result<FinalResult2, AError> {
-    Ok(state)
+    Answer::from_ratio(&state)
 }
 
-fn main() {
-    //let (bounds, file) = ((7isize, 27isize), "test-input.txt");
-    //let (bounds, file) = ((7isize, 27isize), "test-input2.txt");
-    let (bounds, file) = ((200000000000000isize, 400000000000000isize), "input.txt");
+struct Day24;
+
+impl Solver for Day24 {
+    fn metadata() -> PuzzleMetadata {
+        PuzzleMetadata::new(24, "Never Tell Me The Odds")
+            .with_parameter(
+                "test_area_min",
+                "Lower bound of the x and y test area",
+                200000000000000isize,
+            )
+            .with_parameter(
+                "test_area_max",
+                "Upper bound of the x and y test area",
+                400000000000000isize,
+            )
+            .with_parameter(
+                "epsilon",
+                "How close floats have to be to count as equal in part 1",
+                DEFAULT_EPSILON,
+            )
+            .with_parameter(
+                "crossings",
+                "How part 1 finds crossings, checking every pair or only those a spatial_hash puts near each other",
+                "pairs",
+            )
+            .with_parameter(
+                "check_crossings",
+                "Check the crossings the spatial_hash finds against checking every pair",
+                false,
+            )
+            .with_slow_part(2)
+    }
+}
 
-    fn initial_state(bounds: (isize, isize)) -> State {
-        State {
-            test_area: bounds,
+fn main() {
+    describe_if_requested::<Day24>();
+    //let config = PuzzleConfig::new("test-input.txt")
+    //    .with("test_area_min", 7)
+    //    .with("test_area_max", 27);
+    let config = Day24::metadata()
+        .load_config("input.txt")
+        .expect("Invalid configuration");
+
+    fn initial_state() -> LoadingState {
+        LoadingState {
             hailstones: Vec::default(),
         }
     }
 
-    let started1_at = time::Instant::now();
-    let result1 = process(
-        file,
-        initial_state(bounds),
-        parse_line,
-        finalise_state,
-        perform_processing,
-        calc_result,
-    );
-    match result1 {
-        Ok(res) => println!(
-            "Result 1: {:?} (took: {}s)",
-            res,
-            started1_at.elapsed().as_secs_f32()
-        ),
-        Err(e) => println!("Error on 1: {}", e),
+    if part_requested(1) {
+        let config1 = config.for_part(1);
+        let started1_at = time::Instant::now();
+        let result1 = TestArea::from_config(&config1).and_then(|area| {
+            process(
+                &config1.file,
+                initial_state(),
+                parse_line,
+                finalise_state,
+                |state| perform_processing(state, &area),
+                calc_result,
+            )
+        });
+        match result1 {
+            Ok(res) => println!(
+                "Result 1: {:?} (took: {}s)",
+                res,
+                started1_at.elapsed().as_secs_f32()
+            ),
+            Err(e) => println!("Error on 1: {}", e),
+        }
     }
 
-    let started2_at = time::Instant::now();
-    let result2 = process(
-        file,
-        initial_state(bounds),
-        parse_line,
-        finalise_state,
-        perform_processing_2,
-        calc_result_2,
-    );
-    match result2 {
-        Ok(res) => println!(
-            "Result 2: {:?} (took: {}s)",
-            res,
-            started2_at.elapsed().as_secs_f32()
-        ),
-        Err(e) => println!("Error on 2: {}", e),
+    if part_requested(2) {
+        let config2 = config.for_part(2);
+        let started2_at = time::Instant::now();
+        let result2 = process(
+            &config2.file,
+            initial_state(),
+            parse_line,
+            finalise_state,
+            perform_processing_2,
+            calc_result_2,
+        );
+        match result2 {
+            Ok(res) => println!(
+                "Result 2: {} (took: {}s)",
+                res,
+                started2_at.elapsed().as_secs_f32()
+            ),
+            Err(e) => println!("Error on 2: {}", e),
+        }
     }
 }
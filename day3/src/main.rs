@@ -1,6 +1,9 @@
 use std::collections::HashSet;
 
-use processor::{adjacent_coords_diagonal, process, Cells, CellsBuilder};
+use processor::{
+    adjacent_coords_diagonal, describe_if_requested, input_file, part_requested, process, Cells,
+    CellsBuilder, PuzzleMetadata, Solver,
+};
 
 type AError = anyhow::Error;
 type InitialState = CellsBuilder<Cell>;
@@ -23,50 +26,60 @@ enum PartCell {
     Symbol(char),
 }
 
+struct Day3;
+
+impl Solver for Day3 {
+    fn metadata() -> PuzzleMetadata {
+        PuzzleMetadata::new(3, "Gear Ratios")
+    }
+}
+
 fn main() {
+    describe_if_requested::<Day3>();
     //let file = "test-input.txt";
     //let file = "test-input2.txt";
-    let file = "input.txt";
-
-    let result1 = process(
-        file,
-        CellsBuilder::new_empty(),
-        parse_line,
-        finalise_state,
-        perform_processing_1,
-        calc_result_1,
-    );
-    match result1 {
-        Ok(res) => println!("Result 1: {:?}", res),
-        Err(e) => println!("Error on 1: {}", e),
+    let file = &input_file("input.txt");
+
+    if part_requested(1) {
+        let result1 = process(
+            file,
+            CellsBuilder::new_empty(),
+            parse_line,
+            finalise_state,
+            perform_processing_1,
+            calc_result_1,
+        );
+        match result1 {
+            Ok(res) => println!("Result 1: {:?}", res),
+            Err(e) => println!("Error on 1: {}", e),
+        }
     }
 
-    let result2 = process(
-        file,
-        CellsBuilder::new_empty(),
-        parse_line,
-        finalise_state,
-        perform_processing_2,
-        calc_result_2,
-    );
-    match result2 {
-        Ok(res) => println!("Result 2: {:?}", res),
-        Err(e) => println!("Error on 2: {}", e),
+    if part_requested(2) {
+        let result2 = process(
+            file,
+            CellsBuilder::new_empty(),
+            parse_line,
+            finalise_state,
+            perform_processing_2,
+            calc_result_2,
+        );
+        match result2 {
+            Ok(res) => println!("Result 2: {:?}", res),
+            Err(e) => println!("Error on 2: {}", e),
+        }
     }
 }
 
 fn parse_line(mut state: InitialState, line: String) -> Result<InitialState, AError> {
-    state.new_line();
-    for c in line.chars() {
-        let cell = c
-            .to_digit(10)
+    state.add_line_with(&line, |c| {
+        Ok(c.to_digit(10)
             .map(|d: u32| Cell::Number(d.into()))
             .unwrap_or_else(|| match c {
                 '.' => Cell::Dot,
                 _ => Cell::Symbol(c),
-            });
-        state.add_cell(cell)?;
-    }
+            }))
+    })?;
     Ok(state)
 }
 
@@ -154,7 +167,7 @@ fn is_adjacent_to_symbol(x: usize, y: usize, state: &LoadedState) -> bool {
     let centre = (x, y);
     let adjacent_coords = adjacent_coords_diagonal(&centre, &state.side_lengths);
     adjacent_coords.iter().any(|(x, y)| {
-        let cell = state.get(*x, *y).unwrap();
+        let cell = &state[(*x, *y)];
         is_symbol_cell(cell)
     })
 }
@@ -190,7 +203,7 @@ fn calc_result_1(state: ProcessedState1) -> Result<FinalResult, AError> {
 }
 
 fn get_part(x: usize, y: usize, state: &LoadedState) -> Option<PartCell> {
-    let cell = state.get(x, y).unwrap();
+    let cell = &state[(x, y)];
 
     match cell {
         PartCell::PartNumber { id, number } => Some(PartCell::PartNumber {
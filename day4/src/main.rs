@@ -1,7 +1,7 @@
 use std::collections::HashSet;
 
 use once_cell::sync::Lazy;
-use processor::{process, read_next, read_word};
+use processor::{describe_if_requested, read_next, read_word, Pipeline, PuzzleMetadata, Solver};
 
 type AError = anyhow::Error;
 type InitialState = Vec<Card>;
@@ -33,38 +33,28 @@ impl Card {
     }
 }
 
-fn main() {
-    //let file = "test-input.txt";
-    //let file = "test-input2.txt";
-    let file = "input.txt";
-
-    let result1 = process(
-        file,
-        Vec::new(),
-        parse_line,
-        finalise_state,
-        perform_processing_1,
-        calc_result,
-    );
-    match result1 {
-        Ok(res) => println!("Result 1: {:?}", res),
-        Err(e) => println!("Error on 1: {}", e),
-    }
+struct Day4;
 
-    let result2 = process(
-        file,
-        Vec::new(),
-        parse_line,
-        finalise_state,
-        perform_processing_2,
-        calc_result,
-    );
-    match result2 {
-        Ok(res) => println!("Result 2: {:?}", res),
-        Err(e) => println!("Error on 2: {}", e),
+impl Solver for Day4 {
+    fn metadata() -> PuzzleMetadata {
+        PuzzleMetadata::new(4, "Scratchcards")
     }
 }
 
+fn main() {
+    describe_if_requested::<Day4>();
+    Pipeline::builder()
+        //.input("test-input.txt")
+        //.input("test-input2.txt")
+        .initial_state(Vec::new)
+        .parser(parse_line)
+        .finaliser(finalise_state)
+        .part1(|state| calc_result(perform_processing_1(state)?))
+        .part2(|state| calc_result(perform_processing_2(state)?))
+        .run()
+        .print();
+}
+
 static DELIMITERS: Lazy<HashSet<char>> = Lazy::new(|| HashSet::from([' ', ':']));
 
 fn parse_line(mut state: InitialState, line: String) -> Result<InitialState, AError> {
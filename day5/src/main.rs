@@ -1,18 +1,15 @@
-use std::{cmp::Ordering, collections::HashSet};
+use std::collections::HashSet;
 
 use once_cell::sync::Lazy;
-use processor::{process, read_next, read_word};
+use processor::{
+    describe_if_requested, input_file,
+    intervals::{Interval, RangeMap},
+    part_requested, process, read_next, read_word, PuzzleMetadata, Solver,
+};
 
-type Seeds = Vec<usize>;
+type Seeds = Vec<i64>;
 
-#[derive(Debug)]
-struct IndexMap {
-    source_start: usize,
-    destination_start: usize,
-    length: usize,
-}
-
-type Mapping = Vec<IndexMap>;
+type Mapping = RangeMap;
 
 #[derive(Debug)]
 struct Mappings {
@@ -59,53 +56,64 @@ enum LoadingState {
 type AError = anyhow::Error;
 type InitialState = (LoadingState, State);
 type LoadedState = State;
-type ProcessedState = usize;
+type ProcessedState = i64;
 type FinalResult = ProcessedState;
 
+struct Day5;
+
+impl Solver for Day5 {
+    fn metadata() -> PuzzleMetadata {
+        PuzzleMetadata::new(5, "If You Give A Seed A Fertilizer")
+    }
+}
+
 fn main() {
+    describe_if_requested::<Day5>();
     //let file = "test-input.txt";
     //let file = "test-input2.txt";
-    let file = "input.txt";
-
-    let result1 = process(
-        file,
-        (
-            LoadingState::Seeds,
-            State {
-                seeds: Seeds::new(),
-                mappings: Mappings::new(),
-            },
-        ),
-        parse_line,
-        finalise_state,
-        perform_processing_1,
-        calc_result,
-    );
-    match result1 {
-        Ok(res) => println!("Result 1: {:?}", res),
-        Err(e) => println!("Error on 1: {}", e),
+    let file = &input_file("input.txt");
+
+    if part_requested(1) {
+        let result1 = process(
+            file,
+            initial_state(),
+            parse_line,
+            finalise_state,
+            perform_processing_1,
+            calc_result,
+        );
+        match result1 {
+            Ok(res) => println!("Result 1: {:?}", res),
+            Err(e) => println!("Error on 1: {}", e),
+        }
     }
 
-    let result2 = process(
-        file,
-        (
-            LoadingState::Seeds,
-            State {
-                seeds: Seeds::new(),
-                mappings: Mappings::new(),
-            },
-        ),
-        parse_line,
-        finalise_state,
-        perform_processing_2,
-        calc_result,
-    );
-    match result2 {
-        Ok(res) => println!("Result 2: {:?}", res),
-        Err(e) => println!("Error on 2: {}", e),
+    if part_requested(2) {
+        let result2 = process(
+            file,
+            initial_state(),
+            parse_line,
+            finalise_state,
+            perform_processing_2,
+            calc_result,
+        );
+        match result2 {
+            Ok(res) => println!("Result 2: {:?}", res),
+            Err(e) => println!("Error on 2: {}", e),
+        }
     }
 }
 
+fn initial_state() -> InitialState {
+    (
+        LoadingState::Seeds,
+        State {
+            seeds: Seeds::new(),
+            mappings: Mappings::new(),
+        },
+    )
+}
+
 fn get_next_loading_state(state: LoadingState) -> LoadingState {
     match state {
         LoadingState::Seeds => LoadingState::SeedToSoil,
@@ -126,7 +134,7 @@ fn load_seeds(seeds: &mut Seeds, line: String) {
     let _seeds = read_word(&mut chars, &DELIMITERS).unwrap();
     let mut keep_reading = true;
     while keep_reading {
-        keep_reading = match read_next::<usize>(&mut chars, &DELIMITERS) {
+        keep_reading = match read_next::<i64>(&mut chars, &DELIMITERS) {
             Ok((seed, delimiter)) => {
                 seeds.push(seed);
                 delimiter.is_some()
@@ -141,17 +149,17 @@ fn load_seeds(seeds: &mut Seeds, line: String) {
     }
 }
 
-fn load_mapping_line(mapping: &mut Mapping, line: String) {
+fn load_mapping_line(mapping: &mut Mapping, line: String) -> Result<(), AError> {
     let mut chars = line.chars();
-    if let Ok((destination_start, _)) = read_next::<usize>(&mut chars, &DELIMITERS) {
-        let (source_start, _) = read_next::<usize>(&mut chars, &DELIMITERS).unwrap();
-        let (length, _) = read_next::<usize>(&mut chars, &DELIMITERS).unwrap();
-        mapping.push(IndexMap {
-            source_start,
+    if let Ok((destination_start, _)) = read_next::<i64>(&mut chars, &DELIMITERS) {
+        let (source_start, _) = read_next::<i64>(&mut chars, &DELIMITERS)?;
+        let (length, _) = read_next::<i64>(&mut chars, &DELIMITERS)?;
+        mapping.insert(
+            Interval::from_start_length(source_start, length),
             destination_start,
-            length,
-        });
+        )?;
     }
+    Ok(())
 }
 
 fn parse_line(istate: InitialState, line: String) -> Result<InitialState, AError> {
@@ -161,24 +169,24 @@ fn parse_line(istate: InitialState, line: String) -> Result<InitialState, AError
     } else {
         match loading_state {
             LoadingState::Seeds => load_seeds(&mut state.seeds, line),
-            LoadingState::SeedToSoil => load_mapping_line(&mut state.mappings.seed_to_soil, line),
+            LoadingState::SeedToSoil => load_mapping_line(&mut state.mappings.seed_to_soil, line)?,
             LoadingState::SoilToFertilizer => {
-                load_mapping_line(&mut state.mappings.soil_to_fertilizer, line)
+                load_mapping_line(&mut state.mappings.soil_to_fertilizer, line)?
             }
             LoadingState::FertilizerToWater => {
-                load_mapping_line(&mut state.mappings.fertilizer_to_water, line)
+                load_mapping_line(&mut state.mappings.fertilizer_to_water, line)?
             }
             LoadingState::WaterToLight => {
-                load_mapping_line(&mut state.mappings.water_to_light, line)
+                load_mapping_line(&mut state.mappings.water_to_light, line)?
             }
             LoadingState::LightToTemperature => {
-                load_mapping_line(&mut state.mappings.light_to_temperature, line)
+                load_mapping_line(&mut state.mappings.light_to_temperature, line)?
             }
             LoadingState::TemperatureToHumidity => {
-                load_mapping_line(&mut state.mappings.temperature_to_humidity, line)
+                load_mapping_line(&mut state.mappings.temperature_to_humidity, line)?
             }
             LoadingState::HumidityToLocation => {
-                load_mapping_line(&mut state.mappings.humidity_to_location, line)
+                load_mapping_line(&mut state.mappings.humidity_to_location, line)?
             }
         }
         loading_state
@@ -187,141 +195,52 @@ fn parse_line(istate: InitialState, line: String) -> Result<InitialState, AError
 }
 
 fn finalise_state(istate: InitialState) -> Result<LoadedState, AError> {
-    let (_, mut state) = istate;
-    fn source_first(map1: &IndexMap, map2: &IndexMap) -> Ordering {
-        map1.source_start.cmp(&map2.source_start)
-    }
-    state.mappings.seed_to_soil.sort_by(source_first);
-    state.mappings.soil_to_fertilizer.sort_by(source_first);
-    state.mappings.fertilizer_to_water.sort_by(source_first);
-    state.mappings.water_to_light.sort_by(source_first);
-    state.mappings.light_to_temperature.sort_by(source_first);
-    state.mappings.temperature_to_humidity.sort_by(source_first);
-    state.mappings.humidity_to_location.sort_by(source_first);
+    let (_, state) = istate;
     Ok(state)
 }
 
-//Assuming sorted
-fn get_destination(source: usize, mapping: &Mapping) -> usize {
-    for index_map in mapping.iter() {
-        if index_map.source_start > source {
-            break; //before the index map
-        }
-        if source > index_map.source_start + index_map.length - 1 {
-            continue; //try the next one, we're after the index map
-        }
-        //In the index map
-        return index_map.destination_start + (source - index_map.source_start);
-    }
-    //None found - use the same as source
-    source
-}
-
-fn calculate_location(seed: &usize, mappings: &Mappings) -> usize {
-    let soil = get_destination(*seed, &mappings.seed_to_soil);
-    let fertilizer = get_destination(soil, &mappings.soil_to_fertilizer);
-    let water = get_destination(fertilizer, &mappings.fertilizer_to_water);
-    let light = get_destination(water, &mappings.water_to_light);
-    let temp = get_destination(light, &mappings.light_to_temperature);
-    let humidity = get_destination(temp, &mappings.temperature_to_humidity);
-    get_destination(humidity, &mappings.humidity_to_location)
+fn calculate_location(seed: &i64, mappings: &Mappings) -> i64 {
+    let soil = mappings.seed_to_soil.map(*seed);
+    let fertilizer = mappings.soil_to_fertilizer.map(soil);
+    let water = mappings.fertilizer_to_water.map(fertilizer);
+    let light = mappings.water_to_light.map(water);
+    let temp = mappings.light_to_temperature.map(light);
+    let humidity = mappings.temperature_to_humidity.map(temp);
+    mappings.humidity_to_location.map(humidity)
 }
 
 fn perform_processing_1(state: LoadedState) -> Result<ProcessedState, AError> {
-    let minimum = state.seeds.iter().fold(usize::MAX, |acc, seed| {
+    let minimum = state.seeds.iter().fold(i64::MAX, |acc, seed| {
         let location = calculate_location(seed, &state.mappings);
         location.min(acc)
     });
     Ok(minimum)
 }
 
-fn add_destination_ranges(
-    start: usize,
-    length: usize,
-    mapping: &Mapping,
-    destination_ranges: &mut Vec<(usize, usize)>,
-) {
-    let mut length_remaining = length;
-    let mut current_index = start;
-
-    let mut mapping_iter = mapping.iter();
-    let mut current_index_map = mapping_iter.next();
-
-    while length_remaining > 0 && current_index_map.is_some() {
-        let index_map = current_index_map.unwrap();
-        let last_index = current_index + length_remaining - 1;
-        let last_map_index = index_map.source_start + index_map.length - 1;
-        //everything before the index_map, if so drop out
-        if last_index < index_map.source_start {
-            break;
-        }
-        //are we after the index map? Move to the next one
-        if current_index > last_map_index {
-            current_index_map = mapping_iter.next();
-            continue;
-        }
-        //anything before the index map?
-        if current_index < index_map.source_start {
-            //something is in the index map - add a range up to the map and adjust
-            let length_to_consume = index_map.source_start - current_index;
-            destination_ranges.push((current_index, length_to_consume));
-            current_index = index_map.source_start;
-            length_remaining -= length_to_consume;
-            continue;
-        }
-        //must be in the index map then
-        let next_index = last_index.min(last_map_index) + 1;
-        let length_to_consume = next_index - current_index;
-        let destination_index =
-            index_map.destination_start + (current_index - index_map.source_start);
-        destination_ranges.push((destination_index, length_to_consume));
-        current_index = next_index;
-        length_remaining -= length_to_consume;
-    }
-
-    if length_remaining > 0 {
-        destination_ranges.push((current_index, length_remaining));
-    }
-}
-
-fn get_destination_ranges(
-    source_ranges: Vec<(usize, usize)>,
-    mapping: &Mapping,
-) -> Vec<(usize, usize)> {
-    let mut destination_ranges = Vec::new();
-    for (start, length) in source_ranges {
-        add_destination_ranges(start, length, mapping, &mut destination_ranges);
-    }
-    destination_ranges
-}
-
-fn get_location_ranges(
-    start_seed: usize,
-    length: usize,
-    mappings: &Mappings,
-) -> Vec<(usize, usize)> {
-    let soil_ranges =
-        get_destination_ranges(Vec::from([(start_seed, length)]), &mappings.seed_to_soil);
-    let fertilizer_ranges = get_destination_ranges(soil_ranges, &mappings.soil_to_fertilizer);
-    let water_ranges = get_destination_ranges(fertilizer_ranges, &mappings.fertilizer_to_water);
-    let light_ranges = get_destination_ranges(water_ranges, &mappings.water_to_light);
-    let temperature_ranges = get_destination_ranges(light_ranges, &mappings.light_to_temperature);
-    let humidity_ranges =
-        get_destination_ranges(temperature_ranges, &mappings.temperature_to_humidity);
-    get_destination_ranges(humidity_ranges, &mappings.humidity_to_location)
+fn get_location_ranges(seeds: Interval, mappings: &Mappings) -> Vec<Interval> {
+    let soil_ranges = mappings.seed_to_soil.map_interval(seeds);
+    let fertilizer_ranges = mappings.soil_to_fertilizer.map_intervals(soil_ranges);
+    let water_ranges = mappings
+        .fertilizer_to_water
+        .map_intervals(fertilizer_ranges);
+    let light_ranges = mappings.water_to_light.map_intervals(water_ranges);
+    let temperature_ranges = mappings.light_to_temperature.map_intervals(light_ranges);
+    let humidity_ranges = mappings
+        .temperature_to_humidity
+        .map_intervals(temperature_ranges);
+    mappings.humidity_to_location.map_intervals(humidity_ranges)
 }
 
 fn perform_processing_2(state: LoadedState) -> Result<ProcessedState, AError> {
     let minimum = state
         .seeds
         .chunks_exact(2)
-        .fold(usize::MAX, |min_so_far, start_length| {
-            let start = start_length[0];
-            let length = start_length[1];
-            let location_ranges = get_location_ranges(start, length, &state.mappings);
+        .fold(i64::MAX, |min_so_far, start_length| {
+            let seeds = Interval::from_start_length(start_length[0], start_length[1]);
+            let location_ranges = get_location_ranges(seeds, &state.mappings);
             location_ranges
                 .iter()
-                .fold(min_so_far, |min, (start, _)| min.min(*start))
+                .fold(min_so_far, |min, range| min.min(range.start))
         });
     Ok(minimum)
 }
@@ -332,110 +251,31 @@ fn calc_result(state: ProcessedState) -> Result<FinalResult, AError> {
 
 #[cfg(test)]
 mod tests {
-    use super::*;
-
-    #[test]
-    fn range_before_any_index_map() {
-        let mapping = vec![IndexMap {
-            source_start: 10,
-            destination_start: 20,
-            length: 5,
-        }];
-        let mut ranges = Vec::new();
-        add_destination_ranges(3, 6, &mapping, &mut ranges);
-        assert_eq!(ranges, vec![(3, 6)]);
-    }
+    use processor::process_reader;
 
-    #[test]
-    fn range_just_before_any_index_map() {
-        let mapping = vec![IndexMap {
-            source_start: 10,
-            destination_start: 20,
-            length: 5,
-        }];
-        let mut ranges = Vec::new();
-        add_destination_ranges(3, 7, &mapping, &mut ranges);
-        assert_eq!(ranges, vec![(3, 7)]);
-    }
-
-    #[test]
-    fn range_overlapping_start_of_first_index_map() {
-        let mapping = vec![IndexMap {
-            source_start: 10,
-            destination_start: 20,
-            length: 5,
-        }];
-        let mut ranges = Vec::new();
-        add_destination_ranges(8, 6, &mapping, &mut ranges);
-        assert_eq!(ranges, vec![(8, 2), (20, 4)]);
-    }
-
-    #[test]
-    fn range_overlapping_first_index_map() {
-        let mapping = vec![IndexMap {
-            source_start: 10,
-            destination_start: 20,
-            length: 2,
-        }];
-        let mut ranges = Vec::new();
-        add_destination_ranges(8, 6, &mapping, &mut ranges);
-        assert_eq!(ranges, vec![(8, 2), (20, 2), (12, 2)]);
-    }
-
-    #[test]
-    fn range_overlapping_first_and_second_map() {
-        let mapping = vec![
-            IndexMap {
-                source_start: 10,
-                destination_start: 20,
-                length: 2,
-            },
-            IndexMap {
-                source_start: 14,
-                destination_start: 24,
-                length: 2,
-            },
-        ];
-        let mut ranges = Vec::new();
-        add_destination_ranges(8, 10, &mapping, &mut ranges);
-        assert_eq!(ranges, vec![(8, 2), (20, 2), (12, 2), (24, 2), (16, 2)]);
-    }
+    use super::*;
 
-    #[test]
-    fn range_overlapping_first_and_second_map_maps_next_to_each_other() {
-        let mapping = vec![
-            IndexMap {
-                source_start: 10,
-                destination_start: 20,
-                length: 2,
-            },
-            IndexMap {
-                source_start: 12,
-                destination_start: 30,
-                length: 2,
-            },
-        ];
-        let mut ranges = Vec::new();
-        add_destination_ranges(8, 8, &mapping, &mut ranges);
-        assert_eq!(ranges, vec![(8, 2), (20, 2), (30, 2), (14, 2)]);
-    }
+    const EXAMPLE: &str = include_str!("../test-input.txt");
 
     #[test]
-    fn range_after_the_maps() {
-        let mapping = vec![
-            IndexMap {
-                source_start: 10,
-                destination_start: 20,
-                length: 2,
-            },
-            IndexMap {
-                source_start: 12,
-                destination_start: 30,
-                length: 2,
-            },
-        ];
-        let mut ranges = Vec::new();
-        add_destination_ranges(14, 2, &mapping, &mut ranges);
-        assert_eq!(ranges, vec![(14, 2)]);
+    fn example_lowest_locations() {
+        let part1 = process_reader(
+            EXAMPLE.as_bytes(),
+            initial_state(),
+            parse_line,
+            finalise_state,
+            perform_processing_1,
+            calc_result,
+        );
+        assert_eq!(part1.unwrap(), 35);
+        let part2 = process_reader(
+            EXAMPLE.as_bytes(),
+            initial_state(),
+            parse_line,
+            finalise_state,
+            perform_processing_2,
+            calc_result,
+        );
+        assert_eq!(part2.unwrap(), 46);
     }
 }
@@ -1,7 +1,10 @@
 use std::collections::HashSet;
 
 use once_cell::sync::Lazy;
-use processor::{process, read_next, read_word};
+use processor::{
+    describe_if_requested, input_file, part_requested, process, read_next, read_word,
+    PuzzleMetadata, Solver,
+};
 
 type AError = anyhow::Error;
 
@@ -24,35 +27,48 @@ type LoadedState2 = RaceStats;
 type ProcessedState2 = u64;
 type FinalResult = u64;
 
+struct Day6;
+
+impl Solver for Day6 {
+    fn metadata() -> PuzzleMetadata {
+        PuzzleMetadata::new(6, "Wait For It")
+    }
+}
+
 fn main() {
-    let file = "test-input.txt";
+    describe_if_requested::<Day6>();
+    let file = &input_file("test-input.txt");
     //let file = "test-input2.txt";
     //let file = "input.txt";
 
-    let result1 = process(
-        file,
-        (LoadingState::Times, (Vec::new(), Vec::new())),
-        parse_line,
-        finalise_state_1,
-        perform_processing_1,
-        calc_result_1,
-    );
-    match result1 {
-        Ok(res) => println!("Result 1: {:?}", res),
-        Err(e) => println!("Error on 1: {}", e),
+    if part_requested(1) {
+        let result1 = process(
+            file,
+            (LoadingState::Times, (Vec::new(), Vec::new())),
+            parse_line,
+            finalise_state_1,
+            perform_processing_1,
+            calc_result_1,
+        );
+        match result1 {
+            Ok(res) => println!("Result 1: {:?}", res),
+            Err(e) => println!("Error on 1: {}", e),
+        }
     }
 
-    let result2 = process(
-        file,
-        (LoadingState::Times, (Vec::new(), Vec::new())),
-        parse_line,
-        finalise_state_2,
-        perform_processing_2,
-        calc_result_2,
-    );
-    match result2 {
-        Ok(res) => println!("Result 2: {:?}", res),
-        Err(e) => println!("Error on 2: {}", e),
+    if part_requested(2) {
+        let result2 = process(
+            file,
+            (LoadingState::Times, (Vec::new(), Vec::new())),
+            parse_line,
+            finalise_state_2,
+            perform_processing_2,
+            calc_result_2,
+        );
+        match result2 {
+            Ok(res) => println!("Result 2: {:?}", res),
+            Err(e) => println!("Error on 2: {}", e),
+        }
     }
 }
 
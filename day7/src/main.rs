@@ -10,7 +10,10 @@ use itertools::{
     Itertools,
 };
 use once_cell::sync::Lazy;
-use processor::{process, read_next, read_word};
+use processor::{
+    describe_if_requested, input_file, part_requested, process, read_next, read_word,
+    PuzzleMetadata, Solver,
+};
 
 type AError = anyhow::Error;
 
@@ -115,35 +118,48 @@ type LoadedState = InitialState;
 type ProcessedState = LoadedState;
 type FinalResult = u64;
 
+struct Day7;
+
+impl Solver for Day7 {
+    fn metadata() -> PuzzleMetadata {
+        PuzzleMetadata::new(7, "Camel Cards")
+    }
+}
+
 fn main() {
+    describe_if_requested::<Day7>();
     //let file = "test-input.txt";
     //let file = "test-input2.txt";
-    let file = "input.txt";
+    let file = &input_file("input.txt");
 
-    let result1 = process(
-        file,
-        Vec::new(),
-        parse_line,
-        finalise_state_1,
-        perform_processing,
-        calc_result,
-    );
-    match result1 {
-        Ok(res) => println!("Result 1: {:?}", res),
-        Err(e) => println!("Error on 1: {}", e),
+    if part_requested(1) {
+        let result1 = process(
+            file,
+            Vec::new(),
+            parse_line,
+            finalise_state_1,
+            perform_processing,
+            calc_result,
+        );
+        match result1 {
+            Ok(res) => println!("Result 1: {:?}", res),
+            Err(e) => println!("Error on 1: {}", e),
+        }
     }
 
-    let result2 = process(
-        file,
-        Vec::new(),
-        parse_line,
-        finalise_state_2,
-        perform_processing,
-        calc_result,
-    );
-    match result2 {
-        Ok(res) => println!("Result 2: {:?}", res),
-        Err(e) => println!("Error on 2: {}", e),
+    if part_requested(2) {
+        let result2 = process(
+            file,
+            Vec::new(),
+            parse_line,
+            finalise_state_2,
+            perform_processing,
+            calc_result,
+        );
+        match result2 {
+            Ok(res) => println!("Result 2: {:?}", res),
+            Err(e) => println!("Error on 2: {}", e),
+        }
     }
 }
 
@@ -283,8 +299,8 @@ fn categorize_hand_2(hand: &Hand) -> HandType {
         let length_sort = cards1.len().cmp(&cards2.len()).reverse();
         match length_sort {
             Ordering::Equal => {
-                let card1 = cards1.get(0).unwrap();
-                let card2 = cards2.get(0).unwrap();
+                let card1 = cards1.first().unwrap();
+                let card2 = cards2.first().unwrap();
                 card1.strength.cmp(&card2.strength).reverse()
             }
             other => other,
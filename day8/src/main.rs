@@ -1,11 +1,10 @@
-use std::{
-    collections::{BTreeMap, HashSet},
-    fmt::Display,
-};
+use std::{collections::BTreeMap, fmt::Display};
 
 use num::Integer;
-use once_cell::sync::Lazy;
-use processor::{process, read_word};
+use processor::{
+    capture_into, describe_if_requested, input_file, part_requested, process, PuzzleMetadata,
+    Solver,
+};
 
 #[derive(Debug)]
 enum Step {
@@ -56,14 +55,12 @@ struct State {
 impl Display for State {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         write!(f, "steps: ")?;
-        self.steps
-            .iter()
-            .fold(Ok(()), |_, step| write!(f, "{step}"))?;
+        self.steps.iter().try_for_each(|step| write!(f, "{step}"))?;
         writeln!(f)?;
         writeln!(f)?;
         self.nodes
             .iter()
-            .fold(Ok(()), |_, (node, path)| writeln!(f, "{} = {}", node, path))
+            .try_for_each(|(node, path)| writeln!(f, "{} = {}", node, path))
     }
 }
 
@@ -78,36 +75,49 @@ type LoadedState = State;
 type ProcessedState = u64;
 type FinalResult = u64;
 
+struct Day8;
+
+impl Solver for Day8 {
+    fn metadata() -> PuzzleMetadata {
+        PuzzleMetadata::new(8, "Haunted Wasteland")
+    }
+}
+
 fn main() {
+    describe_if_requested::<Day8>();
     //let file = "test-input.txt";
     //let file = "test-input2.txt";
     //let file = "test-input3.txt";
-    let file = "input.txt";
-
-    let result1 = process(
-        file,
-        (LoadingState::Steps, State::default()),
-        parse_line,
-        finalise_state,
-        perform_processing_1,
-        calc_result,
-    );
-    match result1 {
-        Ok(res) => println!("Result 1: {:?}", res),
-        Err(e) => println!("Error on 1: {}", e),
+    let file = &input_file("input.txt");
+
+    if part_requested(1) {
+        let result1 = process(
+            file,
+            (LoadingState::Steps, State::default()),
+            parse_line,
+            finalise_state,
+            perform_processing_1,
+            calc_result,
+        );
+        match result1 {
+            Ok(res) => println!("Result 1: {:?}", res),
+            Err(e) => println!("Error on 1: {}", e),
+        }
     }
 
-    let result2 = process(
-        file,
-        (LoadingState::Steps, State::default()),
-        parse_line,
-        finalise_state,
-        perform_processing_2,
-        calc_result,
-    );
-    match result2 {
-        Ok(res) => println!("Result 2: {:?}", res),
-        Err(e) => println!("Error on 2: {}", e),
+    if part_requested(2) {
+        let result2 = process(
+            file,
+            (LoadingState::Steps, State::default()),
+            parse_line,
+            finalise_state,
+            perform_processing_2,
+            calc_result,
+        );
+        match result2 {
+            Ok(res) => println!("Result 2: {:?}", res),
+            Err(e) => println!("Error on 2: {}", e),
+        }
     }
 }
 
@@ -119,27 +129,27 @@ fn map_step(c: char) -> Step {
     }
 }
 
-static DELIMITERS: Lazy<HashSet<char>> = Lazy::new(|| HashSet::from([' ', '=', '(', ',', ')']));
-
 fn parse_node_line(line: String) -> Option<(Node, Path)> {
-    //JKT = (KFV, CFQ)
-    let mut chars = line.chars();
-    match read_word(&mut chars, &DELIMITERS) {
-        Some((name, _)) => {
-            let (left, _) = read_word(&mut chars, &DELIMITERS).expect("Failed to read left node");
-            let (right, _) = read_word(&mut chars, &DELIMITERS).expect("Failed to read right node");
-            let node = Node { name: name.clone() };
-            Some((
-                node,
-                Path {
-                    node: Node { name: name.clone() },
-                    left: Node { name: left },
-                    right: Node { name: right },
-                },
-            ))
-        }
-        None => None,
+    if line.is_empty() {
+        return None;
     }
+    //JKT = (KFV, CFQ)
+    let (name, left, right) = capture_into!(
+        line,
+        r"^(?<name>\w+) = \((?<left>\w+), (?<right>\w+)\)$",
+        name: String,
+        left: String,
+        right: String
+    )
+    .expect("Failed to read node");
+    Some((
+        Node { name: name.clone() },
+        Path {
+            node: Node { name },
+            left: Node { name: left },
+            right: Node { name: right },
+        },
+    ))
 }
 
 fn parse_line(istate: InitialState, line: String) -> Result<InitialState, AError> {
@@ -1,61 +1,64 @@
 use std::collections::HashSet;
 
 use once_cell::sync::Lazy;
-use processor::{process, read_next};
+use processor::{read_next, run_day, Answer, Day, PuzzleMetadata, Solver};
 
 type AError = anyhow::Error;
-type InitialState = Vec<Vec<i64>>;
-type LoadedState = InitialState;
-type ProcessedState = Vec<i64>;
-type FinalResult = i64;
+type Sequences = Vec<Vec<i64>>;
 
-fn main() {
-    //let file = "test-input.txt";
-    //let file = "test-input2.txt";
-    let file = "input.txt";
+struct Day9;
 
-    let result1 = process(
-        file,
-        Vec::new(),
-        parse_line,
-        finalise_state,
-        perform_processing_1,
-        calc_result,
-    );
-    match result1 {
-        Ok(res) => println!("Result 1: {:?}", res),
-        Err(e) => println!("Error on 1: {}", e),
+impl Solver for Day9 {
+    fn metadata() -> PuzzleMetadata {
+        PuzzleMetadata::new(9, "Mirage Maintenance")
     }
+}
 
-    let result2 = process(
-        file,
-        Vec::new(),
-        parse_line,
-        finalise_state,
-        perform_processing_2,
-        calc_result,
-    );
-    match result2 {
-        Ok(res) => println!("Result 2: {:?}", res),
-        Err(e) => println!("Error on 2: {}", e),
+impl Day for Day9 {
+    type Parsed = Sequences;
+
+    fn parse(lines: impl Iterator<Item = String>) -> Result<Self::Parsed, AError> {
+        Ok(lines.map(|line| parse_line(&line)).collect())
+    }
+
+    fn part1(sequences: &Self::Parsed) -> Result<Answer, AError> {
+        let next_nums = sequences.iter().map(|nums| {
+            calculate_seq_number(
+                nums,
+                |nums| *nums.last().unwrap(),
+                &|num_in_seq, adjustment| num_in_seq + adjustment,
+            )
+        });
+        Ok(next_nums.sum::<i64>().into())
+    }
+
+    fn part2(sequences: &Self::Parsed) -> Result<Answer, AError> {
+        let previous_nums = sequences.iter().map(|nums| {
+            calculate_seq_number(
+                nums,
+                |nums| *nums.first().unwrap(),
+                &|num_in_seq, adjustment| num_in_seq - adjustment,
+            )
+        });
+        Ok(previous_nums.sum::<i64>().into())
     }
 }
 
+fn main() {
+    //run_day::<Day9>("test-input.txt");
+    //run_day::<Day9>("test-input2.txt");
+    run_day::<Day9>("input.txt");
+}
+
 static DELIMITERS: Lazy<HashSet<char>> = Lazy::new(|| HashSet::from([' ']));
 
-fn parse_line(mut state: InitialState, line: String) -> Result<InitialState, AError> {
+fn parse_line(line: &str) -> Vec<i64> {
     let mut chars = line.chars();
     let mut nums: Vec<i64> = Vec::default();
     while let Ok((num, _)) = read_next::<i64>(&mut chars, &DELIMITERS) {
         nums.push(num);
     }
-    state.push(nums);
-    Ok(state)
-}
-
-fn finalise_state(state: InitialState) -> Result<LoadedState, AError> {
-    //println!("{state:?}");
-    Ok(state)
+    nums
 }
 
 fn calculate_seq_number<F1, F2>(
@@ -89,35 +92,14 @@ where
     }
 }
 
-fn perform_processing_1(state: LoadedState) -> Result<ProcessedState, AError> {
-    let next_nums = state
-        .iter()
-        .map(|nums| {
-            calculate_seq_number(
-                nums,
-                |nums| *nums.last().unwrap(),
-                &|num_in_seq, adjustment| num_in_seq + adjustment,
-            )
-        })
-        .collect();
-    Ok(next_nums)
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use processor::assert_day;
 
-fn perform_processing_2(state: LoadedState) -> Result<ProcessedState, AError> {
-    let next_nums = state
-        .iter()
-        .map(|nums| {
-            calculate_seq_number(
-                nums,
-                |nums| *nums.first().unwrap(),
-                &|num_in_seq, adjustment| num_in_seq - adjustment,
-            )
-        })
-        .collect();
-    Ok(next_nums)
-}
-
-fn calc_result(state: ProcessedState) -> Result<FinalResult, AError> {
-    let result = state.iter().sum();
-    Ok(result)
+    #[test]
+    fn example_extrapolated() {
+        assert_day!(Day9, part1, "test-input.txt", 114);
+        assert_day!(Day9, part2, "test-input.txt", 2);
+    }
 }
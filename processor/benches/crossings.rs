@@ -0,0 +1,60 @@
+//! Times finding the paths that cross in a box by checking every pair against only checking the pairs a
+//! spatial hash puts near each other, for paths that mostly cross (like day24's) and paths that mostly don't.
+//! Run with `cargo bench -p processor --bench crossings`.
+
+use std::time::{Duration, Instant};
+
+use processor::geometry::{intersections_within, intersections_within_naive, Ray, DEFAULT_EPSILON};
+
+const RUNS: u32 = 5;
+
+fn time<R>(f: impl Fn() -> R) -> (R, Duration) {
+    let result = f();
+    let started_at = Instant::now();
+    (0..RUNS).for_each(|_| {
+        f();
+    });
+    (result, started_at.elapsed() / RUNS)
+}
+
+fn compare(name: &str, rays: &[Ray], min: (f64, f64), max: (f64, f64)) {
+    let (naive, naive_took) = time(|| intersections_within_naive(rays, min, max, DEFAULT_EPSILON));
+    let (hashed, hashed_took) = time(|| intersections_within(rays, min, max, DEFAULT_EPSILON));
+    assert_eq!(
+        hashed, naive,
+        "{name}: the spatial hash found different crossings"
+    );
+    println!(
+        "{name}: {} paths, {} crossings, every pair {:.3}ms, spatial hash {:.3}ms",
+        rays.len(),
+        naive.len(),
+        naive_took.as_secs_f64() * 1000.0,
+        hashed_took.as_secs_f64() * 1000.0
+    );
+}
+
+fn main() {
+    let (min, max) = ((0.0, 0.0), (1000.0, 1000.0));
+
+    // paths in every direction across the box, most pairs crossing in it as day24's do
+    let dense: Vec<Ray> = (0..300)
+        .map(|i| {
+            let i = i as f64;
+            Ray::new(
+                (i * 37.0 % 1000.0, -1.0 - i),
+                ((i * 13.0 % 41.0) - 20.0, 1.0 + i % 7.0),
+            )
+        })
+        .collect();
+    compare("dense", &dense, min, max);
+
+    // nearly vertical paths, each only near those in its own band, with a third missing the box altogether
+    let sparse: Vec<Ray> = (0..3000)
+        .map(|i| {
+            let i = i as f64;
+            let x = i * 7.919 % 1500.0;
+            Ray::new((x, -10.0), ((i % 11.0 - 5.0) / 1000.0, 1.0))
+        })
+        .collect();
+    compare("sparse", &sparse, min, max);
+}
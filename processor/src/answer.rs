@@ -0,0 +1,184 @@
+use std::{fmt::Display, str::FromStr};
+
+use num::{rational::Ratio, Integer};
+
+use crate::AError;
+
+/// A part's answer in the form it's submitted in, which for the numeric puzzles is always a whole number
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Answer {
+    Integer(i128),
+    Text(String),
+}
+
+impl Answer {
+    /// The answer from an exact fraction, which is an error unless it is a whole number, since a fractional
+    /// answer means the working has gone wrong somewhere
+    pub fn from_ratio<T>(ratio: &Ratio<T>) -> Result<Answer, AError>
+    where
+        T: Integer + Clone + Display + Into<i128>,
+    {
+        if ratio.is_integer() {
+            Ok(Answer::Integer(ratio.to_integer().into()))
+        } else {
+            Err(AError::msg(format!(
+                "Answer {}/{} is not a whole number",
+                ratio.numer(),
+                ratio.denom()
+            )))
+        }
+    }
+
+    /// The answer as shown to people, text answers being left alone.  Display gives the plain form that's
+    /// submitted.
+    pub fn formatted(&self, format: NumberFormat) -> String {
+        match self {
+            Answer::Integer(value) => format.format_integer(*value),
+            Answer::Text(text) => text.clone(),
+        }
+    }
+
+    pub fn as_integer(&self) -> Option<i128> {
+        match self {
+            Answer::Integer(value) => Some(*value),
+            Answer::Text(_) => None,
+        }
+    }
+}
+
+/// How whole numbers are shown to people, e.g. in the runner's tables.  Answers are always submitted and
+/// compared in the plain form.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NumberFormat {
+    #[default]
+    Plain,
+    /// Grouped in threes with commas, e.g. 1,234,567
+    Thousands,
+    /// To 4 significant figures with the exponent, e.g. 1.235e6
+    Scientific,
+}
+
+impl NumberFormat {
+    pub fn format_integer(&self, value: i128) -> String {
+        match self {
+            NumberFormat::Plain => value.to_string(),
+            NumberFormat::Thousands => {
+                let digits = value.unsigned_abs().to_string();
+                let grouped: Vec<&str> = digits
+                    .as_bytes()
+                    .rchunks(3)
+                    .rev()
+                    .map(|group| std::str::from_utf8(group).unwrap())
+                    .collect();
+                let sign = if value < 0 { "-" } else { "" };
+                format!("{sign}{}", grouped.join(","))
+            }
+            NumberFormat::Scientific => format!("{:.3e}", value as f64),
+        }
+    }
+
+    /// The text formatted if it is a whole number, e.g. an answer read back from a day's output, and left as
+    /// it is otherwise
+    pub fn format_text(&self, text: &str) -> String {
+        match text.parse::<i128>() {
+            Ok(value) => self.format_integer(value),
+            Err(_) => text.to_string(),
+        }
+    }
+}
+
+impl FromStr for NumberFormat {
+    type Err = AError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "plain" => Ok(NumberFormat::Plain),
+            "thousands" => Ok(NumberFormat::Thousands),
+            "scientific" => Ok(NumberFormat::Scientific),
+            _ => Err(AError::msg(format!(
+                "Unknown number format '{s}', expected plain, thousands or scientific"
+            ))),
+        }
+    }
+}
+
+impl Display for Answer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Answer::Integer(value) => write!(f, "{value}"),
+            Answer::Text(text) => write!(f, "{text}"),
+        }
+    }
+}
+
+macro_rules! answer_from_integer {
+    ($($t:ty),*) => {
+        $(impl From<$t> for Answer {
+            fn from(value: $t) -> Self {
+                Answer::Integer(value as i128)
+            }
+        })*
+    };
+}
+
+answer_from_integer!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize, i128);
+
+impl From<String> for Answer {
+    fn from(text: String) -> Self {
+        Answer::Text(text)
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(text: &str) -> Self {
+        Answer::Text(text.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn whole_ratios_become_integers() {
+        let answer = Answer::from_ratio(&Ratio::new(920630818300104i64 * 3, 3)).unwrap();
+        assert_eq!(answer, Answer::Integer(920630818300104));
+        assert_eq!(answer.to_string(), "920630818300104");
+        assert_eq!(
+            Answer::from_ratio(&Ratio::new(7i64, 2))
+                .unwrap_err()
+                .to_string(),
+            "Answer 7/2 is not a whole number"
+        );
+        assert_eq!(Answer::from(42usize).as_integer(), Some(42));
+        assert_eq!(Answer::from("bgpsvlq").to_string(), "bgpsvlq");
+        assert_eq!(Answer::from("bgpsvlq").as_integer(), None);
+    }
+
+    #[test]
+    fn numbers_formatted() {
+        let answer = Answer::from(920630818300104i64);
+        assert_eq!(answer.formatted(NumberFormat::Plain), "920630818300104");
+        assert_eq!(
+            answer.formatted(NumberFormat::Thousands),
+            "920,630,818,300,104"
+        );
+        assert_eq!(answer.formatted(NumberFormat::Scientific), "9.206e14");
+        assert_eq!(NumberFormat::Thousands.format_integer(-1234), "-1,234");
+        assert_eq!(NumberFormat::Thousands.format_integer(999), "999");
+        assert_eq!(
+            NumberFormat::Thousands.format_text("Ratio { numer: 9 }"),
+            "Ratio { numer: 9 }"
+        );
+        assert_eq!(NumberFormat::Thousands.format_text("1000"), "1,000");
+        assert_eq!(
+            Answer::from("bgpsvlq").formatted(NumberFormat::Scientific),
+            "bgpsvlq"
+        );
+        assert_eq!(
+            "scientific".parse::<NumberFormat>().unwrap(),
+            NumberFormat::Scientific
+        );
+        assert!("commas".parse::<NumberFormat>().is_err());
+    }
+}
@@ -0,0 +1,223 @@
+//! A compact binary form of [`Cells`] of plain values, so that large grids can be checkpointed to disk and
+//! read back quickly.  The layout is a header (`CELLS`, a version byte, the size of each value, then the
+//! width and height) followed by every value in row order, all little endian.
+
+use std::{fs, path::Path};
+
+use anyhow::Context;
+
+use crate::{AError, Cells};
+
+const MAGIC: &[u8; 5] = b"CELLS";
+const VERSION: u8 = 1;
+const HEADER_LEN: usize = MAGIC.len() + 1 + 4 + 8 + 8;
+
+/// A value with a fixed size little endian byte representation
+pub trait Pod: Sized {
+    const SIZE: usize;
+
+    fn write_bytes(&self, out: &mut Vec<u8>);
+
+    /// Read back from exactly [`Pod::SIZE`] bytes
+    fn read_bytes(bytes: &[u8]) -> Result<Self, AError>;
+}
+
+macro_rules! pod_numbers {
+    ($($t:ty),*) => {
+        $(
+            impl Pod for $t {
+                const SIZE: usize = std::mem::size_of::<$t>();
+
+                fn write_bytes(&self, out: &mut Vec<u8>) {
+                    out.extend_from_slice(&self.to_le_bytes());
+                }
+
+                fn read_bytes(bytes: &[u8]) -> Result<Self, AError> {
+                    Ok(<$t>::from_le_bytes(bytes.try_into()?))
+                }
+            }
+        )*
+    };
+}
+
+pod_numbers!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128, f32, f64);
+
+// usize and isize are always stored as 64 bits so that checkpoints are portable
+impl Pod for usize {
+    const SIZE: usize = 8;
+
+    fn write_bytes(&self, out: &mut Vec<u8>) {
+        (*self as u64).write_bytes(out)
+    }
+
+    fn read_bytes(bytes: &[u8]) -> Result<Self, AError> {
+        Ok(usize::try_from(u64::read_bytes(bytes)?)?)
+    }
+}
+
+impl Pod for isize {
+    const SIZE: usize = 8;
+
+    fn write_bytes(&self, out: &mut Vec<u8>) {
+        (*self as i64).write_bytes(out)
+    }
+
+    fn read_bytes(bytes: &[u8]) -> Result<Self, AError> {
+        Ok(isize::try_from(i64::read_bytes(bytes)?)?)
+    }
+}
+
+impl Pod for bool {
+    const SIZE: usize = 1;
+
+    fn write_bytes(&self, out: &mut Vec<u8>) {
+        out.push(*self as u8)
+    }
+
+    fn read_bytes(bytes: &[u8]) -> Result<Self, AError> {
+        match bytes {
+            [0] => Ok(false),
+            [1] => Ok(true),
+            _ => Err(AError::msg(format!("Invalid bool: {bytes:?}"))),
+        }
+    }
+}
+
+impl Pod for char {
+    const SIZE: usize = 4;
+
+    fn write_bytes(&self, out: &mut Vec<u8>) {
+        (*self as u32).write_bytes(out)
+    }
+
+    fn read_bytes(bytes: &[u8]) -> Result<Self, AError> {
+        let value = u32::read_bytes(bytes)?;
+        char::from_u32(value).ok_or_else(|| AError::msg(format!("Invalid char: {value}")))
+    }
+}
+
+impl<A: Pod, B: Pod> Pod for (A, B) {
+    const SIZE: usize = A::SIZE + B::SIZE;
+
+    fn write_bytes(&self, out: &mut Vec<u8>) {
+        self.0.write_bytes(out);
+        self.1.write_bytes(out);
+    }
+
+    fn read_bytes(bytes: &[u8]) -> Result<Self, AError> {
+        Ok((
+            A::read_bytes(&bytes[..A::SIZE])?,
+            B::read_bytes(&bytes[A::SIZE..])?,
+        ))
+    }
+}
+
+impl<T: Pod> Pod for Option<T> {
+    const SIZE: usize = 1 + T::SIZE;
+
+    fn write_bytes(&self, out: &mut Vec<u8>) {
+        match self {
+            Some(value) => {
+                out.push(1);
+                value.write_bytes(out);
+            }
+            None => out.resize(out.len() + Self::SIZE, 0),
+        }
+    }
+
+    fn read_bytes(bytes: &[u8]) -> Result<Self, AError> {
+        match bytes[0] {
+            0 => Ok(None),
+            1 => Ok(Some(T::read_bytes(&bytes[1..])?)),
+            flag => Err(AError::msg(format!("Invalid option flag: {flag}"))),
+        }
+    }
+}
+
+impl<T: Pod> Cells<T> {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(HEADER_LEN + self.contents.len() * T::SIZE);
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+        bytes.extend_from_slice(&(T::SIZE as u32).to_le_bytes());
+        self.side_lengths.0.write_bytes(&mut bytes);
+        self.side_lengths.1.write_bytes(&mut bytes);
+        self.contents
+            .iter()
+            .for_each(|value| value.write_bytes(&mut bytes));
+        bytes
+    }
+
+    /// Read back the output of [`Cells::to_bytes`], checking it holds values of the same size
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, AError> {
+        if bytes.len() < HEADER_LEN || &bytes[..MAGIC.len()] != MAGIC {
+            return Err(AError::msg("Not a cells dump"));
+        }
+        let version = bytes[MAGIC.len()];
+        if version != VERSION {
+            return Err(AError::msg(format!(
+                "Unsupported cells dump version: {version}"
+            )));
+        }
+        let header = &bytes[MAGIC.len() + 1..HEADER_LEN];
+        let value_size = u32::read_bytes(&header[..4])? as usize;
+        if value_size != T::SIZE {
+            return Err(AError::msg(format!(
+                "Cells dump holds values of {value_size} bytes, expected {}",
+                T::SIZE
+            )));
+        }
+        let width = usize::read_bytes(&header[4..12])?;
+        let height = usize::read_bytes(&header[12..])?;
+        let values = &bytes[HEADER_LEN..];
+        if values.len() != width * height * T::SIZE {
+            return Err(AError::msg(format!(
+                "Cells dump of {width}x{height} has {} bytes of values, expected {}",
+                values.len(),
+                width * height * T::SIZE
+            )));
+        }
+        let contents = values
+            .chunks_exact(T::SIZE)
+            .map(T::read_bytes)
+            .collect::<Result<Vec<T>, AError>>()?;
+        Ok(Cells {
+            contents,
+            side_lengths: (width, height),
+        })
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), AError> {
+        fs::write(path, self.to_bytes())
+            .with_context(|| format!("Failed writing {}", path.display()))
+    }
+
+    pub fn load(path: &Path) -> Result<Self, AError> {
+        let bytes = fs::read(path).with_context(|| format!("Failed reading {}", path.display()))?;
+        Self::from_bytes(&bytes).with_context(|| format!("Failed loading {}", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cells_round_trip() {
+        let mut cells = Cells::with_dimension(3, 2, None::<(usize, i32)>);
+        *cells.get_mut(2, 1).unwrap() = Some((usize::MAX, -7));
+        *cells.get_mut(0, 0).unwrap() = Some((1, 2));
+        let bytes = cells.to_bytes();
+        assert_eq!(bytes.len(), HEADER_LEN + 6 * 13);
+        assert_eq!(Cells::from_bytes(&bytes).unwrap(), cells);
+
+        assert!(Cells::<u8>::from_bytes(&bytes).is_err());
+        assert!(Cells::<Option<(usize, i32)>>::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+
+        let path = std::env::temp_dir().join(format!("aoc-cells-test-{}", std::process::id()));
+        let chars = Cells::with_dimension(2, 2, '#');
+        chars.save(&path).unwrap();
+        assert_eq!(Cells::<char>::load(&path).unwrap(), chars);
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,254 @@
+use std::{
+    iter,
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+    thread,
+};
+
+use crossbeam_deque::{Injector, Stealer, Worker};
+
+use crate::parallel::thread_count;
+
+/// Counts gathered while pumping a branching search
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BranchStats {
+    /// Nodes taken from the pending stack and expanded
+    pub expanded: usize,
+    /// Nodes dropped because their bound could not beat the best so far
+    pub pruned: usize,
+    /// Nodes accepted as complete solutions
+    pub accepted: usize,
+    /// Largest number of nodes pending at once
+    pub max_pending: usize,
+}
+
+impl BranchStats {
+    fn combine(self, other: BranchStats) -> BranchStats {
+        BranchStats {
+            expanded: self.expanded + other.expanded,
+            pruned: self.pruned + other.pruned,
+            accepted: self.accepted + other.accepted,
+            max_pending: self.max_pending.max(other.max_pending),
+        }
+    }
+}
+
+/// Result of a branching search: the best (or folded) value along with the stats
+#[derive(Debug, Clone)]
+pub struct BranchResult<V> {
+    pub value: V,
+    pub stats: BranchStats,
+}
+
+/// Depth first branch and bound search for the highest value of any accepted node.
+/// - `expand` gives the child nodes of a node
+/// - `bound` gives an upper bound on the value of any node reachable from (and including) a node, nodes
+///   whose bound is no better than the best found so far are pruned without being expanded
+/// - `accept` gives the value of a node if it is a complete solution
+///
+/// Accepted nodes are still expanded, so `expand` should return nothing for them if that is not wanted.
+pub fn branch_and_bound<N, V, E, B, A>(
+    root: N,
+    mut expand: E,
+    mut bound: B,
+    mut accept: A,
+) -> BranchResult<Option<V>>
+where
+    V: Ord + Copy,
+    E: FnMut(&N) -> Vec<N>,
+    B: FnMut(&N) -> V,
+    A: FnMut(&N) -> Option<V>,
+{
+    let mut stats = BranchStats::default();
+    let mut best: Option<V> = None;
+    let mut pending = vec![root];
+    while let Some(node) = pending.pop() {
+        if best.is_some_and(|best| bound(&node) <= best) {
+            stats.pruned += 1;
+            continue;
+        }
+        if let Some(value) = accept(&node) {
+            stats.accepted += 1;
+            best = Some(best.map_or(value, |best| best.max(value)));
+        }
+        stats.expanded += 1;
+        pending.extend(expand(&node));
+        stats.max_pending = stats.max_pending.max(pending.len());
+    }
+    BranchResult { value: best, stats }
+}
+
+/// Exhaustive depth first search, folding the values of every accepted node.  The same as
+/// [`branch_and_bound`] but nothing is ever pruned.
+pub fn branch_and_fold<N, V, R, E, A, F>(
+    root: N,
+    mut expand: E,
+    mut accept: A,
+    initial: R,
+    mut fold: F,
+) -> BranchResult<R>
+where
+    E: FnMut(&N) -> Vec<N>,
+    A: FnMut(&N) -> Option<V>,
+    F: FnMut(R, V) -> R,
+{
+    let mut stats = BranchStats::default();
+    let mut result = initial;
+    let mut pending = vec![root];
+    while let Some(node) = pending.pop() {
+        if let Some(value) = accept(&node) {
+            stats.accepted += 1;
+            result = fold(result, value);
+        }
+        stats.expanded += 1;
+        pending.extend(expand(&node));
+        stats.max_pending = stats.max_pending.max(pending.len());
+    }
+    BranchResult {
+        value: result,
+        stats,
+    }
+}
+
+/// Take the next node from this thread's own deque, or failing that steal one from the injector or another thread
+fn find_node<N>(local: &Worker<N>, injector: &Injector<N>, stealers: &[Stealer<N>]) -> Option<N> {
+    local.pop().or_else(|| {
+        iter::repeat_with(|| {
+            injector
+                .steal_batch_and_pop(local)
+                .or_else(|| stealers.iter().map(|s| s.steal()).collect())
+        })
+        .find(|s| !s.is_retry())
+        .and_then(|s| s.success())
+    })
+}
+
+/// Parallel version of [`branch_and_bound`].  Each thread works depth first through its own deque of nodes,
+/// stealing from the others when it runs out.  The best value found so far is shared between the threads
+/// so any of them can prune against it.
+pub fn par_branch_and_bound<N, E, B, A>(
+    root: N,
+    expand: E,
+    bound: B,
+    accept: A,
+) -> BranchResult<Option<usize>>
+where
+    N: Send,
+    E: Fn(&N) -> Vec<N> + Sync,
+    B: Fn(&N) -> usize + Sync,
+    A: Fn(&N) -> Option<usize> + Sync,
+{
+    let injector = Injector::new();
+    injector.push(root);
+    let workers: Vec<Worker<N>> = (0..thread_count()).map(|_| Worker::new_lifo()).collect();
+    let stealers: Vec<Stealer<N>> = workers.iter().map(|w| w.stealer()).collect();
+    // nodes pushed that haven't been fully processed yet, the search is over when this gets to 0
+    let pending = AtomicUsize::new(1);
+    let best = AtomicUsize::new(0);
+    let found = AtomicBool::new(false);
+
+    let stats = thread::scope(|scope| {
+        let handles: Vec<_> = workers
+            .into_iter()
+            .map(|local| {
+                let (injector, stealers) = (&injector, &stealers);
+                let (expand, bound, accept) = (&expand, &bound, &accept);
+                let (pending, best, found) = (&pending, &best, &found);
+                scope.spawn(move || {
+                    let mut stats = BranchStats::default();
+                    loop {
+                        let Some(node) = find_node(&local, injector, stealers) else {
+                            if pending.load(Ordering::SeqCst) == 0 {
+                                break;
+                            }
+                            thread::yield_now();
+                            continue;
+                        };
+                        if found.load(Ordering::SeqCst)
+                            && bound(&node) <= best.load(Ordering::SeqCst)
+                        {
+                            stats.pruned += 1;
+                        } else {
+                            if let Some(value) = accept(&node) {
+                                stats.accepted += 1;
+                                best.fetch_max(value, Ordering::SeqCst);
+                                found.store(true, Ordering::SeqCst);
+                            }
+                            stats.expanded += 1;
+                            let children = expand(&node);
+                            pending.fetch_add(children.len(), Ordering::SeqCst);
+                            children.into_iter().for_each(|child| local.push(child));
+                            stats.max_pending = stats.max_pending.max(local.len());
+                        }
+                        pending.fetch_sub(1, Ordering::SeqCst);
+                    }
+                    stats
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .fold(BranchStats::default(), BranchStats::combine)
+    });
+
+    BranchResult {
+        value: found.into_inner().then(|| best.into_inner()),
+        stats,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Nodes are (depth, sum) choosing to add 1..=3 at each level down to depth 3
+    fn expand(node: &(usize, usize)) -> Vec<(usize, usize)> {
+        let (depth, sum) = *node;
+        if depth == 3 {
+            return vec![];
+        }
+        (1..=3).map(|add| (depth + 1, sum + add)).collect()
+    }
+
+    fn accept(node: &(usize, usize)) -> Option<usize> {
+        (node.0 == 3).then_some(node.1)
+    }
+
+    #[test]
+    fn branch_and_bound_prunes() {
+        let unbounded = branch_and_bound((0, 0), expand, |_| usize::MAX, accept);
+        assert_eq!(unbounded.value, Some(9));
+        assert_eq!(unbounded.stats.accepted, 27);
+        assert_eq!(unbounded.stats.pruned, 0);
+
+        let bounded =
+            branch_and_bound((0, 0), expand, |(depth, sum)| sum + 3 * (3 - depth), accept);
+        assert_eq!(bounded.value, Some(9));
+        assert!(bounded.stats.pruned > 0);
+        assert!(bounded.stats.expanded < unbounded.stats.expanded);
+    }
+
+    #[test]
+    fn par_branch_and_bound_matches() {
+        let unbounded = par_branch_and_bound((0, 0), expand, |_| usize::MAX, accept);
+        assert_eq!(unbounded.value, Some(9));
+        assert_eq!(unbounded.stats.accepted, 27);
+        assert_eq!(unbounded.stats.expanded, 1 + 3 + 9 + 27);
+
+        let bounded =
+            par_branch_and_bound((0, 0), expand, |(depth, sum)| sum + 3 * (3 - depth), accept);
+        assert_eq!(bounded.value, Some(9));
+
+        let none = par_branch_and_bound((0, 0), expand, |_| usize::MAX, |_| None);
+        assert_eq!(none.value, None);
+    }
+
+    #[test]
+    fn branch_and_fold_visits_all() {
+        let result = branch_and_fold((0, 0), expand, accept, 0, |acc, sum| acc + sum);
+        // 27 leaves, each of the 3 levels adds an average of 2
+        assert_eq!(result.value, 27 * 6);
+        assert_eq!(result.stats.accepted, 27);
+        assert_eq!(result.stats.expanded, 1 + 3 + 9 + 27);
+    }
+}
@@ -0,0 +1,73 @@
+//! Support for [`capture_into!`](crate::capture_into), parsing a line with a regex instead of the tokenizer
+
+use std::{error::Error, str::FromStr};
+
+use anyhow::Context;
+pub use regex::{Captures, Regex};
+
+use crate::AError;
+
+#[doc(hidden)]
+pub fn compile(pattern: &str) -> Regex {
+    Regex::new(pattern).unwrap_or_else(|e| panic!("Invalid capture_into! pattern: {e}"))
+}
+
+#[doc(hidden)]
+pub fn captures<'l>(regex: &Regex, line: &'l str) -> Result<Captures<'l>, AError> {
+    regex
+        .captures(line)
+        .ok_or_else(|| AError::msg(format!("'{line}' did not match '{regex}'")))
+}
+
+#[doc(hidden)]
+pub fn parse_capture<T>(captures: &Captures<'_>, name: &str) -> Result<T, AError>
+where
+    T: FromStr,
+    T::Err: Error + Send + Sync + 'static,
+{
+    let value = captures
+        .name(name)
+        .ok_or_else(|| AError::msg(format!("No '{name}' captured")))?
+        .as_str();
+    value
+        .parse::<T>()
+        .with_context(|| format!("Failed parsing capture '{name}': '{value}'"))
+}
+
+/// Match a line against a regex, parsing its named captures to the given types.  Gives a `Result` with a
+/// tuple of the values in the order they were named, e.g.
+/// ```
+/// use processor::capture_into;
+///
+/// let (name, amount) = capture_into!("px=-12", r"(?<name>\w+)=(?<amount>-?\d+)", name: String, amount: isize)?;
+/// assert_eq!((name.as_str(), amount), ("px", -12));
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+/// The pattern must be a string literal, as each use caches the regex it compiles to: it's compiled the first
+/// time that use is reached and panics if it isn't valid.
+#[macro_export]
+macro_rules! capture_into {
+    ($line:expr, $pattern:literal, $($name:ident: $t:ty),+ $(,)?) => {{
+        static REGEX: std::sync::OnceLock<$crate::captures::Regex> = std::sync::OnceLock::new();
+        let regex = REGEX.get_or_init(|| $crate::captures::compile($pattern));
+        let line: &str = &$line;
+        $crate::captures::captures(regex, line).and_then(|captures| {
+            Ok(($($crate::captures::parse_capture::<$t>(&captures, stringify!($name))?,)+))
+        })
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn capture_typed_values() {
+        let line = "19, 13 @ -2".to_string();
+        let capture = |line: &str| capture_into!(line, r"(?<x>\d+), (?<y>\d+) @ (?<v>-?\d+)", x: usize, y: u8, v: isize);
+        assert_eq!(capture(&line).unwrap(), (19, 13, -2));
+        assert!(capture("1, 2").is_err());
+        assert!(capture("1, 300 @ 3").is_err());
+        assert!(
+            capture_into!("1, 2 @ 3", r"(?<x>\d+), (?<y>\d+) @ (?<v>-?\d+)", z: usize).is_err()
+        );
+    }
+}
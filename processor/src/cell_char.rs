@@ -0,0 +1,58 @@
+/// A cell that is read from, and displayed as, a single char of a grid
+pub trait CellChar: Sized {
+    /// The cell for the char, or None if the char isn't recognised
+    fn from_char(c: char) -> Option<Self>;
+    fn to_char(&self) -> char;
+}
+
+/// Declare the char mapping of a cell enum once, implementing [`CellChar`] and `Display` from it.  Variants
+/// can carry fields as long as the same fields appear in every mapping for them, and extra chars can be given
+/// that read as a variant, with the first used when displaying it:
+/// ```
+/// use processor::{cell_chars, CellChar};
+///
+/// #[derive(Debug, PartialEq)]
+/// enum Direction { North, South }
+///
+/// #[derive(Debug, PartialEq)]
+/// enum Tile { Path, Forest, Slope { direction: Direction } }
+///
+/// cell_chars!(Tile {
+///     '.' | 'S' => Path,
+///     '#' => Forest,
+///     '^' => Slope { direction: Direction::North },
+///     'v' => Slope { direction: Direction::South },
+/// });
+///
+/// assert_eq!(Tile::from_char('S'), Some(Tile::Path));
+/// assert_eq!(Tile::from_char('x'), None);
+/// assert_eq!(Tile::Slope { direction: Direction::South }.to_string(), "v");
+/// ```
+#[macro_export]
+macro_rules! cell_chars {
+    ($cell:ident {
+        $($c:literal $(| $alternative:literal)* => $variant:ident
+            $(( $($tuple:tt)* ))? $({ $($fields:tt)* })?),+ $(,)?
+    }) => {
+        impl $crate::CellChar for $cell {
+            fn from_char(c: char) -> Option<Self> {
+                match c {
+                    $($c $(| $alternative)* => Some($cell::$variant $(( $($tuple)* ))? $({ $($fields)* })?),)+
+                    _ => None,
+                }
+            }
+
+            fn to_char(&self) -> char {
+                match self {
+                    $($cell::$variant $(( $($tuple)* ))? $({ $($fields)* })? => $c,)+
+                }
+            }
+        }
+
+        impl std::fmt::Display for $cell {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "{}", $crate::CellChar::to_char(self))
+            }
+        }
+    };
+}
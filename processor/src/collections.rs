@@ -0,0 +1,13 @@
+//! Map and set types for places where iteration order can leak into results or output (priming module
+//! inputs, picking a starting node, printing).  They are the hash based collections by default, building
+//! with the `deterministic` feature swaps in the BTree based ones so that runs and traces are reproducible.
+//! Keys need to be both `Hash` and `Ord` so that either build works.
+
+#[cfg(not(feature = "deterministic"))]
+pub use std::collections::{HashMap as Map, HashSet as Set};
+
+#[cfg(feature = "deterministic")]
+pub use std::collections::{BTreeMap as Map, BTreeSet as Set};
+
+/// Whether the `deterministic` feature is enabled, e.g. to seed any randomness
+pub const DETERMINISTIC: bool = cfg!(feature = "deterministic");
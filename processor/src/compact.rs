@@ -0,0 +1,131 @@
+use crate::Cells;
+
+/// Cells with runs of identical consecutive rows and columns collapsed to one, recording how many of the
+/// original rows/columns each stands for.  [`CompactedCells::expand`] gives back the original cells.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompactedCells<T> {
+    pub cells: Cells<T>,
+    /// Number of original columns each compacted column stands for
+    pub column_counts: Vec<usize>,
+    /// Number of original rows each compacted row stands for
+    pub row_counts: Vec<usize>,
+}
+
+/// Lengths of the runs of consecutive equal lines, where `same(a, b)` says whether lines a and b are equal
+fn run_lengths(length: usize, same: impl Fn(usize, usize) -> bool) -> Vec<usize> {
+    let mut runs: Vec<usize> = Vec::new();
+    for index in 0..length {
+        match runs.last_mut() {
+            Some(run) if same(index - 1, index) => *run += 1,
+            _ => runs.push(1),
+        }
+    }
+    runs
+}
+
+/// Index of the first original line of each run
+fn run_starts(runs: &[usize]) -> Vec<usize> {
+    runs.iter()
+        .scan(0, |start, run| {
+            let run_start = *start;
+            *start += run;
+            Some(run_start)
+        })
+        .collect()
+}
+
+impl<T: Clone + PartialEq> Cells<T> {
+    /// Collapse runs of identical consecutive rows and columns, e.g. to shrink large uniform regions before
+    /// searching them
+    pub fn compact(&self) -> CompactedCells<T> {
+        let (width, height) = self.side_lengths;
+        let cell = |x, y| &self[(x, y)];
+        let column_counts =
+            run_lengths(width, |a, b| (0..height).all(|y| cell(a, y) == cell(b, y)));
+        let row_counts = run_lengths(height, |a, b| (0..width).all(|x| cell(x, a) == cell(x, b)));
+        let (xs, ys) = (run_starts(&column_counts), run_starts(&row_counts));
+        let contents = ys
+            .iter()
+            .flat_map(|y| xs.iter().map(|x| cell(*x, *y).clone()))
+            .collect();
+        CompactedCells {
+            cells: Cells {
+                contents,
+                side_lengths: (xs.len(), ys.len()),
+            },
+            column_counts,
+            row_counts,
+        }
+    }
+}
+
+impl<T: Clone> CompactedCells<T> {
+    /// Side lengths of the cells before they were compacted
+    pub fn original_side_lengths(&self) -> (usize, usize) {
+        (
+            self.column_counts.iter().sum(),
+            self.row_counts.iter().sum(),
+        )
+    }
+
+    /// How many original cells the compacted cell at (x, y) stands for
+    pub fn weight(&self, x: usize, y: usize) -> usize {
+        self.column_counts[x] * self.row_counts[y]
+    }
+
+    /// Number of original cells that satisfy the predicate
+    pub fn count_original<P: Fn(&T) -> bool>(&self, predicate: P) -> usize {
+        self.cells
+            .iter()
+            .filter(|(_, cell)| predicate(cell))
+            .map(|((x, y), _)| self.weight(x, y))
+            .sum()
+    }
+
+    /// Repeat each row and column by its count to get back the original cells
+    pub fn expand(&self) -> Cells<T> {
+        let (width, height) = self.original_side_lengths();
+        let mut contents = Vec::with_capacity(width * height);
+        for (y, row_count) in self.row_counts.iter().enumerate() {
+            let row: Vec<T> = self
+                .column_counts
+                .iter()
+                .enumerate()
+                .flat_map(|(x, column_count)| {
+                    std::iter::repeat_n(&self.cells[(x, y)], *column_count).cloned()
+                })
+                .collect();
+            for _ in 0..*row_count {
+                contents.extend(row.iter().cloned());
+            }
+        }
+        Cells {
+            contents,
+            side_lengths: (width, height),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Cells, CellsBuilder};
+
+    #[test]
+    fn compact_and_expand() {
+        let mut builder = CellsBuilder::new_empty();
+        for line in ["...#", "...#", "....", "...#"] {
+            builder.new_line();
+            line.chars().for_each(|c| builder.add_cell(c).unwrap());
+        }
+        let cells: Cells<char> = builder.build_cells('.').unwrap();
+        let compacted = cells.compact();
+        assert_eq!(compacted.cells.side_lengths, (2, 3));
+        assert_eq!(format!("{}", compacted.cells), ".#\n..\n.#\n");
+        assert_eq!(compacted.column_counts, vec![3, 1]);
+        assert_eq!(compacted.row_counts, vec![2, 1, 1]);
+        assert_eq!(compacted.original_side_lengths(), (4, 4));
+        assert_eq!(compacted.weight(0, 0), 6);
+        assert_eq!(compacted.count_original(|c| *c == '#'), 3);
+        assert_eq!(compacted.expand(), cells);
+    }
+}
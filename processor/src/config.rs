@@ -0,0 +1,295 @@
+use std::{collections::BTreeMap, error::Error, fmt::Display, str::FromStr};
+
+use anyhow::Context;
+
+use crate::AError;
+
+/// Environment variable overriding the input file, both for [`PuzzleConfig::apply_env`] with the `AOC` prefix
+/// and for [`input_file`]
+pub const FILE_ENV: &str = "AOC_FILE";
+
+/// Optional TOML file, in the directory a day is run from, holding the parameters for its input so that they
+/// needn't be edited in to the source, see [`crate::PuzzleMetadata::load_config`]
+pub const DAY_CONFIG_FILE: &str = "config.toml";
+
+/// The input file for days without a [`PuzzleConfig`]: the default unless overridden by [`FILE_ENV`]
+pub fn input_file(default: &str) -> String {
+    std::env::var(FILE_ENV).unwrap_or_else(|_| default.to_string())
+}
+
+/// Named parameters for a puzzle run - the input file along with any puzzle specific values
+/// (numbers of steps, bounds, etc.).  Parameters can be scoped to a part by prefixing them with
+/// `partN.`, e.g. `part2.total_steps`, which then override the unscoped value in [`PuzzleConfig::for_part`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PuzzleConfig {
+    pub file: String,
+    params: BTreeMap<String, String>,
+}
+
+impl PuzzleConfig {
+    pub fn new(file: &str) -> Self {
+        PuzzleConfig {
+            file: file.to_string(),
+            params: BTreeMap::default(),
+        }
+    }
+
+    /// Set a parameter, builder style
+    pub fn with<V: Display>(mut self, name: &str, value: V) -> Self {
+        self.set(name, value);
+        self
+    }
+
+    pub fn set<V: Display>(&mut self, name: &str, value: V) {
+        self.params.insert(name.to_string(), value.to_string());
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.params.contains_key(name)
+    }
+
+    /// Get the named parameter, parsed to the required type
+    pub fn get<T>(&self, name: &str) -> Result<T, AError>
+    where
+        T: FromStr,
+        T::Err: Error + Send + Sync + 'static,
+    {
+        let value = self
+            .params
+            .get(name)
+            .ok_or_else(|| AError::msg(format!("No '{name}' parameter configured")))?;
+        value
+            .parse::<T>()
+            .with_context(|| format!("Failed parsing parameter '{name}': '{value}'"))
+    }
+
+    /// Get the named parameter, or the default if it hasn't been configured
+    pub fn get_or<T>(&self, name: &str, default: T) -> Result<T, AError>
+    where
+        T: FromStr,
+        T::Err: Error + Send + Sync + 'static,
+    {
+        if self.contains(name) {
+            self.get(name)
+        } else {
+            Ok(default)
+        }
+    }
+
+    /// The parameters as seen by the given part, i.e. with any `partN.` parameters overriding the unscoped ones
+    pub fn for_part(&self, part: usize) -> PuzzleConfig {
+        let prefix = format!("part{part}.");
+        let mut config = self.clone();
+        for (name, value) in self.params.iter() {
+            if let Some(name) = name.strip_prefix(&prefix) {
+                if name == "file" {
+                    config.file = value.clone();
+                } else {
+                    config.params.insert(name.to_string(), value.clone());
+                }
+            }
+        }
+        config
+    }
+
+    /// Override from `name=value` arguments, e.g. from the command line.  `file=...` sets the file.  A bare
+    /// `--table` argument scopes the `name=value` arguments after it as a TOML table would, so that
+    /// `--inspect step=10` sets `inspect.step`.
+    pub fn apply_args<I, S>(mut self, args: I) -> Result<Self, AError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut scope: Option<(String, bool)> = None;
+        for arg in args {
+            let arg = arg.as_ref();
+            if arg.starts_with("--") {
+                if let Some((table, false)) = scope.take() {
+                    return Err(AError::msg(format!(
+                        "Expected name=value after '--{table}'"
+                    )));
+                }
+            }
+            let Some((name, value)) = arg.trim_start_matches("--").split_once('=') else {
+                match arg.strip_prefix("--") {
+                    Some(table) if !table.is_empty() => {
+                        scope = Some((table.to_string(), false));
+                        continue;
+                    }
+                    _ => return Err(AError::msg(format!("Expected name=value but got '{arg}'"))),
+                }
+            };
+            let name = match scope.as_mut() {
+                Some((table, used)) if !arg.starts_with("--") => {
+                    *used = true;
+                    format!("{table}.{}", name.trim())
+                }
+                _ => name.trim().to_string(),
+            };
+            self.set_named(&name, value.trim());
+        }
+        if let Some((table, false)) = scope {
+            return Err(AError::msg(format!(
+                "Expected name=value after '--{table}'"
+            )));
+        }
+        Ok(self)
+    }
+
+    /// Override any already configured parameters (and the file) from environment variables named
+    /// `{PREFIX}_{NAME}`, with the name upper cased and any '.' replaced by '__'
+    pub fn apply_env(mut self, prefix: &str) -> Self {
+        let names: Vec<String> = self
+            .params
+            .keys()
+            .cloned()
+            .chain(std::iter::once("file".to_string()))
+            .collect();
+        for name in names {
+            let variable = format!("{prefix}_{}", name.to_uppercase().replace('.', "__"));
+            if let Ok(value) = std::env::var(variable) {
+                self.set_named(&name, &value);
+            }
+        }
+        self
+    }
+
+    /// Override from a TOML document.  Tables are flattened in to dotted names so that `[part2]`
+    /// tables give part scoped parameters
+    pub fn apply_toml_str(mut self, toml: &str) -> Result<Self, AError> {
+        let table = toml
+            .parse::<toml::Table>()
+            .with_context(|| "Failed parsing TOML config")?;
+        self.apply_toml_table("", &table)?;
+        Ok(self)
+    }
+
+    pub fn apply_toml_file(self, file_name: &str) -> Result<Self, AError> {
+        let contents = std::fs::read_to_string(file_name)
+            .with_context(|| format!("Failed reading config file: '{file_name}'"))?;
+        self.apply_toml_str(&contents)
+    }
+
+    /// As [`PuzzleConfig::apply_toml_file`], leaving the config as it is if there's no such file
+    pub fn apply_toml_file_if_present(self, file_name: &str) -> Result<Self, AError> {
+        if std::path::Path::new(file_name).exists() {
+            self.apply_toml_file(file_name)
+        } else {
+            Ok(self)
+        }
+    }
+
+    fn apply_toml_table(&mut self, prefix: &str, table: &toml::Table) -> Result<(), AError> {
+        for (name, value) in table.iter() {
+            let name = format!("{prefix}{name}");
+            match value {
+                toml::Value::Table(table) => self.apply_toml_table(&format!("{name}."), table)?,
+                toml::Value::String(s) => self.set_named(&name, s),
+                toml::Value::Integer(i) => self.set_named(&name, &i.to_string()),
+                toml::Value::Float(f) => self.set_named(&name, &f.to_string()),
+                toml::Value::Boolean(b) => self.set_named(&name, &b.to_string()),
+                _ => {
+                    return Err(AError::msg(format!(
+                        "Unsupported value for config parameter '{name}': {value}"
+                    )))
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn set_named(&mut self, name: &str, value: &str) {
+        if name == "file" {
+            self.file = value.to_string();
+        } else {
+            self.set(name, value);
+        }
+    }
+}
+
+impl Display for PuzzleConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "file={}", self.file)?;
+        for (name, value) in self.params.iter() {
+            write!(f, " {name}={value}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn typed_parameters() {
+        let config = PuzzleConfig::new("input.txt")
+            .with("total_steps", 64)
+            .with("bound", -7isize)
+            .with("part2.total_steps", 500);
+        assert_eq!(config.get::<usize>("total_steps").unwrap(), 64);
+        assert_eq!(config.get::<isize>("bound").unwrap(), -7);
+        assert!(config.get::<usize>("bound").is_err());
+        assert!(config.get::<usize>("missing").is_err());
+        assert_eq!(config.get_or("missing", 3usize).unwrap(), 3);
+
+        let part2 = config.for_part(2);
+        assert_eq!(part2.get::<usize>("total_steps").unwrap(), 500);
+        assert_eq!(config.for_part(1).get::<usize>("total_steps").unwrap(), 64);
+    }
+
+    #[test]
+    fn overrides() {
+        let config = PuzzleConfig::new("input.txt")
+            .with("total_steps", 64)
+            .apply_args(["file=test-input.txt", "--total_steps=6"])
+            .unwrap();
+        assert_eq!(config.file, "test-input.txt");
+        assert_eq!(config.get::<usize>("total_steps").unwrap(), 6);
+        assert!(config.clone().apply_args(["nonsense"]).is_err());
+
+        let scoped = config
+            .clone()
+            .apply_args([
+                "--inspect",
+                "step=10",
+                "every=2",
+                "--total_steps=7",
+                "bound=3",
+            ])
+            .unwrap();
+        assert_eq!(scoped.get::<usize>("inspect.step").unwrap(), 10);
+        assert_eq!(scoped.get::<usize>("inspect.every").unwrap(), 2);
+        assert_eq!(scoped.get::<usize>("total_steps").unwrap(), 7);
+        assert_eq!(scoped.get::<usize>("bound").unwrap(), 3);
+        assert!(config.clone().apply_args(["--inspect"]).is_err());
+        assert!(config
+            .clone()
+            .apply_args(["--inspect", "--bound=1"])
+            .is_err());
+
+        let config = config
+            .apply_toml_str("total_steps = 10\n[part2]\ntotal_steps = 20\nfile = \"input.txt\"\n")
+            .unwrap();
+        assert_eq!(config.get::<usize>("total_steps").unwrap(), 10);
+        let part2 = config.for_part(2);
+        assert_eq!(part2.get::<usize>("total_steps").unwrap(), 20);
+        assert_eq!(part2.file, "input.txt");
+        assert_eq!(
+            format!("{config}"),
+            "file=test-input.txt part2.file=input.txt part2.total_steps=20 total_steps=10"
+        );
+
+        let path =
+            std::env::temp_dir().join(format!("aoc-config-test-{}.toml", std::process::id()));
+        let path = path.to_str().unwrap();
+        assert_eq!(
+            config.clone().apply_toml_file_if_present(path).unwrap(),
+            config
+        );
+        std::fs::write(path, "inside_tile = \"1,1\"\n").unwrap();
+        let from_file = config.apply_toml_file_if_present(path).unwrap();
+        std::fs::remove_file(path).unwrap();
+        assert_eq!(from_file.get::<String>("inside_tile").unwrap(), "1,1");
+    }
+}
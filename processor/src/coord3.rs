@@ -0,0 +1,112 @@
+//! A point (or vector) in 3d, e.g. day22's brick corners or day24's hailstone positions and velocities, with
+//! the arithmetic for moving them about.
+
+use std::{
+    fmt::Display,
+    ops::{Add, Mul, Neg, Sub},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Coord3<T> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+}
+
+impl<T> Coord3<T> {
+    pub fn new(x: T, y: T, z: T) -> Coord3<T> {
+        Coord3 { x, y, z }
+    }
+
+    /// The same coord with f applied to each value, e.g. to change their type
+    pub fn map<U>(self, mut f: impl FnMut(T) -> U) -> Coord3<U> {
+        Coord3::new(f(self.x), f(self.y), f(self.z))
+    }
+
+    /// x, y and z in that order
+    pub fn to_array(self) -> [T; 3] {
+        [self.x, self.y, self.z]
+    }
+}
+
+impl<T> Coord3<T>
+where
+    T: Copy + Add<Output = T> + Sub<Output = T> + Mul<Output = T>,
+{
+    pub fn dot(&self, other: &Coord3<T>) -> T {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    /// The vector at right angles to both, following the right hand rule
+    pub fn cross(&self, other: &Coord3<T>) -> Coord3<T> {
+        Coord3::new(
+            self.y * other.z - self.z * other.y,
+            self.z * other.x - self.x * other.z,
+            self.x * other.y - self.y * other.x,
+        )
+    }
+}
+
+impl<T: Add<Output = T>> Add for Coord3<T> {
+    type Output = Coord3<T>;
+
+    fn add(self, other: Coord3<T>) -> Coord3<T> {
+        Coord3::new(self.x + other.x, self.y + other.y, self.z + other.z)
+    }
+}
+
+impl<T: Sub<Output = T>> Sub for Coord3<T> {
+    type Output = Coord3<T>;
+
+    fn sub(self, other: Coord3<T>) -> Coord3<T> {
+        Coord3::new(self.x - other.x, self.y - other.y, self.z - other.z)
+    }
+}
+
+/// Scaling by a scalar
+impl<T: Copy + Mul<Output = T>> Mul<T> for Coord3<T> {
+    type Output = Coord3<T>;
+
+    fn mul(self, scalar: T) -> Coord3<T> {
+        Coord3::new(self.x * scalar, self.y * scalar, self.z * scalar)
+    }
+}
+
+impl<T: Neg<Output = T>> Neg for Coord3<T> {
+    type Output = Coord3<T>;
+
+    fn neg(self) -> Coord3<T> {
+        Coord3::new(-self.x, -self.y, -self.z)
+    }
+}
+
+impl<T: Display> Display for Coord3<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "({},{},{})", self.x, self.y, self.z)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vector_arithmetic() {
+        let a = Coord3::new(1, 2, 3);
+        let b = Coord3::new(4, -5, 6);
+        assert_eq!(a + b, Coord3::new(5, -3, 9));
+        assert_eq!(a - b, Coord3::new(-3, 7, -3));
+        assert_eq!(a * 2, Coord3::new(2, 4, 6));
+        assert_eq!(-a, Coord3::new(-1, -2, -3));
+        assert_eq!(a.dot(&b), 12);
+        let cross = a.cross(&b);
+        assert_eq!(cross, Coord3::new(27, 6, -13));
+        assert_eq!((cross.dot(&a), cross.dot(&b)), (0, 0));
+        assert_eq!(
+            a.map(|value| value as f64 / 2.0),
+            Coord3::new(0.5, 1.0, 1.5)
+        );
+        assert_eq!(a.to_array(), [1, 2, 3]);
+        assert_eq!(Coord3::new(1usize, 20, 300).to_string(), "(1,20,300)");
+    }
+}
@@ -0,0 +1,252 @@
+use std::{collections::HashSet, error::Error, str::Chars, str::FromStr};
+
+use crate::{read_next, read_radix, read_word, AError, Delimiter, Token, BRACKETS, QUOTE};
+
+/// A position in a [`Cursor`] that can be returned to with [`Cursor::reset`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Mark(usize);
+
+/// Reads through a `&str` a char at a time, with peeking and backtracking to a [`Mark`]
+#[derive(Debug, Clone)]
+pub struct Cursor<'a> {
+    source: &'a str,
+    position: usize,
+}
+
+impl<'a> Cursor<'a> {
+    pub fn new(source: &'a str) -> Self {
+        Cursor {
+            source,
+            position: 0,
+        }
+    }
+
+    /// Byte offset of the next char in the source
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Everything not yet consumed
+    pub fn rest(&self) -> &'a str {
+        &self.source[self.position..]
+    }
+
+    pub fn is_at_end(&self) -> bool {
+        self.position == self.source.len()
+    }
+
+    pub fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    /// Consume the next char only if it satisfies the predicate
+    pub fn next_if(&mut self, predicate: impl FnOnce(char) -> bool) -> Option<char> {
+        let c = self.peek().filter(|c| predicate(*c))?;
+        self.position += c.len_utf8();
+        Some(c)
+    }
+
+    /// Consume the next char if it is the expected one, returning whether it was
+    pub fn eat(&mut self, expected: char) -> bool {
+        self.next_if(|c| c == expected).is_some()
+    }
+
+    pub fn mark(&self) -> Mark {
+        Mark(self.position)
+    }
+
+    /// Go back (or forward) to a mark taken from this cursor
+    pub fn reset(&mut self, mark: Mark) {
+        self.position = mark.0;
+    }
+
+    /// Everything consumed since the mark
+    pub fn since(&self, mark: Mark) -> &'a str {
+        &self.source[mark.0..self.position]
+    }
+
+    /// Run something that reads from `Chars`, advancing the cursor past whatever it consumed
+    fn with_chars<R>(&mut self, read: impl FnOnce(&mut Chars<'a>) -> R) -> R {
+        let mut chars = self.rest().chars();
+        let result = read(&mut chars);
+        self.position = self.source.len() - chars.as_str().len();
+        result
+    }
+
+    /// See [`crate::read_word`]
+    pub fn read_word(
+        &mut self,
+        delimiters: &HashSet<Delimiter>,
+    ) -> Option<(String, Option<Delimiter>)> {
+        self.with_chars(|chars| read_word(chars, delimiters))
+    }
+
+    /// See [`crate::read_next`]
+    pub fn read_next<T>(
+        &mut self,
+        delimiters: &HashSet<Delimiter>,
+    ) -> Result<(T, Option<Delimiter>), AError>
+    where
+        T: FromStr,
+        T::Err: Error + Send + Sync + 'static,
+    {
+        self.with_chars(|chars| read_next(chars, delimiters))
+    }
+
+    /// See [`crate::read_radix`]
+    pub fn read_radix<T>(
+        &mut self,
+        delimiters: &HashSet<Delimiter>,
+        radix: u32,
+    ) -> Result<(T, Option<Delimiter>), AError>
+    where
+        T: num::Num,
+        T::FromStrRadixErr: Error + Send + Sync + 'static,
+    {
+        self.with_chars(|chars| read_radix(chars, delimiters, radix))
+    }
+
+    /// Consume the next char if it is a delimiter, returning it
+    fn take_delimiter(&mut self, delimiters: &HashSet<Delimiter>) -> Option<Delimiter> {
+        self.next_if(|c| delimiters.contains(&c))
+    }
+
+    /// Read from just after an opening bracket to its matching closing bracket.  Nested brackets (of any kind)
+    /// and quoted strings are kept as part of the contents.
+    fn read_group_contents(&mut self, open: char) -> Result<String, AError> {
+        let start = self.mark();
+        let mut expected_closes = vec![closing_bracket(open).unwrap()];
+        let mut in_quotes = false;
+        while let Some(c) = self.next() {
+            if in_quotes {
+                in_quotes = c != QUOTE;
+            } else if c == QUOTE {
+                in_quotes = true;
+            } else if let Some(close) = closing_bracket(c) {
+                expected_closes.push(close);
+            } else if BRACKETS.iter().any(|(_, close)| *close == c) {
+                if expected_closes.pop() != Some(c) {
+                    return Err(AError::msg(format!(
+                        "Mismatched '{c}' in group: '{open}{}'",
+                        self.since(start)
+                    )));
+                }
+                if expected_closes.is_empty() {
+                    let contents = self.since(start);
+                    return Ok(contents[..contents.len() - c.len_utf8()].to_string());
+                }
+            }
+        }
+        Err(AError::msg(format!(
+            "Unclosed group: '{open}{}'",
+            self.since(start)
+        )))
+    }
+
+    /// See [`crate::read_token`]
+    pub fn read_token(
+        &mut self,
+        delimiters: &HashSet<Delimiter>,
+    ) -> Result<Option<(Token, Option<Delimiter>)>, AError> {
+        while self.take_delimiter(delimiters).is_some() {}
+        if self.is_at_end() {
+            return Ok(None);
+        }
+        let token = if let Some(open) = self.next_if(|c| closing_bracket(c).is_some()) {
+            Token::Group {
+                open,
+                contents: self.read_group_contents(open)?,
+            }
+        } else if self.eat(QUOTE) {
+            let start = self.mark();
+            while self.next_if(|c| c != QUOTE).is_some() {}
+            let quoted = self.since(start).to_string();
+            if !self.eat(QUOTE) {
+                return Err(AError::msg(format!("Unclosed quote: '\"{quoted}'")));
+            }
+            Token::Quoted(quoted)
+        } else {
+            let start = self.mark();
+            while self
+                .next_if(|c| !delimiters.contains(&c) && c != QUOTE && closing_bracket(c).is_none())
+                .is_some()
+            {}
+            Token::Word(self.since(start).to_string())
+        };
+        Ok(Some((token, self.take_delimiter(delimiters))))
+    }
+
+    /// See [`crate::read_group`]
+    pub fn read_group(
+        &mut self,
+        delimiters: &HashSet<Delimiter>,
+        open: char,
+    ) -> Result<(String, Option<Delimiter>), AError> {
+        match self.read_token(delimiters)? {
+            Some((Token::Group { open: o, contents }, delimiter)) if o == open => {
+                Ok((contents, delimiter))
+            }
+            Some((token, _)) => Err(AError::msg(format!(
+                "Expected a '{open}' group but got {token:?}"
+            ))),
+            None => Err(AError::msg(format!(
+                "Expected a '{open}' group but got nothing"
+            ))),
+        }
+    }
+}
+
+impl Iterator for Cursor<'_> {
+    type Item = char;
+
+    fn next(&mut self) -> Option<char> {
+        self.next_if(|_| true)
+    }
+}
+
+/// Run a cursor over the remaining chars, advancing them past whatever the cursor consumed
+pub(crate) fn on_chars<'a, R>(chars: &mut Chars<'a>, read: impl FnOnce(&mut Cursor<'a>) -> R) -> R {
+    let mut cursor = Cursor::new(chars.as_str());
+    let result = read(&mut cursor);
+    *chars = cursor.rest().chars();
+    result
+}
+
+fn closing_bracket(open: char) -> Option<char> {
+    BRACKETS
+        .iter()
+        .find(|(o, _)| *o == open)
+        .map(|(_, close)| *close)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use once_cell::sync::Lazy;
+
+    static WORD_DELIMITERS: Lazy<HashSet<char>> = Lazy::new(|| HashSet::from([' ', ',']));
+
+    #[test]
+    fn peek_mark_and_reset() {
+        let mut cursor = Cursor::new("%ab -> c");
+        assert_eq!(cursor.peek(), Some('%'));
+        assert!(!cursor.eat('&'));
+        assert!(cursor.eat('%'));
+        let mark = cursor.mark();
+        assert_eq!(
+            cursor.read_word(&WORD_DELIMITERS),
+            Some(("ab".to_string(), Some(' ')))
+        );
+        assert_eq!(cursor.since(mark), "ab ");
+        assert_eq!(cursor.position(), 4);
+        cursor.reset(mark);
+        assert_eq!(cursor.rest(), "ab -> c");
+        assert_eq!(cursor.collect::<String>(), "ab -> c");
+
+        let mut cursor = Cursor::new("é1");
+        assert_eq!(cursor.next(), Some('é'));
+        assert_eq!(cursor.position(), 2);
+        assert_eq!(cursor.read_next::<u8>(&WORD_DELIMITERS).unwrap(), (1, None));
+        assert!(cursor.is_at_end());
+    }
+}
@@ -0,0 +1,124 @@
+//! Finding where a sequence of states starts repeating, e.g. a simulation that ends up back where it was, so
+//! the state after a huge number of iterations can be read from the ones already seen.
+
+use std::{collections::HashMap, hash::Hash};
+
+/// The states from `start` onwards repeat every `period`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    /// 0 based index of the first state in the repetition, i.e. the length of the prefix before it
+    pub start: usize,
+    pub period: usize,
+}
+
+impl Cycle {
+    /// The index of the first state seen that is the same as the one at `index`, always less than
+    /// `start + period`
+    pub fn equivalent_index(&self, index: usize) -> usize {
+        if index < self.start {
+            index
+        } else {
+            self.start + (index - self.start) % self.period
+        }
+    }
+
+    /// The value at `index` given the values for (at least) the prefix and one period
+    pub fn value_at<'a, V>(&self, values: &'a [V], index: usize) -> Option<&'a V> {
+        values.get(self.equivalent_index(index))
+    }
+}
+
+/// The first repeat in the states, remembering each state until it is found.  None if the states run out first.
+pub fn find_cycle<T: Hash + Eq>(states: impl IntoIterator<Item = T>) -> Option<Cycle> {
+    let mut seen: HashMap<T, usize> = HashMap::default();
+    for (index, state) in states.into_iter().enumerate() {
+        if let Some(start) = seen.insert(state, index) {
+            return Some(Cycle {
+                start,
+                period: index - start,
+            });
+        }
+    }
+    None
+}
+
+/// Brent's algorithm for the first repeat, only keeping a couple of states at a time but running through the
+/// states more than once (which is why they must be cloneable).  None if the states run out first.
+pub fn brent<I>(states: I) -> Option<Cycle>
+where
+    I: Iterator + Clone,
+    I::Item: PartialEq,
+{
+    // find the period, moving the tortoise up to the hare at each power of 2
+    let mut hares = states.clone();
+    let mut tortoise = hares.next()?;
+    let mut hare = hares.next()?;
+    let mut power = 1;
+    let mut period = 1;
+    while tortoise != hare {
+        if power == period {
+            tortoise = hare;
+            power *= 2;
+            period = 0;
+        }
+        hare = hares.next()?;
+        period += 1;
+    }
+
+    // then the start, moving two states a period apart along together until they meet
+    let mut tortoises = states.clone();
+    let mut hares = states;
+    let mut tortoise = tortoises.next()?;
+    let mut hare = hares.nth(period)?;
+    let mut start = 0;
+    while tortoise != hare {
+        tortoise = tortoises.next()?;
+        hare = hares.next()?;
+        start += 1;
+    }
+    Some(Cycle { start, period })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::iter::successors;
+
+    use super::*;
+
+    fn states(seed: u64, modulus: u64) -> impl Iterator<Item = u64> + Clone {
+        successors(Some(seed), move |x| Some((x * x + 1) % modulus))
+    }
+
+    #[test]
+    fn cycles_found() {
+        let sequence = [5, 3, 8, 1, 2, 8, 1, 2, 8];
+        let cycle = find_cycle(sequence).unwrap();
+        assert_eq!(
+            cycle,
+            Cycle {
+                start: 2,
+                period: 3
+            }
+        );
+        assert_eq!(brent(sequence.iter()), Some(cycle));
+        assert_eq!(cycle.equivalent_index(1), 1);
+        assert_eq!(cycle.equivalent_index(100), 4);
+        assert_eq!(cycle.value_at(&sequence[..5], 100), Some(&2));
+
+        for (seed, modulus) in [(0, 255), (3, 1000), (7, 9973), (2, 2)] {
+            let cycle = find_cycle(states(seed, modulus)).unwrap();
+            assert_eq!(
+                brent(states(seed, modulus)),
+                Some(cycle),
+                "{seed} mod {modulus}"
+            );
+            let values: Vec<u64> = states(seed, modulus).take(500).collect();
+            let expected = states(seed, modulus).nth(499).unwrap();
+            assert_eq!(cycle.value_at(&values, 499), Some(&expected));
+        }
+
+        assert_eq!(find_cycle(1..10), None);
+        assert_eq!(brent(1..10), None);
+        assert_eq!(brent(std::iter::empty::<u8>()), None);
+    }
+}
@@ -0,0 +1,124 @@
+//! Days written as a parse of the input shared by both parts followed by each part's answer from it, which
+//! needs none of the `main` boilerplate of the [`crate::process`] pipeline: [`run_day`] does all of it.
+
+use std::{
+    fs::File,
+    io::{BufRead, BufReader},
+};
+
+use crate::{
+    diagnostics, part_requested, timings::PhaseTimer, AError, Answer, Solver, STDIN_FILE_NAME,
+};
+
+pub trait Day: Solver {
+    /// What both parts work from
+    type Parsed;
+
+    fn parse(lines: impl Iterator<Item = String>) -> Result<Self::Parsed, AError>;
+    fn part1(parsed: &Self::Parsed) -> Result<Answer, AError>;
+    fn part2(parsed: &Self::Parsed) -> Result<Answer, AError>;
+}
+
+/// The answer to the part, 1 or 2
+pub fn solve<D: Day>(parsed: &D::Parsed, part: u8) -> Result<Answer, AError> {
+    match part {
+        1 => D::part1(parsed),
+        2 => D::part2(parsed),
+        _ => Err(AError::msg(format!("No part {part}"))),
+    }
+}
+
+/// Parse a string, e.g. an example in a test
+pub fn parse_str<D: Day>(input: &str) -> Result<D::Parsed, AError> {
+    D::parse(input.lines().map(str::to_string))
+}
+
+/// Parse the file, or stdin if it's [`STDIN_FILE_NAME`]
+pub fn parse_file<D: Day>(file_name: &str) -> Result<D::Parsed, AError> {
+    let lines: Vec<String> = if file_name == STDIN_FILE_NAME {
+        std::io::stdin().lock().lines().collect::<Result<_, _>>()?
+    } else {
+        BufReader::new(File::open(file_name)?)
+            .lines()
+            .collect::<Result<_, _>>()?
+    };
+    D::parse(lines.into_iter())
+}
+
+/// The whole of a day's `main`: describe the day if asked to, then parse its input once and print the answers
+/// to the parts not skipped, with their phase timings and diagnostics as for [`crate::process`].  The input is
+/// the default file unless overridden by the day's config (see [`crate::PuzzleMetadata::load_config`]), a
+/// part scoped `partN.file` being parsed separately for that part.
+pub fn run_day<D: Day>(default_file: &str) {
+    crate::describe_if_requested::<D>();
+    let config = D::metadata()
+        .load_config(default_file)
+        .expect("Invalid configuration");
+    let mut parsed: Option<(String, Result<D::Parsed, AError>)> = None;
+    for part in [1, 2].into_iter().filter(|part| part_requested(*part)) {
+        let file = config.for_part(part as usize).file;
+        let mut timer = PhaseTimer::start();
+        if parsed
+            .as_ref()
+            .is_some_and(|(parsed_file, _)| *parsed_file != file)
+        {
+            parsed = None;
+        }
+        let (_, parsed) = parsed.get_or_insert_with(|| {
+            let parsed = parse_file::<D>(&file);
+            timer.lap("parse");
+            (file.clone(), parsed)
+        });
+        let result = match parsed {
+            Ok(parsed) => solve::<D>(parsed, part),
+            Err(e) => Err(AError::msg(format!("Failed parsing {file}: {e}"))),
+        };
+        timer.lap("process");
+        diagnostics::report(timer.report_as(part as usize));
+        match result {
+            Ok(answer) => println!("Result {part}: {answer}"),
+            Err(e) => println!("Error on {part}: {e}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PuzzleMetadata;
+
+    struct Sums;
+
+    impl Solver for Sums {
+        fn metadata() -> PuzzleMetadata {
+            PuzzleMetadata::new(0, "Sums")
+        }
+    }
+
+    impl Day for Sums {
+        type Parsed = Vec<i64>;
+
+        fn parse(lines: impl Iterator<Item = String>) -> Result<Self::Parsed, AError> {
+            lines
+                .map(|line| line.parse::<i64>().map_err(AError::from))
+                .collect()
+        }
+
+        fn part1(parsed: &Self::Parsed) -> Result<Answer, AError> {
+            Ok(parsed.iter().sum::<i64>().into())
+        }
+
+        fn part2(parsed: &Self::Parsed) -> Result<Answer, AError> {
+            Ok(parsed.iter().product::<i64>().into())
+        }
+    }
+
+    #[test]
+    fn parts_solved_from_one_parse() {
+        let parsed = parse_str::<Sums>("2\n3\n4").unwrap();
+        assert_eq!(solve::<Sums>(&parsed, 1).unwrap(), Answer::Integer(9));
+        assert_eq!(solve::<Sums>(&parsed, 2).unwrap(), Answer::Integer(24));
+        assert!(solve::<Sums>(&parsed, 3).is_err());
+        assert!(parse_str::<Sums>("2\nthree").is_err());
+    }
+}
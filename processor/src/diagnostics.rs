@@ -0,0 +1,73 @@
+//! Intermediate values a day records while solving (e.g. how many parts were accepted), printed as
+//! `Diagnostic N: name: value` lines after each run of [`crate::process`] when [`DIAGNOSTICS_ENV`] is set,
+//! so that the runner can show them alongside a wrong answer.
+
+use std::{fmt::Display, sync::Mutex};
+
+/// Environment variable that turns on printing the diagnostics
+pub const DIAGNOSTICS_ENV: &str = "AOC_DIAGNOSTICS";
+
+static RECORDED: Mutex<Vec<(String, String)>> = Mutex::new(Vec::new());
+
+/// Record a value for the current run, replacing any already recorded under the same name
+pub fn diagnostic(name: &str, value: impl Display) {
+    record(&mut RECORDED.lock().unwrap(), name, value.to_string());
+}
+
+fn record(recorded: &mut Vec<(String, String)>, name: &str, value: String) {
+    match recorded
+        .iter_mut()
+        .find(|(recorded_name, _)| recorded_name == name)
+    {
+        Some((_, recorded_value)) => *recorded_value = value,
+        None => recorded.push((name.to_string(), value)),
+    }
+}
+
+/// Everything recorded since the last run, in the order first recorded
+pub fn take_diagnostics() -> Vec<(String, String)> {
+    std::mem::take(&mut *RECORDED.lock().unwrap())
+}
+
+pub fn format_diagnostics(run: usize, diagnostics: &[(String, String)]) -> Vec<String> {
+    diagnostics
+        .iter()
+        .map(|(name, value)| format!("Diagnostic {run}: {name}: {value}"))
+        .collect()
+}
+
+/// Whether [`DIAGNOSTICS_ENV`] is set, for days to skip gathering diagnostics that are costly to work out
+pub fn diagnostics_requested() -> bool {
+    std::env::var_os(DIAGNOSTICS_ENV).is_some()
+}
+
+/// Print what was recorded during the run if it has been asked for
+pub(crate) fn report(run: usize) {
+    let diagnostics = take_diagnostics();
+    if diagnostics_requested() {
+        format_diagnostics(run, &diagnostics)
+            .iter()
+            .for_each(|line| println!("{line}"));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diagnostics_recorded_and_formatted() {
+        // recorded in to a local list as the pipeline's tests may take the global one at any time
+        let mut diagnostics = vec![];
+        record(&mut diagnostics, "accepted parts", 3.to_string());
+        record(&mut diagnostics, "finished walks", "none".to_string());
+        record(&mut diagnostics, "accepted parts", 4.to_string());
+        assert_eq!(
+            format_diagnostics(1, &diagnostics),
+            vec![
+                "Diagnostic 1: accepted parts: 4",
+                "Diagnostic 1: finished walks: none"
+            ]
+        );
+    }
+}
@@ -0,0 +1,111 @@
+//! Cheapest path through a space of search states (e.g. day17's crucible positions, headings and runs) by
+//! Dijkstra's algorithm.  States are packed by their [`StateKey`], so the best costs live in a flat
+//! [`StateMap`] and the priority queue only holds the packed indexes.
+
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+use crate::state_key::{StateKey, StateMap};
+
+/// Counts gathered during a search
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DijkstraStats {
+    /// States taken from the queue at their best cost and expanded
+    pub expanded: usize,
+    /// Entries taken from the queue after a cheaper way to their state had already been expanded
+    pub stale: usize,
+    /// Largest number of entries queued at once
+    pub max_queued: usize,
+}
+
+/// The goal reached by [`dijkstra`], if one was, along with how the search went
+#[derive(Debug, Clone)]
+pub struct DijkstraResult<K> {
+    /// The first goal state taken from the queue and its cost, the cheapest of any goal
+    pub goal: Option<(K, usize)>,
+    pub stats: DijkstraStats,
+}
+
+/// Search outwards from the starts, each costing nothing, cheapest first until a goal is taken from the queue.
+/// The neighbours function gives the states that can be moved to from a state with the cost of each move.
+pub fn dijkstra<K, S, N, I, G>(
+    space: K::Space,
+    starts: S,
+    mut neighbours: N,
+    mut is_goal: G,
+) -> DijkstraResult<K>
+where
+    K: StateKey,
+    S: IntoIterator<Item = K>,
+    N: FnMut(&K) -> I,
+    I: IntoIterator<Item = (K, usize)>,
+    G: FnMut(&K) -> bool,
+{
+    let mut best: StateMap<K, usize> = StateMap::new(space.clone());
+    let mut queue: BinaryHeap<Reverse<(usize, usize)>> = BinaryHeap::default();
+    for start in starts {
+        best.insert(&start, 0);
+        queue.push(Reverse((0, start.pack(&space))));
+    }
+    let mut stats = DijkstraStats {
+        max_queued: queue.len(),
+        ..DijkstraStats::default()
+    };
+    while let Some(Reverse((cost, index))) = queue.pop() {
+        let state = K::unpack(index, &space);
+        if best.get(&state).is_some_and(|best| *best < cost) {
+            stats.stale += 1;
+            continue;
+        }
+        if is_goal(&state) {
+            return DijkstraResult {
+                goal: Some((state, cost)),
+                stats,
+            };
+        }
+        stats.expanded += 1;
+        for (next, step_cost) in neighbours(&state) {
+            let next_cost = cost + step_cost;
+            if best.get(&next).is_none_or(|best| next_cost < *best) {
+                best.insert(&next, next_cost);
+                queue.push(Reverse((next_cost, next.pack(&space))));
+            }
+        }
+        stats.max_queued = stats.max_queued.max(queue.len());
+    }
+    DijkstraResult { goal: None, stats }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{Cells, Direction};
+
+    #[test]
+    fn cheapest_path_found() {
+        // entering a cell costs its value, the cheap way round being down the left then along the bottom
+        let costs = [[1, 9, 1, 1], [1, 9, 1, 9], [1, 1, 1, 9], [9, 9, 1, 1]];
+        let mut grid = Cells::with_dimension(4, 4, 0);
+        for (y, row) in costs.iter().enumerate() {
+            for (x, cost) in row.iter().enumerate() {
+                grid[(x, y)] = *cost;
+            }
+        }
+        let neighbours = |coord: &(usize, usize)| -> Vec<((usize, usize), usize)> {
+            Direction::ALL
+                .into_iter()
+                .filter_map(|direction| grid.step(*coord, direction))
+                .map(|next| (next, grid[next]))
+                .collect()
+        };
+        let result = dijkstra(grid.side_lengths, [(0, 0)], neighbours, |coord| {
+            *coord == (3, 3)
+        });
+        assert_eq!(result.goal, Some(((3, 3), 6)));
+        assert!(result.stats.expanded > 0 && result.stats.expanded < 16);
+
+        let unreachable = dijkstra((2, 1), [(0, 0)], |_| vec![], |coord| *coord == (1, 0));
+        assert_eq!(unreachable.goal, None);
+        assert_eq!(unreachable.stats.expanded, 1);
+    }
+}
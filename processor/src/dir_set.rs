@@ -0,0 +1,91 @@
+//! [`DirSet`] - a set of directions in the bits of a `u8`, so that checking a direction in an inner loop
+//! doesn't need any hashing.
+
+use crate::direction::Direction;
+
+/// A set of [`Direction`]s held as bit flags, e.g. the directions a tile has already been visited in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct DirSet(u8);
+
+impl DirSet {
+    fn bit(direction: Direction) -> u8 {
+        1 << direction.index()
+    }
+
+    pub fn from_bits(bits: u8) -> Self {
+        DirSet(bits & 0b1111)
+    }
+
+    pub fn bits(&self) -> u8 {
+        self.0
+    }
+
+    pub fn contains(&self, direction: Direction) -> bool {
+        self.0 & Self::bit(direction) != 0
+    }
+
+    /// Whether the direction was newly added
+    pub fn insert(&mut self, direction: Direction) -> bool {
+        let added = !self.contains(direction);
+        self.0 |= Self::bit(direction);
+        added
+    }
+
+    /// Whether the direction was present
+    pub fn remove(&mut self, direction: Direction) -> bool {
+        let present = self.contains(direction);
+        self.0 &= !Self::bit(direction);
+        present
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Direction> + '_ {
+        Direction::ALL
+            .into_iter()
+            .filter(|direction| self.contains(*direction))
+    }
+}
+
+impl FromIterator<Direction> for DirSet {
+    fn from_iter<I: IntoIterator<Item = Direction>>(directions: I) -> Self {
+        directions
+            .into_iter()
+            .fold(DirSet::default(), |mut set, direction| {
+                set.insert(direction);
+                set
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn direction_set_bits() {
+        let mut set = DirSet::default();
+        assert!(set.is_empty());
+        assert!(set.insert(Direction::South));
+        assert!(!set.insert(Direction::South));
+        assert!(set.insert(Direction::North));
+        assert!(set.contains(Direction::North) && !set.contains(Direction::East));
+        assert_eq!(set.bits(), 0b0101);
+        assert_eq!(set.len(), 2);
+        assert!(set.remove(Direction::North));
+        assert!(!set.remove(Direction::North));
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![Direction::South]);
+        assert_eq!(
+            [Direction::West, Direction::East]
+                .into_iter()
+                .collect::<DirSet>(),
+            DirSet::from_bits(0b1010)
+        );
+    }
+}
@@ -0,0 +1,199 @@
+use std::fmt::Display;
+
+/// Compass direction on a grid, with north being towards y = 0
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Direction {
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Direction {
+    /// In clockwise order from north, the order used by [`Direction::index`]
+    pub const ALL: [Direction; 4] = [
+        Direction::North,
+        Direction::East,
+        Direction::South,
+        Direction::West,
+    ];
+
+    /// Position in [`Direction::ALL`], for array backed lookups
+    pub fn index(&self) -> usize {
+        *self as usize
+    }
+
+    /// From the up/down/left/right letters used in instructions, e.g. `R 6`
+    pub fn from_udlr(c: char) -> Option<Direction> {
+        match c {
+            'U' => Some(Direction::North),
+            'R' => Some(Direction::East),
+            'D' => Some(Direction::South),
+            'L' => Some(Direction::West),
+            _ => None,
+        }
+    }
+
+    /// From the arrow characters used in grids, e.g. slopes
+    pub fn from_arrow(c: char) -> Option<Direction> {
+        match c {
+            '^' => Some(Direction::North),
+            '>' => Some(Direction::East),
+            'v' => Some(Direction::South),
+            '<' => Some(Direction::West),
+            _ => None,
+        }
+    }
+
+    pub fn to_arrow(&self) -> char {
+        match self {
+            Direction::North => '^',
+            Direction::East => '>',
+            Direction::South => 'v',
+            Direction::West => '<',
+        }
+    }
+
+    pub fn opposite(&self) -> Direction {
+        match self {
+            Direction::North => Direction::South,
+            Direction::East => Direction::West,
+            Direction::South => Direction::North,
+            Direction::West => Direction::East,
+        }
+    }
+
+    /// The direction after turning anticlockwise a quarter turn
+    pub fn turn_left(&self) -> Direction {
+        Direction::ALL[(self.index() + 3) % 4]
+    }
+
+    /// The direction after turning clockwise a quarter turn
+    pub fn turn_right(&self) -> Direction {
+        Direction::ALL[(self.index() + 1) % 4]
+    }
+
+    /// The (x, y) change of a step in this direction
+    pub fn deltas(&self) -> (isize, isize) {
+        match self {
+            Direction::North => (0, -1),
+            Direction::East => (1, 0),
+            Direction::South => (0, 1),
+            Direction::West => (-1, 0),
+        }
+    }
+
+    /// One step on from the coord in this direction, which may be off the grid
+    pub fn step(&self, (x, y): (isize, isize)) -> (isize, isize) {
+        let (delta_x, delta_y) = self.deltas();
+        (x + delta_x, y + delta_y)
+    }
+
+    /// One step on from the coord in this direction, if that's still within a grid of the side lengths
+    pub fn step_within(
+        &self,
+        (x, y): (usize, usize),
+        side_lengths: &(usize, usize),
+    ) -> Option<(usize, usize)> {
+        let (delta_x, delta_y) = self.deltas();
+        let next_x = x.checked_add_signed(delta_x)?;
+        let next_y = y.checked_add_signed(delta_y)?;
+        (next_x < side_lengths.0 && next_y < side_lengths.1).then_some((next_x, next_y))
+    }
+}
+
+/// Displays as the arrow
+impl Display for Direction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_arrow())
+    }
+}
+
+/// Compass direction on a grid including the diagonals, with north being towards y = 0
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Direction8 {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+impl Direction8 {
+    /// In clockwise order from north, the order used by [`Direction8::index`]
+    pub const ALL: [Direction8; 8] = [
+        Direction8::North,
+        Direction8::NorthEast,
+        Direction8::East,
+        Direction8::SouthEast,
+        Direction8::South,
+        Direction8::SouthWest,
+        Direction8::West,
+        Direction8::NorthWest,
+    ];
+
+    /// Position in [`Direction8::ALL`], for array backed lookups
+    pub fn index(&self) -> usize {
+        *self as usize
+    }
+
+    /// The (x, y) change of a step in this direction
+    pub fn deltas(&self) -> (isize, isize) {
+        match self {
+            Direction8::North => (0, -1),
+            Direction8::NorthEast => (1, -1),
+            Direction8::East => (1, 0),
+            Direction8::SouthEast => (1, 1),
+            Direction8::South => (0, 1),
+            Direction8::SouthWest => (-1, 1),
+            Direction8::West => (-1, 0),
+            Direction8::NorthWest => (-1, -1),
+        }
+    }
+}
+
+impl From<Direction> for Direction8 {
+    fn from(direction: Direction) -> Self {
+        match direction {
+            Direction::North => Direction8::North,
+            Direction::East => Direction8::East,
+            Direction::South => Direction8::South,
+            Direction::West => Direction8::West,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_directions() {
+        assert_eq!(Direction::from_udlr('U'), Some(Direction::North));
+        assert_eq!(Direction::from_udlr('L'), Some(Direction::West));
+        assert_eq!(Direction::from_udlr('^'), None);
+        for c in ['^', '>', 'v', '<'] {
+            let direction = Direction::from_arrow(c).unwrap();
+            assert_eq!(direction.to_string(), c.to_string());
+            assert_eq!(direction.opposite().opposite(), direction);
+        }
+        assert_eq!(Direction::from_arrow('V'), None);
+        assert_eq!(Direction::South.deltas(), (0, 1));
+        assert_eq!(Direction::North.turn_left(), Direction::West);
+        assert_eq!(Direction::West.turn_right(), Direction::North);
+        assert_eq!(Direction::West.step((0, 3)), (-1, 3));
+        assert_eq!(Direction::West.step_within((0, 3), &(4, 4)), None);
+        assert_eq!(Direction::South.step_within((0, 2), &(4, 4)), Some((0, 3)));
+        assert_eq!(Direction::South.step_within((0, 3), &(4, 4)), None);
+        for (index, direction) in Direction::ALL.iter().enumerate() {
+            assert_eq!(direction.index(), index);
+            assert_eq!(direction.turn_left().turn_right(), *direction);
+            assert_eq!(direction.turn_right().turn_right(), direction.opposite());
+            assert_eq!(Direction8::from(*direction).deltas(), direction.deltas());
+        }
+        assert_eq!(Direction8::SouthWest.index(), 5);
+    }
+}
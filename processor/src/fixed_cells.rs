@@ -0,0 +1,165 @@
+use std::fmt::Display;
+
+use num::ToPrimitive;
+
+use crate::{AError, Cells};
+
+/// Represents a W * H block of data where the dimensions are known at compile time.  The contents
+/// are held inline (i.e. on the stack unless boxed) rather than in a Vec as with [`Cells`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FixedCells<T, const W: usize, const H: usize> {
+    contents: [[T; W]; H],
+}
+
+impl<T, const W: usize, const H: usize> FixedCells<T, W, H> {
+    pub const SIDE_LENGTHS: (usize, usize) = (W, H);
+
+    pub fn from_rows(contents: [[T; W]; H]) -> Self {
+        FixedCells { contents }
+    }
+
+    pub fn side_lengths(&self) -> (usize, usize) {
+        Self::SIDE_LENGTHS
+    }
+
+    /// Checks whether the input can be represented as coordinates (i.e. can be converted to usize)
+    /// and that the values are within the range of the cells' sides
+    pub fn in_bounds<N>(&self, x: N, y: N) -> bool
+    where
+        N: ToPrimitive,
+    {
+        match (x.to_usize(), y.to_usize()) {
+            (Some(x), Some(y)) => x < W && y < H,
+            _ => false,
+        }
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Result<&T, AError> {
+        if !self.in_bounds(x, y) {
+            return Err(AError::msg(format!("({}, {}) is not in bounds", x, y)));
+        }
+        Ok(&self.contents[y][x])
+    }
+
+    pub fn get_mut(&mut self, x: usize, y: usize) -> Result<&mut T, AError> {
+        if !self.in_bounds(x, y) {
+            return Err(AError::msg(format!("({}, {}) is not in bounds", x, y)));
+        }
+        Ok(&mut self.contents[y][x])
+    }
+
+    pub fn swap(&mut self, x1: usize, y1: usize, x2: usize, y2: usize) -> Result<(), AError> {
+        if !self.in_bounds(x1, y1) {
+            return Err(AError::msg(format!(
+                "First ({}, {}) is not in bounds",
+                x1, y1
+            )));
+        }
+        if !self.in_bounds(x2, y2) {
+            return Err(AError::msg(format!(
+                "Second ({}, {}) is not in bounds",
+                x2, y2
+            )));
+        }
+        let contents = self.contents.as_flattened_mut();
+        contents.swap(y1 * W + x1, y2 * W + x2);
+        Ok(())
+    }
+
+    /// Iterate over the cells row by row, as with [`Cells::iter`]
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+        self.contents
+            .iter()
+            .enumerate()
+            .flat_map(|(y, row)| row.iter().enumerate().map(move |(x, c)| ((x, y), c)))
+    }
+}
+
+impl<T: Copy, const W: usize, const H: usize> FixedCells<T, W, H> {
+    pub fn with_value(initial_value: T) -> Self {
+        FixedCells {
+            contents: [[initial_value; W]; H],
+        }
+    }
+}
+
+impl<T: Copy + Default, const W: usize, const H: usize> Default for FixedCells<T, W, H> {
+    fn default() -> Self {
+        Self::with_value(T::default())
+    }
+}
+
+impl<T: Copy, const W: usize, const H: usize> TryFrom<&Cells<T>> for FixedCells<T, W, H> {
+    type Error = AError;
+
+    fn try_from(cells: &Cells<T>) -> Result<Self, Self::Error> {
+        if cells.side_lengths != (W, H) {
+            return Err(AError::msg(format!(
+                "Cells of size {:?} cannot be fixed to ({}, {})",
+                cells.side_lengths, W, H
+            )));
+        }
+        let first = *cells
+            .contents
+            .first()
+            .ok_or_else(|| AError::msg("Cannot fix empty cells"))?;
+        let mut fixed = FixedCells::with_value(first);
+        fixed
+            .contents
+            .as_flattened_mut()
+            .copy_from_slice(&cells.contents);
+        Ok(fixed)
+    }
+}
+
+impl<T: Clone, const W: usize, const H: usize> From<&FixedCells<T, W, H>> for Cells<T> {
+    fn from(fixed: &FixedCells<T, W, H>) -> Self {
+        Cells {
+            contents: fixed.contents.as_flattened().to_vec(),
+            side_lengths: (W, H),
+        }
+    }
+}
+
+impl<T: Display, const W: usize, const H: usize> Display for FixedCells<T, W, H> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for row in self.contents.iter() {
+            for cell in row.iter() {
+                write!(f, "{cell}")?
+            }
+            writeln!(f)?
+        }
+        write!(f, "")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixed_cells_round_trip() {
+        let mut fixed: FixedCells<u8, 3, 2> = FixedCells::from_rows([[1, 2, 3], [4, 5, 6]]);
+        assert_eq!(fixed.side_lengths(), (3, 2));
+        assert!(fixed.in_bounds(2, 1));
+        assert!(!fixed.in_bounds(3, 0));
+        assert!(!fixed.in_bounds(-1, 0));
+        assert_eq!(*fixed.get(2, 1).unwrap(), 6);
+        fixed.swap(0, 0, 2, 1).unwrap();
+        *fixed.get_mut(1, 0).unwrap() = 9;
+        assert_eq!(format!("{fixed}"), "693\n451\n");
+
+        let cells = Cells::from(&fixed);
+        assert_eq!(cells.side_lengths, (3, 2));
+        assert_eq!(*cells.get(1, 0).unwrap(), 9);
+        let back: FixedCells<u8, 3, 2> = FixedCells::try_from(&cells).unwrap();
+        assert_eq!(back, fixed);
+        assert!(FixedCells::<u8, 2, 3>::try_from(&cells).is_err());
+
+        let coords: Vec<(usize, usize)> = fixed.iter().map(|(coord, _)| coord).collect();
+        assert_eq!(
+            coords,
+            cells.iter().map(|(coord, _)| coord).collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,323 @@
+use std::collections::HashSet;
+
+use crate::polyline::Point;
+
+/// Fewest points before a frontier is considered for a [`BitGrid`]
+const MIN_DENSE_LEN: usize = 1024;
+/// Fraction of the bounding box covered at which a frontier switches to a [`BitGrid`]
+const DENSE_DENSITY: f64 = 1.0 / 16.0;
+/// Fraction of the bounding box covered below which a frontier switches back to a `HashSet`.  Lower than
+/// [`DENSE_DENSITY`] so that a frontier hovering around the threshold doesn't keep switching.
+const SPARSE_DENSITY: f64 = 1.0 / 64.0;
+
+fn area((min, max): (Point, Point)) -> usize {
+    (min.0.abs_diff(max.0) + 1) * (min.1.abs_diff(max.1) + 1)
+}
+
+fn include(bounds: Option<(Point, Point)>, point: Point) -> (Point, Point) {
+    match bounds {
+        None => (point, point),
+        Some((min, max)) => (
+            (min.0.min(point.0), min.1.min(point.1)),
+            (max.0.max(point.0), max.1.max(point.1)),
+        ),
+    }
+}
+
+/// A set of points within fixed bounds, a bit per point
+#[derive(Debug, Clone)]
+pub struct BitGrid {
+    min: Point,
+    max: Point,
+    width: usize,
+    bits: Vec<u64>,
+    len: usize,
+}
+
+impl BitGrid {
+    /// Empty grid covering min to max inclusive
+    pub fn new(min: Point, max: Point) -> Self {
+        let width = min.0.abs_diff(max.0) + 1;
+        BitGrid {
+            min,
+            max,
+            width,
+            bits: vec![0; area((min, max)).div_ceil(64)],
+            len: 0,
+        }
+    }
+
+    pub fn bounds(&self) -> (Point, Point) {
+        (self.min, self.max)
+    }
+
+    fn index(&self, (x, y): Point) -> Option<usize> {
+        if x < self.min.0 || x > self.max.0 || y < self.min.1 || y > self.max.1 {
+            return None;
+        }
+        Some((y - self.min.1) as usize * self.width + (x - self.min.0) as usize)
+    }
+
+    /// Whether the point was newly added, None if it is outside the bounds
+    pub fn insert(&mut self, point: Point) -> Option<bool> {
+        let index = self.index(point)?;
+        let (word, bit) = (index / 64, 1u64 << (index % 64));
+        let added = self.bits[word] & bit == 0;
+        if added {
+            self.bits[word] |= bit;
+            self.len += 1;
+        }
+        Some(added)
+    }
+
+    pub fn contains(&self, point: Point) -> bool {
+        self.index(point)
+            .is_some_and(|index| self.bits[index / 64] & (1u64 << (index % 64)) != 0)
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn clear(&mut self) {
+        self.bits.fill(0);
+        self.len = 0;
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Point> + '_ {
+        self.bits
+            .iter()
+            .enumerate()
+            .filter(|(_, word)| **word != 0)
+            .flat_map(move |(word_index, word)| {
+                (0..64)
+                    .filter(move |bit| word & (1u64 << bit) != 0)
+                    .map(move |bit| {
+                        let index = word_index * 64 + bit;
+                        (
+                            self.min.0 + (index % self.width) as isize,
+                            self.min.1 + (index / self.width) as isize,
+                        )
+                    })
+            })
+    }
+}
+
+/// Counts gathered while using an [`AdaptiveFrontier`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FrontierStats {
+    /// Points inserted that weren't already in the frontier
+    pub inserted: usize,
+    /// Points inserted that were already in the frontier
+    pub duplicates: usize,
+    /// Times the frontier switched between a HashSet and a BitGrid
+    pub switches: usize,
+    /// Most points held at once
+    pub peak_len: usize,
+}
+
+#[derive(Debug, Clone)]
+enum Storage {
+    Sparse(HashSet<Point>),
+    Dense(BitGrid),
+}
+
+/// The set of points reached by a breadth first simulation.  Starts out as a `HashSet`, then each
+/// [`AdaptiveFrontier::rebalance`] switches to a [`BitGrid`] once the points are dense enough within their
+/// bounding box (and back again if they thin out), recording the latest switch as a diagnostic.
+#[derive(Debug, Clone)]
+pub struct AdaptiveFrontier {
+    name: String,
+    storage: Storage,
+    bounds: Option<(Point, Point)>,
+    stats: FrontierStats,
+}
+
+impl AdaptiveFrontier {
+    /// The name is that of the diagnostic any switches are recorded under
+    pub fn new(name: &str) -> Self {
+        AdaptiveFrontier {
+            name: name.to_string(),
+            storage: Storage::Sparse(HashSet::default()),
+            bounds: None,
+            stats: FrontierStats::default(),
+        }
+    }
+
+    /// Whether the point was newly added
+    pub fn insert(&mut self, point: Point) -> bool {
+        self.bounds = Some(include(self.bounds, point));
+        let added = match &mut self.storage {
+            Storage::Sparse(points) => points.insert(point),
+            Storage::Dense(grid) => match grid.insert(point) {
+                Some(added) => added,
+                None => {
+                    // grow by half again so that a steadily expanding frontier isn't copied every time
+                    let (min, max) = include(Some(grid.bounds()), point);
+                    let margin = (min.0.abs_diff(max.0).max(min.1.abs_diff(max.1)) / 2) as isize;
+                    let mut grown = BitGrid::new(
+                        (min.0 - margin, min.1 - margin),
+                        (max.0 + margin, max.1 + margin),
+                    );
+                    grid.iter().for_each(|p| {
+                        grown.insert(p);
+                    });
+                    grown.insert(point);
+                    *grid = grown;
+                    true
+                }
+            },
+        };
+        if added {
+            self.stats.inserted += 1;
+            self.stats.peak_len = self.stats.peak_len.max(self.len());
+        } else {
+            self.stats.duplicates += 1;
+        }
+        added
+    }
+
+    pub fn contains(&self, point: Point) -> bool {
+        match &self.storage {
+            Storage::Sparse(points) => points.contains(&point),
+            Storage::Dense(grid) => grid.contains(point),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match &self.storage {
+            Storage::Sparse(points) => points.len(),
+            Storage::Dense(grid) => grid.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn is_dense(&self) -> bool {
+        matches!(self.storage, Storage::Dense(_))
+    }
+
+    /// Remove all of the points, keeping the storage for reuse
+    pub fn clear(&mut self) {
+        match &mut self.storage {
+            Storage::Sparse(points) => points.clear(),
+            Storage::Dense(grid) => grid.clear(),
+        }
+        self.bounds = None;
+    }
+
+    pub fn iter(&self) -> Box<dyn Iterator<Item = Point> + '_> {
+        match &self.storage {
+            Storage::Sparse(points) => Box::new(points.iter().copied()),
+            Storage::Dense(grid) => Box::new(grid.iter()),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn stats(&self) -> FrontierStats {
+        self.stats
+    }
+
+    /// Fraction of the bounding box of the points that they cover
+    pub fn density(&self) -> f64 {
+        self.bounds
+            .map_or(0.0, |bounds| self.len() as f64 / area(bounds) as f64)
+    }
+
+    /// Switch storage if the density of the points calls for it, call between generations
+    pub fn rebalance(&mut self) {
+        let density = self.density();
+        let switch_to_dense = match &self.storage {
+            Storage::Sparse(points)
+                if points.len() >= MIN_DENSE_LEN && density >= DENSE_DENSITY =>
+            {
+                true
+            }
+            Storage::Dense(grid) if !grid.is_empty() && density < SPARSE_DENSITY => false,
+            _ => return,
+        };
+        let (min, max) = self.bounds.unwrap();
+        self.storage = if switch_to_dense {
+            let mut grid = BitGrid::new(min, max);
+            self.iter().for_each(|p| {
+                grid.insert(p);
+            });
+            Storage::Dense(grid)
+        } else {
+            Storage::Sparse(self.iter().collect())
+        };
+        self.stats.switches += 1;
+        crate::diagnostic(
+            &format!("{} frontier", self.name),
+            format!(
+                "switch {} to {} with {} points (density {density:.3})",
+                self.stats.switches,
+                if switch_to_dense {
+                    "a bit grid"
+                } else {
+                    "a hash set"
+                },
+                self.len()
+            ),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bit_grid_points() {
+        let mut grid = BitGrid::new((-2, -2), (2, 2));
+        assert_eq!(grid.insert((-2, 1)), Some(true));
+        assert_eq!(grid.insert((-2, 1)), Some(false));
+        assert_eq!(grid.insert((3, 0)), None);
+        assert!(grid.contains((-2, 1)));
+        assert!(!grid.contains((1, -2)));
+        grid.insert((2, 2));
+        assert_eq!(grid.iter().collect::<Vec<_>>(), vec![(-2, 1), (2, 2)]);
+        grid.clear();
+        assert!(grid.is_empty());
+    }
+
+    #[test]
+    fn frontier_switches_with_density() {
+        let mut frontier = AdaptiveFrontier::new("test");
+        (0..40).for_each(|x| {
+            (0..40).for_each(|y| {
+                frontier.insert((x, y));
+            })
+        });
+        assert!(!frontier.insert((0, 0)));
+        frontier.rebalance();
+        assert!(frontier.is_dense());
+        // growing past the grid still works
+        assert!(frontier.insert((100, -100)));
+        assert!(frontier.contains((100, -100)));
+        assert_eq!(frontier.len(), 1601);
+
+        frontier.clear();
+        (0..1100).for_each(|i| {
+            frontier.insert((i * 10, 0));
+            frontier.insert((0, i * 10));
+        });
+        frontier.rebalance();
+        assert!(!frontier.is_dense());
+        assert_eq!(frontier.len(), 2199);
+
+        let stats = frontier.stats();
+        assert_eq!(stats.switches, 2);
+        assert_eq!(stats.duplicates, 2);
+        assert_eq!(stats.peak_len, 2199);
+    }
+}
@@ -0,0 +1,180 @@
+//! Harnesses for fuzzing the parts of the library every day leans on, each taking arbitrary bytes and
+//! panicking if an invariant doesn't hold.  They're driven by the cargo-fuzz targets in `processor/fuzz`:
+//!
+//! ```text
+//! cd processor
+//! cargo +nightly fuzz run build_cells
+//! ```
+//!
+//! and being plain functions they can also be run over a corpus, or a crash, without libFuzzer.
+
+use std::collections::HashSet;
+
+use crate::{
+    adjacent_coords_cartesian,
+    reachability::{bfs_distances, count_reachable_by_parity},
+    read_token, CellChar, Cells, CellsBuilder, Token,
+};
+
+type Tile = crate::tiles::Tile;
+
+/// Read the bytes as lines of [`Tile`]s, checking the built cells hold each line's cells padded out to the
+/// longest, and that [`Cells::parse_lines`] only accepts lines that are all recognised and the same length
+pub fn build_cells(data: &[u8]) {
+    let text = String::from_utf8_lossy(data);
+    let lines: Vec<&str> = text.split('\n').collect();
+    let mut builder: CellsBuilder<Tile> = CellsBuilder::new_empty();
+    let mut parsed_lines: Vec<Vec<Tile>> = vec![];
+    for line in &lines {
+        let result = builder.add_line(line);
+        // a bad char leaves the line holding the cells before it
+        let parsed: Vec<Tile> = line.chars().map_while(Tile::from_char).collect();
+        assert_eq!(result.is_ok(), parsed.len() == line.chars().count());
+        parsed_lines.push(parsed);
+    }
+
+    let width = parsed_lines.iter().map(Vec::len).max().unwrap();
+    match builder.build_cells(Tile::Empty) {
+        Err(_) => assert_eq!(width, 0),
+        Ok(cells) => {
+            assert_eq!(cells.side_lengths, (width, parsed_lines.len()));
+            for (y, parsed) in parsed_lines.iter().enumerate() {
+                for x in 0..width {
+                    let expected = parsed.get(x).copied().unwrap_or(Tile::Empty);
+                    assert_eq!(*cells.get(x, y).unwrap(), expected);
+                }
+            }
+        }
+    }
+
+    let all_parsed = lines
+        .iter()
+        .zip(&parsed_lines)
+        .all(|(line, parsed)| parsed.len() == line.chars().count());
+    let all_same_length = parsed_lines.iter().all(|parsed| parsed.len() == width);
+    assert_eq!(
+        Cells::<Tile>::parse_lines(&lines).is_ok(),
+        all_parsed && all_same_length && width > 0
+    );
+}
+
+/// Delimiters the first byte picks between, one per bit
+const DELIMITER_CHOICES: [char; 8] = [' ', ',', ':', ';', '=', '|', '(', '"'];
+
+/// Tokenize the bytes (after the first, which picks the delimiters), checking every token read consumes some
+/// of the input and that words are never empty or hold a delimiter.  The contents of groups are tokenized in
+/// turn.
+pub fn tokenizer(data: &[u8]) {
+    let Some((choice, rest)) = data.split_first() else {
+        return;
+    };
+    let delimiters: HashSet<char> = DELIMITER_CHOICES
+        .iter()
+        .enumerate()
+        .filter(|(bit, _)| choice & (1 << bit) != 0)
+        .map(|(_, c)| *c)
+        .collect();
+    tokenize(&String::from_utf8_lossy(rest), &delimiters);
+}
+
+fn tokenize(text: &str, delimiters: &HashSet<char>) {
+    let mut chars = text.chars();
+    loop {
+        let remaining = chars.as_str().len();
+        match read_token(&mut chars, delimiters) {
+            Ok(Some((token, delimiter))) => {
+                assert!(chars.as_str().len() < remaining, "token read from nothing");
+                assert!(delimiter.is_none_or(|d| delimiters.contains(&d)));
+                match token {
+                    Token::Word(word) => {
+                        assert!(!word.is_empty());
+                        assert!(!word.chars().any(|c| delimiters.contains(&c)));
+                    }
+                    Token::Group { contents, .. } => tokenize(&contents, delimiters),
+                    Token::Quoted(_) => {}
+                }
+            }
+            Ok(None) => {
+                assert!(chars.as_str().chars().all(|c| delimiters.contains(&c)));
+                return;
+            }
+            Err(_) => return,
+        }
+    }
+}
+
+/// Flood fill a grid (up to 16 by 16, sized by the first two bytes with the rest marking walls) from its
+/// corners, checking every distance is one more than its nearest neighbour's and that the fill stops only at
+/// walls and edges
+pub fn flood_fill(data: &[u8]) {
+    let [width, height, walls @ ..] = data else {
+        return;
+    };
+    let (width, height) = (*width as usize % 16 + 1, *height as usize % 16 + 1);
+    let mut grid = Cells::with_dimension(width, height, false);
+    for (index, byte) in walls.iter().take(width * height).enumerate() {
+        grid[(index % width, index / width)] = byte % 2 == 1;
+    }
+    let starts = [(0, 0), (width - 1, height - 1)];
+    let passable = |wall: &bool| !wall;
+    let distances = bfs_distances(&grid, &starts, passable);
+
+    let mut reached = 0;
+    for ((x, y), distance) in distances.iter() {
+        let wall = grid[(x, y)];
+        let Some(distance) = *distance else {
+            assert!(wall || !starts.contains(&(x, y)));
+            continue;
+        };
+        assert!(!wall);
+        reached += 1;
+        let neighbours: Vec<Option<usize>> = adjacent_coords_cartesian(&(x, y), &grid.side_lengths)
+            .into_iter()
+            .filter(|(nx, ny)| passable(&grid[(*nx, *ny)]))
+            .map(|(nx, ny)| distances[(nx, ny)])
+            .collect();
+        assert!(neighbours.iter().all(|neighbour| neighbour.is_some()));
+        let nearest = neighbours.iter().flatten().min().copied();
+        if starts.contains(&(x, y)) {
+            assert_eq!(distance, 0);
+        } else {
+            assert_eq!(Some(distance), nearest.map(|nearest| nearest + 1));
+        }
+    }
+
+    let counts = count_reachable_by_parity(&grid, &starts, width * height, passable);
+    assert_eq!(counts.even + counts.odd, reached);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn harnesses_hold_on_seeds() {
+        [
+            &b"#.>\n/\\|-\n"[..],
+            b"..\n.x.\n",
+            b"",
+            b"\n\n",
+            b"\xff\xfe#",
+        ]
+        .into_iter()
+        .for_each(build_cells);
+        [
+            &b"\x01a b(c, [d]) \"e f\""[..],
+            b"\xffa(b\"c)",
+            b"\x00(((",
+            b"\x40x((y)z",
+        ]
+        .into_iter()
+        .for_each(tokenizer);
+        [
+            &b"\x03\x03\x00\x01\x00\x00\x01\x00\x01\x00\x00"[..],
+            b"\x00\x00\x01",
+            b"\x0f\x0f",
+        ]
+        .into_iter()
+        .for_each(flood_fill);
+    }
+}
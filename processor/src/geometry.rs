@@ -0,0 +1,247 @@
+//! Geometry that's still done in floating point (e.g. day24's hailstone paths), with the comparisons kept in
+//! one place so that how close counts as equal is a single choice that can be tuned per puzzle.
+
+use crate::{
+    parallel::{par_map, par_pairs},
+    spatial_hash::SpatialHash,
+};
+
+pub const DEFAULT_EPSILON: f64 = 1e-9;
+
+/// Whether a and b are within epsilon of each other, the epsilon being scaled up by the size of the larger of
+/// them once that's over 1 so that it's relative for big values
+pub fn approx_eq(a: f64, b: f64, epsilon: f64) -> bool {
+    let scale = 1.0f64.max(a.abs()).max(b.abs());
+    (a - b).abs() <= epsilon * scale
+}
+
+/// Whether the time is now or in the future, allowing for it being just before now by rounding
+pub fn is_non_negative_time(time: f64, epsilon: f64) -> bool {
+    time >= 0.0 || approx_eq(time, 0.0, epsilon)
+}
+
+pub type Point2 = (f64, f64);
+
+/// The points start + t * direction for all t >= 0, e.g. where a hailstone goes in x and y
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ray {
+    pub start: Point2,
+    pub direction: Point2,
+}
+
+impl Ray {
+    pub fn new(start: Point2, direction: Point2) -> Self {
+        Ray { start, direction }
+    }
+
+    pub fn at(&self, t: f64) -> Point2 {
+        (
+            self.start.0 + t * self.direction.0,
+            self.start.1 + t * self.direction.1,
+        )
+    }
+
+    /// When the ray is at the point, which has to be on it
+    fn time_at(&self, point: Point2) -> f64 {
+        if self.direction.0.abs() >= self.direction.1.abs() {
+            (point.0 - self.start.0) / self.direction.0
+        } else {
+            (point.1 - self.start.1) / self.direction.1
+        }
+    }
+
+    /// The times the ray is within the box, `None` if it never is
+    fn times_within(&self, min: Point2, max: Point2) -> Option<(f64, f64)> {
+        let axes = [
+            (self.start.0, self.direction.0, min.0, max.0),
+            (self.start.1, self.direction.1, min.1, max.1),
+        ];
+        let (mut from, mut to) = (0.0f64, f64::INFINITY);
+        for (start, direction, min, max) in axes {
+            if direction == 0.0 {
+                if start < min || start > max {
+                    return None;
+                }
+                continue;
+            }
+            let (t1, t2) = ((min - start) / direction, (max - start) / direction);
+            from = from.max(t1.min(t2));
+            to = to.min(t1.max(t2));
+        }
+        (from <= to).then_some((from, to))
+    }
+}
+
+/// Where two rays cross, and the time along each that they get there
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Intersection {
+    pub first: usize,
+    pub second: usize,
+    pub point: Point2,
+    pub times: (f64, f64),
+}
+
+/// Where the rays' paths cross, with the times each reaches it, if they aren't parallel and neither gets there
+/// before it starts
+pub fn ray_intersection(a: &Ray, b: &Ray, epsilon: f64) -> Option<(Point2, (f64, f64))> {
+    // each line as a1 x + b1 y = c1, see
+    // https://www.topcoder.com/thrive/articles/Geometry%20Concepts%20part%202:%20%20Line%20Intersection%20and%20its%20Applications
+    let line = |ray: &Ray| {
+        let (a, b) = (ray.direction.1, -ray.direction.0);
+        (a, b, a * ray.start.0 + b * ray.start.1)
+    };
+    let (a1, b1, c1) = line(a);
+    let (a2, b2, c2) = line(b);
+    let det = a1 * b2 - a2 * b1;
+    if approx_eq(det, 0.0, epsilon) {
+        return None; //parallel
+    }
+    let point = ((b2 * c1 - b1 * c2) / det, (a1 * c2 - a2 * c1) / det);
+    let times = (a.time_at(point), b.time_at(point));
+    (is_non_negative_time(times.0, epsilon) && is_non_negative_time(times.1, epsilon))
+        .then_some((point, times))
+}
+
+fn within(point: Point2, min: Point2, max: Point2) -> bool {
+    point.0 >= min.0 && point.0 <= max.0 && point.1 >= min.1 && point.1 <= max.1
+}
+
+/// Every pair of rays that cross inside the box (edges included), checking every pair spread over the threads.
+/// This is the reference for [`intersections_within`].
+pub fn intersections_within_naive(
+    rays: &[Ray],
+    min: Point2,
+    max: Point2,
+    epsilon: f64,
+) -> Vec<Intersection> {
+    par_pairs(rays, |(first, first_ray), (second, second_ray)| {
+        let (point, times) = ray_intersection(first_ray, second_ray, epsilon)?;
+        within(point, min, max).then_some(Intersection {
+            first,
+            second,
+            point,
+            times,
+        })
+    })
+}
+
+/// How many cells of a [`SpatialHash`] go across the box in [`intersections_within`]
+const CELLS_ACROSS: f64 = 16.0;
+
+/// Every pair of rays that cross inside the box (edges included), ordered by the pair's indices.  Each ray is
+/// cut down to the part inside the box, and only rays whose parts pass through a shared cell of a
+/// [`SpatialHash`] over the box are paired up, so rays that miss the box or each other are never compared.
+/// The pairs that are compared are spread over the threads.  This only beats
+/// [`intersections_within_naive`] when most pairs of paths don't come near each other, see the `crossings`
+/// benchmark.
+pub fn intersections_within(
+    rays: &[Ray],
+    min: Point2,
+    max: Point2,
+    epsilon: f64,
+) -> Vec<Intersection> {
+    // a little slack so that rounding in the clipping can't lose an intersection on an edge
+    let scale = [min.0, min.1, max.0, max.1]
+        .iter()
+        .fold(1.0f64, |scale, bound| scale.max(bound.abs()));
+    let slack = epsilon * scale;
+    let (slack_min, slack_max) = (
+        (min.0 - slack, min.1 - slack),
+        (max.0 + slack, max.1 + slack),
+    );
+    let extent = (max.0 - min.0).max(max.1 - min.1);
+    let mut hash = SpatialHash::new((extent / CELLS_ACROSS).max(slack).max(f64::MIN_POSITIVE));
+    for (index, ray) in rays.iter().enumerate() {
+        if let Some((from, to)) = ray.times_within(slack_min, slack_max) {
+            hash.insert_segment(ray.at(from), ray.at(to), slack, index);
+        }
+    }
+
+    // the pairs are in order of the first ray, and each thread takes all of a ray's pairs at once
+    let pairs = hash.candidate_pairs();
+    let by_first: Vec<&[(usize, usize)]> = pairs
+        .chunk_by(|(first, _), (next_first, _)| first == next_first)
+        .collect();
+    par_map(by_first, |pairs| {
+        pairs
+            .iter()
+            .filter_map(|(first, second)| {
+                let (point, times) = ray_intersection(&rays[*first], &rays[*second], epsilon)?;
+                within(point, min, max).then_some(Intersection {
+                    first: *first,
+                    second: *second,
+                    point,
+                    times,
+                })
+            })
+            .collect::<Vec<_>>()
+    })
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn approximate_comparisons() {
+        assert!(approx_eq(0.1 + 0.2, 0.3, DEFAULT_EPSILON));
+        assert!(!approx_eq(0.1, 0.2, DEFAULT_EPSILON));
+        assert!(approx_eq(3e14, 3e14 + 0.1, DEFAULT_EPSILON));
+        assert!(!approx_eq(3e14, 3e14 + 1e6, DEFAULT_EPSILON));
+        assert!(approx_eq(1.0, 1.5, 0.5));
+
+        assert!(is_non_negative_time(0.0, DEFAULT_EPSILON));
+        assert!(is_non_negative_time(-1e-12, DEFAULT_EPSILON));
+        assert!(!is_non_negative_time(-1e-3, DEFAULT_EPSILON));
+        assert!(is_non_negative_time(5.0, 0.0));
+    }
+
+    #[test]
+    fn rays_crossing_in_a_box() {
+        // the hailstones from day24's example, in x and y
+        let rays = [
+            Ray::new((19.0, 13.0), (-2.0, 1.0)),
+            Ray::new((18.0, 19.0), (-1.0, -1.0)),
+            Ray::new((20.0, 25.0), (-2.0, -2.0)),
+            Ray::new((12.0, 31.0), (-1.0, -2.0)),
+            Ray::new((20.0, 19.0), (1.0, -5.0)),
+        ];
+        let (point, times) = ray_intersection(&rays[0], &rays[1], DEFAULT_EPSILON).unwrap();
+        assert!(approx_eq(point.0, 14.333, 1e-3) && approx_eq(point.1, 15.333, 1e-3));
+        assert!(approx_eq(times.0, 2.333, 1e-3) && approx_eq(times.1, 3.667, 1e-3));
+        // parallel
+        assert_eq!(ray_intersection(&rays[1], &rays[2], DEFAULT_EPSILON), None);
+        // crossed in the past for the second
+        assert_eq!(ray_intersection(&rays[0], &rays[4], DEFAULT_EPSILON), None);
+
+        let (min, max) = ((7.0, 7.0), (27.0, 27.0));
+        let naive = intersections_within_naive(&rays, min, max, DEFAULT_EPSILON);
+        let pairs: Vec<(usize, usize)> = naive.iter().map(|i| (i.first, i.second)).collect();
+        assert_eq!(pairs, vec![(0, 1), (0, 2)]);
+        assert_eq!(
+            intersections_within(&rays, min, max, DEFAULT_EPSILON),
+            naive
+        );
+
+        // a fan of rays from below the box, with plenty missing it or each other
+        let rays: Vec<Ray> = (0..200)
+            .map(|i| {
+                let i = i as f64;
+                Ray::new(
+                    (i * 7.0 % 150.0 - 25.0, -10.0 - i),
+                    ((i % 13.0) - 6.0, 1.0 + i % 5.0),
+                )
+            })
+            .collect();
+        let (min, max) = ((0.0, 0.0), (100.0, 100.0));
+        let naive = intersections_within_naive(&rays, min, max, DEFAULT_EPSILON);
+        assert!(naive.len() > 100);
+        assert_eq!(
+            intersections_within(&rays, min, max, DEFAULT_EPSILON),
+            naive
+        );
+    }
+}
@@ -0,0 +1,1341 @@
+//! A graph of nodes joined by (optionally weighted) edges, directed or not, for the days whose puzzles are
+//! really about graphs (day20's modules, day23's junctions, day25's components).  Graphs can be written out
+//! as DOT for Graphviz or as JSON, with a [`Highlight`] picking out the interesting parts (a cut, a path).
+//! Minimum cuts can be found either deterministically (Stoer-Wagner) or by random contraction (Karger).
+
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet},
+    fmt::{Display, Write},
+    fs,
+    hash::Hash,
+    ops::{Index, IndexMut},
+    path::Path,
+};
+
+use rand::{seq::SliceRandom, Rng};
+
+use crate::{union_find::UnionFind, AError, Cells, Direction, SmallBitSet};
+
+/// Environment variable naming the file to export a day's graph to, see [`export_if_requested`]
+pub const EXPORT_GRAPH_ENV: &str = "AOC_EXPORT_GRAPH";
+
+/// Index of a node in its [`Graph`], handed out in the order nodes are added
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NodeId(usize);
+
+impl NodeId {
+    pub fn new(index: usize) -> Self {
+        NodeId(index)
+    }
+
+    pub fn index(&self) -> usize {
+        self.0
+    }
+}
+
+impl Display for NodeId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// A value for each node of a graph, held densely in id order rather than hashed
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NodeMap<T> {
+    values: Vec<T>,
+}
+
+impl<T> NodeMap<T> {
+    pub fn new() -> Self {
+        NodeMap { values: Vec::new() }
+    }
+
+    /// Add the value for the next node
+    pub fn push(&mut self, value: T) -> NodeId {
+        self.values.push(value);
+        NodeId(self.values.len() - 1)
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn get(&self, id: NodeId) -> Option<&T> {
+        self.values.get(id.0)
+    }
+
+    pub fn ids(&self) -> impl Iterator<Item = NodeId> {
+        (0..self.values.len()).map(NodeId)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (NodeId, &T)> {
+        self.values
+            .iter()
+            .enumerate()
+            .map(|(index, value)| (NodeId(index), value))
+    }
+
+    pub fn values(&self) -> std::slice::Iter<'_, T> {
+        self.values.iter()
+    }
+
+    pub fn values_mut(&mut self) -> std::slice::IterMut<'_, T> {
+        self.values.iter_mut()
+    }
+}
+
+impl<T: Clone> NodeMap<T> {
+    /// The same value for each of the nodes
+    pub fn filled(node_count: usize, value: T) -> Self {
+        NodeMap {
+            values: vec![value; node_count],
+        }
+    }
+}
+
+impl<T> FromIterator<T> for NodeMap<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        NodeMap {
+            values: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl<T> Index<NodeId> for NodeMap<T> {
+    type Output = T;
+
+    fn index(&self, id: NodeId) -> &T {
+        &self.values[id.0]
+    }
+}
+
+impl<T> IndexMut<NodeId> for NodeMap<T> {
+    fn index_mut(&mut self, id: NodeId) -> &mut T {
+        &mut self.values[id.0]
+    }
+}
+
+/// Shortest distance between every pair of nodes, as `distances[from][to]`, with None where there's no path
+pub type Distances = NodeMap<NodeMap<Option<usize>>>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Edge {
+    pub from: NodeId,
+    pub to: NodeId,
+    pub weight: Option<usize>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Graph<N> {
+    directed: bool,
+    nodes: NodeMap<N>,
+    edges: Vec<Edge>,
+    /// Indexes in to edges of the edges leaving each node, both ways round for an undirected graph
+    adjacency: NodeMap<Vec<usize>>,
+}
+
+impl<N> Graph<N> {
+    pub fn directed() -> Self {
+        Graph::new(true)
+    }
+
+    pub fn undirected() -> Self {
+        Graph::new(false)
+    }
+
+    fn new(directed: bool) -> Self {
+        Graph {
+            directed,
+            nodes: NodeMap::new(),
+            edges: Vec::default(),
+            adjacency: NodeMap::new(),
+        }
+    }
+
+    pub fn is_directed(&self) -> bool {
+        self.directed
+    }
+
+    pub fn add_node(&mut self, node: N) -> NodeId {
+        self.adjacency.push(Vec::default());
+        self.nodes.push(node)
+    }
+
+    pub fn add_edge(&mut self, from: NodeId, to: NodeId) {
+        self.push_edge(from, to, None);
+    }
+
+    pub fn add_weighted_edge(&mut self, from: NodeId, to: NodeId, weight: usize) {
+        self.push_edge(from, to, Some(weight));
+    }
+
+    fn push_edge(&mut self, from: NodeId, to: NodeId, weight: Option<usize>) {
+        assert!(
+            from.0 < self.nodes.len() && to.0 < self.nodes.len(),
+            "Edge {from} -> {to} is to a node not in the graph"
+        );
+        self.edges.push(Edge { from, to, weight });
+        let index = self.edges.len() - 1;
+        self.adjacency[from].push(index);
+        if !self.directed && from != to {
+            self.adjacency[to].push(index);
+        }
+    }
+
+    pub fn node(&self, id: NodeId) -> &N {
+        &self.nodes[id]
+    }
+
+    pub fn nodes(&self) -> impl Iterator<Item = (NodeId, &N)> {
+        self.nodes.iter()
+    }
+
+    /// The first node satisfying the predicate
+    pub fn find_node(&self, predicate: impl Fn(&N) -> bool) -> Option<NodeId> {
+        self.nodes.values().position(predicate).map(NodeId)
+    }
+
+    /// The same value for each of the graph's nodes, to be filled in by id
+    pub fn node_map<T: Clone>(&self, value: T) -> NodeMap<T> {
+        NodeMap::filled(self.nodes.len(), value)
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn edges(&self) -> &[Edge] {
+        &self.edges
+    }
+
+    /// The nodes reached along the edges leaving the node, with the weight of each edge
+    pub fn neighbours(&self, id: NodeId) -> impl Iterator<Item = (NodeId, Option<usize>)> + '_ {
+        self.adjacency[id].iter().map(move |index| {
+            let edge = &self.edges[*index];
+            let other = if edge.from == id { edge.to } else { edge.from };
+            (other, edge.weight)
+        })
+    }
+
+    /// The other end of the edge at the index, from the node
+    fn other_end(&self, id: NodeId, index: usize) -> NodeId {
+        let edge = &self.edges[index];
+        if edge.from == id {
+            edge.to
+        } else {
+            edge.from
+        }
+    }
+
+    /// The strongly connected components, i.e. the sets of nodes that can all reach each other, using
+    /// Tarjan's algorithm (without recursion, so large graphs don't overflow the stack).  Components come out
+    /// in reverse topological order - nothing in a component has an edge to a component after it.  In an
+    /// undirected graph these are just the connected components.
+    pub fn strongly_connected_components(&self) -> Vec<Vec<NodeId>> {
+        let mut tarjan = Tarjan::new(self.nodes.len());
+        let mut components = vec![];
+        for root in self.nodes.ids() {
+            if tarjan.indexes[root].is_some() {
+                continue;
+            }
+            // each call is the node being visited and how many of its edges have been followed
+            let mut calls: Vec<(NodeId, usize)> = vec![(root, 0)];
+            tarjan.visit(root);
+            while let Some((id, followed)) = calls.last_mut() {
+                let id = *id;
+                if let Some(index) = self.adjacency[id].get(*followed) {
+                    *followed += 1;
+                    let next = self.other_end(id, *index);
+                    match tarjan.indexes[next] {
+                        None => {
+                            tarjan.visit(next);
+                            calls.push((next, 0));
+                        }
+                        Some(next_index) if tarjan.on_stack[next] => {
+                            tarjan.low_links[id] = tarjan.low_links[id].min(next_index)
+                        }
+                        Some(_) => {}
+                    }
+                    continue;
+                }
+                calls.pop();
+                if let Some((parent, _)) = calls.last() {
+                    tarjan.low_links[*parent] = tarjan.low_links[*parent].min(tarjan.low_links[id]);
+                }
+                if Some(tarjan.low_links[id]) == tarjan.indexes[id] {
+                    components.push(tarjan.pop_component(id));
+                }
+            }
+        }
+        components
+    }
+
+    /// The graph with each strongly connected component collapsed to a single node holding the ids of its
+    /// members.  The components are in topological order, so every edge goes from a lower id to a higher one.
+    /// Edges are unweighted, and only one is kept between any two components.
+    pub fn condensation(&self) -> Condensation {
+        let components: Vec<Vec<NodeId>> = self
+            .strongly_connected_components()
+            .into_iter()
+            .rev()
+            .collect();
+        let mut component_of = self.node_map(NodeId::default());
+        for (component, members) in components.iter().enumerate() {
+            members
+                .iter()
+                .for_each(|id| component_of[*id] = NodeId(component));
+        }
+        let mut graph = Graph::new(self.directed);
+        components.into_iter().for_each(|members| {
+            graph.add_node(members);
+        });
+        let mut joined: HashSet<(NodeId, NodeId)> = HashSet::default();
+        for edge in self.edges.iter() {
+            let (from, to) = (component_of[edge.from], component_of[edge.to]);
+            if from != to && joined.insert((from, to)) {
+                graph.add_edge(from, to);
+            }
+        }
+        Condensation {
+            graph,
+            component_of,
+        }
+    }
+
+    /// Shortest distances from the node to every other, by Dijkstra's algorithm
+    pub fn shortest_distances_from(&self, from: NodeId) -> NodeMap<Option<usize>> {
+        let mut distances = self.node_map(None);
+        let mut queue = BinaryHeap::from([Reverse((0, from))]);
+        while let Some(Reverse((distance, id))) = queue.pop() {
+            if distances[id].is_some() {
+                continue;
+            }
+            distances[id] = Some(distance);
+            for index in self.adjacency[id].iter() {
+                let next = self.other_end(id, *index);
+                if distances[next].is_none() {
+                    queue.push(Reverse((distance + edge_length(&self.edges[*index]), next)));
+                }
+            }
+        }
+        distances
+    }
+
+    /// Shortest distances between every pair of nodes by Floyd-Warshall, which at O(n^3) suits small dense
+    /// graphs such as the junctions left after contracting a maze's corridors
+    pub fn floyd_warshall(&self) -> Distances {
+        let mut distances: Distances = self.node_map(self.node_map(None));
+        self.nodes.ids().for_each(|id| distances[id][id] = Some(0));
+        for edge in self.edges.iter() {
+            let length = Some(edge_length(edge));
+            let mut shorten = |from: NodeId, to: NodeId| {
+                if distances[from][to].is_none_or(|distance| length < Some(distance)) {
+                    distances[from][to] = length;
+                }
+            };
+            shorten(edge.from, edge.to);
+            if !self.directed {
+                shorten(edge.to, edge.from);
+            }
+        }
+        for via in self.nodes.ids() {
+            // the row for via can't shorten while going via itself
+            let from_via = distances[via].clone();
+            for row in distances.values_mut() {
+                let Some(to_via) = row[via] else {
+                    continue;
+                };
+                for (distance, from_via) in row.values_mut().zip(from_via.values()) {
+                    if let Some(from_via) = from_via {
+                        let through = to_via + from_via;
+                        if distance.is_none_or(|distance| through < distance) {
+                            *distance = Some(through);
+                        }
+                    }
+                }
+            }
+        }
+        distances
+    }
+
+    /// Shortest distances between every pair of nodes by Johnson's algorithm, better than
+    /// [`Graph::floyd_warshall`] for large sparse graphs.  Weights can't be negative here, so there's no
+    /// reweighting to do and this comes down to [`Graph::shortest_distances_from`] every node.
+    pub fn johnson(&self) -> Distances {
+        self.nodes
+            .ids()
+            .map(|from| self.shortest_distances_from(from))
+            .collect()
+    }
+
+    /// Every simple path (one never revisiting a node) from one node to another, found lazily by a depth
+    /// first search, optionally only those no longer than the maximum length.  There can be exponentially
+    /// many such paths - a grid of junctions only a few dozen across has millions - so take care over
+    /// collecting them all, and prefer a cutoff or taking only as many as needed.
+    pub fn simple_paths(
+        &self,
+        from: NodeId,
+        to: NodeId,
+        max_length: Option<usize>,
+    ) -> SimplePaths<'_, N> {
+        let mut on_path = self.node_map(false);
+        let (steps, trivial) = if from == to {
+            (
+                vec![],
+                Some(SimplePath {
+                    nodes: vec![from],
+                    length: 0,
+                }),
+            )
+        } else {
+            on_path[from] = true;
+            (
+                vec![PathStep {
+                    id: from,
+                    followed: 0,
+                    length: 0,
+                }],
+                None,
+            )
+        };
+        SimplePaths {
+            graph: self,
+            to,
+            max_length,
+            steps,
+            on_path,
+            trivial,
+        }
+    }
+
+    /// Length of the longest simple path from one node to another, None if there's no path at all.  Graphs of
+    /// up to 128 nodes (e.g. a maze's junctions, see [`contract_corridors`]) keep the nodes on the path in a
+    /// [`SmallBitSet`], making the depth first search through the exponentially many paths quick enough;
+    /// larger graphs fall back on [`Graph::simple_paths`].
+    pub fn longest_simple_path_length(&self, from: NodeId, to: NodeId) -> Option<usize> {
+        if self.nodes.len() > SmallBitSet::CAPACITY {
+            return self
+                .simple_paths(from, to, None)
+                .map(|path| path.length)
+                .max();
+        }
+        let adjacency: NodeMap<Vec<(NodeId, usize)>> = self
+            .nodes
+            .ids()
+            .map(|id| {
+                self.adjacency[id]
+                    .iter()
+                    .map(|index| (self.other_end(id, *index), edge_length(&self.edges[*index])))
+                    .collect()
+            })
+            .collect();
+        longest_from(&adjacency, from, to, SmallBitSet::default().with(from.0))
+    }
+
+    /// The graph as DOT, for Graphviz (e.g. `dot -Tsvg`), with each node labelled by the labeler.  Weights are
+    /// shown on their edges.
+    pub fn to_dot<L>(&self, labeler: L, highlight: &Highlight) -> String
+    where
+        L: Fn(NodeId, &N) -> String,
+    {
+        const HIGHLIGHTED: &str = "color=red, penwidth=3";
+        let (kind, arrow) = if self.directed {
+            ("digraph", "->")
+        } else {
+            ("graph", "--")
+        };
+        let mut dot = format!("{kind} {{\n");
+        for (id, node) in self.nodes() {
+            let style = if highlight.nodes.contains(&id) {
+                format!(", {HIGHLIGHTED}")
+            } else {
+                String::new()
+            };
+            let label = escape(&labeler(id, node));
+            writeln!(dot, "  {id} [label=\"{label}\"{style}];").unwrap();
+        }
+        for edge in self.edges.iter() {
+            let mut attributes = vec![];
+            if let Some(weight) = edge.weight {
+                attributes.push(format!("label=\"{weight}\""));
+            }
+            if highlight.has_edge(edge, self.directed) {
+                attributes.push(HIGHLIGHTED.to_string());
+            }
+            let attributes = if attributes.is_empty() {
+                String::new()
+            } else {
+                format!(" [{}]", attributes.join(", "))
+            };
+            writeln!(dot, "  {} {arrow} {}{attributes};", edge.from, edge.to).unwrap();
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// The graph as JSON: `{"directed":..,"nodes":[{"id":..,"label":..,"highlighted":..}],
+    /// "edges":[{"from":..,"to":..,"weight":..,"highlighted":..}]}` with a null weight for unweighted edges
+    pub fn to_json<L>(&self, labeler: L, highlight: &Highlight) -> String
+    where
+        L: Fn(NodeId, &N) -> String,
+    {
+        let nodes: Vec<String> = self
+            .nodes()
+            .map(|(id, node)| {
+                format!(
+                    "{{\"id\":{id},\"label\":\"{}\",\"highlighted\":{}}}",
+                    escape(&labeler(id, node)),
+                    highlight.nodes.contains(&id)
+                )
+            })
+            .collect();
+        let edges: Vec<String> = self
+            .edges
+            .iter()
+            .map(|edge| {
+                let weight = edge
+                    .weight
+                    .map_or("null".to_string(), |weight| weight.to_string());
+                format!(
+                    "{{\"from\":{},\"to\":{},\"weight\":{weight},\"highlighted\":{}}}",
+                    edge.from,
+                    edge.to,
+                    highlight.has_edge(edge, self.directed)
+                )
+            })
+            .collect();
+        format!(
+            "{{\"directed\":{},\"nodes\":[{}],\"edges\":[{}]}}\n",
+            self.directed,
+            nodes.join(","),
+            edges.join(",")
+        )
+    }
+}
+
+/// A path found by [`Graph::simple_paths`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimplePath {
+    /// Both ends included
+    pub nodes: Vec<NodeId>,
+    /// Total weight of the edges, unweighted edges counting as a single step
+    pub length: usize,
+}
+
+/// A node on the path being extended by [`SimplePaths`]
+#[derive(Debug, Clone)]
+struct PathStep {
+    id: NodeId,
+    /// How many of the node's edges have been tried
+    followed: usize,
+    /// Length of the path up to the node
+    length: usize,
+}
+
+/// Iterator over the simple paths between two nodes, see [`Graph::simple_paths`]
+#[derive(Debug)]
+pub struct SimplePaths<'a, N> {
+    graph: &'a Graph<N>,
+    to: NodeId,
+    max_length: Option<usize>,
+    steps: Vec<PathStep>,
+    on_path: NodeMap<bool>,
+    /// The single node path when the ends are the same node
+    trivial: Option<SimplePath>,
+}
+
+impl<N> Iterator for SimplePaths<'_, N> {
+    type Item = SimplePath;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(trivial) = self.trivial.take() {
+            return Some(trivial);
+        }
+        while let Some(step) = self.steps.last_mut() {
+            let Some(index) = self.graph.adjacency[step.id].get(step.followed) else {
+                self.on_path[step.id] = false;
+                self.steps.pop();
+                continue;
+            };
+            step.followed += 1;
+            let next = self.graph.other_end(step.id, *index);
+            let length = step.length + edge_length(&self.graph.edges[*index]);
+            if self.on_path[next] || self.max_length.is_some_and(|max| length > max) {
+                continue;
+            }
+            if next == self.to {
+                let mut nodes: Vec<NodeId> = self.steps.iter().map(|step| step.id).collect();
+                nodes.push(next);
+                return Some(SimplePath { nodes, length });
+            }
+            self.on_path[next] = true;
+            self.steps.push(PathStep {
+                id: next,
+                followed: 0,
+                length,
+            });
+        }
+        None
+    }
+}
+
+/// Book keeping for [`Graph::strongly_connected_components`]
+struct Tarjan {
+    /// The order nodes were first visited in
+    indexes: NodeMap<Option<usize>>,
+    /// The lowest index reachable from each node through nodes still on the stack
+    low_links: NodeMap<usize>,
+    on_stack: NodeMap<bool>,
+    stack: Vec<NodeId>,
+    next_index: usize,
+}
+
+impl Tarjan {
+    fn new(node_count: usize) -> Self {
+        Tarjan {
+            indexes: NodeMap::filled(node_count, None),
+            low_links: NodeMap::filled(node_count, 0),
+            on_stack: NodeMap::filled(node_count, false),
+            stack: vec![],
+            next_index: 0,
+        }
+    }
+
+    fn visit(&mut self, id: NodeId) {
+        self.indexes[id] = Some(self.next_index);
+        self.low_links[id] = self.next_index;
+        self.next_index += 1;
+        self.stack.push(id);
+        self.on_stack[id] = true;
+    }
+
+    /// Everything on the stack down to and including the root of the component
+    fn pop_component(&mut self, root: NodeId) -> Vec<NodeId> {
+        let mut component = vec![];
+        while let Some(member) = self.stack.pop() {
+            self.on_stack[member] = false;
+            component.push(member);
+            if member == root {
+                break;
+            }
+        }
+        component
+    }
+}
+
+/// A graph of strongly connected components, see [`Graph::condensation`]
+#[derive(Debug, Clone)]
+pub struct Condensation {
+    pub graph: Graph<Vec<NodeId>>,
+    /// The component (node in the condensed graph) each node of the original graph ended up in
+    pub component_of: NodeMap<NodeId>,
+}
+
+/// The ids of the nodes of a graph of cells' coordinates (e.g. the junctions of a maze), held in a grid the size
+/// of the cells so that finding a coordinate's node is an index rather than a search
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoordIds {
+    ids: Cells<Option<NodeId>>,
+}
+
+impl CoordIds {
+    pub fn new(side_lengths: (usize, usize)) -> Self {
+        CoordIds {
+            ids: Cells::with_dimension(side_lengths.0, side_lengths.1, None),
+        }
+    }
+
+    /// Every node of the graph, by its coordinate
+    pub fn of_graph(graph: &Graph<(usize, usize)>, side_lengths: (usize, usize)) -> Self {
+        let mut ids = CoordIds::new(side_lengths);
+        graph.nodes().for_each(|(id, coord)| ids.insert(*coord, id));
+        ids
+    }
+
+    /// Panics if the coordinate is outside the cells
+    pub fn insert(&mut self, (x, y): (usize, usize), id: NodeId) {
+        self.ids[(x, y)] = Some(id);
+    }
+
+    /// The node at the coordinate, None if there isn't one or the coordinate is outside the cells
+    pub fn get(&self, (x, y): (usize, usize)) -> Option<NodeId> {
+        self.ids.get_opt(x, y).copied().flatten()
+    }
+}
+
+/// The longest simple path from the node to the end avoiding the nodes already on the path, see
+/// [`Graph::longest_simple_path_length`]
+fn longest_from(
+    adjacency: &NodeMap<Vec<(NodeId, usize)>>,
+    at: NodeId,
+    to: NodeId,
+    on_path: SmallBitSet,
+) -> Option<usize> {
+    if at == to {
+        return Some(0);
+    }
+    adjacency[at]
+        .iter()
+        .filter(|(next, _)| !on_path.contains(next.0))
+        .filter_map(|(next, length)| {
+            longest_from(adjacency, *next, to, on_path.with(next.0)).map(|rest| rest + length)
+        })
+        .max()
+}
+
+/// Collapse the corridors of a grid into the weighted edges of a directed graph between its junctions, the
+/// cells with more than two open neighbours, and any other cells to keep (such as a maze's start and end).  A
+/// cell is open if it can be entered heading in some direction, and a corridor is only followed while each of
+/// its cells can be entered heading the way it's walked, so one way cells (day23's slopes) give one way edges.
+/// Edges are as long as the steps along their corridor, and corridors leading nowhere are dropped.
+pub fn contract_corridors<T, F>(
+    cells: &Cells<T>,
+    keep: &[(usize, usize)],
+    can_enter: F,
+) -> Graph<(usize, usize)>
+where
+    F: Fn(&T, Direction) -> bool,
+{
+    let is_open = |(x, y): (usize, usize)| {
+        let cell = &cells[(x, y)];
+        Direction::ALL
+            .into_iter()
+            .any(|heading| can_enter(cell, heading))
+    };
+    let open_neighbours = |coord: (usize, usize)| {
+        Direction::ALL
+            .into_iter()
+            .filter_map(|direction| cells.step(coord, direction))
+            .filter(|next| is_open(*next))
+            .count()
+    };
+    let mut graph = Graph::directed();
+    cells
+        .iter()
+        .filter(|(coord, _)| {
+            is_open(*coord) && (keep.contains(coord) || open_neighbours(*coord) > 2)
+        })
+        .for_each(|(coord, _)| {
+            graph.add_node(coord);
+        });
+    let junctions: Vec<(NodeId, (usize, usize))> =
+        graph.nodes().map(|(id, coord)| (id, *coord)).collect();
+    let ids = CoordIds::of_graph(&graph, cells.side_lengths);
+    for (from, junction) in junctions {
+        for direction in Direction::ALL {
+            let (mut coord, mut heading) = (junction, direction);
+            let mut steps = 0;
+            let reached = loop {
+                let Some(next) = cells.step(coord, heading) else {
+                    break None;
+                };
+                if !can_enter(&cells[next], heading) {
+                    break None;
+                }
+                steps += 1;
+                if let Some(to) = ids.get(next) {
+                    break Some(to);
+                }
+                let Some(turn) = Direction::ALL.into_iter().find(|turn| {
+                    *turn != heading.opposite() && cells.step(next, *turn).is_some_and(&is_open)
+                }) else {
+                    break None;
+                };
+                (coord, heading) = (next, turn);
+            };
+            if let Some(to) = reached {
+                graph.add_weighted_edge(from, to, steps);
+            }
+        }
+    }
+    graph
+}
+
+/// Nodes and edges to pick out when exporting a graph, e.g. the edges of a cut or the steps of a path
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Highlight {
+    pub nodes: HashSet<NodeId>,
+    /// As from and to, matching either way round in an undirected graph
+    pub edges: HashSet<(NodeId, NodeId)>,
+}
+
+impl Highlight {
+    pub fn with_nodes(mut self, nodes: impl IntoIterator<Item = NodeId>) -> Self {
+        self.nodes.extend(nodes);
+        self
+    }
+
+    pub fn with_edges(mut self, edges: impl IntoIterator<Item = (NodeId, NodeId)>) -> Self {
+        self.edges.extend(edges);
+        self
+    }
+
+    /// Every node on the path and the edges between them
+    pub fn with_path(self, path: &[NodeId]) -> Self {
+        self.with_nodes(path.iter().copied())
+            .with_edges(path.windows(2).map(|step| (step[0], step[1])))
+    }
+
+    fn has_edge(&self, edge: &Edge, directed: bool) -> bool {
+        self.edges.contains(&(edge.from, edge.to))
+            || (!directed && self.edges.contains(&(edge.to, edge.from)))
+    }
+}
+
+/// Ids for nodes known by name (e.g. day25's components), handing out the next id to each name not seen before
+#[derive(Debug, Clone, Default)]
+pub struct NodeNames<K> {
+    ids: HashMap<K, NodeId>,
+    names: NodeMap<K>,
+}
+
+impl<K: Clone + Hash + Eq> NodeNames<K> {
+    pub fn new() -> Self {
+        NodeNames {
+            ids: HashMap::default(),
+            names: NodeMap::new(),
+        }
+    }
+
+    /// The name's id, adding it if it's new
+    pub fn intern(&mut self, name: &K) -> NodeId {
+        if let Some(id) = self.ids.get(name) {
+            return *id;
+        }
+        let id = self.names.push(name.clone());
+        self.ids.insert(name.clone(), id);
+        id
+    }
+
+    pub fn id(&self, name: &K) -> Option<NodeId> {
+        self.ids.get(name).copied()
+    }
+
+    pub fn name(&self, id: NodeId) -> &K {
+        &self.names[id]
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// A graph of the names with no edges yet, each node having the same id as its name
+    pub fn graph(self, directed: bool) -> Graph<K> {
+        let mut graph = Graph::new(directed);
+        self.names.values.into_iter().for_each(|name| {
+            graph.add_node(name);
+        });
+        graph
+    }
+}
+
+/// A split of a graph's nodes in to two sides, see [`Graph::stoer_wagner_min_cut`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cut {
+    /// Total weight of the edges crossing the cut, unweighted edges counting as 1
+    pub weight: usize,
+    /// The nodes on one side of the cut in id order, the rest of the graph's nodes being on the other
+    pub side: Vec<NodeId>,
+    pub edges: Vec<Edge>,
+}
+
+impl<N> Graph<N> {
+    /// A minimum cut of the graph by the Stoer-Wagner algorithm, taking the edges as undirected.  Deterministic,
+    /// unlike [`Graph::karger_min_cut`], at O(V E log V).  None if there are fewer than 2 nodes.
+    pub fn stoer_wagner_min_cut(&self) -> Option<Cut> {
+        if self.nodes.len() < 2 {
+            return None;
+        }
+        // weights between the merged nodes, each standing for the original nodes in its members
+        let mut weights: NodeMap<HashMap<NodeId, usize>> = self.node_map(HashMap::default());
+        for edge in self.edges.iter().filter(|edge| edge.from != edge.to) {
+            *weights[edge.from].entry(edge.to).or_default() += edge_length(edge);
+            *weights[edge.to].entry(edge.from).or_default() += edge_length(edge);
+        }
+        let mut members: NodeMap<Vec<NodeId>> = self.nodes.ids().map(|id| vec![id]).collect();
+        let mut merged = self.node_map(false);
+        let mut best: Option<(usize, Vec<NodeId>)> = None;
+        for phase in 0..self.nodes.len() - 1 {
+            // add the most tightly connected node to the set each time, the last two added are then the ends
+            // of a minimum cut between them
+            let mut added = self.node_map(false);
+            let mut connection = self.node_map(0);
+            let start = self.nodes.ids().find(|id| !merged[*id]).unwrap();
+            let mut queue = BinaryHeap::from([(0, start)]);
+            let (mut previous, mut last) = (start, start);
+            let mut added_count = 0;
+            while let Some((weight, id)) = queue.pop() {
+                if added[id] || weight != connection[id] {
+                    continue;
+                }
+                added[id] = true;
+                added_count += 1;
+                (previous, last) = (last, id);
+                for (next, weight) in weights[id].iter() {
+                    if !added[*next] {
+                        connection[*next] += weight;
+                        queue.push((connection[*next], *next));
+                    }
+                }
+            }
+            if added_count < self.nodes.len() - phase {
+                // not everything is connected, so nothing needs cutting
+                let side = added.iter().filter(|(_, added)| **added).map(|(id, _)| id);
+                return Some(self.cut(side.flat_map(|id| members[id].clone()).collect()));
+            }
+            let weight = connection[last];
+            if best.as_ref().is_none_or(|(best, _)| weight < *best) {
+                best = Some((weight, members[last].clone()));
+            }
+            // merge the last node in to the one before it
+            let last_weights = std::mem::take(&mut weights[last]);
+            for (other, weight) in last_weights {
+                weights[other].remove(&last);
+                if other != previous {
+                    *weights[previous].entry(other).or_default() += weight;
+                    *weights[other].entry(previous).or_default() += weight;
+                }
+            }
+            let last_members = std::mem::take(&mut members[last]);
+            members[previous].extend(last_members);
+            merged[last] = true;
+        }
+        best.map(|(_, side)| self.cut(side))
+    }
+
+    /// The smallest cut found by a number of trials of Karger's algorithm, taking the edges as undirected and
+    /// ignoring their weights.  Each trial contracts randomly picked edges until two nodes are left, so is only
+    /// likely to find a minimum cut - take enough trials, or see [`Graph::stoer_wagner_min_cut`].  None if there
+    /// are fewer than 2 nodes.
+    pub fn karger_min_cut(&self, rng: &mut impl Rng, trials: usize) -> Option<Cut> {
+        if self.nodes.len() < 2 {
+            return None;
+        }
+        let mut order: Vec<&Edge> = self.edges.iter().collect();
+        (0..trials.max(1))
+            .map(|_| {
+                order.shuffle(rng);
+                let mut contracted = UnionFind::new(self.nodes.len());
+                for edge in order.iter() {
+                    if contracted.component_count() <= 2 {
+                        break;
+                    }
+                    contracted.union(edge.from.0, edge.to.0);
+                }
+                let side_root = contracted.find(0);
+                self.cut(
+                    self.nodes
+                        .ids()
+                        .filter(|id| contracted.find(id.0) == side_root)
+                        .collect(),
+                )
+            })
+            .min_by_key(|cut| cut.weight)
+    }
+
+    /// The cut with the nodes on one side
+    fn cut(&self, mut side: Vec<NodeId>) -> Cut {
+        side.sort();
+        let mut on_side = self.node_map(false);
+        side.iter().for_each(|id| on_side[*id] = true);
+        let edges: Vec<Edge> = self
+            .edges
+            .iter()
+            .filter(|edge| on_side[edge.from] != on_side[edge.to])
+            .copied()
+            .collect();
+        Cut {
+            weight: edges.iter().map(edge_length).sum(),
+            side,
+            edges,
+        }
+    }
+}
+
+/// Length of the edge, unweighted edges counting as a single step
+fn edge_length(edge: &Edge) -> usize {
+    edge.weight.unwrap_or(1)
+}
+
+/// Escape a string for inside double quotes, which DOT and JSON both take with backslash escapes
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' | '\\' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' => escaped.push_str("\\n"),
+            c if c.is_control() => write!(escaped, "\\u{:04x}", c as u32).unwrap(),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Whether [`EXPORT_GRAPH_ENV`] is set, for days to skip building a graph only wanted for export
+pub fn export_requested() -> bool {
+    std::env::var_os(EXPORT_GRAPH_ENV).is_some()
+}
+
+/// Write the graph to the file named by [`EXPORT_GRAPH_ENV`], if it is set, as JSON for a `.json` file and
+/// DOT otherwise
+pub fn export_if_requested<N, L>(
+    graph: &Graph<N>,
+    labeler: L,
+    highlight: &Highlight,
+) -> Result<(), AError>
+where
+    L: Fn(NodeId, &N) -> String,
+{
+    let Some(path) = std::env::var_os(EXPORT_GRAPH_ENV) else {
+        return Ok(());
+    };
+    let path = Path::new(&path);
+    let contents = if path
+        .extension()
+        .is_some_and(|extension| extension == "json")
+    {
+        graph.to_json(labeler, highlight)
+    } else {
+        graph.to_dot(labeler, highlight)
+    };
+    fs::write(path, contents)
+        .map_err(|e| AError::msg(format!("Failed exporting graph to {}: {e}", path.display())))
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+
+    fn n(index: usize) -> NodeId {
+        NodeId::new(index)
+    }
+
+    fn sample() -> Graph<&'static str> {
+        let mut graph = Graph::undirected();
+        let a = graph.add_node("a");
+        let b = graph.add_node("b\"");
+        let c = graph.add_node("c");
+        graph.add_weighted_edge(a, b, 5);
+        graph.add_edge(c, b);
+        graph
+    }
+
+    #[test]
+    fn neighbours_both_ways_when_undirected() {
+        let graph = sample();
+        assert_eq!(
+            graph.neighbours(n(1)).collect::<Vec<_>>(),
+            vec![(n(0), Some(5)), (n(2), None)]
+        );
+        assert_eq!(graph.find_node(|node| *node == "c"), Some(n(2)));
+
+        let mut directed = Graph::directed();
+        let (a, b) = (directed.add_node(()), directed.add_node(()));
+        directed.add_edge(a, b);
+        assert_eq!(directed.neighbours(b).count(), 0);
+    }
+
+    #[test]
+    fn components_found_and_condensed() {
+        // a -> b -> c -> a -> d <-> e, with f on its own
+        let mut graph = Graph::directed();
+        let ids: Vec<NodeId> = "abcdef".chars().map(|c| graph.add_node(c)).collect();
+        [(0, 1), (1, 2), (2, 0), (0, 3), (3, 4), (4, 3)]
+            .into_iter()
+            .for_each(|(from, to)| graph.add_edge(ids[from], ids[to]));
+        let mut components = graph.strongly_connected_components();
+        components.iter_mut().for_each(|members| members.sort());
+        assert_eq!(components.len(), 3);
+        assert!(components.contains(&vec![n(0), n(1), n(2)]));
+        assert!(components.contains(&vec![n(3), n(4)]));
+        assert!(components.contains(&vec![n(5)]));
+        let position = |members: &[NodeId]| components.iter().position(|c| c == members);
+        assert!(position(&[n(3), n(4)]) < position(&[n(0), n(1), n(2)]));
+
+        let condensation = graph.condensation();
+        assert_eq!(condensation.graph.node_count(), 3);
+        let (abc, de) = (
+            condensation.component_of[n(0)],
+            condensation.component_of[n(3)],
+        );
+        assert_eq!(condensation.component_of[n(1)], abc);
+        assert!(abc < de);
+        assert_eq!(
+            condensation.graph.edges(),
+            &[Edge {
+                from: abc,
+                to: de,
+                weight: None
+            }]
+        );
+
+        // a long chain, which would overflow the stack if visited recursively
+        let mut chain = Graph::directed();
+        (0..100_000).for_each(|id| {
+            chain.add_node(());
+            if id > 0 {
+                chain.add_edge(n(id - 1), n(id));
+            }
+        });
+        chain.add_edge(n(99_999), n(0));
+        assert_eq!(chain.strongly_connected_components().len(), 1);
+    }
+
+    #[test]
+    fn all_pairs_shortest_paths() {
+        let mut graph = Graph::directed();
+        (0..5).for_each(|_| {
+            graph.add_node(());
+        });
+        graph.add_weighted_edge(n(0), n(1), 4);
+        graph.add_weighted_edge(n(0), n(2), 1);
+        graph.add_weighted_edge(n(2), n(1), 2);
+        graph.add_edge(n(1), n(3));
+        graph.add_weighted_edge(n(3), n(0), 7);
+        let distances = graph.floyd_warshall();
+        assert_eq!(distances[n(0)][n(1)], Some(3));
+        assert_eq!(distances[n(0)][n(3)], Some(4));
+        assert_eq!(distances[n(3)][n(2)], Some(8));
+        assert_eq!(distances[n(1)][n(2)], Some(9));
+        assert_eq!(distances[n(0)][n(4)], None);
+        assert_eq!(distances[n(4)][n(4)], Some(0));
+        assert_eq!(graph.johnson(), distances);
+
+        let mut undirected = Graph::undirected();
+        (0..12).for_each(|_| {
+            undirected.add_node(());
+        });
+        (0..12).for_each(|id| {
+            undirected.add_weighted_edge(n(id), n((id * 5 + 3) % 12), id % 4 + 1);
+            undirected.add_weighted_edge(n(id), n((id + 1) % 12), 6);
+        });
+        let distances = undirected.floyd_warshall();
+        assert_eq!(undirected.johnson(), distances);
+        assert!((0..12)
+            .all(|from| (0..12).all(|to| distances[n(from)][n(to)] == distances[n(to)][n(from)])));
+    }
+
+    #[test]
+    fn simple_paths_enumerated() {
+        // 0 - 1 - 3 with a longer way round through 2, and 4 hanging off 3
+        let mut graph = Graph::undirected();
+        (0..5).for_each(|_| {
+            graph.add_node(());
+        });
+        graph.add_weighted_edge(n(0), n(1), 1);
+        graph.add_weighted_edge(n(1), n(3), 1);
+        graph.add_weighted_edge(n(0), n(2), 2);
+        graph.add_weighted_edge(n(2), n(1), 2);
+        graph.add_weighted_edge(n(2), n(3), 5);
+        graph.add_edge(n(3), n(4));
+        let mut paths: Vec<(Vec<NodeId>, usize)> = graph
+            .simple_paths(n(0), n(4), None)
+            .map(|path| (path.nodes, path.length))
+            .collect();
+        paths.sort_by_key(|(_, length)| *length);
+        assert_eq!(
+            paths,
+            vec![
+                (vec![n(0), n(1), n(3), n(4)], 3),
+                (vec![n(0), n(2), n(1), n(3), n(4)], 6),
+                (vec![n(0), n(2), n(3), n(4)], 8),
+                (vec![n(0), n(1), n(2), n(3), n(4)], 9),
+            ]
+        );
+        assert_eq!(graph.simple_paths(n(0), n(4), Some(6)).count(), 2);
+        assert_eq!(
+            graph.simple_paths(n(2), n(2), None).collect::<Vec<_>>(),
+            vec![SimplePath {
+                nodes: vec![n(2)],
+                length: 0
+            }]
+        );
+        assert_eq!(graph.simple_paths(n(4), n(0), Some(2)).count(), 0);
+    }
+
+    #[test]
+    fn longest_simple_path_found() {
+        let graph = {
+            let mut graph = Graph::undirected();
+            (0..5).for_each(|_| {
+                graph.add_node(());
+            });
+            graph.add_weighted_edge(n(0), n(1), 1);
+            graph.add_weighted_edge(n(1), n(3), 1);
+            graph.add_weighted_edge(n(0), n(2), 2);
+            graph.add_weighted_edge(n(2), n(1), 2);
+            graph.add_weighted_edge(n(2), n(3), 5);
+            graph.add_edge(n(3), n(4));
+            graph
+        };
+        assert_eq!(graph.longest_simple_path_length(n(0), n(4)), Some(9));
+        assert_eq!(graph.longest_simple_path_length(n(2), n(2)), Some(0));
+
+        // too many nodes for a small bit set, a line of 130 with a detour around the middle
+        let mut line = Graph::directed();
+        (0..130).for_each(|_| {
+            line.add_node(());
+        });
+        (0..129).for_each(|i| line.add_edge(n(i), n(i + 1)));
+        line.add_weighted_edge(n(30), n(31), 10);
+        assert_eq!(line.longest_simple_path_length(n(0), n(129)), Some(138));
+        assert_eq!(line.longest_simple_path_length(n(129), n(0)), None);
+    }
+
+    #[test]
+    fn corridors_contracted() {
+        use crate::tiles::Tile;
+
+        let maze = Cells::<Tile>::parse_lines([
+            "#.#####", //
+            "#...#.#", //
+            "#.#.>.#", //
+            "#...#.#", //
+            "#####.#",
+        ])
+        .unwrap();
+        let graph = contract_corridors(&maze, &[(1, 0), (5, 4)], |tile, heading| match tile {
+            Tile::Wall => false,
+            Tile::Slope(direction) => *direction == heading,
+            _ => true,
+        });
+        let coords: Vec<(usize, usize)> = graph.nodes().map(|(_, coord)| *coord).collect();
+        assert_eq!(coords, vec![(1, 0), (1, 1), (3, 2), (5, 2), (5, 4)]);
+        let mut edges: Vec<_> = graph
+            .edges()
+            .iter()
+            .map(|edge| (*graph.node(edge.from), *graph.node(edge.to), edge.weight))
+            .collect();
+        edges.sort();
+        // both ways round the loop, but only down the slope and not up the dead end
+        assert_eq!(
+            edges,
+            vec![
+                ((1, 0), (1, 1), Some(1)),
+                ((1, 1), (1, 0), Some(1)),
+                ((1, 1), (3, 2), Some(3)),
+                ((1, 1), (3, 2), Some(5)),
+                ((3, 2), (1, 1), Some(3)),
+                ((3, 2), (1, 1), Some(5)),
+                ((3, 2), (5, 2), Some(2)),
+                ((5, 2), (5, 4), Some(2)),
+                ((5, 4), (5, 2), Some(2)),
+            ]
+        );
+        assert_eq!(graph.longest_simple_path_length(n(0), n(4)), Some(10));
+        assert_eq!(graph.longest_simple_path_length(n(4), n(0)), None);
+    }
+
+    #[test]
+    fn nodes_looked_up_by_id_and_coord() {
+        let mut graph = Graph::undirected();
+        let corner = graph.add_node((0, 0));
+        let middle = graph.add_node((2, 1));
+        graph.add_edge(corner, middle);
+        let mut degrees = graph.node_map(0);
+        graph.edges().iter().for_each(|edge| {
+            [edge.from, edge.to]
+                .into_iter()
+                .for_each(|id| degrees[id] += 1)
+        });
+        assert_eq!(degrees.values().copied().collect::<Vec<_>>(), vec![1, 1]);
+        assert_eq!(degrees.get(n(2)), None);
+
+        let ids = CoordIds::of_graph(&graph, (3, 2));
+        assert_eq!(ids.get((2, 1)), Some(middle));
+        assert_eq!(ids.get((1, 1)), None);
+        assert_eq!(ids.get((3, 0)), None);
+        assert_eq!(middle.to_string(), "1");
+    }
+
+    #[test]
+    fn min_cuts_found() {
+        // two squares with their diagonals, joined by a pair of edges
+        let mut names = NodeNames::new();
+        let edges: Vec<(NodeId, NodeId)> = [
+            "a-b", "b-c", "c-d", "d-a", "a-c", "b-d", "e-f", "f-g", "g-h", "h-e", "e-g", "f-h",
+        ]
+        .iter()
+        .map(|edge| {
+            let (from, to) = edge.split_once('-').unwrap();
+            (names.intern(&from), names.intern(&to))
+        })
+        .collect();
+        assert_eq!(names.intern(&"c"), n(2));
+        assert_eq!(names.id(&"h"), Some(n(7)));
+        assert_eq!(names.id(&"z"), None);
+        let mut graph = names.graph(false);
+        edges
+            .into_iter()
+            .for_each(|(from, to)| graph.add_edge(from, to));
+        graph.add_edge(n(1), n(4));
+        graph.add_edge(n(2), n(6));
+
+        let cut = graph.stoer_wagner_min_cut().unwrap();
+        assert_eq!(cut.weight, 2);
+        let mut sides = [
+            cut.side.clone(),
+            (0..8).map(n).filter(|id| !cut.side.contains(id)).collect(),
+        ];
+        sides.sort();
+        assert_eq!(
+            sides,
+            [vec![n(0), n(1), n(2), n(3)], vec![n(4), n(5), n(6), n(7)]]
+        );
+        assert_eq!(cut.edges.len(), 2);
+
+        let mut rng = StdRng::seed_from_u64(2023);
+        let karger = graph.karger_min_cut(&mut rng, 50).unwrap();
+        assert_eq!(karger.weight, 2);
+        assert_eq!(karger.side.len(), 4);
+
+        // weights count, a heavy bridge leaving a or f, with 3 edges each, as the lightest cut off
+        graph.add_weighted_edge(n(3), n(7), 5);
+        let cut = graph.stoer_wagner_min_cut().unwrap();
+        assert_eq!(cut.weight, 3);
+        assert!([1, 7].contains(&cut.side.len()));
+
+        let mut apart = Graph::undirected();
+        (0..3).for_each(|_| {
+            apart.add_node(());
+        });
+        apart.add_edge(n(0), n(1));
+        assert_eq!(apart.stoer_wagner_min_cut().unwrap().weight, 0);
+        assert_eq!(Graph::<()>::undirected().stoer_wagner_min_cut(), None);
+    }
+
+    #[test]
+    fn exported_as_dot_and_json() {
+        let graph = sample();
+        let highlight = Highlight::default()
+            .with_path(&[n(0), n(1)])
+            .with_edges([(n(1), n(2))]);
+        let labeler = |_, node: &&str| node.to_string();
+        assert_eq!(
+            graph.to_dot(labeler, &highlight),
+            "graph {\n  0 [label=\"a\", color=red, penwidth=3];\n  1 [label=\"b\\\"\", color=red, penwidth=3];\n  \
+             2 [label=\"c\"];\n  0 -- 1 [label=\"5\", color=red, penwidth=3];\n  2 -- 1 [color=red, penwidth=3];\n}\n"
+        );
+        assert_eq!(
+            graph.to_json(labeler, &Highlight::default()),
+            "{\"directed\":false,\"nodes\":[{\"id\":0,\"label\":\"a\",\"highlighted\":false},\
+             {\"id\":1,\"label\":\"b\\\"\",\"highlighted\":false},{\"id\":2,\"label\":\"c\",\"highlighted\":false}],\
+             \"edges\":[{\"from\":0,\"to\":1,\"weight\":5,\"highlighted\":false},\
+             {\"from\":2,\"to\":1,\"weight\":null,\"highlighted\":false}]}\n"
+        );
+    }
+}
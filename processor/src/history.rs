@@ -0,0 +1,155 @@
+//! States recorded every so many steps of a simulation, so that what e.g. the grid looked like part way through
+//! can be printed afterwards without rerunning with printlns.  Asked for with the `inspect.step` parameter
+//! (`--inspect step=3000` on the command line), see [`Inspector`].
+
+use std::{collections::BTreeMap, fmt::Display};
+
+use crate::{AError, PuzzleConfig};
+
+/// The step to print the recorded state closest to
+pub const INSPECT_STEP: &str = "inspect.step";
+/// How often to record the state, 1 for every step
+pub const INSPECT_EVERY: &str = "inspect.every";
+
+/// Every `every`th state of a simulation, by step
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct History<S> {
+    every: usize,
+    states: BTreeMap<usize, S>,
+}
+
+impl<S> History<S> {
+    pub fn every(every: usize) -> Self {
+        History {
+            every: every.max(1),
+            states: BTreeMap::default(),
+        }
+    }
+
+    /// Record the state at the step if it is one being kept, only making it if so
+    pub fn record_with(&mut self, step: usize, state: impl FnOnce() -> S) {
+        if step.is_multiple_of(self.every) {
+            self.states.insert(step, state());
+        }
+    }
+
+    /// The recorded state with the step nearest to the one asked for, the earlier one on a tie
+    pub fn closest(&self, step: usize) -> Option<(usize, &S)> {
+        let before = self.states.range(..=step).next_back();
+        let after = self.states.range(step..).next();
+        match (before, after) {
+            (Some(before), Some(after)) if after.0 - step < step - before.0 => Some(after),
+            (Some(before), _) => Some(before),
+            (None, after) => after,
+        }
+        .map(|(recorded, state)| (*recorded, state))
+    }
+
+    pub fn len(&self) -> usize {
+        self.states.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.states.is_empty()
+    }
+}
+
+/// Records a simulation's states when [`INSPECT_STEP`] is configured, doing nothing otherwise
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Inspector<S> {
+    inspecting: Option<(usize, History<S>)>,
+}
+
+impl<S> Inspector<S> {
+    pub fn off() -> Self {
+        Inspector { inspecting: None }
+    }
+
+    pub fn inspecting(step: usize, every: usize) -> Self {
+        Inspector {
+            inspecting: Some((step, History::every(every))),
+        }
+    }
+
+    /// Inspecting if [`INSPECT_STEP`] is set to a step, blank leaves it off
+    pub fn from_config(config: &PuzzleConfig) -> Result<Self, AError> {
+        let step: String = config.get_or(INSPECT_STEP, String::default())?;
+        if step.trim().is_empty() {
+            return Ok(Inspector::off());
+        }
+        let step = step
+            .trim()
+            .parse()
+            .map_err(|_| AError::msg(format!("Invalid {INSPECT_STEP}: '{step}'")))?;
+        Ok(Inspector::inspecting(
+            step,
+            config.get_or(INSPECT_EVERY, 1)?,
+        ))
+    }
+
+    pub fn is_on(&self) -> bool {
+        self.inspecting.is_some()
+    }
+
+    /// See [`History::record_with`]
+    pub fn record_with(&mut self, step: usize, state: impl FnOnce() -> S) {
+        if let Some((_, history)) = self.inspecting.as_mut() {
+            history.record_with(step, state);
+        }
+    }
+}
+
+impl<S: Display> Inspector<S> {
+    /// The recorded state closest to the step inspected, None when not inspecting
+    pub fn describe(&self) -> Option<String> {
+        let (step, history) = self.inspecting.as_ref()?;
+        Some(match history.closest(*step) {
+            Some((recorded, state)) => format!(
+                "Step {recorded} (closest of {} recorded to step {step}):\n{state}",
+                history.len()
+            ),
+            None => format!("No states recorded to inspect step {step}"),
+        })
+    }
+
+    /// Print [`Inspector::describe`], if inspecting
+    pub fn report(&self) {
+        if let Some(description) = self.describe() {
+            println!("{description}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn closest_recorded_state_inspected() {
+        let mut history = History::every(10);
+        (0..=35).for_each(|step| history.record_with(step, || step * 2));
+        assert_eq!(history.len(), 4);
+        assert_eq!(history.closest(14), Some((10, &20)));
+        assert_eq!(history.closest(15), Some((10, &20)));
+        assert_eq!(history.closest(16), Some((20, &40)));
+        assert_eq!(history.closest(3000), Some((30, &60)));
+        assert_eq!(History::<usize>::every(0).closest(1), None);
+
+        let config = PuzzleConfig::new("input.txt")
+            .apply_args(["--inspect", "step=7", "every=3"])
+            .unwrap();
+        let mut inspector = Inspector::from_config(&config).unwrap();
+        (0..=20).for_each(|step| inspector.record_with(step, || format!("state {step}")));
+        assert_eq!(
+            inspector.describe().unwrap(),
+            "Step 6 (closest of 7 recorded to step 7):\nstate 6"
+        );
+
+        let mut off =
+            Inspector::<String>::from_config(&config.clone().with(INSPECT_STEP, "")).unwrap();
+        off.record_with(0, || panic!("not inspecting"));
+        assert!(!off.is_on());
+        assert_eq!(off.describe(), None::<String>);
+        assert!(Inspector::<String>::from_config(&config.with(INSPECT_STEP, "x")).is_err());
+    }
+}
@@ -0,0 +1,103 @@
+//! A list of at most `N` items held inline rather than on the heap, for the short lists made over and over in
+//! hot loops (the neighbours of a cell, the beams out of a splitter) where allocating a Vec each time would
+//! dominate.
+
+use std::iter::Flatten;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InlineVec<T, const N: usize> {
+    /// The items in order, followed by Nones
+    items: [Option<T>; N],
+    len: usize,
+}
+
+impl<T, const N: usize> InlineVec<T, N> {
+    pub fn new() -> Self {
+        InlineVec {
+            items: std::array::from_fn(|_| None),
+            len: 0,
+        }
+    }
+
+    /// Panics if there are already `N` items
+    pub fn push(&mut self, item: T) {
+        assert!(self.len < N, "No room for more than {N} items");
+        self.items[self.len] = Some(item);
+        self.len += 1;
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.items.get(index).and_then(Option::as_ref)
+    }
+
+    pub fn iter(&self) -> Flatten<std::slice::Iter<'_, Option<T>>> {
+        self.items.iter().flatten()
+    }
+}
+
+impl<T, const N: usize> Default for InlineVec<T, N> {
+    fn default() -> Self {
+        InlineVec::new()
+    }
+}
+
+/// Panics if there are more than `N` items
+impl<T, const N: usize> FromIterator<T> for InlineVec<T, N> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut inline = InlineVec::new();
+        iter.into_iter().for_each(|item| inline.push(item));
+        inline
+    }
+}
+
+impl<T, const N: usize> IntoIterator for InlineVec<T, N> {
+    type Item = T;
+    type IntoIter = Flatten<std::array::IntoIter<Option<T>, N>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.into_iter().flatten()
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a InlineVec<T, N> {
+    type Item = &'a T;
+    type IntoIter = Flatten<std::slice::Iter<'a, Option<T>>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn items_held_inline() {
+        let mut inline: InlineVec<&str, 3> = ["a", "b"].into_iter().collect();
+        assert_eq!(inline.len(), 2);
+        assert_eq!(inline.get(1), Some(&"b"));
+        assert_eq!(inline.get(2), None);
+        inline.push("c");
+        assert_eq!(
+            inline.iter().copied().collect::<Vec<_>>(),
+            vec!["a", "b", "c"]
+        );
+        assert_eq!(inline.into_iter().rev().collect::<String>(), "cba");
+        assert!(InlineVec::<u8, 0>::new().is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "No room for more than 1 items")]
+    fn overfilling_panics() {
+        let _: InlineVec<u8, 1> = [1, 2].into_iter().collect();
+    }
+}
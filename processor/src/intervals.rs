@@ -0,0 +1,287 @@
+//! Half-open integer intervals and piecewise offset mappings over them, for puzzles that push whole ranges of
+//! numbers through a series of maps (e.g. day5's seeds through soil, fertilizer and so on) rather than one
+//! number at a time.
+
+use std::fmt::Display;
+
+use crate::AError;
+
+/// The integers from `start` up to but not including `end`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Interval {
+    pub start: i64,
+    pub end: i64,
+}
+
+impl Interval {
+    pub fn new(start: i64, end: i64) -> Self {
+        Interval { start, end }
+    }
+
+    pub fn from_start_length(start: i64, length: i64) -> Self {
+        Interval::new(start, start + length)
+    }
+
+    pub fn len(&self) -> i64 {
+        (self.end - self.start).max(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.end <= self.start
+    }
+
+    pub fn contains(&self, value: i64) -> bool {
+        self.start <= value && value < self.end
+    }
+
+    /// The values in both, `None` if there aren't any
+    pub fn intersection(&self, other: &Interval) -> Option<Interval> {
+        let overlap = Interval::new(self.start.max(other.start), self.end.min(other.end));
+        (!overlap.is_empty()).then_some(overlap)
+    }
+
+    /// The values in this but not the other: none, or the parts before and/or after the other
+    pub fn difference(&self, other: &Interval) -> Vec<Interval> {
+        if self.intersection(other).is_none() {
+            return if self.is_empty() { vec![] } else { vec![*self] };
+        }
+        [
+            Interval::new(self.start, other.start),
+            Interval::new(other.end, self.end),
+        ]
+        .into_iter()
+        .filter(|part| !part.is_empty())
+        .collect()
+    }
+
+    /// The values in either, as one interval if they overlap or touch, otherwise as both in order
+    pub fn union(&self, other: &Interval) -> Vec<Interval> {
+        merge([*self, *other])
+    }
+
+    /// Every value moved by the offset
+    pub fn offset(&self, offset: i64) -> Interval {
+        Interval::new(self.start + offset, self.end + offset)
+    }
+}
+
+impl Display for Interval {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}, {})", self.start, self.end)
+    }
+}
+
+/// The non-empty intervals, in order with those that overlap or touch merged together
+pub fn merge(intervals: impl IntoIterator<Item = Interval>) -> Vec<Interval> {
+    let mut intervals: Vec<Interval> = intervals
+        .into_iter()
+        .filter(|interval| !interval.is_empty())
+        .collect();
+    intervals.sort();
+    let mut merged: Vec<Interval> = Vec::with_capacity(intervals.len());
+    for interval in intervals {
+        match merged.last_mut() {
+            Some(last) if interval.start <= last.end => last.end = last.end.max(interval.end),
+            _ => merged.push(interval),
+        }
+    }
+    merged
+}
+
+/// Moves values in each of its (non-overlapping) source intervals by that interval's offset, leaving values
+/// outside all of them where they are
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RangeMap {
+    /// Sorted by source
+    pieces: Vec<(Interval, i64)>,
+}
+
+impl RangeMap {
+    pub fn new() -> Self {
+        RangeMap::default()
+    }
+
+    /// Map the source interval to the one of the same length starting at the destination
+    pub fn insert(&mut self, source: Interval, destination_start: i64) -> Result<(), AError> {
+        if let Some((existing, _)) = self
+            .pieces
+            .iter()
+            .find(|(existing, _)| existing.intersection(&source).is_some())
+        {
+            return Err(AError::msg(format!(
+                "Mapping {source} overlaps already mapped {existing}"
+            )));
+        }
+        let index = self
+            .pieces
+            .partition_point(|(existing, _)| existing.start < source.start);
+        self.pieces
+            .insert(index, (source, destination_start - source.start));
+        Ok(())
+    }
+
+    pub fn map(&self, value: i64) -> i64 {
+        self.pieces
+            .iter()
+            .find(|(source, _)| source.contains(value))
+            .map_or(value, |(_, offset)| value + offset)
+    }
+
+    /// Where the interval's values go, split up at the edges of the source intervals and in the order of the
+    /// values they came from
+    pub fn map_interval(&self, interval: Interval) -> Vec<Interval> {
+        let mut mapped = vec![];
+        let mut remaining = interval;
+        for (source, offset) in &self.pieces {
+            if remaining.is_empty() || source.start >= remaining.end {
+                break;
+            }
+            let Some(overlap) = remaining.intersection(source) else {
+                continue;
+            };
+            if remaining.start < overlap.start {
+                mapped.push(Interval::new(remaining.start, overlap.start));
+            }
+            mapped.push(overlap.offset(*offset));
+            remaining = Interval::new(overlap.end, remaining.end);
+        }
+        if !remaining.is_empty() {
+            mapped.push(remaining);
+        }
+        mapped
+    }
+
+    pub fn map_intervals(&self, intervals: impl IntoIterator<Item = Interval>) -> Vec<Interval> {
+        intervals
+            .into_iter()
+            .flat_map(|interval| self.map_interval(interval))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn span(start: i64, length: i64) -> Interval {
+        Interval::from_start_length(start, length)
+    }
+
+    fn range_map(pieces: &[(i64, i64, i64)]) -> RangeMap {
+        let mut map = RangeMap::new();
+        for (source_start, destination_start, length) in pieces {
+            map.insert(span(*source_start, *length), *destination_start)
+                .unwrap();
+        }
+        map
+    }
+
+    #[test]
+    fn interval_arithmetic() {
+        let a = Interval::new(2, 8);
+        assert_eq!(a.len(), 6);
+        assert!(a.contains(2) && a.contains(7) && !a.contains(8));
+        assert_eq!(
+            a.intersection(&Interval::new(5, 10)),
+            Some(Interval::new(5, 8))
+        );
+        assert_eq!(a.intersection(&Interval::new(8, 10)), None);
+        assert_eq!(
+            a.difference(&Interval::new(4, 5)),
+            vec![Interval::new(2, 4), Interval::new(5, 8)]
+        );
+        assert_eq!(
+            a.difference(&Interval::new(0, 5)),
+            vec![Interval::new(5, 8)]
+        );
+        assert_eq!(a.difference(&Interval::new(0, 10)), vec![]);
+        assert_eq!(a.difference(&Interval::new(9, 10)), vec![a]);
+        assert_eq!(a.union(&Interval::new(8, 10)), vec![Interval::new(2, 10)]);
+        assert_eq!(
+            a.union(&Interval::new(9, 10)),
+            vec![a, Interval::new(9, 10)]
+        );
+        assert_eq!(a.offset(-3), Interval::new(-1, 5));
+        assert_eq!(
+            merge([
+                Interval::new(7, 9),
+                Interval::new(1, 3),
+                Interval::new(4, 4),
+                Interval::new(2, 5)
+            ]),
+            vec![Interval::new(1, 5), Interval::new(7, 9)]
+        );
+    }
+
+    #[test]
+    fn values_mapped() {
+        let map = range_map(&[(98, 50, 2), (50, 52, 48)]);
+        assert_eq!(map.map(79), 81);
+        assert_eq!(map.map(99), 51);
+        assert_eq!(map.map(100), 100);
+        assert_eq!(map.map(13), 13);
+
+        let mut map = map;
+        assert_eq!(
+            map.insert(span(40, 20), 0).unwrap_err().to_string(),
+            "Mapping [40, 60) overlaps already mapped [50, 98)"
+        );
+    }
+
+    #[test]
+    fn range_before_any_index_map() {
+        let map = range_map(&[(10, 20, 5)]);
+        assert_eq!(map.map_interval(span(3, 6)), vec![span(3, 6)]);
+    }
+
+    #[test]
+    fn range_just_before_any_index_map() {
+        let map = range_map(&[(10, 20, 5)]);
+        assert_eq!(map.map_interval(span(3, 7)), vec![span(3, 7)]);
+    }
+
+    #[test]
+    fn range_overlapping_start_of_first_index_map() {
+        let map = range_map(&[(10, 20, 5)]);
+        assert_eq!(map.map_interval(span(8, 6)), vec![span(8, 2), span(20, 4)]);
+    }
+
+    #[test]
+    fn range_overlapping_first_index_map() {
+        let map = range_map(&[(10, 20, 2)]);
+        assert_eq!(
+            map.map_interval(span(8, 6)),
+            vec![span(8, 2), span(20, 2), span(12, 2)]
+        );
+    }
+
+    #[test]
+    fn range_overlapping_first_and_second_map() {
+        let map = range_map(&[(10, 20, 2), (14, 24, 2)]);
+        assert_eq!(
+            map.map_interval(span(8, 10)),
+            vec![
+                span(8, 2),
+                span(20, 2),
+                span(12, 2),
+                span(24, 2),
+                span(16, 2)
+            ]
+        );
+    }
+
+    #[test]
+    fn range_overlapping_first_and_second_map_maps_next_to_each_other() {
+        let map = range_map(&[(10, 20, 2), (12, 30, 2)]);
+        assert_eq!(
+            map.map_interval(span(8, 8)),
+            vec![span(8, 2), span(20, 2), span(30, 2), span(14, 2)]
+        );
+    }
+
+    #[test]
+    fn range_after_the_maps() {
+        let map = range_map(&[(10, 20, 2), (12, 30, 2)]);
+        assert_eq!(map.map_interval(span(14, 2)), vec![span(14, 2)]);
+    }
+}
@@ -1,52 +1,324 @@
 use std::{
-    collections::HashSet,
+    collections::{hash_map::DefaultHasher, HashSet},
     error::Error,
     fmt::Display,
     fs::File,
+    hash::{Hash, Hasher},
     io::{BufRead, BufReader},
+    ops::{Index, IndexMut},
     str::{Chars, FromStr},
 };
 
 use anyhow::Context;
-use num::ToPrimitive;
+use num::{Num, ToPrimitive};
 use once_cell::sync::Lazy;
+#[cfg(feature = "unicode")]
+use unicode_segmentation::UnicodeSegmentation;
+
+pub mod answer;
+pub mod binary;
+pub mod branching;
+#[cfg(feature = "regex")]
+pub mod captures;
+pub mod cell_char;
+pub mod collections;
+pub mod compact;
+pub mod config;
+pub mod coord3;
+pub mod cursor;
+pub mod cycles;
+pub mod day;
+pub mod diagnostics;
+pub mod dijkstra;
+pub mod dir_set;
+pub mod direction;
+pub mod fixed_cells;
+pub mod frontier;
+#[cfg(feature = "fuzzing")]
+pub mod fuzz;
+pub mod geometry;
+pub mod graph;
+pub mod history;
+pub mod inline_vec;
+pub mod intervals;
+pub mod local_search;
+pub mod memo;
+pub mod meta_cells;
+pub mod parallel;
+pub mod parse;
+pub mod pipeline;
+pub mod polygon;
+pub mod polyline;
+pub mod polynomial;
+pub mod profile;
+pub mod reachability;
+pub mod rings;
+pub mod small_bit_set;
+pub mod solver;
+pub mod spatial_hash;
+pub mod state_key;
+pub mod stats;
+pub mod testing;
+pub mod tiles;
+pub mod timings;
+pub mod union_find;
+
+pub use answer::Answer;
+pub use cell_char::CellChar;
+pub use config::{input_file, PuzzleConfig};
+pub use coord3::Coord3;
+pub use cursor::Cursor;
+pub use day::{run_day, Day};
+pub use diagnostics::diagnostic;
+pub use dir_set::DirSet;
+pub use direction::{Direction, Direction8};
+pub use fixed_cells::FixedCells;
+pub use inline_vec::InlineVec;
+pub use meta_cells::MetaCells;
+pub use pipeline::Pipeline;
+pub use polyline::Polyline;
+pub use profile::start_profiling;
+pub use small_bit_set::SmallBitSet;
+pub use solver::{describe_if_requested, part_requested, PuzzleMetadata, Solver};
+pub use state_key::{StateKey, StateMap, StateSet};
+use timings::PhaseTimer;
 
 type AError = anyhow::Error;
 type Delimiter = char;
 
 pub static BLANK_DELIMITERS: Lazy<HashSet<Delimiter>> = Lazy::new(HashSet::default);
 
+/// Name to give [`process`] for it to read stdin rather than a file
+pub const STDIN_FILE_NAME: &str = "-";
+
+/// Parse the file a line at a time in to a state, then finalise it, process it and calculate the result from
+/// that.  Each step can be a closure, e.g. capturing the puzzle's parameters so they needn't go in the state.
+/// [`Pipeline`] builds the same up a step at a time for both parts.
 pub fn process<LoadState, State, ProcessedState, FinalResult>(
     file_name: &str,
     initial_state: LoadState,
-    parse_line: fn(LoadState, String) -> Result<LoadState, AError>,
-    finalise_state: fn(LoadState) -> Result<State, AError>,
-    perform_processing: fn(State) -> Result<ProcessedState, AError>,
-    calc_result: fn(ProcessedState) -> Result<FinalResult, AError>,
+    parse_line: impl FnMut(LoadState, String) -> Result<LoadState, AError>,
+    finalise_state: impl FnOnce(LoadState) -> Result<State, AError>,
+    perform_processing: impl FnOnce(State) -> Result<ProcessedState, AError>,
+    calc_result: impl FnOnce(ProcessedState) -> Result<FinalResult, AError>,
+) -> Result<FinalResult, AError> {
+    if file_name == STDIN_FILE_NAME {
+        let stdin = std::io::stdin();
+        return process_reader(
+            stdin.lock(),
+            initial_state,
+            parse_line,
+            finalise_state,
+            perform_processing,
+            calc_result,
+        );
+    }
+    let file = File::open(file_name)?;
+    process_reader(
+        BufReader::new(file),
+        initial_state,
+        parse_line,
+        finalise_state,
+        perform_processing,
+        calc_result,
+    )
+}
+
+/// [`process`] reading the lines from anywhere, e.g. a string in a test (`input.as_bytes()`)
+pub fn process_reader<LoadState, State, ProcessedState, FinalResult>(
+    reader: impl BufRead,
+    initial_state: LoadState,
+    mut parse_line: impl FnMut(LoadState, String) -> Result<LoadState, AError>,
+    finalise_state: impl FnOnce(LoadState) -> Result<State, AError>,
+    perform_processing: impl FnOnce(State) -> Result<ProcessedState, AError>,
+    calc_result: impl FnOnce(ProcessedState) -> Result<FinalResult, AError>,
+) -> Result<FinalResult, AError> {
+    let mut timer = PhaseTimer::start();
+    let loaded_state = reader
+        .lines()
+        .try_fold(initial_state, |state, line| parse_line(state, line?))?;
+    timer.lap("parse");
+    let finalised_state = finalise_state(loaded_state)?;
+    timer.lap("finalise");
+    let processed_state = perform_processing(finalised_state)?;
+    timer.lap("process");
+    let result = calc_result(processed_state)?;
+    timer.lap("result");
+    diagnostics::report(timer.report());
+    Ok(result)
+}
+
+/// A line being parsed along with where it came from, see [`process_with_context`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineContext {
+    /// 0 based index of the line in the file, blank lines included
+    pub index: usize,
+    pub raw: String,
+}
+
+impl LineContext {
+    /// 1 based, as shown by editors
+    pub fn line_number(&self) -> usize {
+        self.index + 1
+    }
+}
+
+/// [`process`] for parsers that want to know which line they're on, e.g. to give each item read an id, rather
+/// than working it out from the state.  Any error parsing a line is prefixed with its line number, as with
+/// [`LineError`].
+pub fn process_with_context<LoadState, State, ProcessedState, FinalResult>(
+    file_name: &str,
+    initial_state: LoadState,
+    mut parse_line: impl FnMut(LoadState, LineContext) -> Result<LoadState, AError>,
+    finalise_state: impl FnOnce(LoadState) -> Result<State, AError>,
+    perform_processing: impl FnOnce(State) -> Result<ProcessedState, AError>,
+    calc_result: impl FnOnce(ProcessedState) -> Result<FinalResult, AError>,
 ) -> Result<FinalResult, AError> {
+    let mut timer = PhaseTimer::start();
     let loaded_state = {
         let file = File::open(file_name)?;
-        BufReader::new(file)
-            .lines()
-            .map(|l| l.unwrap())
-            .try_fold(initial_state, parse_line)?
+        BufReader::new(file).lines().enumerate().try_fold(
+            initial_state,
+            |state, (index, raw)| {
+                let context = LineContext { index, raw: raw? };
+                let line_number = context.line_number();
+                parse_line(state, context)
+                    .map_err(|error| AError::msg(format!("line {line_number}: {error}")))
+            },
+        )?
     };
+    timer.lap("parse");
+    let finalised_state = finalise_state(loaded_state)?;
+    timer.lap("finalise");
+    let processed_state = perform_processing(finalised_state)?;
+    timer.lap("process");
+    let result = calc_result(processed_state)?;
+    timer.lap("result");
+    diagnostics::report(timer.report());
+    Ok(result)
+}
+
+/// A line that failed to load, see [`process_recovering`]
+#[derive(Debug)]
+pub struct LineError {
+    /// 1 based, as shown by editors
+    pub line_number: usize,
+    pub line: Option<String>,
+    pub error: AError,
+}
+
+impl Display for LineError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.line {
+            Some(line) => write!(f, "line {}: {} ('{}')", self.line_number, self.error, line),
+            None => write!(f, "line {}: {}", self.line_number, self.error),
+        }
+    }
+}
+
+/// A load state that can be put back as it was before a line that failed to load, see [`load_recovering`]
+pub trait Rollback {
+    type Checkpoint;
+
+    fn checkpoint(&self) -> Self::Checkpoint;
+    fn rollback(&mut self, checkpoint: Self::Checkpoint);
+}
+
+/// Rolls back whatever was pushed since the checkpoint, so lines may only push to it
+impl<T> Rollback for Vec<T> {
+    type Checkpoint = usize;
+
+    fn checkpoint(&self) -> usize {
+        self.len()
+    }
+
+    fn rollback(&mut self, checkpoint: usize) {
+        self.truncate(checkpoint);
+    }
+}
+
+/// Load the file as with [`process`], but carry on past any lines that can't be read or fail to parse,
+/// collecting the errors instead.  The state is checkpointed before each line and rolled back if it fails, so
+/// that a failed line leaves it as it was.
+pub fn load_recovering<LoadState: Rollback>(
+    file_name: &str,
+    initial_state: LoadState,
+    mut parse_line: impl FnMut(&mut LoadState, String) -> Result<(), AError>,
+) -> Result<(LoadState, Vec<LineError>), AError> {
+    let file = File::open(file_name)?;
+    let mut errors = Vec::default();
+    let mut state = initial_state;
+    for (index, line) in BufReader::new(file).lines().enumerate() {
+        let line_number = index + 1;
+        let line = match line {
+            Ok(line) => line,
+            Err(e) => {
+                errors.push(LineError {
+                    line_number,
+                    line: None,
+                    error: e.into(),
+                });
+                continue;
+            }
+        };
+        let checkpoint = state.checkpoint();
+        if let Err(error) = parse_line(&mut state, line.clone()) {
+            state.rollback(checkpoint);
+            errors.push(LineError {
+                line_number,
+                line: Some(line),
+                error,
+            });
+        }
+    }
+    Ok((state, errors))
+}
+
+/// [`process`] in a mode that recovers from lines failing to load (see [`load_recovering`]).  The rest of the
+/// pipeline runs on whatever did load, with the line errors returned alongside the result.
+pub fn process_recovering<LoadState, State, ProcessedState, FinalResult>(
+    file_name: &str,
+    initial_state: LoadState,
+    parse_line: impl FnMut(&mut LoadState, String) -> Result<(), AError>,
+    finalise_state: impl FnOnce(LoadState) -> Result<State, AError>,
+    perform_processing: impl FnOnce(State) -> Result<ProcessedState, AError>,
+    calc_result: impl FnOnce(ProcessedState) -> Result<FinalResult, AError>,
+) -> Result<(FinalResult, Vec<LineError>), AError>
+where
+    LoadState: Rollback,
+{
+    let mut timer = PhaseTimer::start();
+    let (loaded_state, errors) = load_recovering(file_name, initial_state, parse_line)?;
+    timer.lap("parse");
     let finalised_state = finalise_state(loaded_state)?;
+    timer.lap("finalise");
     let processed_state = perform_processing(finalised_state)?;
-    calc_result(processed_state)
+    timer.lap("process");
+    let result = calc_result(processed_state)?;
+    timer.lap("result");
+    diagnostics::report(timer.report());
+    Ok((result, errors))
 }
 
 pub fn ok_identity<T>(t: T) -> Result<T, AError> {
     Ok(t)
 }
 
+#[cfg(not(feature = "unicode"))]
 pub fn reverse(s: &str) -> String {
-    //assume no graphemes - use unicode_segmentation if this is not the case
+    //assume no graphemes - enable the unicode feature if this is not the case
     s.chars().rev().collect()
 }
 
+/// Reverse keeping grapheme clusters (e.g. combining characters, emoji sequences) intact
+#[cfg(feature = "unicode")]
+pub fn reverse(s: &str) -> String {
+    s.graphemes(true).rev().collect()
+}
+
 /// Read a word for the current positions of chars, advancing to the next non-delimiter and reading to the end
 /// or the next delimiter
+#[cfg(not(feature = "unicode"))]
 pub fn read_word(
     chars: &mut Chars<'_>,
     delimiters: &HashSet<Delimiter>,
@@ -75,6 +347,78 @@ pub fn read_word(
     }
 }
 
+/// Read a word for the current positions of chars, advancing to the next non-delimiter and reading to the end
+/// or the next delimiter.  Works a grapheme cluster at a time, so a delimiter with combining characters
+/// attached is part of the word rather than a delimiter.
+#[cfg(feature = "unicode")]
+pub fn read_word(
+    chars: &mut Chars<'_>,
+    delimiters: &HashSet<Delimiter>,
+) -> Option<(String, Option<Delimiter>)> {
+    let remaining = chars.as_str();
+    let mut consumed = String::new();
+    let mut delimiter: Option<Delimiter> = None;
+    let mut used_bytes = 0;
+    for grapheme in remaining.graphemes(true) {
+        used_bytes += grapheme.len();
+        let mut grapheme_chars = grapheme.chars();
+        let single_delimiter = match (grapheme_chars.next(), grapheme_chars.next()) {
+            (Some(c), None) if delimiters.contains(&c) => Some(c),
+            _ => None,
+        };
+        if let Some(c) = single_delimiter {
+            if consumed.is_empty() {
+                continue;
+            } else {
+                delimiter = Some(c);
+                break;
+            }
+        }
+        consumed.push_str(grapheme);
+    }
+    *chars = remaining[used_bytes..].chars();
+    if consumed.is_empty() {
+        None
+    } else {
+        Some((consumed, delimiter))
+    }
+}
+
+/// Pairs of brackets whose contents [`read_token`] reads as a single group
+pub const BRACKETS: [(char, char); 3] = [('(', ')'), ('[', ']'), ('{', '}')];
+pub(crate) const QUOTE: char = '"';
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Token {
+    Word(String),
+    /// Contents of a bracketed group, without the outer brackets
+    Group {
+        open: char,
+        contents: String,
+    },
+    /// Contents of a double quoted string, without the quotes
+    Quoted(String),
+}
+
+/// Read the next token, skipping any leading delimiters.  This is a bracketed group (see [`BRACKETS`]), a double
+/// quoted string, or otherwise a word as with [`read_word`] that also ends before any opening bracket or quote.
+/// The delimiter returned is the one immediately following the token, if any.
+pub fn read_token(
+    chars: &mut Chars<'_>,
+    delimiters: &HashSet<Delimiter>,
+) -> Result<Option<(Token, Option<Delimiter>)>, AError> {
+    cursor::on_chars(chars, |cursor| cursor.read_token(delimiters))
+}
+
+/// Read the next token, which must be a group in the given kind of brackets, returning its contents
+pub fn read_group(
+    chars: &mut Chars<'_>,
+    delimiters: &HashSet<Delimiter>,
+    open: char,
+) -> Result<(String, Option<Delimiter>), AError> {
+    cursor::on_chars(chars, |cursor| cursor.read_group(delimiters, open))
+}
+
 /// Read the next word and parse it to a type implementing FromStr
 pub fn read_next<T>(
     chars: &mut Chars<'_>,
@@ -94,13 +438,35 @@ where
         })
 }
 
+/// Read the next word and parse it as an integer in the given radix, e.g. 16 for hex.  Works for any
+/// [`num::Num`], so the wide `u128`/`i128` and [`num::BigInt`] as well as the usual integers.
+pub fn read_radix<T>(
+    chars: &mut Chars<'_>,
+    delimiters: &HashSet<Delimiter>,
+    radix: u32,
+) -> Result<(T, Option<Delimiter>), AError>
+where
+    T: Num,
+    T::FromStrRadixErr: Error + Send + Sync + 'static,
+{
+    let (word, delimiter) = read_word(chars, delimiters).ok_or_else(|| {
+        AError::msg(format!(
+            "No word found to convert to integer in radix {radix}"
+        ))
+    })?;
+    T::from_str_radix(&word, radix)
+        .map(|t| (t, delimiter))
+        .with_context(|| format!("Failed parsing word: '{word}' in radix {radix}"))
+}
+
 /// Get coords adjacent to the given centre, including diagonals, excluding any coords that would be outside the side lengths.
 /// This will only return actual coordinates (i.e. if the centre is at an edge coords over the edge will not be returned).
-fn adjacent_coords(
+/// Held inline as these are made for every step of many searches.
+fn adjacent_coords<const N: usize>(
     centre: &(usize, usize),
     side_lengths: &(usize, usize),
-    deltas: &[(i8, i8)],
-) -> Vec<(usize, usize)> {
+    deltas: &[(i8, i8); N],
+) -> InlineVec<(usize, usize), N> {
     deltas
         .iter()
         .map(|(delta_x, delta_y)| {
@@ -115,57 +481,35 @@ fn adjacent_coords(
         .collect()
 }
 
-static ADJACENT_DELTAS_DIAGONAL: Lazy<Vec<(i8, i8)>> = Lazy::new(|| {
-    Vec::from([
-        (-1, -1),
-        (0, -1),
-        (1, -1), //line above
-        (-1, 0),
-        (1, 0), //this line
-        (-1, 1),
-        (0, 1),
-        (1, 1), //line below
-    ])
-});
+const ADJACENT_DELTAS_DIAGONAL: [(i8, i8); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1), //line above
+    (-1, 0),
+    (1, 0), //this line
+    (-1, 1),
+    (0, 1),
+    (1, 1), //line below
+];
 
 pub fn adjacent_coords_diagonal(
     centre: &(usize, usize),
     side_lengths: &(usize, usize),
-) -> Vec<(usize, usize)> {
+) -> InlineVec<(usize, usize), 8> {
     adjacent_coords(centre, side_lengths, &ADJACENT_DELTAS_DIAGONAL)
 }
 
-static ADJACENT_DELTAS_CARTESION: Lazy<Vec<(i8, i8)>> =
-    Lazy::new(|| Vec::from([(0, -1), (-1, 0), (1, 0), (0, 1)]));
+const ADJACENT_DELTAS_CARTESION: [(i8, i8); 4] = [(0, -1), (-1, 0), (1, 0), (0, 1)];
 
 pub fn adjacent_coords_cartesian(
     centre: &(usize, usize),
     side_lengths: &(usize, usize),
-) -> Vec<(usize, usize)> {
+) -> InlineVec<(usize, usize), 4> {
     adjacent_coords(centre, side_lengths, &ADJACENT_DELTAS_CARTESION)
 }
 
-#[derive(Debug, Clone, Copy)]
-pub struct Coord3 {
-    pub x: usize,
-    pub y: usize,
-    pub z: usize,
-}
-
-impl Coord3 {
-    pub fn new(x: usize, y: usize, z: usize) -> Coord3 {
-        Coord3 { x, y, z }
-    }
-}
-
-impl Display for Coord3 {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "({},{},{})", self.x, self.y, self.z)
-    }
-}
-
 /// Represents an n * m block of data
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Cells<T> {
     contents: Vec<T>,
     pub side_lengths: (usize, usize),
@@ -212,7 +556,19 @@ impl<T> Cells<T> {
         Ok(cell)
     }
 
-    pub fn iter(&self) -> CellsIter<T> {
+    /// The cell at (x, y), None if that's outside the cells
+    pub fn get_opt(&self, x: usize, y: usize) -> Option<&T> {
+        self.in_bounds(x, y)
+            .then(|| &self.contents[self.calculate_index(x, y)])
+    }
+
+    /// The cell at the signed (x, y), e.g. after adding a delta, None if that's outside the cells
+    pub fn get_signed(&self, x: isize, y: isize) -> Option<&T> {
+        self.in_bounds(x, y)
+            .then(|| &self.contents[self.calculate_index(x as usize, y as usize)])
+    }
+
+    pub fn iter(&self) -> CellsIter<'_, T> {
         CellsIter {
             x: 0,
             y: 0,
@@ -239,6 +595,11 @@ impl<T> Cells<T> {
         Ok(())
     }
 
+    /// The coord one step on from the one given in the direction, None if that would be off the cells
+    pub fn step(&self, coord: (usize, usize), direction: Direction) -> Option<(usize, usize)> {
+        direction.step_within(coord, &self.side_lengths)
+    }
+
     /// Given a coord (isize, isize) with possibly negative values, return the equivalent
     /// non-negative coord (usize, usize) that corresponds to it within the cells bounds,
     /// assuming that the cells are tile an infinite plane
@@ -259,7 +620,259 @@ impl<T> Cells<T> {
     }
 }
 
+/// The cell at `cells[(x, y)]`, panicking if that's outside the cells - for when the coord is known to be in
+/// them (see [`Cells::get_opt`] for when it might not be)
+impl<T> Index<(usize, usize)> for Cells<T> {
+    type Output = T;
+
+    fn index(&self, (x, y): (usize, usize)) -> &T {
+        self.get_opt(x, y).unwrap_or_else(|| {
+            panic!(
+                "({x}, {y}) is outside cells of size {:?}",
+                self.side_lengths
+            )
+        })
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Cells<T> {
+    fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut T {
+        if !self.in_bounds(x, y) {
+            panic!(
+                "({x}, {y}) is outside cells of size {:?}",
+                self.side_lengths
+            );
+        }
+        let index = self.calculate_index(x, y);
+        &mut self.contents[index]
+    }
+}
+
+impl<T> Cells<T> {
+    /// The cells of the row at y, from west to east
+    pub fn iter_row(&self, y: usize) -> Result<std::slice::Iter<'_, T>, AError> {
+        if y >= self.side_lengths.1 {
+            return Err(AError::msg(format!("Row {y} is not in bounds")));
+        }
+        let start = self.calculate_index(0, y);
+        Ok(self.contents[start..start + self.side_lengths.0].iter())
+    }
+
+    /// The cells of the column at x, from north to south
+    pub fn iter_col(&self, x: usize) -> Result<std::iter::StepBy<std::slice::Iter<'_, T>>, AError> {
+        if x >= self.side_lengths.0 {
+            return Err(AError::msg(format!("Column {x} is not in bounds")));
+        }
+        Ok(self.contents[x..].iter().step_by(self.side_lengths.0))
+    }
+
+    /// Each row in turn from the north, as with [`Cells::iter_row`]
+    pub fn rows(&self) -> impl Iterator<Item = std::slice::Iter<'_, T>> {
+        self.contents
+            .chunks(self.side_lengths.0.max(1))
+            .map(|row| row.iter())
+    }
+
+    /// Each column in turn from the west, as with [`Cells::iter_col`]
+    pub fn cols(&self) -> impl Iterator<Item = std::iter::StepBy<std::slice::Iter<'_, T>>> {
+        (0..self.side_lengths.0).map(|x| self.iter_col(x).unwrap())
+    }
+}
+
+impl<T> Cells<T> {
+    /// Apply a set of (coord, value) patches atomically - if any coord is out of bounds or is
+    /// patched more than once nothing is applied.  Returns a token that can be used to undo the patches
+    pub fn apply_patches<I>(&mut self, patches: I) -> Result<PatchUndo<T>, AError>
+    where
+        I: IntoIterator<Item = ((usize, usize), T)>,
+    {
+        let patches: Vec<((usize, usize), T)> = patches.into_iter().collect();
+        let mut seen: HashSet<(usize, usize)> = HashSet::with_capacity(patches.len());
+        for ((x, y), _) in patches.iter() {
+            if !self.in_bounds(*x, *y) {
+                return Err(AError::msg(format!(
+                    "Patch ({}, {}) is not in bounds",
+                    x, y
+                )));
+            }
+            if !seen.insert((*x, *y)) {
+                return Err(AError::msg(format!(
+                    "Conflicting patches for ({}, {})",
+                    x, y
+                )));
+            }
+        }
+        let previous = patches
+            .into_iter()
+            .map(|((x, y), value)| {
+                let index = self.calculate_index(x, y);
+                ((x, y), std::mem::replace(&mut self.contents[index], value))
+            })
+            .collect();
+        Ok(PatchUndo {
+            previous,
+            side_lengths: self.side_lengths,
+        })
+    }
+
+    /// Put back the values replaced when the patches were applied
+    pub fn undo_patches(&mut self, undo: PatchUndo<T>) -> Result<(), AError> {
+        if undo.side_lengths != self.side_lengths {
+            return Err(AError::msg(format!(
+                "Patches were applied to cells of size {:?}, not {:?}",
+                undo.side_lengths, self.side_lengths
+            )));
+        }
+        for ((x, y), value) in undo.previous.into_iter() {
+            let index = self.calculate_index(x, y);
+            self.contents[index] = value;
+        }
+        Ok(())
+    }
+
+    /// Apply the patches, evaluate the patched cells and then revert the patches
+    pub fn with_patches<I, R, F>(&mut self, patches: I, evaluate: F) -> Result<R, AError>
+    where
+        I: IntoIterator<Item = ((usize, usize), T)>,
+        F: FnOnce(&Cells<T>) -> R,
+    {
+        let undo = self.apply_patches(patches)?;
+        let result = evaluate(self);
+        self.undo_patches(undo)?;
+        Ok(result)
+    }
+}
+
+/// Holds the values replaced by [`Cells::apply_patches`] so that they can be put back
+#[must_use = "patches can only be undone using this token"]
+#[derive(Debug)]
+pub struct PatchUndo<T> {
+    previous: Vec<((usize, usize), T)>,
+    side_lengths: (usize, usize),
+}
+
+impl<T> PatchUndo<T> {
+    /// The coords that were patched
+    pub fn coords(&self) -> impl Iterator<Item = &(usize, usize)> {
+        self.previous.iter().map(|(coord, _)| coord)
+    }
+}
+
+impl<T: Hash> Cells<T> {
+    /// Hash of the row at y
+    pub fn row_hash(&self, y: usize) -> Result<u64, AError> {
+        let mut hasher = DefaultHasher::new();
+        self.iter_row(y)?.as_slice().hash(&mut hasher);
+        Ok(hasher.finish())
+    }
+
+    /// Hash of the column at x
+    pub fn column_hash(&self, x: usize) -> Result<u64, AError> {
+        let column = self.iter_col(x)?;
+        let mut hasher = DefaultHasher::new();
+        hasher.write_usize(self.side_lengths.1);
+        column.for_each(|cell| cell.hash(&mut hasher));
+        Ok(hasher.finish())
+    }
+
+    /// Hashes for each row, indexed by y.  Equal rows have equal hashes, so these can be compared
+    /// instead of the rows themselves
+    pub fn row_hashes(&self) -> Vec<u64> {
+        (0..self.side_lengths.1)
+            .map(|y| self.row_hash(y).unwrap())
+            .collect()
+    }
+
+    /// Hashes for each column, indexed by x
+    pub fn column_hashes(&self) -> Vec<u64> {
+        (0..self.side_lengths.0)
+            .map(|x| self.column_hash(x).unwrap())
+            .collect()
+    }
+
+    /// Row and column hashes together, which can be kept up to date as cells are mutated
+    pub fn line_hashes(&self) -> LineHashes {
+        LineHashes {
+            rows: self.row_hashes(),
+            columns: self.column_hashes(),
+        }
+    }
+}
+
+/// Precomputed hashes for each row and column of some cells
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LineHashes {
+    pub rows: Vec<u64>,
+    pub columns: Vec<u64>,
+}
+
+impl LineHashes {
+    /// Recalculate the row and column hashes affected by a change to the cell at (x, y)
+    pub fn rehash_cell<T: Hash>(
+        &mut self,
+        cells: &Cells<T>,
+        x: usize,
+        y: usize,
+    ) -> Result<(), AError> {
+        let row = self
+            .rows
+            .get_mut(y)
+            .ok_or_else(|| AError::msg(format!("No row hash for y={y}")))?;
+        *row = cells.row_hash(y)?;
+        let column = self
+            .columns
+            .get_mut(x)
+            .ok_or_else(|| AError::msg(format!("No column hash for x={x}")))?;
+        *column = cells.column_hash(x)?;
+        Ok(())
+    }
+
+    /// Recalculate all of the hashes, e.g. after a large number of mutations
+    pub fn rehash_all<T: Hash>(&mut self, cells: &Cells<T>) {
+        self.rows = cells.row_hashes();
+        self.columns = cells.column_hashes();
+    }
+}
+
 impl<T: Clone> Cells<T> {
+    /// New cells of the given size, each taking the value of the cell at the coord in these cells given by
+    /// the source function
+    fn remapped<F>(&self, side_lengths: (usize, usize), source: F) -> Cells<T>
+    where
+        F: Fn(usize, usize) -> (usize, usize),
+    {
+        let (width, height) = side_lengths;
+        let contents = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| {
+                let (source_x, source_y) = source(x, y);
+                self.contents[self.calculate_index(source_x, source_y)].clone()
+            })
+            .collect();
+        Cells {
+            contents,
+            side_lengths,
+        }
+    }
+
+    /// Flipped over the diagonal from the north west corner, so rows become columns
+    pub fn transpose(&self) -> Cells<T> {
+        let (width, height) = self.side_lengths;
+        self.remapped((height, width), |x, y| (y, x))
+    }
+
+    /// Turned a quarter clockwise, so that the west edge becomes the north edge
+    pub fn rotate_cw(&self) -> Cells<T> {
+        let (width, height) = self.side_lengths;
+        self.remapped((height, width), |x, y| (y, height - 1 - x))
+    }
+
+    /// Turned a quarter anticlockwise, so that the east edge becomes the north edge
+    pub fn rotate_ccw(&self) -> Cells<T> {
+        let (width, height) = self.side_lengths;
+        self.remapped((height, width), |x, y| (width - 1 - y, x))
+    }
+
     pub fn with_dimension(width: usize, height: usize, initial_value: T) -> Cells<T> {
         let mut contents = Vec::with_capacity(width * height);
         contents.resize_with(width * height, || initial_value.clone());
@@ -268,6 +881,131 @@ impl<T: Clone> Cells<T> {
             side_lengths: (width, height),
         }
     }
+
+    /// Insert a row of the value before the row at y (at the bottom if y is the height)
+    pub fn insert_row(&mut self, y: usize, value: T) -> Result<(), AError> {
+        let (width, height) = self.side_lengths;
+        if y > height {
+            return Err(AError::msg(format!(
+                "Can't insert row {y} in to cells {height} high"
+            )));
+        }
+        let index = y * width;
+        self.contents
+            .splice(index..index, std::iter::repeat_n(value, width));
+        self.side_lengths = (width, height + 1);
+        Ok(())
+    }
+
+    /// Insert a column of the value before the column at x (at the east edge if x is the width)
+    pub fn insert_col(&mut self, x: usize, value: T) -> Result<(), AError> {
+        let (width, height) = self.side_lengths;
+        if x > width {
+            return Err(AError::msg(format!(
+                "Can't insert column {x} in to cells {width} wide"
+            )));
+        }
+        // from the bottom so that the rows still to do don't move
+        for y in (0..height).rev() {
+            self.contents.insert(y * width + x, value.clone());
+        }
+        self.side_lengths = (width + 1, height);
+        Ok(())
+    }
+}
+
+impl<T> Cells<T> {
+    /// Where everything ends up when each row and column made up entirely of cells satisfying the predicate is
+    /// replaced by factor copies of itself (e.g. day11's empty space).  Only the remapping is worked out, so it
+    /// works for factors far too big to build the expanded cells, which [`Expansion::expanded`] does.
+    pub fn expand_where<F>(&self, predicate: F, factor: usize) -> Expansion
+    where
+        F: Fn(&T) -> bool,
+    {
+        let positions = |expand: Vec<bool>| -> (Vec<usize>, usize) {
+            let mut next = 0;
+            let positions = expand
+                .iter()
+                .map(|expand| {
+                    let position = next;
+                    next += if *expand { factor } else { 1 };
+                    position
+                })
+                .collect();
+            (positions, next)
+        };
+        let (xs, width) = positions(self.cols().map(|mut col| col.all(&predicate)).collect());
+        let (ys, height) = positions(self.rows().map(|mut row| row.all(&predicate)).collect());
+        Expansion {
+            xs,
+            ys,
+            side_lengths: (width, height),
+        }
+    }
+}
+
+/// Where the rows and columns of cells end up after some have been expanded, see [`Cells::expand_where`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Expansion {
+    /// The new x of each of the original columns, the first of the copies for an expanded column
+    xs: Vec<usize>,
+    /// The new y of each of the original rows
+    ys: Vec<usize>,
+    /// Of the expanded cells
+    pub side_lengths: (usize, usize),
+}
+
+impl Expansion {
+    /// Where the original coord ends up, None if it wasn't in the original cells
+    pub fn map(&self, (x, y): (usize, usize)) -> Option<(usize, usize)> {
+        Some((*self.xs.get(x)?, *self.ys.get(y)?))
+    }
+
+    /// The expanded cells, each copy of an expanded row or column taking its values
+    pub fn expanded<T: Clone>(&self, cells: &Cells<T>) -> Cells<T> {
+        // the last original line starting at or before the new one
+        let original = |positions: &[usize], new: usize| {
+            positions.partition_point(|position| *position <= new) - 1
+        };
+        cells.remapped(self.side_lengths, |x, y| {
+            (original(&self.xs, x), original(&self.ys, y))
+        })
+    }
+}
+
+impl<T> Cells<T> {
+    /// Read a block of lines with a cell for each char, mapped by the function.  The lines must all be the
+    /// same length.
+    pub fn parse<I, S, F>(lines: I, map: F) -> Result<Self, AError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+        F: FnMut(char) -> Result<T, AError>,
+    {
+        let builder = CellsBuilder::from_lines(lines, map)?;
+        let (width, height) = (builder.max_width, builder.line_starts.len());
+        if width == 0 {
+            return Err(AError::msg("No cells in the lines"));
+        }
+        if builder.contents.len() != width * height {
+            return Err(AError::msg("Lines are not all the same length"));
+        }
+        Ok(Cells {
+            contents: builder.contents,
+            side_lengths: (width, height),
+        })
+    }
+}
+
+impl<T: CellChar> Cells<T> {
+    /// Read a block of lines with a cell for each char.  The lines must all be the same length.
+    pub fn parse_lines<I, S>(lines: I) -> Result<Self, AError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        Cells::parse(lines, cell_from_char)
+    }
 }
 
 pub struct CellsIter<'a, T> {
@@ -296,75 +1034,115 @@ impl<'a, T> Iterator for CellsIter<'a, T> {
     }
 }
 
+/// A row per line.  Cells that are all a single char wide are written next to each other, as a grid is drawn in
+/// the puzzles, while wider cells (e.g. numbers) are right aligned to the widest and separated by a space so
+/// that the columns line up.
 impl<T: Display> Display for Cells<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for y in 0..self.side_lengths.1 {
-            for x in 0..self.side_lengths.0 {
-                let cell = self.get(x, y).unwrap();
-                write!(f, "{cell}")?
+        let cells: Vec<String> = self.contents.iter().map(|cell| cell.to_string()).collect();
+        let width = cells
+            .iter()
+            .map(|cell| cell.chars().count())
+            .max()
+            .unwrap_or(0);
+        for row in (0..self.side_lengths.1).map(|y| {
+            let start = y * self.side_lengths.0;
+            &cells[start..start + self.side_lengths.0]
+        }) {
+            for (x, cell) in row.iter().enumerate() {
+                if width <= 1 {
+                    write!(f, "{cell}")?
+                } else {
+                    let separator = if x == 0 { "" } else { " " };
+                    write!(f, "{separator}{cell:>width$}")?
+                }
             }
             writeln!(f)?
         }
-        write!(f, "")
+        Ok(())
     }
 }
 
-/// Represents a builder for a block/table of data
+fn cell_from_char<T: CellChar>(c: char) -> Result<T, AError> {
+    T::from_char(c).ok_or_else(|| AError::msg(format!("No cell for '{c}'")))
+}
+
+/// Represents a builder for a block/table of data.  Cells are appended straight into a single
+/// contents vector, with the start index of each line tracked alongside
 #[derive(Debug, Default)]
 pub struct CellsBuilder<T> {
-    lines: Vec<Vec<T>>,
+    contents: Vec<T>,
+    line_starts: Vec<usize>,
     max_width: usize,
 }
 
 impl<T> CellsBuilder<T> {
     pub fn new_empty() -> Self {
         CellsBuilder {
-            lines: Vec::new(),
+            contents: Vec::new(),
+            line_starts: Vec::new(),
             max_width: 0,
         }
     }
 
     pub fn new_line(&mut self) {
-        self.lines.push(Vec::new());
+        self.line_starts.push(self.contents.len());
+    }
+
+    /// Start a first line if none has been started yet, so that cells can be added
+    pub fn ensure_line(&mut self) {
+        if self.line_starts.is_empty() {
+            self.new_line();
+        }
     }
 
+    /// Add a cell to the current line, which is an error if no line has been started
     pub fn add_cell(&mut self, cell: T) -> Result<(), AError> {
-        let current_line = self
-            .lines
-            .last_mut()
+        let line_start = *self
+            .line_starts
+            .last()
             .ok_or_else(|| AError::msg("Cannot add a cell when no line has been added"))?;
-        current_line.push(cell);
-        self.max_width = self.max_width.max(current_line.len());
+        self.contents.push(cell);
+        self.max_width = self.max_width.max(self.contents.len() - line_start);
         Ok(())
     }
 
-    pub fn get(&self, x: usize, y: usize) -> Result<&T, AError> {
-        let line = self
-            .lines
-            .get(y)
+    /// The range of indexes in contents for the line at y
+    fn line_range(&self, y: usize) -> Option<std::ops::Range<usize>> {
+        let start = *self.line_starts.get(y)?;
+        let end = self
+            .line_starts
+            .get(y + 1)
+            .copied()
+            .unwrap_or(self.contents.len());
+        Some(start..end)
+    }
+
+    fn index_of(&self, x: usize, y: usize) -> Result<usize, AError> {
+        let range = self
+            .line_range(y)
             .ok_or_else(|| AError::msg(format!("No line for y={y} created yet")))?;
-        let cell = line
-            .get(x)
-            .ok_or_else(|| AError::msg(format!("No cell at ({x}, {y}) yet")))?;
-        Ok(cell)
+        if x >= range.len() {
+            return Err(AError::msg(format!("No cell at ({x}, {y}) yet")));
+        }
+        Ok(range.start + x)
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Result<&T, AError> {
+        let index = self.index_of(x, y)?;
+        Ok(&self.contents[index])
     }
 
     pub fn get_mut(&mut self, x: usize, y: usize) -> Result<&mut T, AError> {
-        let line = self
-            .lines
-            .get_mut(y)
-            .ok_or_else(|| AError::msg(format!("No line for y={y} created yet")))?;
-        let cell = line
-            .get_mut(x)
-            .ok_or_else(|| AError::msg(format!("No cell at ({x}, {y}) yet")))?;
-        Ok(cell)
+        let index = self.index_of(x, y)?;
+        Ok(&mut self.contents[index])
     }
 
     pub fn build_cells(&mut self, default_value: T) -> Result<Cells<T>, AError>
     where
         T: Clone,
     {
-        if self.lines.is_empty() {
+        if self.line_starts.is_empty() {
             return Err(AError::msg(
                 "No point in building cells when there are no lines",
             ));
@@ -375,38 +1153,78 @@ impl<T> CellsBuilder<T> {
             ));
         }
 
-        let lines = std::mem::take(&mut self.lines);
-        let height = lines.len();
+        let height = self.line_starts.len();
+        let width = self.max_width;
+        let line_starts = std::mem::take(&mut self.line_starts);
+        let mut contents = std::mem::take(&mut self.contents);
+        self.max_width = 0;
+
+        // only short lines need padding, otherwise the contents are already laid out as the cells
+        if contents.len() != height * width {
+            let mut padded = Vec::with_capacity(height * width);
+            let mut drain = contents.drain(..);
+            for (y, start) in line_starts.iter().enumerate() {
+                let end = line_starts
+                    .get(y + 1)
+                    .copied()
+                    .unwrap_or(start + drain.len());
+                padded.extend(drain.by_ref().take(end - start));
+                padded.resize((y + 1) * width, default_value.clone());
+            }
+            drop(drain);
+            contents = padded;
+        }
+        contents.shrink_to_fit();
 
-        let contents = lines.into_iter().enumerate().fold(
-            Vec::with_capacity(height * self.max_width),
-            |mut acc, (y, mut line)| {
-                let start_index = y * self.max_width;
-                for cell in line.drain(..) {
-                    acc.push(cell);
-                }
-                let expected_number_after_this_line = start_index + self.max_width;
-                while acc.len() < expected_number_after_this_line {
-                    acc.push(default_value.clone());
-                }
-                acc
-            },
-        );
         Ok(Cells {
             contents,
-            side_lengths: (self.max_width, height),
+            side_lengths: (width, height),
         })
     }
 
-    pub fn current_cell(&self) -> Option<(usize, usize)> {
-        if self.lines.is_empty() {
-            return None;
+    /// Start a new line with a cell for each char of the line
+    pub fn add_line(&mut self, line: &str) -> Result<(), AError>
+    where
+        T: CellChar,
+    {
+        self.add_line_with(line, cell_from_char)
+    }
+
+    /// Start a new line with a cell for each char of the line, mapped by the function
+    pub fn add_line_with<F>(&mut self, line: &str, mut map: F) -> Result<(), AError>
+    where
+        F: FnMut(char) -> Result<T, AError>,
+    {
+        self.new_line();
+        let y = self.line_starts.len() - 1;
+        for (x, c) in line.chars().enumerate() {
+            let cell = map(c).with_context(|| format!("Unrecognised cell '{c}' at ({x}, {y})"))?;
+            self.add_cell(cell)?;
         }
-        let line = self.lines.last().unwrap();
-        if line.is_empty() {
+        Ok(())
+    }
+
+    /// A builder with a line for each of the lines, each char mapped to a cell by the function
+    pub fn from_lines<I, S, F>(lines: I, mut map: F) -> Result<Self, AError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+        F: FnMut(char) -> Result<T, AError>,
+    {
+        let mut builder = CellsBuilder::new_empty();
+        for line in lines {
+            builder.add_line_with(line.as_ref(), &mut map)?;
+        }
+        Ok(builder)
+    }
+
+    pub fn current_cell(&self) -> Option<(usize, usize)> {
+        let y = self.line_starts.len().checked_sub(1)?;
+        let line_length = self.line_range(y)?.len();
+        if line_length == 0 {
             return None;
         }
-        Some((line.len() - 1, self.lines.len() - 1))
+        Some((line_length - 1, y))
     }
 }
 
@@ -442,6 +1260,18 @@ mod tests {
         assert_eq!(cells.get_position_in_bounds(-4, -4), (2, 2));
     }
 
+    #[test]
+    fn step_in_bounds() {
+        let cells = Cells::with_dimension(3, 2, 0);
+        assert_eq!(cells.step((0, 0), Direction::East), Some((1, 0)));
+        assert_eq!(cells.step((0, 0), Direction::South), Some((0, 1)));
+        assert_eq!(cells.step((0, 0), Direction::North), None);
+        assert_eq!(cells.step((0, 0), Direction::West), None);
+        assert_eq!(cells.step((2, 1), Direction::East), None);
+        assert_eq!(cells.step((2, 1), Direction::South), None);
+        assert_eq!(cells.step((2, 1), Direction::West), Some((1, 1)));
+    }
+
     #[test]
     fn load_file() {
         let initial_state: Vec<String> = Vec::new();
@@ -462,6 +1292,80 @@ mod tests {
         }
     }
 
+    #[test]
+    fn load_from_reader() {
+        let res = process_reader(
+            "first\nsecond\n\nfourth".as_bytes(),
+            Vec::<String>::new(),
+            |mut vec, line| {
+                vec.push(line);
+                Ok(vec)
+            },
+            ok_identity,
+            |vec| Ok(vec.join("+")),
+            ok_identity,
+        );
+        assert_eq!(res.unwrap(), "first+second++fourth");
+    }
+
+    #[test]
+    fn load_file_with_context() {
+        let res = process_with_context(
+            "test-input.txt",
+            Vec::<String>::new(),
+            |mut vec, context| {
+                vec.push(format!("{}:{}", context.index, context.raw));
+                Ok(vec)
+            },
+            ok_identity,
+            |vec| Ok(vec.join("+")),
+            ok_identity,
+        );
+        assert_eq!(res.unwrap(), "0:Some Input Here+1:It's Good");
+
+        let res = process_with_context(
+            "test-input.txt",
+            (),
+            |_, context| {
+                if context.raw.contains('\'') {
+                    return Err(AError::msg("Quotes not allowed"));
+                }
+                Ok(())
+            },
+            ok_identity,
+            ok_identity,
+            ok_identity,
+        );
+        assert_eq!(res.unwrap_err().to_string(), "line 2: Quotes not allowed");
+    }
+
+    #[test]
+    fn load_file_recovering() {
+        let res = process_recovering(
+            "test-input.txt",
+            Vec::<String>::new(),
+            |vec, line| {
+                let quoted = line.contains('\'');
+                vec.push(line);
+                if quoted {
+                    return Err(AError::msg("Quotes not allowed"));
+                }
+                Ok(())
+            },
+            ok_identity,
+            |vec| Ok(vec.join("+")),
+            ok_identity,
+        );
+        let (message, errors) = res.unwrap();
+        assert_eq!(message, "Some Input Here");
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].line_number, 2);
+        assert_eq!(
+            format!("{}", errors[0]),
+            "line 2: Quotes not allowed ('It's Good')"
+        );
+    }
+
     #[test]
     fn build_cells() {
         //Arrange
@@ -476,7 +1380,7 @@ mod tests {
             builder.new_line();
             for ((_, _), value) in line_vals {
                 if *value != '?' {
-                    builder.add_cell(value.clone()).unwrap();
+                    builder.add_cell(*value).unwrap();
                 }
             }
         }
@@ -490,6 +1394,90 @@ mod tests {
         }
     }
 
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Tile {
+        Space,
+        Rock,
+        Light(bool),
+    }
+
+    cell_chars!(Tile {
+        '.' => Space,
+        '#' => Rock,
+        '*' => Light(true),
+        'o' => Light(false),
+    });
+
+    #[test]
+    fn parse_lines_with_cell_chars() {
+        let cells = Cells::<Tile>::parse_lines([".#*", "o.."]).unwrap();
+        assert_eq!(cells.side_lengths, (3, 2));
+        assert_eq!(cells.get(2, 0).unwrap(), &Tile::Light(true));
+        assert_eq!(cells.get(0, 1).unwrap(), &Tile::Light(false));
+        assert_eq!(format!("{cells}"), ".#*\no..\n");
+
+        let error = Cells::<Tile>::parse_lines([".#*", "o.x"]).unwrap_err();
+        assert_eq!(error.to_string(), "Unrecognised cell 'x' at (2, 1)");
+        assert!(Cells::<Tile>::parse_lines([".#*", "o."]).is_err());
+        assert!(Cells::<Tile>::parse_lines([""]).is_err());
+    }
+
+    #[test]
+    fn parse_with_char_mapping() {
+        let digit = |c: char| {
+            c.to_digit(10)
+                .ok_or_else(|| AError::msg(format!("'{c}' is not a digit")))
+        };
+        let cells = Cells::parse(["123", "456"], digit).unwrap();
+        assert_eq!(cells.side_lengths, (3, 2));
+        assert_eq!(cells[(2, 1)], 6);
+
+        let error = Cells::parse(["12", "3x"], digit).unwrap_err();
+        assert_eq!(error.to_string(), "Unrecognised cell 'x' at (1, 1)");
+        assert_eq!(
+            format!("{error:#}"),
+            "Unrecognised cell 'x' at (1, 1): 'x' is not a digit"
+        );
+
+        let mut builder = CellsBuilder::from_lines(["12", "3"], digit).unwrap();
+        assert_eq!(builder.current_cell(), Some((0, 1)));
+        assert_eq!(format!("{}", builder.build_cells(0).unwrap()), "12\n30\n");
+    }
+
+    #[test]
+    fn builder_ragged_lines() {
+        let mut builder: CellsBuilder<char> = CellsBuilder::new_empty();
+        assert!(builder.add_cell('x').is_err());
+        assert_eq!(builder.current_cell(), None);
+        builder.new_line();
+        builder.add_cell('a').unwrap();
+        builder.new_line();
+        assert_eq!(builder.current_cell(), None);
+        builder.add_cell('b').unwrap();
+        builder.add_cell('c').unwrap();
+        builder.new_line();
+        assert_eq!(*builder.get(1, 1).unwrap(), 'c');
+        assert!(builder.get(1, 0).is_err());
+        assert!(builder.get(0, 3).is_err());
+        *builder.get_mut(0, 0).unwrap() = 'z';
+        assert_eq!(builder.current_cell(), None);
+
+        let cells = builder.build_cells('.').unwrap();
+        assert_eq!(cells.side_lengths, (2, 3));
+        assert_eq!(format!("{cells}"), "z.\nbc\n..\n");
+    }
+
+    #[test]
+    fn builder_line_ensured() {
+        let mut builder: CellsBuilder<char> = CellsBuilder::new_empty();
+        builder.ensure_line();
+        builder.add_cell('a').unwrap();
+        builder.ensure_line();
+        builder.add_cell('b').unwrap();
+        assert_eq!(builder.current_cell(), Some((1, 0)));
+        assert_eq!(format!("{}", builder.build_cells('.').unwrap()), "ab\n");
+    }
+
     #[test]
     fn edit_cells() {
         let mut builder: CellsBuilder<char> = CellsBuilder::new_empty();
@@ -535,8 +1523,309 @@ mod tests {
         }
     }
 
+    #[test]
+    fn line_hashes() {
+        let mut builder: CellsBuilder<char> = CellsBuilder::new_empty();
+        for line in ["ab", "ab", "ba"] {
+            builder.new_line();
+            for c in line.chars() {
+                builder.add_cell(c).unwrap();
+            }
+        }
+        let mut cells = builder.build_cells('?').unwrap();
+        let rows = cells.row_hashes();
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0], rows[1]);
+        assert_ne!(rows[1], rows[2]);
+        let columns = cells.column_hashes();
+        assert_eq!(columns.len(), 2);
+        assert_ne!(columns[0], columns[1]);
+
+        let mut hashes = cells.line_hashes();
+        *cells.get_mut(1, 2).unwrap() = 'b';
+        hashes.rehash_cell(&cells, 1, 2).unwrap();
+        assert_eq!(hashes, cells.line_hashes());
+        assert_ne!(hashes.rows[0], hashes.rows[2]);
+        assert_eq!(hashes.columns[1], cells.column_hash(1).unwrap());
+        assert!(hashes.rehash_cell(&cells, 2, 0).is_err());
+    }
+
+    #[test]
+    fn rows_and_columns() {
+        let mut builder: CellsBuilder<char> = CellsBuilder::new_empty();
+        for line in ["abc", "def"] {
+            builder.new_line();
+            line.chars().for_each(|c| builder.add_cell(c).unwrap());
+        }
+        let cells = builder.build_cells(' ').unwrap();
+        assert_eq!(cells.iter_row(1).unwrap().collect::<String>(), "def");
+        assert_eq!(cells.iter_col(2).unwrap().collect::<String>(), "cf");
+        assert!(cells.iter_row(2).is_err());
+        assert!(cells.iter_col(3).is_err());
+        assert_eq!(
+            cells
+                .rows()
+                .map(|row| row.collect::<String>())
+                .collect::<Vec<_>>(),
+            vec!["abc", "def"]
+        );
+        assert_eq!(
+            cells
+                .cols()
+                .map(|column| column.collect::<String>())
+                .collect::<Vec<_>>(),
+            vec!["ad", "be", "cf"]
+        );
+    }
+
+    #[test]
+    fn transposed_and_rotated() {
+        let mut cells = Cells::with_dimension(3, 2, 0);
+        (0..6).for_each(|i| *cells.get_mut(i % 3, i / 3).unwrap() = i);
+        // 0 1 2
+        // 3 4 5
+        let rows = |cells: &Cells<usize>| -> Vec<Vec<usize>> {
+            cells.rows().map(|row| row.copied().collect()).collect()
+        };
+        assert_eq!(
+            rows(&cells.transpose()),
+            vec![vec![0, 3], vec![1, 4], vec![2, 5]]
+        );
+        assert_eq!(
+            rows(&cells.rotate_cw()),
+            vec![vec![3, 0], vec![4, 1], vec![5, 2]]
+        );
+        assert_eq!(
+            rows(&cells.rotate_ccw()),
+            vec![vec![2, 5], vec![1, 4], vec![0, 3]]
+        );
+        assert_eq!(cells.rotate_cw().side_lengths, (2, 3));
+        assert_eq!(cells.rotate_cw().rotate_ccw(), cells);
+        assert_eq!(cells.transpose().transpose(), cells);
+        let half_turn = cells.rotate_cw().rotate_cw();
+        assert_eq!(half_turn.rotate_cw().rotate_cw(), cells);
+        assert_eq!(rows(&half_turn), vec![vec![5, 4, 3], vec![2, 1, 0]]);
+    }
+
+    #[test]
+    fn rows_and_columns_inserted_and_expanded() {
+        let rows = |cells: &Cells<usize>| -> Vec<Vec<usize>> {
+            cells.rows().map(|row| row.copied().collect()).collect()
+        };
+        let mut cells = Cells::with_dimension(2, 2, 1);
+        cells.insert_row(1, 0).unwrap();
+        cells.insert_col(2, 0).unwrap();
+        cells.insert_col(0, 0).unwrap();
+        assert_eq!(
+            rows(&cells),
+            vec![vec![0, 1, 1, 0], vec![0, 0, 0, 0], vec![0, 1, 1, 0]]
+        );
+        assert_eq!(cells.side_lengths, (4, 3));
+        assert!(cells.insert_row(4, 0).is_err());
+        assert!(cells.insert_col(5, 0).is_err());
+
+        // the empty (0) rows and columns doubled
+        let expansion = cells.expand_where(|value| *value == 0, 2);
+        assert_eq!(expansion.side_lengths, (6, 4));
+        assert_eq!(expansion.map((1, 0)), Some((2, 0)));
+        assert_eq!(expansion.map((3, 2)), Some((4, 3)));
+        assert_eq!(expansion.map((4, 0)), None);
+        assert_eq!(
+            rows(&expansion.expanded(&cells)),
+            vec![
+                vec![0, 0, 1, 1, 0, 0],
+                vec![0, 0, 0, 0, 0, 0],
+                vec![0, 0, 0, 0, 0, 0],
+                vec![0, 0, 1, 1, 0, 0]
+            ]
+        );
+        let far = cells.expand_where(|value| *value == 0, 1_000_000);
+        assert_eq!(far.map((2, 2)), Some((1_000_001, 1_000_001)));
+        let removed = cells.expand_where(|value| *value == 0, 0);
+        assert_eq!(
+            rows(&removed.expanded(&cells)),
+            vec![vec![1, 1], vec![1, 1]]
+        );
+    }
+
+    #[test]
+    fn cells_indexed() {
+        let mut cells = Cells::with_dimension(3, 2, 0);
+        cells[(2, 1)] = 5;
+        cells[(0, 0)] += 1;
+        assert_eq!(cells[(2, 1)], 5);
+        assert_eq!(cells.get_opt(0, 0), Some(&1));
+        assert_eq!(cells.get_opt(3, 0), None);
+        assert_eq!(cells.get_signed(2, 1), Some(&5));
+        assert_eq!(cells.get_signed(-1, 1), None);
+        assert_eq!(cells.get_signed(0, 2), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "(0, 2) is outside cells of size (3, 2)")]
+    fn index_outside_cells() {
+        let _ = Cells::with_dimension(3, 2, 0)[(0, 2)];
+    }
+
+    #[test]
+    fn wide_cells_aligned() {
+        let mut cells = Cells::with_dimension(3, 2, 7);
+        *cells.get_mut(1, 0).unwrap() = 10;
+        *cells.get_mut(2, 1).unwrap() = 123;
+        assert_eq!(cells.to_string(), "  7  10   7\n  7   7 123\n");
+        let single = Cells::with_dimension(3, 2, '#');
+        assert_eq!(single.to_string(), "###\n###\n");
+    }
+
+    #[test]
+    fn apply_and_undo_patches() {
+        let mut cells = Cells::with_dimension(2, 2, 0);
+        let undo = cells.apply_patches([((0, 0), 1), ((1, 1), 2)]).unwrap();
+        assert_eq!(*cells.get(0, 0).unwrap(), 1);
+        assert_eq!(*cells.get(1, 1).unwrap(), 2);
+        assert_eq!(undo.coords().count(), 2);
+        cells.undo_patches(undo).unwrap();
+        assert!(cells.iter().all(|(_, value)| *value == 0));
+
+        // conflicts and out of bounds patches leave the cells untouched
+        assert!(cells.apply_patches([((0, 0), 1), ((0, 0), 2)]).is_err());
+        assert!(cells.apply_patches([((0, 1), 1), ((2, 0), 2)]).is_err());
+        assert!(cells.iter().all(|(_, value)| *value == 0));
+
+        let sum = cells
+            .with_patches([((1, 0), 5)], |patched| {
+                patched.iter().map(|(_, value)| *value).sum::<i32>()
+            })
+            .unwrap();
+        assert_eq!(sum, 5);
+        assert_eq!(*cells.get(1, 0).unwrap(), 0);
+    }
+
     static DELIMITERS: Lazy<HashSet<char>> = Lazy::new(|| HashSet::from(['@']));
 
+    static WORD_DELIMITERS: Lazy<HashSet<char>> = Lazy::new(|| HashSet::from([' ', ',']));
+
+    #[test]
+    fn read_word_works() {
+        let mut chars = "  abc, de  ".chars();
+        assert_eq!(
+            read_word(&mut chars, &WORD_DELIMITERS),
+            Some(("abc".to_string(), Some(',')))
+        );
+        assert_eq!(
+            read_word(&mut chars, &WORD_DELIMITERS),
+            Some(("de".to_string(), Some(' ')))
+        );
+        assert_eq!(read_word(&mut chars, &WORD_DELIMITERS), None);
+        assert_eq!(reverse("abc"), "cba");
+    }
+
+    #[test]
+    fn read_word_emoji() {
+        let mut chars = "🎄🎁 ⭐,x".chars();
+        assert_eq!(
+            read_word(&mut chars, &WORD_DELIMITERS),
+            Some(("🎄🎁".to_string(), Some(' ')))
+        );
+        assert_eq!(
+            read_word(&mut chars, &WORD_DELIMITERS),
+            Some(("⭐".to_string(), Some(',')))
+        );
+        assert_eq!(
+            read_word(&mut chars, &WORD_DELIMITERS),
+            Some(("x".to_string(), None))
+        );
+        assert_eq!(reverse("🎄🎁"), "🎁🎄");
+    }
+
+    #[cfg(feature = "unicode")]
+    #[test]
+    fn read_word_graphemes() {
+        // ',' with a combining acute accent is part of the word, not a delimiter
+        let mut chars = "a,\u{301}b,c".chars();
+        assert_eq!(
+            read_word(&mut chars, &WORD_DELIMITERS),
+            Some(("a,\u{301}b".to_string(), Some(',')))
+        );
+        assert_eq!(chars.as_str(), "c");
+        assert_eq!(reverse("e\u{301}x"), "xe\u{301}");
+        // family emoji joined with zero width joiners stays as one
+        let family = "👨\u{200d}👩\u{200d}👧";
+        assert_eq!(reverse(&format!("{family}!")), format!("!{family}"));
+    }
+
+    #[test]
+    fn read_token_groups() {
+        let mut chars = "px{a<2006:qkq,m>2090:A,rfg} (#70c710), [a(b)c] \"x, y\"".chars();
+        let mut next = || read_token(&mut chars, &WORD_DELIMITERS).unwrap();
+        assert_eq!(next(), Some((Token::Word("px".to_string()), None)));
+        assert_eq!(
+            next(),
+            Some((
+                Token::Group {
+                    open: '{',
+                    contents: "a<2006:qkq,m>2090:A,rfg".to_string()
+                },
+                Some(' ')
+            ))
+        );
+        assert_eq!(
+            next(),
+            Some((
+                Token::Group {
+                    open: '(',
+                    contents: "#70c710".to_string()
+                },
+                Some(',')
+            ))
+        );
+        assert_eq!(
+            next(),
+            Some((
+                Token::Group {
+                    open: '[',
+                    contents: "a(b)c".to_string()
+                },
+                Some(' ')
+            ))
+        );
+        assert_eq!(next(), Some((Token::Quoted("x, y".to_string()), None)));
+        assert_eq!(next(), None);
+
+        assert_eq!(
+            read_group(&mut "{x=787,m={2}}".chars(), &WORD_DELIMITERS, '{').unwrap(),
+            ("x=787,m={2}".to_string(), None)
+        );
+        assert!(read_group(&mut "(a}".chars(), &WORD_DELIMITERS, '(').is_err());
+        assert!(read_group(&mut "(a".chars(), &WORD_DELIMITERS, '(').is_err());
+        assert!(read_group(&mut "a".chars(), &WORD_DELIMITERS, '(').is_err());
+    }
+
+    #[test]
+    fn read_radix_works() {
+        assert_eq!(
+            read_radix::<u32>(&mut "70c71@".chars(), &DELIMITERS, 16).unwrap(),
+            (0x70c71, Some('@'))
+        );
+        assert_eq!(
+            read_radix::<i128>(&mut "-101".chars(), &DELIMITERS, 2).unwrap(),
+            (-5, None)
+        );
+        assert_eq!(
+            read_radix::<num::BigInt>(
+                &mut "ffffffffffffffffffffffffffffffffff".chars(),
+                &DELIMITERS,
+                16
+            )
+            .unwrap()
+            .0,
+            num::BigInt::from(u128::MAX) * 256 + 255
+        );
+        let error = read_radix::<u8>(&mut "1g".chars(), &DELIMITERS, 16).unwrap_err();
+        assert_eq!(error.to_string(), "Failed parsing word: '1g' in radix 16");
+        assert!(read_radix::<u8>(&mut "@".chars(), &DELIMITERS, 16).is_err());
+    }
+
     #[test]
     fn read_next_works() {
         let s = "57";
@@ -556,5 +1845,22 @@ mod tests {
             read_next::<usize>(&mut s.chars(), &DELIMITERS).unwrap(),
             (57usize, None)
         );
+        assert_eq!(
+            read_next::<u128>(
+                &mut "340282366920938463463374607431768211455".chars(),
+                &DELIMITERS
+            )
+            .unwrap(),
+            (u128::MAX, None)
+        );
+        assert_eq!(
+            read_next::<num::BigInt>(
+                &mut "-340282366920938463463374607431768211456".chars(),
+                &DELIMITERS
+            )
+            .unwrap()
+            .0,
+            -num::BigInt::from(u128::MAX) - 1
+        );
     }
 }
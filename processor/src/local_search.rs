@@ -0,0 +1,215 @@
+/// Small, seedable pseudo random number generator (SplitMix64) so that searches are repeatable
+#[derive(Debug, Clone)]
+pub struct SeededRng {
+    state: u64,
+}
+
+impl SeededRng {
+    pub fn new(seed: u64) -> Self {
+        SeededRng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform value in [0, 1)
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Uniform value in [0, upper), upper must be > 0
+    pub fn next_below(&mut self, upper: usize) -> usize {
+        (self.next_u64() % upper as u64) as usize
+    }
+}
+
+/// How the temperature falls as the search progresses
+#[derive(Debug, Clone, Copy)]
+pub enum TemperatureSchedule {
+    /// Falls linearly from initial to final
+    Linear { initial: f64, last: f64 },
+    /// Falls geometrically from initial to final
+    Exponential { initial: f64, last: f64 },
+    /// Never accept a worse state, i.e. hill climbing
+    Zero,
+}
+
+impl TemperatureSchedule {
+    /// Temperature given the progress through the search, in [0, 1]
+    pub fn temperature(&self, progress: f64) -> f64 {
+        match self {
+            TemperatureSchedule::Linear { initial, last } => initial + (last - initial) * progress,
+            TemperatureSchedule::Exponential { initial, last } => {
+                initial * (last / initial).powf(progress)
+            }
+            TemperatureSchedule::Zero => 0.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SearchConfig {
+    pub iterations: usize,
+    pub schedule: TemperatureSchedule,
+    pub seed: u64,
+    /// Stop early when this many iterations pass without a new best
+    pub patience: Option<usize>,
+}
+
+impl SearchConfig {
+    pub fn hill_climbing(iterations: usize, seed: u64) -> Self {
+        SearchConfig {
+            iterations,
+            schedule: TemperatureSchedule::Zero,
+            seed,
+            patience: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SearchResult<S> {
+    pub best: S,
+    pub best_score: f64,
+    pub iterations: usize,
+    pub accepted: usize,
+}
+
+/// Search for the state with the highest score.  Neighbours scoring better are always moved to, worse ones
+/// are moved to with probability exp(delta / temperature).
+pub fn simulated_annealing<S, N, F>(
+    initial: S,
+    config: &SearchConfig,
+    mut neighbour: N,
+    mut score: F,
+) -> SearchResult<S>
+where
+    S: Clone,
+    N: FnMut(&S, &mut SeededRng) -> S,
+    F: FnMut(&S) -> f64,
+{
+    let mut rng = SeededRng::new(config.seed);
+    let mut current_score = score(&initial);
+    let mut best = initial.clone();
+    let mut best_score = current_score;
+    let mut current = initial;
+    let mut accepted = 0;
+    let mut since_best = 0;
+    let mut iterations = 0;
+
+    while iterations < config.iterations {
+        let progress = iterations as f64 / config.iterations as f64;
+        let temperature = config.schedule.temperature(progress);
+        iterations += 1;
+
+        let candidate = neighbour(&current, &mut rng);
+        let candidate_score = score(&candidate);
+        let delta = candidate_score - current_score;
+        let accept =
+            delta >= 0.0 || (temperature > 0.0 && rng.next_f64() < (delta / temperature).exp());
+        if accept {
+            accepted += 1;
+            current = candidate;
+            current_score = candidate_score;
+        }
+
+        if current_score > best_score {
+            best = current.clone();
+            best_score = current_score;
+            since_best = 0;
+        } else {
+            since_best += 1;
+            if config
+                .patience
+                .is_some_and(|patience| since_best >= patience)
+            {
+                break;
+            }
+        }
+    }
+
+    SearchResult {
+        best,
+        best_score,
+        iterations,
+        accepted,
+    }
+}
+
+/// Simulated annealing without ever accepting a worse state
+pub fn hill_climb<S, N, F>(
+    initial: S,
+    iterations: usize,
+    seed: u64,
+    neighbour: N,
+    score: F,
+) -> SearchResult<S>
+where
+    S: Clone,
+    N: FnMut(&S, &mut SeededRng) -> S,
+    F: FnMut(&S) -> f64,
+{
+    simulated_annealing(
+        initial,
+        &SearchConfig::hill_climbing(iterations, seed),
+        neighbour,
+        score,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn step(x: &i64, rng: &mut SeededRng) -> i64 {
+        if rng.next_below(2) == 0 {
+            x - 1
+        } else {
+            x + 1
+        }
+    }
+
+    fn peak_at_42(x: &i64) -> f64 {
+        -((x - 42) * (x - 42)) as f64
+    }
+
+    #[test]
+    fn hill_climb_finds_peak() {
+        let result = hill_climb(0i64, 1000, 7, step, peak_at_42);
+        assert_eq!(result.best, 42);
+        assert_eq!(result.best_score, 0.0);
+    }
+
+    #[test]
+    fn annealing_is_repeatable() {
+        let config = SearchConfig {
+            iterations: 2000,
+            schedule: TemperatureSchedule::Exponential {
+                initial: 10.0,
+                last: 0.01,
+            },
+            seed: 99,
+            patience: None,
+        };
+        let first = simulated_annealing(100i64, &config, step, peak_at_42);
+        let second = simulated_annealing(100i64, &config, step, peak_at_42);
+        assert_eq!(first.best, 42);
+        assert_eq!(first.accepted, second.accepted);
+
+        let stopped = simulated_annealing(
+            42i64,
+            &SearchConfig {
+                patience: Some(10),
+                ..config
+            },
+            step,
+            peak_at_42,
+        );
+        assert_eq!(stopped.iterations, 10);
+    }
+}
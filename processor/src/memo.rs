@@ -0,0 +1,118 @@
+//! Memoisation for recursive solutions (e.g. day12's arrangement counts), taking care of the cache lookups and
+//! inserts so that the recursion only has to say how to work out a value it hasn't seen before.
+
+use std::{collections::HashMap, hash::Hash};
+
+/// Values worked out so far, by key
+#[derive(Debug, Clone)]
+pub struct Memo<K, V> {
+    cache: HashMap<K, V>,
+}
+
+impl<K, V> Default for Memo<K, V> {
+    fn default() -> Self {
+        Memo {
+            cache: HashMap::default(),
+        }
+    }
+}
+
+impl<K: Eq + Hash, V: Clone> Memo<K, V> {
+    pub fn new() -> Self {
+        Memo::default()
+    }
+
+    /// The value for the key if already known, otherwise worked out with `compute` and remembered.  `compute`
+    /// is given the memo to recurse with.
+    pub fn get_or_compute(&mut self, key: K, compute: impl FnOnce(&mut Self) -> V) -> V {
+        if let Some(value) = self.cache.get(&key) {
+            return value.clone();
+        }
+        let value = compute(self);
+        self.cache.insert(key, value.clone());
+        value
+    }
+
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.cache.get(key)
+    }
+
+    pub fn len(&self) -> usize {
+        self.cache.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cache.is_empty()
+    }
+}
+
+/// A function remembering what it returned for each argument, see [`memoize`]
+pub struct Memoized<K, V> {
+    memo: Memo<K, V>,
+    function: fn(&mut Memoized<K, V>, &K) -> V,
+}
+
+impl<K: Eq + Hash + Clone, V: Clone> Memoized<K, V> {
+    pub fn call(&mut self, key: K) -> V {
+        if let Some(value) = self.memo.get(&key) {
+            return value.clone();
+        }
+        let value = (self.function)(self, &key);
+        self.memo.cache.insert(key, value.clone());
+        value
+    }
+
+    pub fn memo(&self) -> &Memo<K, V> {
+        &self.memo
+    }
+}
+
+/// Memoise the function, which recurses by calling the [`Memoized`] it is given, e.g.
+/// `memoize(|fib, n| if *n < 2 { *n } else { fib.call(n - 1) + fib.call(n - 2) })`
+pub fn memoize<K, V>(function: fn(&mut Memoized<K, V>, &K) -> V) -> Memoized<K, V> {
+    Memoized {
+        memo: Memo::default(),
+        function,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn values_remembered() {
+        let mut fib = memoize(|fib, n: &u64| {
+            if *n < 2 {
+                *n
+            } else {
+                fib.call(n - 1) + fib.call(n - 2)
+            }
+        });
+        assert_eq!(fib.call(90), 2880067194370816120);
+        assert_eq!(fib.memo().len(), 91);
+
+        // paths through a grid only moving right or down, counting how often each is really worked out
+        fn paths(
+            memo: &mut Memo<(u32, u32), u64>,
+            (x, y): (u32, u32),
+            computed: &mut usize,
+        ) -> u64 {
+            memo.get_or_compute((x, y), |memo| {
+                *computed += 1;
+                if x == 0 || y == 0 {
+                    1
+                } else {
+                    paths(memo, (x - 1, y), computed) + paths(memo, (x, y - 1), computed)
+                }
+            })
+        }
+        let mut memo = Memo::new();
+        let mut computed = 0;
+        assert_eq!(paths(&mut memo, (16, 16), &mut computed), 601080390);
+        assert_eq!(computed, memo.len());
+        assert_eq!(memo.get(&(1, 1)), Some(&2));
+        assert_eq!(paths(&mut memo, (16, 16), &mut computed), 601080390);
+        assert_eq!(computed, memo.len());
+    }
+}
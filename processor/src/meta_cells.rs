@@ -0,0 +1,112 @@
+//! An infinite tiling of a base [`Cells`], addressed by global coordinates that can run off any edge.  Tiles
+//! are only materialised (as copies of the base) when first written to, each along with a summary of
+//! whatever the puzzle wants to track per tile, e.g. how many cells have been reached and when that stopped
+//! changing.
+
+use crate::{collections::Map, Cells};
+
+pub type TileCoord = (isize, isize);
+
+/// A materialised copy of the base cells and its summary
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetaTile<T, S> {
+    pub cells: Cells<T>,
+    pub summary: S,
+}
+
+#[derive(Debug, Clone)]
+pub struct MetaCells<T, S = ()> {
+    base: Cells<T>,
+    tiles: Map<TileCoord, MetaTile<T, S>>,
+}
+
+impl<T: Clone, S: Default> MetaCells<T, S> {
+    pub fn new(base: Cells<T>) -> Self {
+        MetaCells {
+            base,
+            tiles: Map::default(),
+        }
+    }
+
+    pub fn base(&self) -> &Cells<T> {
+        &self.base
+    }
+
+    /// The tile holding the global coordinate and the coordinate within it
+    pub fn locate(&self, (x, y): (isize, isize)) -> (TileCoord, (usize, usize)) {
+        let (width, height) = (
+            self.base.side_lengths.0 as isize,
+            self.base.side_lengths.1 as isize,
+        );
+        (
+            (x.div_euclid(width), y.div_euclid(height)),
+            (x.rem_euclid(width) as usize, y.rem_euclid(height) as usize),
+        )
+    }
+
+    /// The cell at the global coordinate, from the base if its tile hasn't been materialised
+    pub fn get(&self, coord: (isize, isize)) -> &T {
+        let (tile, (x, y)) = self.locate(coord);
+        let cells = self.tiles.get(&tile).map_or(&self.base, |tile| &tile.cells);
+        &cells[(x, y)]
+    }
+
+    /// The cell at the global coordinate, materialising its tile if needed
+    pub fn get_mut(&mut self, coord: (isize, isize)) -> &mut T {
+        let (tile, (x, y)) = self.locate(coord);
+        &mut self.tile_mut(tile).cells[(x, y)]
+    }
+
+    pub fn tile(&self, tile: TileCoord) -> Option<&MetaTile<T, S>> {
+        self.tiles.get(&tile)
+    }
+
+    /// The tile, materialising it if needed
+    pub fn tile_mut(&mut self, tile: TileCoord) -> &mut MetaTile<T, S> {
+        let base = &self.base;
+        self.tiles.entry(tile).or_insert_with(|| MetaTile {
+            cells: base.clone(),
+            summary: S::default(),
+        })
+    }
+
+    /// The summary for the tile holding the global coordinate, materialising it if needed
+    pub fn summary_mut(&mut self, coord: (isize, isize)) -> &mut S {
+        let (tile, _) = self.locate(coord);
+        &mut self.tile_mut(tile).summary
+    }
+
+    /// The materialised tiles, in no particular order
+    pub fn tiles(&self) -> impl Iterator<Item = (&TileCoord, &MetaTile<T, S>)> {
+        self.tiles.iter()
+    }
+
+    pub fn tile_count(&self) -> usize {
+        self.tiles.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tiles_materialised_lazily() {
+        let mut base = Cells::with_dimension(3, 2, 0);
+        *base.get_mut(2, 1).unwrap() = 5;
+        let mut meta: MetaCells<i32, usize> = MetaCells::new(base);
+        assert_eq!(meta.locate((-1, 2)), ((-1, 1), (2, 0)));
+        assert_eq!(*meta.get((-1, -1)), 5);
+        assert_eq!(meta.tile_count(), 0);
+
+        *meta.get_mut((4, -2)) = 7;
+        *meta.summary_mut((5, -1)) += 1;
+        assert_eq!(meta.tile_count(), 1);
+        assert_eq!(*meta.get((4, -2)), 7);
+        assert_eq!(*meta.get((1, 0)), 0);
+        let tile = meta.tile((1, -1)).unwrap();
+        assert_eq!(tile.summary, 1);
+        assert_eq!(*tile.cells.get(2, 1).unwrap(), 5);
+        assert!(meta.tile((0, 0)).is_none());
+    }
+}
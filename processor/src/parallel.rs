@@ -0,0 +1,158 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex, OnceLock,
+    },
+    thread,
+};
+
+/// Environment variable fixing the number of threads used by the parallel helpers, so that timings can be
+/// compared across machines
+pub const THREADS_ENV: &str = "AOC_THREADS";
+
+static THREAD_COUNT: OnceLock<usize> = OnceLock::new();
+
+/// A positive number of threads from the value of [`THREADS_ENV`]
+fn parse_thread_count(value: &str) -> Option<usize> {
+    value.trim().parse::<usize>().ok().filter(|n| *n > 0)
+}
+
+/// Number of threads to spread parallel work over, from [`THREADS_ENV`] if it is set and otherwise the
+/// available parallelism.  Decided on first use and then fixed for the rest of the run.
+pub fn thread_count() -> usize {
+    *THREAD_COUNT.get_or_init(|| {
+        let available = thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1);
+        match std::env::var(THREADS_ENV) {
+            Ok(value) => parse_thread_count(&value).unwrap_or_else(|| {
+                eprintln!("Ignoring {THREADS_ENV}='{value}', using {available} threads");
+                available
+            }),
+            Err(_) => available,
+        }
+    })
+}
+
+/// Map each of the items on scoped threads.  Threads take the next unprocessed item as they become free,
+/// but the results are returned in the same order as the items so any reduction over them is deterministic
+pub fn par_map<T, R, F>(items: Vec<T>, f: F) -> Vec<R>
+where
+    T: Send,
+    R: Send,
+    F: Fn(T) -> R + Sync,
+{
+    let num_items = items.len();
+    let num_threads = thread_count().min(num_items);
+    if num_threads <= 1 {
+        return items.into_iter().map(f).collect();
+    }
+
+    let items: Vec<Mutex<Option<T>>> = items.into_iter().map(|i| Mutex::new(Some(i))).collect();
+    let results: Vec<Mutex<Option<R>>> = (0..num_items).map(|_| Mutex::new(None)).collect();
+    let next_index = AtomicUsize::new(0);
+
+    thread::scope(|scope| {
+        for _ in 0..num_threads {
+            scope.spawn(|| loop {
+                let index = next_index.fetch_add(1, Ordering::Relaxed);
+                if index >= num_items {
+                    break;
+                }
+                let item = items[index].lock().unwrap().take().unwrap();
+                let result = f(item);
+                *results[index].lock().unwrap() = Some(result);
+            });
+        }
+    });
+
+    results
+        .into_iter()
+        .map(|r| r.into_inner().unwrap().unwrap())
+        .collect()
+}
+
+/// Map each of the items in parallel and then fold the results, in item order, on the calling thread
+pub fn par_map_reduce<T, R, A, F, G>(items: Vec<T>, f: F, initial: A, reduce: G) -> A
+where
+    T: Send,
+    R: Send,
+    F: Fn(T) -> R + Sync,
+    G: FnMut(A, R) -> A,
+{
+    par_map(items, f).into_iter().fold(initial, reduce)
+}
+
+/// Call f for every pair of items, each pair once with the earlier item first, spreading the earlier items over
+/// the threads.  The results f gives are returned in order of the pairs, i.e. by first index then second.
+pub fn par_pairs<T, R, F>(items: &[T], f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn((usize, &T), (usize, &T)) -> Option<R> + Sync,
+{
+    let firsts: Vec<usize> = (0..items.len()).collect();
+    par_map(firsts, |first| {
+        items[first + 1..]
+            .iter()
+            .enumerate()
+            .filter_map(|(offset, second)| f((first, &items[first]), (first + 1 + offset, second)))
+            .collect::<Vec<R>>()
+    })
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn thread_count_parsed() {
+        assert_eq!(parse_thread_count("4"), Some(4));
+        assert_eq!(parse_thread_count(" 1\n"), Some(1));
+        assert_eq!(parse_thread_count("0"), None);
+        assert_eq!(parse_thread_count("all"), None);
+        assert!(thread_count() > 0);
+    }
+
+    #[test]
+    fn par_map_keeps_order() {
+        let items: Vec<usize> = (0..1000).collect();
+        let results = par_map(items, |i| i * 2);
+        assert_eq!(results, (0..1000).map(|i| i * 2).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn par_map_reduce_is_ordered() {
+        let items: Vec<usize> = (0..20).collect();
+        let joined = par_map_reduce(
+            items,
+            |i| i.to_string(),
+            String::new(),
+            |mut acc, s| {
+                acc.push_str(&s);
+                acc
+            },
+        );
+        assert_eq!(joined, (0..20).map(|i| i.to_string()).collect::<String>());
+        assert!(par_map(Vec::<usize>::new(), |i| i).is_empty());
+    }
+
+    #[test]
+    fn par_pairs_visits_each_pair_once() {
+        let items: Vec<usize> = (0..50).collect();
+        let pairs = par_pairs(&items, |(first, a), (second, b)| {
+            assert_eq!((first, second), (*a, *b));
+            Some((*a, *b))
+        });
+        let expected: Vec<(usize, usize)> = (0..50)
+            .flat_map(|a| ((a + 1)..50).map(move |b| (a, b)))
+            .collect();
+        assert_eq!(pairs, expected);
+        let sums_to_ten = par_pairs(&items, |(_, a), (_, b)| (a + b == 10).then_some(*a));
+        assert_eq!(sums_to_ten, vec![0, 1, 2, 3, 4]);
+        assert!(par_pairs(&[1], |_, _| Some(())).is_empty());
+    }
+}
@@ -0,0 +1,138 @@
+//! A few small parser combinators over a [`Cursor`] for lines with a fixed structure, e.g.
+//! `px, py, pz @ vx, vy, vz`.  A parser is anything that can be called with a `&mut Cursor` and returns a
+//! `Result`, the leaf parsers ([`tag`] and [`integer`]) skip any whitespace before what they read.
+
+use std::{error::Error, str::FromStr};
+
+use anyhow::Context;
+
+use crate::{AError, Cursor};
+
+fn skip_whitespace(cursor: &mut Cursor<'_>) {
+    while cursor.next_if(char::is_whitespace).is_some() {}
+}
+
+fn unexpected(cursor: &Cursor<'_>, expected: &str) -> AError {
+    AError::msg(format!(
+        "Expected {expected} at {} but found '{}'",
+        cursor.position(),
+        cursor.rest()
+    ))
+}
+
+/// Matches exactly the expected text, leaving the cursor where it was if it doesn't match
+pub fn tag(expected: &str) -> impl Fn(&mut Cursor<'_>) -> Result<(), AError> + '_ {
+    move |cursor: &mut Cursor<'_>| {
+        let start = cursor.mark();
+        skip_whitespace(cursor);
+        if expected.chars().all(|c| cursor.eat(c)) {
+            Ok(())
+        } else {
+            cursor.reset(start);
+            Err(unexpected(cursor, &format!("'{expected}'")))
+        }
+    }
+}
+
+/// Reads an optionally signed integer
+pub fn integer<T>(cursor: &mut Cursor<'_>) -> Result<T, AError>
+where
+    T: FromStr,
+    T::Err: Error + Send + Sync + 'static,
+{
+    let start = cursor.mark();
+    skip_whitespace(cursor);
+    let number_start = cursor.mark();
+    cursor.next_if(|c| c == '-' || c == '+');
+    if cursor.next_if(|c| c.is_ascii_digit()).is_none() {
+        cursor.reset(start);
+        return Err(unexpected(cursor, "an integer"));
+    }
+    while cursor.next_if(|c| c.is_ascii_digit()).is_some() {}
+    let number = cursor.since(number_start);
+    number
+        .parse::<T>()
+        .with_context(|| format!("Failed parsing integer: '{number}'"))
+}
+
+/// One or more items with the separator between each.  The list ends at the first separator not followed
+/// by an item, which is left unconsumed.
+pub fn separated_list<T, I, S>(
+    item: I,
+    separator: S,
+) -> impl Fn(&mut Cursor<'_>) -> Result<Vec<T>, AError>
+where
+    I: Fn(&mut Cursor<'_>) -> Result<T, AError>,
+    S: Fn(&mut Cursor<'_>) -> Result<(), AError>,
+{
+    move |cursor: &mut Cursor<'_>| {
+        let mut items = vec![item(cursor)?];
+        loop {
+            let before_separator = cursor.mark();
+            match separator(cursor).and_then(|_| item(cursor)) {
+                Ok(next) => items.push(next),
+                Err(_) => {
+                    cursor.reset(before_separator);
+                    return Ok(items);
+                }
+            }
+        }
+    }
+}
+
+/// Runs the prefix then the parser, giving only the parser's result
+pub fn preceded<T, P, Q>(prefix: P, parser: Q) -> impl Fn(&mut Cursor<'_>) -> Result<T, AError>
+where
+    P: Fn(&mut Cursor<'_>) -> Result<(), AError>,
+    Q: Fn(&mut Cursor<'_>) -> Result<T, AError>,
+{
+    move |cursor: &mut Cursor<'_>| {
+        prefix(cursor)?;
+        parser(cursor)
+    }
+}
+
+/// Parse the whole of the line, failing if anything other than whitespace is left over
+pub fn parse_complete<'a, T>(
+    line: &'a str,
+    parser: impl FnOnce(&mut Cursor<'a>) -> Result<T, AError>,
+) -> Result<T, AError> {
+    let mut cursor = Cursor::new(line);
+    let result = parser(&mut cursor).with_context(|| format!("Failed parsing '{line}'"))?;
+    skip_whitespace(&mut cursor);
+    if cursor.is_at_end() {
+        Ok(result)
+    } else {
+        Err(unexpected(&cursor, "the end of the line"))
+            .with_context(|| format!("Failed parsing '{line}'"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_structured_lines() {
+        let coords = separated_list(integer::<isize>, tag(","));
+        let (position, velocity) = parse_complete("19, 13, 30 @ -2,  1, -2", |cursor| {
+            Ok((coords(cursor)?, preceded(tag("@"), &coords)(cursor)?))
+        })
+        .unwrap();
+        assert_eq!(position, vec![19, 13, 30]);
+        assert_eq!(velocity, vec![-2, 1, -2]);
+
+        // the trailing separator is left for parse_complete to complain about
+        let error = parse_complete("1,2,", &coords).unwrap_err();
+        assert_eq!(
+            format!("{:#}", error),
+            "Failed parsing '1,2,': Expected the end of the line at 3 but found ','"
+        );
+        let error = parse_complete("1~2", preceded(tag(","), &coords)).unwrap_err();
+        assert_eq!(
+            format!("{:#}", error),
+            "Failed parsing '1~2': Expected ',' at 0 but found '1~2'"
+        );
+        assert!(parse_complete("300", integer::<u8>).is_err());
+    }
+}
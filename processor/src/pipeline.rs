@@ -0,0 +1,193 @@
+//! A builder for the parse, finalise then solve pipeline run by [`crate::process`] for each part, so that new
+//! options can be added to it without changing the signature every day calls, e.g.
+//!
+//! ```ignore
+//! Pipeline::builder()
+//!     .initial_state(Vec::new)
+//!     .parser(parse_line)
+//!     .finaliser(finalise_state)
+//!     .part1(|state| calc_result(perform_processing_1(state)?))
+//!     .part2(|state| calc_result(perform_processing_2(state)?))
+//!     .run()
+//!     .print();
+//! ```
+
+use std::fmt::Debug;
+
+use crate::{input_file, part_requested, process, AError};
+
+type Parser<'a, L> = Box<dyn FnMut(L, String) -> Result<L, AError> + 'a>;
+type Finaliser<'a, L, S> = Box<dyn FnMut(L) -> Result<S, AError> + 'a>;
+type Part<'a, S, R> = Box<dyn FnOnce(S) -> Result<R, AError> + 'a>;
+
+/// Each part is run on its own parse of the input, loaded from a fresh initial state in to `L` a line at a
+/// time and finalised in to `S`, giving its result `R1` or `R2`
+pub struct Pipeline<'a, L, S, R1 = (), R2 = ()> {
+    input: String,
+    initial_state: Option<Box<dyn FnMut() -> L + 'a>>,
+    parser: Option<Parser<'a, L>>,
+    finaliser: Option<Finaliser<'a, L, S>>,
+    part1: Option<Part<'a, S, R1>>,
+    part2: Option<Part<'a, S, R2>>,
+}
+
+impl<'a, L, S> Pipeline<'a, L, S> {
+    /// A pipeline reading input.txt, unless overridden (see [`input_file`]), with no parts yet
+    pub fn builder() -> Self {
+        Pipeline {
+            input: input_file("input.txt"),
+            initial_state: None,
+            parser: None,
+            finaliser: None,
+            part1: None,
+            part2: None,
+        }
+    }
+}
+
+impl<'a, L, S, R1, R2> Pipeline<'a, L, S, R1, R2> {
+    /// The file to read, or [`crate::STDIN_FILE_NAME`] for stdin
+    pub fn input(mut self, file_name: &str) -> Self {
+        self.input = file_name.to_string();
+        self
+    }
+
+    /// Makes the state that each part's parse starts from
+    pub fn initial_state(mut self, initial_state: impl FnMut() -> L + 'a) -> Self {
+        self.initial_state = Some(Box::new(initial_state));
+        self
+    }
+
+    pub fn parser(mut self, parse_line: impl FnMut(L, String) -> Result<L, AError> + 'a) -> Self {
+        self.parser = Some(Box::new(parse_line));
+        self
+    }
+
+    pub fn finaliser(mut self, finalise_state: impl FnMut(L) -> Result<S, AError> + 'a) -> Self {
+        self.finaliser = Some(Box::new(finalise_state));
+        self
+    }
+
+    pub fn part1<R>(
+        self,
+        solve: impl FnOnce(S) -> Result<R, AError> + 'a,
+    ) -> Pipeline<'a, L, S, R, R2> {
+        Pipeline {
+            input: self.input,
+            initial_state: self.initial_state,
+            parser: self.parser,
+            finaliser: self.finaliser,
+            part1: Some(Box::new(solve)),
+            part2: self.part2,
+        }
+    }
+
+    pub fn part2<R>(
+        self,
+        solve: impl FnOnce(S) -> Result<R, AError> + 'a,
+    ) -> Pipeline<'a, L, S, R1, R> {
+        Pipeline {
+            input: self.input,
+            initial_state: self.initial_state,
+            parser: self.parser,
+            finaliser: self.finaliser,
+            part1: self.part1,
+            part2: Some(Box::new(solve)),
+        }
+    }
+
+    fn run_part<R>(&mut self, solve: Part<'a, S, R>) -> Result<R, AError> {
+        let (Some(initial_state), Some(parser), Some(finaliser)) = (
+            self.initial_state.as_mut(),
+            self.parser.as_mut(),
+            self.finaliser.as_mut(),
+        ) else {
+            return Err(AError::msg(
+                "Pipeline needs an initial state, a parser and a finaliser",
+            ));
+        };
+        process(&self.input, initial_state(), parser, finaliser, solve, Ok)
+    }
+
+    /// Run each part given that hasn't been skipped (see [`part_requested`])
+    pub fn run(mut self) -> PipelineResults<R1, R2> {
+        let part1 = self
+            .part1
+            .take()
+            .filter(|_| part_requested(1))
+            .map(|solve| self.run_part(solve));
+        let part2 = self
+            .part2
+            .take()
+            .filter(|_| part_requested(2))
+            .map(|solve| self.run_part(solve));
+        PipelineResults { part1, part2 }
+    }
+}
+
+/// The result of each part that was run
+#[derive(Debug)]
+pub struct PipelineResults<R1, R2> {
+    pub part1: Option<Result<R1, AError>>,
+    pub part2: Option<Result<R2, AError>>,
+}
+
+fn print_result<R: Debug>(part: u8, result: &Option<Result<R, AError>>) {
+    match result {
+        Some(Ok(res)) => println!("Result {part}: {:?}", res),
+        Some(Err(e)) => println!("Error on {part}: {}", e),
+        None => {}
+    }
+}
+
+impl<R1: Debug, R2: Debug> PipelineResults<R1, R2> {
+    /// Print the results as the days always have, `Result N: ...` or `Error on N: ...`
+    pub fn print(&self) {
+        print_result(1, &self.part1);
+        print_result(2, &self.part2);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parts_run_on_their_own_parse() {
+        let file = std::env::temp_dir().join(format!("pipeline-{}.txt", std::process::id()));
+        std::fs::write(&file, "3\n4\n\n5\n").unwrap();
+        let mut parses = 0;
+        let results = Pipeline::builder()
+            .input(file.to_str().unwrap())
+            .initial_state(|| {
+                parses += 1;
+                Vec::new()
+            })
+            .parser(|mut numbers: Vec<i64>, line| {
+                if !line.is_empty() {
+                    numbers.push(line.parse()?);
+                }
+                Ok(numbers)
+            })
+            .finaliser(Ok)
+            .part1(|numbers| Ok(numbers.iter().sum::<i64>()))
+            .part2(|numbers| Ok(format!("{} numbers", numbers.len())))
+            .run();
+        assert_eq!(results.part1.unwrap().unwrap(), 12);
+        assert_eq!(results.part2.unwrap().unwrap(), "3 numbers");
+        assert_eq!(parses, 2);
+
+        let results = Pipeline::builder()
+            .input(file.to_str().unwrap())
+            .initial_state(|| 0)
+            .parser(|_, line| Ok(line.len()))
+            .part1(|_: usize| Ok(0))
+            .run();
+        assert_eq!(
+            results.part1.unwrap().unwrap_err().to_string(),
+            "Pipeline needs an initial state, a parser and a finaliser"
+        );
+        assert!(results.part2.is_none());
+        std::fs::remove_file(&file).unwrap();
+    }
+}
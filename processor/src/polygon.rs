@@ -0,0 +1,80 @@
+//! Measuring the polygon with integer vertices given in order around its edge (the last joining back to the
+//! first), e.g. day18's trench: its area by the shoelace formula and, from that, how many whole points lie
+//! inside it by Pick's theorem.
+
+use num::integer::gcd;
+
+use crate::{polyline::Point, AError};
+
+/// The edges, each vertex paired with the next and the last with the first
+fn edges(points: &[Point]) -> impl Iterator<Item = (Point, Point)> + '_ {
+    points
+        .iter()
+        .copied()
+        .zip(points.iter().copied().cycle().skip(1))
+}
+
+/// Twice the area, which is always a whole number
+pub fn twice_area(points: &[Point]) -> usize {
+    edges(points)
+        .map(|((x1, y1), (x2, y2))| x1 * y2 - x2 * y1)
+        .sum::<isize>()
+        .unsigned_abs()
+}
+
+/// The area enclosed by the vertices in either direction round, using the shoelace formula, rounded down if
+/// it's a half (which can only happen when some edges are diagonal)
+pub fn area(points: &[Point]) -> usize {
+    twice_area(points) / 2
+}
+
+/// The number of whole points on the edges, which is the length of the edges when they're all horizontal or
+/// vertical
+pub fn perimeter(points: &[Point]) -> usize {
+    edges(points)
+        .map(|((x1, y1), (x2, y2))| gcd(x1.abs_diff(x2), y1.abs_diff(y2)))
+        .sum()
+}
+
+/// Pick's theorem, `area = interior + boundary / 2 - 1`, turned round to give the number of whole points
+/// strictly inside from the [`area`] and the number on the [`perimeter`].  An error if the boundary is too long
+/// for the area, as it can't be for the same polygon.
+pub fn interior_points(area: usize, boundary: usize) -> Result<usize, AError> {
+    (area + 1).checked_sub(boundary / 2).ok_or_else(|| {
+        AError::msg(format!(
+            "A boundary of {boundary} points can't enclose an area of {area}"
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn measured_by_shoelace_and_pick() {
+        // an L shape, 4 by 4 less the 2 by 2 top right
+        let l_shape = [(0, 0), (2, 0), (2, 2), (4, 2), (4, 4), (0, 4)];
+        assert_eq!(area(&l_shape), 12);
+        assert_eq!(perimeter(&l_shape), 16);
+        assert_eq!(interior_points(12, 16).unwrap(), 5);
+        assert!(interior_points(0, 4).is_err());
+
+        // same the other way round, and closed by repeating the first point
+        let mut reversed = l_shape.to_vec();
+        reversed.reverse();
+        reversed.push(reversed[0]);
+        assert_eq!((area(&reversed), perimeter(&reversed)), (12, 16));
+
+        // diagonal edges only count the whole points on them
+        let triangle = [(0, 0), (4, 0), (0, 4)];
+        assert_eq!(twice_area(&triangle), 16);
+        assert_eq!(perimeter(&triangle), 12);
+        assert_eq!(
+            interior_points(area(&triangle), perimeter(&triangle)).unwrap(),
+            3
+        );
+        assert_eq!(twice_area(&[(0, 0), (1, 0), (0, 1)]), 1);
+        assert_eq!(area(&[]), 0);
+    }
+}
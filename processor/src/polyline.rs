@@ -0,0 +1,153 @@
+use crate::{AError, Direction};
+
+pub type Point = (isize, isize);
+
+/// An ordered list of integer points joined by straight segments, e.g. the path dug out by a list of
+/// instructions.  Lengths are measured as grid (taxicab) distances.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Polyline {
+    points: Vec<Point>,
+}
+
+/// Which side of the line through a to b that c is on: positive for anticlockwise, negative for clockwise,
+/// 0 if on the line
+fn orientation(a: Point, b: Point, c: Point) -> isize {
+    ((b.0 - a.0) * (c.1 - a.1) - (b.1 - a.1) * (c.0 - a.0)).signum()
+}
+
+/// Whether c, known to be on the line through a and b, lies within the segment a to b
+fn within_segment(a: Point, b: Point, c: Point) -> bool {
+    c.0 >= a.0.min(b.0) && c.0 <= a.0.max(b.0) && c.1 >= a.1.min(b.1) && c.1 <= a.1.max(b.1)
+}
+
+fn segments_intersect((a, b): (Point, Point), (c, d): (Point, Point)) -> bool {
+    let (o1, o2) = (orientation(a, b, c), orientation(a, b, d));
+    let (o3, o4) = (orientation(c, d, a), orientation(c, d, b));
+    (o1 != o2 && o3 != o4)
+        || (o1 == 0 && within_segment(a, b, c))
+        || (o2 == 0 && within_segment(a, b, d))
+        || (o3 == 0 && within_segment(c, d, a))
+        || (o4 == 0 && within_segment(c, d, b))
+}
+
+impl Polyline {
+    pub fn new(points: Vec<Point>) -> Self {
+        Polyline { points }
+    }
+
+    /// Follow the instructions from the start, adding a point at the end of each
+    pub fn from_instructions<I>(start: Point, instructions: I) -> Self
+    where
+        I: IntoIterator<Item = (Direction, usize)>,
+    {
+        let mut points = vec![start];
+        let mut current = start;
+        for (direction, steps) in instructions {
+            let (delta_x, delta_y) = direction.deltas();
+            current = (
+                current.0 + delta_x * steps as isize,
+                current.1 + delta_y * steps as isize,
+            );
+            points.push(current);
+        }
+        Polyline { points }
+    }
+
+    /// The instructions to follow the line from its first point.  Fails if any segment is diagonal.
+    pub fn to_instructions(&self) -> Result<Vec<(Direction, usize)>, AError> {
+        self.segments()
+            .filter(|(from, to)| from != to)
+            .map(|(from, to)| {
+                let direction = match (to.0 - from.0, to.1 - from.1) {
+                    (0, dy) if dy < 0 => Direction::North,
+                    (0, _) => Direction::South,
+                    (dx, 0) if dx > 0 => Direction::East,
+                    (_, 0) => Direction::West,
+                    _ => {
+                        return Err(AError::msg(format!(
+                            "Segment {from:?} to {to:?} is not horizontal or vertical"
+                        )))
+                    }
+                };
+                Ok((direction, from.0.abs_diff(to.0) + from.1.abs_diff(to.1)))
+            })
+            .collect()
+    }
+
+    pub fn points(&self) -> &[Point] {
+        &self.points
+    }
+
+    /// Pairs of consecutive points
+    pub fn segments(&self) -> impl Iterator<Item = (Point, Point)> + '_ {
+        self.points.windows(2).map(|pair| (pair[0], pair[1]))
+    }
+
+    /// Whether the line finishes where it started
+    pub fn is_closed(&self) -> bool {
+        self.points.len() > 1 && self.points.first() == self.points.last()
+    }
+
+    pub fn perimeter(&self) -> usize {
+        self.segments()
+            .map(|(from, to)| from.0.abs_diff(to.0) + from.1.abs_diff(to.1))
+            .sum()
+    }
+
+    /// The minimum and maximum corners of the box containing every point, None if there are no points
+    pub fn bounding_box(&self) -> Option<(Point, Point)> {
+        let first = *self.points.first()?;
+        Some(self.points.iter().fold(
+            (first, first),
+            |((min_x, min_y), (max_x, max_y)), (x, y)| {
+                (
+                    (min_x.min(*x), min_y.min(*y)),
+                    (max_x.max(*x), max_y.max(*y)),
+                )
+            },
+        ))
+    }
+
+    /// Whether any two segments touch other than consecutive segments meeting at their shared point (and
+    /// the last meeting the first if the line is closed)
+    pub fn self_intersects(&self) -> bool {
+        let segments: Vec<(Point, Point)> = self.segments().collect();
+        let last = segments.len().saturating_sub(1);
+        (0..segments.len()).any(|i| {
+            ((i + 2)..segments.len())
+                .filter(|j| !(self.is_closed() && i == 0 && *j == last))
+                .any(|j| segments_intersect(segments[i], segments[j]))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn polyline_from_instructions() {
+        let instructions = vec![
+            (Direction::East, 6),
+            (Direction::South, 5),
+            (Direction::West, 6),
+            (Direction::North, 5),
+        ];
+        let polyline = Polyline::from_instructions((0, 0), instructions.clone());
+        assert_eq!(polyline.points().len(), 5);
+        assert!(polyline.is_closed());
+        assert_eq!(polyline.perimeter(), 22);
+        assert_eq!(polyline.bounding_box(), Some(((0, 0), (6, 5))));
+        assert!(!polyline.self_intersects());
+        assert_eq!(polyline.to_instructions().unwrap(), instructions);
+
+        // a figure of eight crosses itself
+        let crossing = Polyline::new(vec![(0, 0), (2, 0), (2, 2), (1, 2), (1, -1)]);
+        assert!(crossing.self_intersects());
+        assert!(!crossing.is_closed());
+        assert!(Polyline::new(vec![(0, 0), (1, 1)])
+            .to_instructions()
+            .is_err());
+        assert_eq!(Polyline::default().bounding_box(), None);
+    }
+}
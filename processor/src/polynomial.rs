@@ -0,0 +1,112 @@
+//! Exact polynomial fitting by Lagrange interpolation, for puzzles where some count grows as a polynomial of
+//! how many times a pattern has repeated (e.g. day21's garden plots, quadratic in the number of gardens
+//! crossed).  Everything is done in rationals over `i128` so that nothing is lost to rounding, and a fit is
+//! only trusted once it has predicted samples that weren't used to make it.
+
+use num::{rational::Ratio, Zero};
+
+use crate::AError;
+
+pub type Rational = Ratio<i128>;
+
+/// The polynomial of lowest degree through the points, kept in Lagrange form
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Polynomial {
+    points: Vec<(i128, i128)>,
+}
+
+impl Polynomial {
+    /// Through the points, which must all have different xs.  The degree is at most one less than the number
+    /// of points.
+    pub fn through(points: &[(i128, i128)]) -> Result<Self, AError> {
+        if points.is_empty() {
+            return Err(AError::msg("No points to fit a polynomial through"));
+        }
+        for (i, (x, _)) in points.iter().enumerate() {
+            if points[..i].iter().any(|(other, _)| other == x) {
+                return Err(AError::msg(format!("More than one point at x={x}")));
+            }
+        }
+        Ok(Polynomial {
+            points: points.to_vec(),
+        })
+    }
+
+    pub fn evaluate(&self, x: i128) -> Rational {
+        self.points
+            .iter()
+            .enumerate()
+            .map(|(i, (x_i, y_i))| {
+                self.points
+                    .iter()
+                    .enumerate()
+                    .filter(|(j, _)| *j != i)
+                    .fold(Rational::from_integer(*y_i), |term, (_, (x_j, _))| {
+                        term * Rational::new(x - x_j, x_i - x_j)
+                    })
+            })
+            .fold(Rational::zero(), |sum, term| sum + term)
+    }
+
+    /// The value at x, which is an error if it isn't a whole number
+    pub fn evaluate_integer(&self, x: i128) -> Result<i128, AError> {
+        let value = self.evaluate(x);
+        if value.is_integer() {
+            Ok(value.to_integer())
+        } else {
+            Err(AError::msg(format!(
+                "Polynomial gives {value} at x={x}, not a whole number"
+            )))
+        }
+    }
+}
+
+/// Fit a polynomial of the degree to the first `degree + 1` samples, and check that it gives the rest of the
+/// samples exactly.  At least one sample has to be held out for the check.
+pub fn fit_polynomial(samples: &[(i128, i128)], degree: usize) -> Result<Polynomial, AError> {
+    if samples.len() <= degree + 1 {
+        return Err(AError::msg(format!(
+            "Need more than {} samples to fit and check a degree {degree} polynomial, only have {}",
+            degree + 1,
+            samples.len()
+        )));
+    }
+    let (fitted, held_out) = samples.split_at(degree + 1);
+    let polynomial = Polynomial::through(fitted)?;
+    for (x, y) in held_out {
+        let predicted = polynomial.evaluate(*x);
+        if predicted != Rational::from_integer(*y) {
+            return Err(AError::msg(format!(
+                "Degree {degree} polynomial predicts {predicted} at x={x} but the sample is {y}"
+            )));
+        }
+    }
+    Ok(polynomial)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn polynomials_fitted_and_checked() {
+        // 3x^2 - 2x + 7
+        let samples: Vec<(i128, i128)> = (0..5).map(|x| (x, 3 * x * x - 2 * x + 7)).collect();
+        let quadratic = fit_polynomial(&samples, 2).unwrap();
+        assert_eq!(quadratic.evaluate_integer(202300).unwrap(), 122_775_465_407);
+        assert_eq!(quadratic.evaluate_integer(-3).unwrap(), 40);
+
+        let error = fit_polynomial(&samples, 1).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Degree 1 polynomial predicts 9 at x=2 but the sample is 15"
+        );
+        assert!(fit_polynomial(&samples[..3], 2).is_err());
+        assert!(Polynomial::through(&[(1, 2), (1, 3)]).is_err());
+
+        // x / 2
+        let half = Polynomial::through(&[(0, 0), (2, 1)]).unwrap();
+        assert_eq!(half.evaluate(3), Rational::new(3, 2));
+        assert!(half.evaluate_integer(3).is_err());
+    }
+}
@@ -0,0 +1,101 @@
+//! Spans around the heavier algorithms, so that where a day's time goes shows up in a flamegraph
+//! (`tracing-flame`) or a trace for Perfetto (`tracing-chrome`) without adding timers by hand.  All of it is
+//! behind the `profile` feature; without it [`profile_span!`] expands to nothing and [`start_profiling`]
+//! does nothing.
+
+#[cfg(feature = "profile")]
+pub use tracing;
+
+/// Environment variable naming the file to write the profile to
+pub const PROFILE_ENV: &str = "AOC_PROFILE";
+
+/// Enter a span for the rest of the enclosing block, e.g. `profile_span!("dijkstra");`.  Fields can follow
+/// the name as they would for `tracing::info_span!`.
+#[cfg(feature = "profile")]
+#[macro_export]
+macro_rules! profile_span {
+    ($($args:tt)*) => {
+        let _profile_span = $crate::profile::tracing::info_span!($($args)*).entered();
+    };
+}
+
+#[cfg(not(feature = "profile"))]
+#[macro_export]
+macro_rules! profile_span {
+    ($($args:tt)*) => {};
+}
+
+#[cfg(feature = "profile")]
+mod enabled {
+    use std::{fs::File, io::BufWriter, path::Path};
+
+    use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+
+    use super::PROFILE_ENV;
+
+    /// Keeps the profile being written, flushing it when dropped at the end of the run
+    pub enum Profiling {
+        Off,
+        Flame(tracing_flame::FlushGuard<BufWriter<File>>),
+        Chrome(tracing_chrome::FlushGuard),
+    }
+
+    /// A `.json` file gets a Chrome trace (which Perfetto opens), anything else folded stacks for
+    /// `inferno-flamegraph`
+    pub(super) fn is_chrome_trace(path: &Path) -> bool {
+        path.extension()
+            .is_some_and(|extension| extension == "json")
+    }
+
+    /// Start writing spans to the file named by [`PROFILE_ENV`], if it is set.  Hold on to the result for
+    /// as long as the day runs.
+    pub fn start_profiling() -> Profiling {
+        let Some(path) = std::env::var_os(PROFILE_ENV) else {
+            return Profiling::Off;
+        };
+        let path = Path::new(&path);
+        if is_chrome_trace(path) {
+            let (layer, guard) = tracing_chrome::ChromeLayerBuilder::new()
+                .file(path)
+                .include_args(true)
+                .build();
+            tracing_subscriber::registry().with(layer).init();
+            return Profiling::Chrome(guard);
+        }
+        match tracing_flame::FlameLayer::with_file(path) {
+            Ok((layer, guard)) => {
+                tracing_subscriber::registry().with(layer).init();
+                Profiling::Flame(guard)
+            }
+            Err(e) => {
+                eprintln!("Not profiling, failed creating {}: {e}", path.display());
+                Profiling::Off
+            }
+        }
+    }
+}
+
+#[cfg(feature = "profile")]
+pub use enabled::{start_profiling, Profiling};
+
+#[cfg(not(feature = "profile"))]
+pub struct Profiling;
+
+#[cfg(not(feature = "profile"))]
+pub fn start_profiling() -> Profiling {
+    Profiling
+}
+
+#[cfg(all(test, feature = "profile"))]
+mod tests {
+    use std::path::Path;
+
+    use super::enabled::is_chrome_trace;
+
+    #[test]
+    fn profile_format_chosen_by_extension() {
+        assert!(is_chrome_trace(Path::new("day17.json")));
+        assert!(!is_chrome_trace(Path::new("day17.folded")));
+        assert!(!is_chrome_trace(Path::new("day17")));
+    }
+}
@@ -0,0 +1,260 @@
+//! How far each cell of a grid is from a set of starts, and from that how many cells can be reached in an
+//! exact number of steps.  Stepping back and forth between two cells takes two steps, so a cell at distance
+//! `d` can be ended on after `n` steps whenever `d <= n` and `d` has the same parity as `n`.
+
+use std::collections::{HashSet, VecDeque};
+
+use crate::{adjacent_coords_cartesian, Cells};
+
+type Coord = (usize, usize);
+
+/// Shortest number of north/east/south/west steps to each cell from the nearest start, None for cells that
+/// can't be reached.  Only cells that are passable can be stepped on (starts included).
+pub fn bfs_distances<T, F>(grid: &Cells<T>, starts: &[Coord], passable: F) -> Cells<Option<usize>>
+where
+    F: Fn(&T) -> bool,
+{
+    let (width, height) = grid.side_lengths;
+    let mut distances = Cells::with_dimension(width, height, None);
+    let mut queue: VecDeque<(Coord, usize)> = VecDeque::default();
+    for start in starts {
+        if grid.get_opt(start.0, start.1).is_some_and(&passable) {
+            queue.push_back((*start, 0));
+        }
+    }
+    while let Some((coord, distance)) = queue.pop_front() {
+        let cell = &mut distances[coord];
+        if cell.is_some() {
+            continue;
+        }
+        *cell = Some(distance);
+        adjacent_coords_cartesian(&coord, &grid.side_lengths)
+            .into_iter()
+            .filter(|(x, y)| distances[(*x, *y)].is_none() && passable(&grid[(*x, *y)]))
+            .for_each(|next| queue.push_back((next, distance + 1)));
+    }
+    distances
+}
+
+/// The cells reached by [`Cells::flood_fill`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FloodFill {
+    pub visited: HashSet<Coord>,
+    /// Whether any visited cell is on the edge of the grid, i.e. the region isn't enclosed
+    pub touched_edge: bool,
+}
+
+impl<T> Cells<T> {
+    /// Every passable cell connected to the start by north/east/south/west steps
+    pub fn flood_fill<F>(&self, start: Coord, passable: F) -> FloodFill
+    where
+        F: Fn(&T) -> bool,
+    {
+        self.flood_fill_from(&[start], passable)
+    }
+
+    /// [`Cells::flood_fill`] from several seeds at once, starts that aren't passable are ignored
+    pub fn flood_fill_from<F>(&self, starts: &[Coord], passable: F) -> FloodFill
+    where
+        F: Fn(&T) -> bool,
+    {
+        let (width, height) = self.side_lengths;
+        let mut visited: HashSet<Coord> = HashSet::default();
+        let mut queue: VecDeque<Coord> = starts
+            .iter()
+            .filter(|(x, y)| self.get_opt(*x, *y).is_some_and(&passable))
+            .copied()
+            .collect();
+        let mut touched_edge = false;
+        while let Some(coord) = queue.pop_front() {
+            if !visited.insert(coord) {
+                continue;
+            }
+            let (x, y) = coord;
+            touched_edge = touched_edge || x == 0 || y == 0 || x + 1 == width || y + 1 == height;
+            adjacent_coords_cartesian(&coord, &self.side_lengths)
+                .into_iter()
+                .filter(|next| !visited.contains(next) && passable(&self[*next]))
+                .for_each(|next| queue.push_back(next));
+        }
+        FloodFill {
+            visited,
+            touched_edge,
+        }
+    }
+}
+
+/// The cells around the edge of the grid that aren't walls, i.e. the ways in and out of a maze, in reading
+/// order (so the openings along the top come first and those along the bottom last)
+pub fn find_openings<T, F>(cells: &Cells<T>, is_wall: F) -> Vec<Coord>
+where
+    F: Fn(&T) -> bool,
+{
+    let (width, height) = cells.side_lengths;
+    cells
+        .iter()
+        .filter(|((x, y), cell)| {
+            (*x == 0 || *y == 0 || x + 1 == width || y + 1 == height) && !is_wall(cell)
+        })
+        .map(|(coord, _)| coord)
+        .collect()
+}
+
+/// Cells reachable within a number of steps, split by the parity of their distance
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParityCounts {
+    /// Cells at an even distance, i.e. those that can be ended on after an even number of steps
+    pub even: usize,
+    pub odd: usize,
+    /// Number of cells at each distance from 0 up to the maximum
+    pub by_distance: Vec<usize>,
+}
+
+impl ParityCounts {
+    /// Cells that can be ended on after exactly the given number of steps (up to the maximum counted)
+    pub fn reachable_in(&self, steps: usize) -> usize {
+        self.by_distance
+            .iter()
+            .take(steps + 1)
+            .skip(steps % 2)
+            .step_by(2)
+            .sum()
+    }
+}
+
+/// Count the cells reachable within `max_steps` of the starts.  The parity rule only holds if the starts'
+/// coordinates all sum to the same parity, as a cell could otherwise be an odd distance from one start and
+/// an even distance from another.
+pub fn count_reachable_by_parity<T, F>(
+    grid: &Cells<T>,
+    starts: &[Coord],
+    max_steps: usize,
+    passable: F,
+) -> ParityCounts
+where
+    F: Fn(&T) -> bool,
+{
+    let mut by_distance = vec![0; max_steps + 1];
+    bfs_distances(grid, starts, passable)
+        .iter()
+        .filter_map(|(_, distance)| *distance)
+        .filter(|distance| *distance <= max_steps)
+        .for_each(|distance| by_distance[distance] += 1);
+    let (even, odd) =
+        by_distance
+            .iter()
+            .enumerate()
+            .fold((0, 0), |(even, odd), (distance, count)| {
+                if distance % 2 == 0 {
+                    (even + count, odd)
+                } else {
+                    (even, odd + count)
+                }
+            });
+    ParityCounts {
+        even,
+        odd,
+        by_distance,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Tile {
+        Plot,
+        Rock,
+    }
+
+    crate::cell_chars!(Tile {
+        '.' | 'S' => Plot,
+        '#' => Rock,
+    });
+
+    const SAMPLE: [&str; 11] = [
+        "...........",
+        ".....###.#.",
+        ".###.##..#.",
+        "..#.#...#..",
+        "....#.#....",
+        ".##..S####.",
+        ".##..#...#.",
+        ".......##..",
+        ".##.#.####.",
+        ".##..##.##.",
+        "...........",
+    ];
+
+    /// Step every position to all of its neighbours, the number of steps times
+    fn simulate(grid: &Cells<Tile>, starts: &[Coord], steps: usize) -> usize {
+        let mut positions: HashSet<Coord> = starts.iter().copied().collect();
+        for _ in 0..steps {
+            positions = positions
+                .iter()
+                .flat_map(|coord| adjacent_coords_cartesian(coord, &grid.side_lengths))
+                .filter(|(x, y)| *grid.get(*x, *y).unwrap() == Tile::Plot)
+                .collect();
+        }
+        positions.len()
+    }
+
+    #[test]
+    fn reachable_counts_match_simulation() {
+        let grid = Cells::<Tile>::parse_lines(SAMPLE).unwrap();
+        let corners = [(0, 0), (10, 0), (0, 10), (10, 10)];
+        for starts in [&[(5, 5)][..], &corners[..]] {
+            for steps in 0..=20 {
+                let counts =
+                    count_reachable_by_parity(&grid, starts, steps, |tile| *tile == Tile::Plot);
+                let expected = simulate(&grid, starts, steps);
+                let by_parity = if steps % 2 == 0 {
+                    counts.even
+                } else {
+                    counts.odd
+                };
+                assert_eq!(by_parity, expected, "{steps} steps from {starts:?}");
+                assert_eq!(counts.reachable_in(steps), expected);
+            }
+        }
+        let counts = count_reachable_by_parity(&grid, &[(5, 5)], 6, |tile| *tile == Tile::Plot);
+        assert_eq!(counts.even, 16);
+        assert_eq!(counts.by_distance[..3], [1, 2, 3]);
+
+        let distances = bfs_distances(&grid, &[(5, 5)], |tile| *tile == Tile::Plot);
+        assert_eq!(*distances.get(5, 5).unwrap(), Some(0));
+        assert_eq!(*distances.get(5, 1).unwrap(), None);
+    }
+
+    #[test]
+    fn flood_fill_finds_regions() {
+        let grid = Cells::<Tile>::parse_lines(["#####", "#..##", "#.#..", "#####"]).unwrap();
+        let enclosed = grid.flood_fill((1, 1), |tile| *tile == Tile::Plot);
+        assert_eq!(enclosed.visited, HashSet::from([(1, 1), (2, 1), (1, 2)]));
+        assert!(!enclosed.touched_edge);
+
+        let open = grid.flood_fill((3, 2), |tile| *tile == Tile::Plot);
+        assert_eq!(open.visited.len(), 2);
+        assert!(open.touched_edge);
+
+        let both = grid.flood_fill_from(&[(1, 1), (3, 2), (0, 0)], |tile| *tile == Tile::Plot);
+        assert_eq!(both.visited.len(), 5);
+        assert!(grid
+            .flood_fill((0, 0), |tile| *tile == Tile::Plot)
+            .visited
+            .is_empty());
+    }
+
+    #[test]
+    fn openings_found_on_the_edge() {
+        let maze =
+            Cells::<Tile>::parse_lines(["##.##", "#...#", "....#", "#.#.#", "###.#"]).unwrap();
+        let is_wall = |tile: &Tile| *tile == Tile::Rock;
+        assert_eq!(find_openings(&maze, is_wall), vec![(2, 0), (0, 2), (3, 4)]);
+        let closed = Cells::<Tile>::parse_lines(["###", "#.#", "###"]).unwrap();
+        assert!(find_openings(&closed, is_wall).is_empty());
+    }
+}
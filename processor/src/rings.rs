@@ -0,0 +1,114 @@
+//! Square rings around a centre and the spiral made by visiting them in turn.  A ring of radius `r` is
+//! every coordinate at a chessboard distance of `r`, starting just north of the south east corner and going
+//! anticlockwise (north, west, south then east, with y increasing downwards), so that consecutive rings
+//! join up in to the usual memory spiral:
+//!
+//! ```text
+//! 5 4 3
+//! 6 1 2
+//! 7 8 9 10
+//! ```
+
+use crate::Cells;
+
+/// The coordinates of the ring, the centre alone for a radius of 0
+pub fn ring(centre: (isize, isize), radius: usize) -> impl Iterator<Item = (isize, isize)> {
+    let (centre_x, centre_y) = centre;
+    let r = radius as isize;
+    let side = 2 * radius;
+    (0..(8 * radius).max(1)).map(move |i| {
+        if radius == 0 {
+            return centre;
+        }
+        let offset = (i % side) as isize;
+        let (x, y) = match i / side {
+            0 => (r, r - 1 - offset),
+            1 => (r - 1 - offset, -r),
+            2 => (-r, -r + 1 + offset),
+            _ => (-r + 1 + offset, r),
+        };
+        (centre_x + x, centre_y + y)
+    })
+}
+
+/// Every coordinate, ring by ring outwards from the centre
+pub fn spiral(centre: (isize, isize)) -> impl Iterator<Item = (isize, isize)> {
+    (0..).flat_map(move |radius| ring(centre, radius))
+}
+
+impl<T> Cells<T> {
+    fn in_grid(&self, (x, y): (isize, isize)) -> Option<(usize, usize)> {
+        self.in_bounds(x, y).then_some((x as usize, y as usize))
+    }
+
+    /// The cells on the ring around the centre, skipping any beyond the edges of the grid
+    pub fn ring_cells(
+        &self,
+        centre: (usize, usize),
+        radius: usize,
+    ) -> impl Iterator<Item = ((usize, usize), &T)> {
+        ring((centre.0 as isize, centre.1 as isize), radius)
+            .filter_map(|coord| self.in_grid(coord))
+            .map(|(x, y)| ((x, y), &self[(x, y)]))
+    }
+
+    /// All of the cells, ring by ring outwards from the centre
+    pub fn spiral_cells(
+        &self,
+        centre: (usize, usize),
+    ) -> impl Iterator<Item = ((usize, usize), &T)> {
+        let (width, height) = self.side_lengths;
+        let max_radius = [
+            centre.0,
+            width.saturating_sub(centre.0 + 1),
+            centre.1,
+            height.saturating_sub(centre.1 + 1),
+        ]
+        .into_iter()
+        .max()
+        .unwrap();
+        (0..=max_radius).flat_map(move |radius| self.ring_cells(centre, radius))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn rings_and_spirals() {
+        assert_eq!(ring((3, 4), 0).collect::<Vec<_>>(), vec![(3, 4)]);
+        assert_eq!(ring((0, 0), 3).count(), 24);
+        assert_eq!(
+            spiral((0, 0)).take(10).collect::<Vec<_>>(),
+            vec![
+                (0, 0),
+                (1, 0),
+                (1, -1),
+                (0, -1),
+                (-1, -1),
+                (-1, 0),
+                (-1, 1),
+                (0, 1),
+                (1, 1),
+                (2, 1)
+            ]
+        );
+
+        let cells = Cells::with_dimension(4, 3, 0);
+        assert_eq!(
+            cells
+                .ring_cells((0, 0), 1)
+                .map(|(coord, _)| coord)
+                .collect::<Vec<_>>(),
+            vec![(1, 0), (0, 1), (1, 1)]
+        );
+        let spiral: Vec<(usize, usize)> =
+            cells.spiral_cells((1, 1)).map(|(coord, _)| coord).collect();
+        assert_eq!(spiral.len(), 12);
+        assert_eq!(spiral.iter().collect::<HashSet<_>>().len(), 12);
+        assert_eq!(spiral[..3], [(1, 1), (2, 1), (2, 0)]);
+    }
+}
@@ -0,0 +1,125 @@
+//! A set of small indexes (below [`SmallBitSet::CAPACITY`]) held in the bits of a `u128`, for searches that keep
+//! track of what they've visited on each branch - e.g. the junctions on a path through day23's maze.  Copying
+//! one is as cheap as copying a number, where cloning a `HashSet` per branch dominates such searches.
+
+/// A set of the indexes 0 to 127, e.g. of the [`crate::graph::NodeId`]s of a small graph
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SmallBitSet(u128);
+
+impl SmallBitSet {
+    /// One more than the largest index that can be held
+    pub const CAPACITY: usize = u128::BITS as usize;
+
+    /// Panics if the index is too big to be held
+    fn bit(index: usize) -> u128 {
+        assert!(
+            index < Self::CAPACITY,
+            "{index} is too big for a small bit set"
+        );
+        1 << index
+    }
+
+    pub fn from_bits(bits: u128) -> Self {
+        SmallBitSet(bits)
+    }
+
+    pub fn bits(&self) -> u128 {
+        self.0
+    }
+
+    /// Whether the index is in the set, never for an index too big to be held
+    pub fn contains(&self, index: usize) -> bool {
+        index < Self::CAPACITY && self.0 & Self::bit(index) != 0
+    }
+
+    /// Whether the index was newly added.  Panics if it's too big to be held.
+    pub fn insert(&mut self, index: usize) -> bool {
+        let added = !self.contains(index);
+        self.0 |= Self::bit(index);
+        added
+    }
+
+    /// Whether the index was present
+    pub fn remove(&mut self, index: usize) -> bool {
+        let present = self.contains(index);
+        if present {
+            self.0 &= !Self::bit(index);
+        }
+        present
+    }
+
+    /// A copy of the set with the index added, for handing on to the next branch of a search
+    pub fn with(&self, index: usize) -> Self {
+        SmallBitSet(self.0 | Self::bit(index))
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Whether every index in this set is also in the other
+    pub fn is_subset(&self, other: &SmallBitSet) -> bool {
+        self.0 & !other.0 == 0
+    }
+
+    pub fn union(&self, other: &SmallBitSet) -> Self {
+        SmallBitSet(self.0 | other.0)
+    }
+
+    pub fn intersection(&self, other: &SmallBitSet) -> Self {
+        SmallBitSet(self.0 & other.0)
+    }
+
+    /// The indexes in increasing order
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..Self::CAPACITY).filter(|index| self.contains(*index))
+    }
+}
+
+impl FromIterator<usize> for SmallBitSet {
+    fn from_iter<I: IntoIterator<Item = usize>>(indexes: I) -> Self {
+        indexes
+            .into_iter()
+            .fold(SmallBitSet::default(), |set, index| set.with(index))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn indexes_held_as_bits() {
+        let mut set = SmallBitSet::default();
+        assert!(set.is_empty());
+        assert!(set.insert(3));
+        assert!(!set.insert(3));
+        assert!(set.insert(127));
+        assert!(set.contains(127) && !set.contains(4) && !set.contains(200));
+        assert_eq!(set.len(), 2);
+        assert_eq!(set.bits(), 1 << 127 | 0b1000);
+        assert!(set.remove(127));
+        assert!(!set.remove(127));
+        assert!(!set.remove(200));
+        assert_eq!(set.with(0).iter().collect::<Vec<_>>(), vec![0, 3]);
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![3]);
+
+        let small: SmallBitSet = [1, 5].into_iter().collect();
+        let large: SmallBitSet = [1, 5, 64, 100].into_iter().collect();
+        assert!(small.is_subset(&large) && !large.is_subset(&small));
+        assert!(SmallBitSet::default().is_subset(&small));
+        assert_eq!(small.union(&set).iter().collect::<Vec<_>>(), vec![1, 3, 5]);
+        assert_eq!(large.intersection(&small), small);
+        assert_eq!(SmallBitSet::from_bits(0b110), [1, 2].into_iter().collect());
+    }
+
+    #[test]
+    #[should_panic(expected = "128 is too big for a small bit set")]
+    fn too_big_to_hold() {
+        SmallBitSet::default().insert(SmallBitSet::CAPACITY);
+    }
+}
@@ -0,0 +1,222 @@
+use std::fmt::Display;
+
+use crate::{
+    config::DAY_CONFIG_FILE,
+    history::{INSPECT_EVERY, INSPECT_STEP},
+    AError, PuzzleConfig,
+};
+
+/// Argument that asks a day's binary to print its metadata, see [`describe_if_requested`]
+pub const DESCRIBE_ARG: &str = "--describe";
+
+/// Environment variable holding a comma separated list of parts that a day's binary should skip,
+/// see [`part_requested`]
+pub const SKIP_PARTS_ENV: &str = "AOC_SKIP_PARTS";
+
+/// Description of a parameter that a puzzle reads from its [`PuzzleConfig`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParameterDescription {
+    pub name: String,
+    pub description: String,
+    pub default: String,
+}
+
+/// Describes a day's puzzle so that the binaries can be discovered without reading their source
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PuzzleMetadata {
+    pub day: u8,
+    pub title: String,
+    pub parameters: Vec<ParameterDescription>,
+    /// Parts too slow for routine runs of the whole suite
+    pub slow_parts: Vec<u8>,
+}
+
+impl PuzzleMetadata {
+    pub fn new(day: u8, title: &str) -> Self {
+        PuzzleMetadata {
+            day,
+            title: title.to_string(),
+            parameters: Vec::default(),
+            slow_parts: Vec::default(),
+        }
+    }
+
+    /// Mark the part as slow so that it can be skipped with the rest of the suite still run
+    pub fn with_slow_part(mut self, part: u8) -> Self {
+        self.slow_parts.push(part);
+        self
+    }
+
+    pub fn is_slow(&self, part: u8) -> bool {
+        self.slow_parts.contains(&part)
+    }
+
+    pub fn with_parameter<V: Display>(mut self, name: &str, description: &str, default: V) -> Self {
+        self.parameters.push(ParameterDescription {
+            name: name.to_string(),
+            description: description.to_string(),
+            default: default.to_string(),
+        });
+        self
+    }
+
+    /// Add the [`crate::history`] parameters, for days whose simulations can be inspected part way through
+    pub fn with_inspection(self) -> Self {
+        self.with_parameter(
+            INSPECT_STEP,
+            "Print the recorded state closest to this step of the simulation, blank for none",
+            "",
+        )
+        .with_parameter(INSPECT_EVERY, "Record the state every this many steps", 1)
+    }
+
+    /// Config for the file with all of the parameters set to their defaults
+    pub fn default_config(&self, file: &str) -> PuzzleConfig {
+        self.parameters
+            .iter()
+            .fold(PuzzleConfig::new(file), |config, parameter| {
+                config.with(&parameter.name, &parameter.default)
+            })
+    }
+
+    /// The config a day runs with: the parameters' defaults, overridden by any [`DAY_CONFIG_FILE`] in the
+    /// directory it's run from, then by `AOC_` environment variables and lastly by the command line arguments
+    pub fn load_config(&self, file: &str) -> Result<PuzzleConfig, AError> {
+        self.default_config(file)
+            .apply_toml_file_if_present(DAY_CONFIG_FILE)?
+            .apply_env("AOC")
+            .apply_args(std::env::args().skip(1))
+    }
+
+    /// Line based `key=value` representation printed for [`DESCRIBE_ARG`]
+    pub fn to_describe_lines(&self) -> String {
+        let mut lines = format!("day={}\ntitle={}\n", self.day, self.title);
+        for parameter in self.parameters.iter() {
+            lines.push_str(&format!(
+                "param={}|{}|{}\n",
+                parameter.name, parameter.description, parameter.default
+            ));
+        }
+        for part in self.slow_parts.iter() {
+            lines.push_str(&format!("slow={part}\n"));
+        }
+        lines
+    }
+
+    /// Read back the output of [`PuzzleMetadata::to_describe_lines`]
+    pub fn parse_describe_lines(lines: &str) -> Result<Self, AError> {
+        let mut day = None;
+        let mut title = None;
+        let mut parameters = Vec::default();
+        let mut slow_parts = Vec::default();
+        for line in lines.lines().filter(|line| !line.is_empty()) {
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| AError::msg(format!("Unrecognised describe line: '{line}'")))?;
+            match key {
+                "day" => day = Some(value.parse::<u8>()?),
+                "title" => title = Some(value.to_string()),
+                "param" => {
+                    let mut parts = value.splitn(3, '|');
+                    let mut next_part = || {
+                        parts
+                            .next()
+                            .map(|part| part.to_string())
+                            .ok_or_else(|| AError::msg(format!("Incomplete parameter: '{value}'")))
+                    };
+                    parameters.push(ParameterDescription {
+                        name: next_part()?,
+                        description: next_part()?,
+                        default: next_part()?,
+                    });
+                }
+                "slow" => slow_parts.push(value.parse::<u8>()?),
+                _ => return Err(AError::msg(format!("Unrecognised describe key: '{key}'"))),
+            }
+        }
+        Ok(PuzzleMetadata {
+            day: day.ok_or_else(|| AError::msg("No day described"))?,
+            title: title.ok_or_else(|| AError::msg("No title described"))?,
+            parameters,
+            slow_parts,
+        })
+    }
+}
+
+impl Display for PuzzleMetadata {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Day {}: {}", self.day, self.title)?;
+        for part in self.slow_parts.iter() {
+            write!(f, " [part {part} slow]")?;
+        }
+        for parameter in self.parameters.iter() {
+            write!(
+                f,
+                "\n    {} - {} (default: {})",
+                parameter.name, parameter.description, parameter.default
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Implemented by each day to describe itself
+pub trait Solver {
+    fn metadata() -> PuzzleMetadata;
+}
+
+/// Print the solver's metadata and exit if the binary was run with [`DESCRIBE_ARG`].  Call at the start of main.
+pub fn describe_if_requested<S: Solver>() {
+    if std::env::args().skip(1).any(|arg| arg == DESCRIBE_ARG) {
+        print!("{}", S::metadata().to_describe_lines());
+        std::process::exit(0);
+    }
+}
+
+fn part_listed(parts: &str, part: u8) -> bool {
+    parts
+        .split(',')
+        .any(|listed| listed.trim().parse::<u8>() == Ok(part))
+}
+
+/// Whether the binary should run the part, i.e. it isn't listed in [`SKIP_PARTS_ENV`]
+pub fn part_requested(part: u8) -> bool {
+    !std::env::var(SKIP_PARTS_ENV).is_ok_and(|parts| part_listed(&parts, part))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn describe_round_trip() {
+        let metadata = PuzzleMetadata::new(21, "Step Counter")
+            .with_parameter("total_steps", "Steps to take from the start", 64)
+            .with_parameter(
+                "part2.total_steps",
+                "Steps to simulate before extrapolating",
+                500,
+            )
+            .with_slow_part(2);
+        let parsed = PuzzleMetadata::parse_describe_lines(&metadata.to_describe_lines()).unwrap();
+        assert_eq!(parsed, metadata);
+        assert!(PuzzleMetadata::parse_describe_lines("title=No Day").is_err());
+
+        let config = metadata.default_config("input.txt");
+        assert_eq!(config.get::<usize>("total_steps").unwrap(), 64);
+        assert_eq!(config.for_part(2).get::<usize>("total_steps").unwrap(), 500);
+        assert_eq!(
+            format!("{metadata}").lines().next().unwrap(),
+            "Day 21: Step Counter [part 2 slow]"
+        );
+        assert!(metadata.is_slow(2) && !metadata.is_slow(1));
+    }
+
+    #[test]
+    fn skipped_parts_listed() {
+        assert!(part_listed("2", 2));
+        assert!(part_listed("1, 2", 2));
+        assert!(!part_listed("1", 2));
+        assert!(!part_listed("", 1));
+    }
+}
@@ -0,0 +1,178 @@
+//! Broad-phase pairing of things by their 2D bounding boxes: each box goes in to every bucket of a fixed grid
+//! of cells it covers, so only things sharing a bucket ever need comparing.  Used for bricks that might land
+//! on each other (day22) and paths that might cross (day24), a path only going in to the cells it passes
+//! through rather than every cell of its box.
+
+use std::collections::HashMap;
+
+/// Lowest (x, y) and highest (x, y), both included
+pub type BBox = ((f64, f64), (f64, f64));
+
+pub fn bboxes_overlap(a: &BBox, b: &BBox) -> bool {
+    let ((a_min_x, a_min_y), (a_max_x, a_max_y)) = *a;
+    let ((b_min_x, b_min_y), (b_max_x, b_max_y)) = *b;
+    a_min_x <= b_max_x && a_max_x >= b_min_x && a_min_y <= b_max_y && a_max_y >= b_min_y
+}
+
+#[derive(Debug, Clone)]
+pub struct SpatialHash<Id> {
+    cell_size: f64,
+    items: Vec<(BBox, Id)>,
+    /// Indices in to items of those covering each cell
+    buckets: HashMap<(i64, i64), Vec<usize>>,
+}
+
+impl<Id: Copy> SpatialHash<Id> {
+    /// Best with cells about the size of a typical box: much smaller and big boxes cover lots of buckets, much
+    /// bigger and too many things share each one
+    pub fn new(cell_size: f64) -> Self {
+        SpatialHash {
+            cell_size,
+            items: vec![],
+            buckets: HashMap::default(),
+        }
+    }
+
+    fn cell(&self, (x, y): (f64, f64)) -> (i64, i64) {
+        (
+            (x / self.cell_size).floor() as i64,
+            (y / self.cell_size).floor() as i64,
+        )
+    }
+
+    pub fn insert(&mut self, bbox: BBox, id: Id) {
+        let index = self.items.len();
+        let (min_cell, max_cell) = (self.cell(bbox.0), self.cell(bbox.1));
+        for cell_x in min_cell.0..=max_cell.0 {
+            for cell_y in min_cell.1..=max_cell.1 {
+                self.buckets
+                    .entry((cell_x, cell_y))
+                    .or_default()
+                    .push(index);
+            }
+        }
+        self.items.push((bbox, id));
+    }
+
+    /// Insert the segment from start to end in to the buckets it passes within `slack` of, walking along it a
+    /// column of cells at a time.  A long diagonal then only shares buckets with things near it, where its
+    /// bounding box would cover most of the grid.
+    pub fn insert_segment(&mut self, start: (f64, f64), end: (f64, f64), slack: f64, id: Id) {
+        let index = self.items.len();
+        let (low_x, high_x) = (start.0.min(end.0), start.0.max(end.0));
+        let (low_y, high_y) = (start.1.min(end.1), start.1.max(end.1));
+        let y_at = |x: f64| match end.0 == start.0 {
+            true => None,
+            false => Some(start.1 + (x - start.0) * (end.1 - start.1) / (end.0 - start.0)),
+        };
+        let (first_column, last_column) = (
+            self.cell((low_x - slack, 0.0)).0,
+            self.cell((high_x + slack, 0.0)).0,
+        );
+        for cell_x in first_column..=last_column {
+            // the part of the segment in the column, or its nearest end for a column only within the slack
+            let from_x = (cell_x as f64 * self.cell_size).clamp(low_x, high_x);
+            let to_x = ((cell_x + 1) as f64 * self.cell_size).clamp(low_x, high_x);
+            let (from_y, to_y) = match (y_at(from_x), y_at(to_x)) {
+                (Some(from_y), Some(to_y)) => (
+                    from_y.min(to_y).clamp(low_y, high_y),
+                    from_y.max(to_y).clamp(low_y, high_y),
+                ),
+                _ => (low_y, high_y),
+            };
+            let (first_row, last_row) = (
+                self.cell((0.0, from_y - slack)).1,
+                self.cell((0.0, to_y + slack)).1,
+            );
+            for cell_y in first_row..=last_row {
+                self.buckets
+                    .entry((cell_x, cell_y))
+                    .or_default()
+                    .push(index);
+            }
+        }
+        let bbox = (
+            (low_x - slack, low_y - slack),
+            (high_x + slack, high_y + slack),
+        );
+        self.items.push((bbox, id));
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Every pair of things whose boxes overlap, each pair once with the one inserted first first, in the order
+    /// they were inserted
+    pub fn candidate_pairs(&self) -> Vec<(Id, Id)> {
+        let mut pairs: Vec<(usize, usize)> = self
+            .buckets
+            .values()
+            .flat_map(|bucket| {
+                bucket.iter().enumerate().flat_map(move |(i, first)| {
+                    bucket[i + 1..].iter().map(move |second| (*first, *second))
+                })
+            })
+            .filter(|(first, second)| bboxes_overlap(&self.items[*first].0, &self.items[*second].0))
+            .collect();
+        pairs.sort_unstable();
+        pairs.dedup();
+        pairs
+            .into_iter()
+            .map(|(first, second)| (self.items[first].1, self.items[second].1))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overlapping_pairs_found() {
+        let boxes: [BBox; 5] = [
+            ((0.0, 0.0), (2.0, 0.0)),
+            ((0.0, 2.0), (2.0, 2.0)),
+            ((0.0, 0.0), (0.0, 2.0)),
+            ((2.0, 0.0), (2.0, 2.0)),
+            ((10.0, 10.0), (12.0, 11.0)),
+        ];
+        let mut hash = SpatialHash::new(1.0);
+        boxes
+            .iter()
+            .enumerate()
+            .for_each(|(id, bbox)| hash.insert(*bbox, id));
+        assert_eq!(hash.len(), 5);
+        assert_eq!(hash.candidate_pairs(), vec![(0, 2), (0, 3), (1, 2), (1, 3)]);
+
+        // the same whatever the cells, they only change how much is compared
+        for cell_size in [0.3, 5.0, 100.0] {
+            let mut hash = SpatialHash::new(cell_size);
+            boxes
+                .iter()
+                .enumerate()
+                .for_each(|(id, bbox)| hash.insert(*bbox, id));
+            assert_eq!(hash.candidate_pairs(), vec![(0, 2), (0, 3), (1, 2), (1, 3)]);
+        }
+    }
+
+    #[test]
+    fn segments_only_in_cells_passed_through() {
+        let mut hash = SpatialHash::new(1.0);
+        // the diagonal passes through 10 cells of the 100 its box covers, and touches another 9 at their corners
+        hash.insert_segment((0.5, 0.5), (9.5, 9.5), 0.0, 0);
+        assert_eq!(hash.buckets.len(), 19);
+        // near the far corners of the diagonal's box, but nowhere near the diagonal
+        hash.insert_segment((8.5, 0.5), (9.5, 1.5), 0.0, 1);
+        hash.insert_segment((0.5, 8.5), (0.5, 9.5), 0.0, 2);
+        // crossing the diagonal, exactly on a cell corner
+        hash.insert_segment((4.0, 6.0), (6.0, 4.0), 0.0, 3);
+        // ending just short of the vertical one, within the slack
+        hash.insert_segment((0.7, 9.0), (3.0, 9.0), 0.25, 4);
+        assert_eq!(hash.candidate_pairs(), vec![(0, 3), (2, 4)]);
+    }
+}
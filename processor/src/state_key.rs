@@ -0,0 +1,228 @@
+//! Search states that pack into a small dense index, so that the "visited" and "best so far" bookkeeping of
+//! a search can live in flat arrays ([`StateSet`] and [`StateMap`]) rather than being hashed.
+
+use std::marker::PhantomData;
+
+use crate::Direction;
+
+/// Combine digits, each given with its radix (one more than the largest value it can take), in to a single
+/// index.  The first digit is the most significant.
+pub fn pack_digits(digits: &[(usize, usize)]) -> usize {
+    digits.iter().fold(0, |index, (digit, radix)| {
+        debug_assert!(digit < radix, "{digit} out of range for radix {radix}");
+        index * radix + digit
+    })
+}
+
+/// Split an index made by [`pack_digits`] back in to its digits
+pub fn unpack_digits<const N: usize>(mut index: usize, radixes: [usize; N]) -> [usize; N] {
+    let mut digits = [0; N];
+    for (digit, radix) in digits.iter_mut().zip(radixes).rev() {
+        *digit = index % radix;
+        index /= radix;
+    }
+    digits
+}
+
+/// A search state that packs in to an index below [`StateKey::key_count`].  The space gives whatever
+/// bounds are needed to do that, e.g. the side lengths of the grid being searched.
+pub trait StateKey: Sized {
+    type Space: Clone;
+
+    fn key_count(space: &Self::Space) -> usize;
+
+    fn pack(&self, space: &Self::Space) -> usize;
+
+    fn unpack(index: usize, space: &Self::Space) -> Self;
+}
+
+/// A coordinate within a grid with the given side lengths
+impl StateKey for (usize, usize) {
+    type Space = (usize, usize);
+
+    fn key_count(&(width, height): &Self::Space) -> usize {
+        width * height
+    }
+
+    fn pack(&self, &(width, height): &Self::Space) -> usize {
+        pack_digits(&[(self.1, height), (self.0, width)])
+    }
+
+    fn unpack(index: usize, &(width, height): &Self::Space) -> Self {
+        let [y, x] = unpack_digits(index, [height, width]);
+        (x, y)
+    }
+}
+
+/// A coordinate and the direction it was entered in
+impl StateKey for ((usize, usize), Direction) {
+    type Space = (usize, usize);
+
+    fn key_count(space: &Self::Space) -> usize {
+        <(usize, usize)>::key_count(space) * Direction::ALL.len()
+    }
+
+    fn pack(&self, space: &Self::Space) -> usize {
+        pack_digits(&[
+            (self.0.pack(space), <(usize, usize)>::key_count(space)),
+            (self.1.index(), Direction::ALL.len()),
+        ])
+    }
+
+    fn unpack(index: usize, space: &Self::Space) -> Self {
+        let [coord, direction] = unpack_digits(
+            index,
+            [<(usize, usize)>::key_count(space), Direction::ALL.len()],
+        );
+        (
+            <(usize, usize)>::unpack(coord, space),
+            Direction::ALL[direction],
+        )
+    }
+}
+
+/// A coordinate, the direction it was entered in and how far the search has run straight on to get there.
+/// The space is the grid's side lengths along with the longest possible run.
+impl StateKey for ((usize, usize), Direction, usize) {
+    type Space = ((usize, usize), usize);
+
+    fn key_count((sides, max_run): &Self::Space) -> usize {
+        <((usize, usize), Direction)>::key_count(sides) * (max_run + 1)
+    }
+
+    fn pack(&self, (sides, max_run): &Self::Space) -> usize {
+        pack_digits(&[
+            (
+                (self.0, self.1).pack(sides),
+                <((usize, usize), Direction)>::key_count(sides),
+            ),
+            (self.2, max_run + 1),
+        ])
+    }
+
+    fn unpack(index: usize, (sides, max_run): &Self::Space) -> Self {
+        let [coord_direction, run] = unpack_digits(
+            index,
+            [<((usize, usize), Direction)>::key_count(sides), max_run + 1],
+        );
+        let (coord, direction) = <((usize, usize), Direction)>::unpack(coord_direction, sides);
+        (coord, direction, run)
+    }
+}
+
+/// A set of states, a bit per possible state
+#[derive(Debug, Clone)]
+pub struct StateSet<K: StateKey> {
+    space: K::Space,
+    bits: Vec<u64>,
+    len: usize,
+}
+
+impl<K: StateKey> StateSet<K> {
+    pub fn new(space: K::Space) -> Self {
+        StateSet {
+            bits: vec![0; K::key_count(&space).div_ceil(64)],
+            space,
+            len: 0,
+        }
+    }
+
+    /// Whether the state was newly added
+    pub fn insert(&mut self, key: &K) -> bool {
+        let index = key.pack(&self.space);
+        let (word, bit) = (index / 64, 1u64 << (index % 64));
+        let added = self.bits[word] & bit == 0;
+        if added {
+            self.bits[word] |= bit;
+            self.len += 1;
+        }
+        added
+    }
+
+    pub fn contains(&self, key: &K) -> bool {
+        let index = key.pack(&self.space);
+        self.bits[index / 64] & (1u64 << (index % 64)) != 0
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+/// A value for any of the possible states, held in a flat array
+#[derive(Debug, Clone)]
+pub struct StateMap<K: StateKey, V> {
+    space: K::Space,
+    values: Vec<Option<V>>,
+    key: PhantomData<K>,
+}
+
+impl<K: StateKey, V> StateMap<K, V> {
+    pub fn new(space: K::Space) -> Self {
+        StateMap {
+            values: std::iter::repeat_with(|| None)
+                .take(K::key_count(&space))
+                .collect(),
+            space,
+            key: PhantomData,
+        }
+    }
+
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.values[key.pack(&self.space)].as_ref()
+    }
+
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        self.values[key.pack(&self.space)].as_mut()
+    }
+
+    /// Gives the previous value, if there was one
+    pub fn insert(&mut self, key: &K, value: V) -> Option<V> {
+        self.values[key.pack(&self.space)].replace(value)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (K, &V)> + '_ {
+        self.values
+            .iter()
+            .enumerate()
+            .filter_map(|(index, value)| Some((K::unpack(index, &self.space), value.as_ref()?)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_pack_densely() {
+        assert_eq!(pack_digits(&[(2, 3), (4, 5)]), 14);
+        assert_eq!(unpack_digits(14, [3, 5]), [2, 4]);
+
+        let space = ((4, 3), 10);
+        let count = <((usize, usize), Direction, usize)>::key_count(&space);
+        assert_eq!(count, 4 * 3 * 4 * 11);
+        let indexes: Vec<usize> = (0..count)
+            .map(|index| <((usize, usize), Direction, usize)>::unpack(index, &space).pack(&space))
+            .collect();
+        assert_eq!(indexes, (0..count).collect::<Vec<_>>());
+
+        let mut visited = StateSet::new((4, 3));
+        assert!(visited.insert(&((3, 2), Direction::West)));
+        assert!(!visited.insert(&((3, 2), Direction::West)));
+        assert!(!visited.contains(&((3, 2), Direction::East)));
+        assert_eq!(visited.len(), 1);
+
+        let mut best = StateMap::new(space);
+        assert_eq!(best.insert(&((1, 1), Direction::South, 3), 20), None);
+        assert_eq!(best.insert(&((1, 1), Direction::South, 3), 15), Some(20));
+        assert_eq!(best.get(&((1, 1), Direction::South, 2)), None);
+        assert_eq!(
+            best.iter().collect::<Vec<_>>(),
+            vec![(((1, 1), Direction::South, 3), &15)]
+        );
+    }
+}
@@ -0,0 +1,286 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::{Debug, Display},
+    hash::Hash,
+};
+
+use crate::Cells;
+
+/// Summary of the values held in a [`Cells`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CellStats {
+    pub count: usize,
+    pub min: i64,
+    pub max: i64,
+    pub sum: i64,
+}
+
+impl CellStats {
+    pub fn mean(&self) -> f64 {
+        self.sum as f64 / self.count as f64
+    }
+}
+
+impl Display for CellStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "count: {}, min: {}, max: {}, sum: {}, mean: {:.3}",
+            self.count,
+            self.min,
+            self.max,
+            self.sum,
+            self.mean()
+        )
+    }
+}
+
+impl<T: Clone + Into<i64>> Cells<T> {
+    fn values(&self) -> impl Iterator<Item = i64> + '_ {
+        self.contents.iter().map(|value| value.clone().into())
+    }
+
+    pub fn min(&self) -> Option<i64> {
+        self.values().min()
+    }
+
+    pub fn max(&self) -> Option<i64> {
+        self.values().max()
+    }
+
+    pub fn sum(&self) -> i64 {
+        self.values().sum()
+    }
+
+    pub fn mean(&self) -> Option<f64> {
+        self.stats().map(|stats| stats.mean())
+    }
+
+    /// All of the summary values in one pass, None if there are no cells
+    pub fn stats(&self) -> Option<CellStats> {
+        self.values().fold(None, |stats, value| {
+            Some(match stats {
+                None => CellStats {
+                    count: 1,
+                    min: value,
+                    max: value,
+                    sum: value,
+                },
+                Some(stats) => CellStats {
+                    count: stats.count + 1,
+                    min: stats.min.min(value),
+                    max: stats.max.max(value),
+                    sum: stats.sum + value,
+                },
+            })
+        })
+    }
+
+    /// How many cells hold each value, in value order
+    pub fn histogram(&self) -> BTreeMap<i64, usize> {
+        histogram(self.values())
+    }
+}
+
+/// How many cells hold each distinct value (or projection of one), in the order the values are first met
+/// reading the cells row by row, e.g. for checking that a grid parsed as expected
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValueCounts<K> {
+    pub counts: Vec<(K, usize)>,
+}
+
+impl<K: PartialEq> ValueCounts<K> {
+    /// How many cells held the value, 0 for one never met
+    pub fn count(&self, value: &K) -> usize {
+        self.counts
+            .iter()
+            .find(|(counted, _)| counted == value)
+            .map_or(0, |(_, count)| *count)
+    }
+}
+
+/// As `Rock: 2013, Plot: 15148, Start: 1`
+impl<K: Debug> Display for ValueCounts<K> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (index, (value, count)) in self.counts.iter().enumerate() {
+            if index > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{value:?}: {count}")?;
+        }
+        Ok(())
+    }
+}
+
+impl<T> Cells<T> {
+    /// How many cells hold each distinct value once projected, e.g. to its variant when they carry fields
+    pub fn value_counts_by<K, F>(&self, projection: F) -> ValueCounts<K>
+    where
+        K: Eq + Hash + Clone,
+        F: Fn(&T) -> K,
+    {
+        let mut positions: HashMap<K, usize> = HashMap::default();
+        let mut counts: Vec<(K, usize)> = Vec::default();
+        for value in self.contents.iter().map(projection) {
+            match positions.get(&value) {
+                Some(position) => counts[*position].1 += 1,
+                None => {
+                    positions.insert(value.clone(), counts.len());
+                    counts.push((value, 1));
+                }
+            }
+        }
+        ValueCounts { counts }
+    }
+}
+
+impl<T: Eq + Hash + Clone> Cells<T> {
+    /// How many cells hold each distinct value
+    pub fn value_counts(&self) -> ValueCounts<T> {
+        self.value_counts_by(T::clone)
+    }
+}
+
+/// How many times each value occurs, in value order
+pub fn histogram<T: Ord>(values: impl IntoIterator<Item = T>) -> BTreeMap<T, usize> {
+    values
+        .into_iter()
+        .fold(BTreeMap::default(), |mut counts, value| {
+            *counts.entry(value).or_default() += 1;
+            counts
+        })
+}
+
+/// The nearest rank percentile, i.e. the smallest value that at least `percent` of the values are less than or
+/// equal to.  `percent` is clamped to 0 - 100, None if there are no values.
+pub fn percentile<T: Ord>(values: impl IntoIterator<Item = T>, percent: f64) -> Option<T> {
+    let mut values: Vec<T> = values.into_iter().collect();
+    if values.is_empty() {
+        return None;
+    }
+    values.sort();
+    let rank = (percent.clamp(0.0, 100.0) / 100.0 * values.len() as f64).ceil() as usize;
+    values.into_iter().nth(rank.max(1) - 1)
+}
+
+/// The most common value, the smallest of them if there's a tie
+pub fn mode<T: Ord>(values: impl IntoIterator<Item = T>) -> Option<T> {
+    histogram(values)
+        .into_iter()
+        .fold(
+            None,
+            |most: Option<(T, usize)>, (value, count)| match most {
+                Some((_, most_count)) if most_count >= count => most,
+                _ => Some((value, count)),
+            },
+        )
+        .map(|(value, _)| value)
+}
+
+/// Where the values lie, for a diagnostic line, e.g. the lengths of the walks found
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Distribution<T> {
+    pub count: usize,
+    pub min: T,
+    pub median: T,
+    pub p90: T,
+    pub max: T,
+    pub mode: T,
+}
+
+impl<T: Ord + Copy> Distribution<T> {
+    /// None if there are no values
+    pub fn of(values: impl IntoIterator<Item = T>) -> Option<Distribution<T>> {
+        let mut values: Vec<T> = values.into_iter().collect();
+        values.sort();
+        Some(Distribution {
+            count: values.len(),
+            min: *values.first()?,
+            median: percentile(values.iter().copied(), 50.0)?,
+            p90: percentile(values.iter().copied(), 90.0)?,
+            max: *values.last()?,
+            mode: mode(values.iter().copied())?,
+        })
+    }
+}
+
+impl<T: Display> Display for Distribution<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "count: {}, min: {}, median: {}, p90: {}, max: {}, mode: {}",
+            self.count, self.min, self.median, self.p90, self.max, self.mode
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cell_values_summarised() {
+        let mut cells = Cells::with_dimension(3, 2, 4u8);
+        *cells.get_mut(0, 0).unwrap() = 1;
+        *cells.get_mut(2, 1).unwrap() = 9;
+        assert_eq!(cells.min(), Some(1));
+        assert_eq!(cells.max(), Some(9));
+        assert_eq!(cells.sum(), 26);
+        assert_eq!(cells.mean(), Some(26.0 / 6.0));
+        assert_eq!(cells.histogram(), BTreeMap::from([(1, 1), (4, 4), (9, 1)]));
+        assert_eq!(
+            cells.stats().unwrap().to_string(),
+            "count: 6, min: 1, max: 9, sum: 26, mean: 4.333"
+        );
+
+        let empty = Cells::with_dimension(0, 0, 0i32);
+        assert_eq!(empty.stats(), None);
+        assert_eq!(empty.mean(), None);
+        assert_eq!(empty.sum(), 0);
+    }
+
+    #[test]
+    fn cell_values_counted() {
+        let mut cells = Cells::with_dimension(3, 2, 'O');
+        cells[(0, 0)] = '#';
+        cells[(2, 1)] = 'S';
+        cells[(1, 1)] = '#';
+        let counts = cells.value_counts();
+        assert_eq!(counts.counts, vec![('#', 2), ('O', 3), ('S', 1)]);
+        assert_eq!(counts.count(&'O'), 3);
+        assert_eq!(counts.count(&'x'), 0);
+        assert_eq!(counts.to_string(), "'#': 2, 'O': 3, 'S': 1");
+        assert_eq!(
+            cells.value_counts_by(|c| *c == '#').to_string(),
+            "true: 2, false: 4"
+        );
+        assert_eq!(
+            Cells::with_dimension(0, 0, 0).value_counts().to_string(),
+            ""
+        );
+    }
+
+    #[test]
+    fn distribution_of_values() {
+        let values = [3usize, 1, 4, 1, 5, 9, 2, 6, 5, 3];
+        assert_eq!(
+            histogram(values),
+            BTreeMap::from([(1, 2), (2, 1), (3, 2), (4, 1), (5, 2), (6, 1), (9, 1)])
+        );
+        assert_eq!(percentile(values, 0.0), Some(1));
+        assert_eq!(percentile(values, 50.0), Some(3));
+        assert_eq!(percentile(values, 75.0), Some(5));
+        assert_eq!(percentile(values, 100.0), Some(9));
+        assert_eq!(percentile(values, 150.0), Some(9));
+        assert_eq!(mode(values), Some(1));
+        assert_eq!(mode([7, 2, 7]), Some(7));
+        assert_eq!(
+            Distribution::of(values).unwrap().to_string(),
+            "count: 10, min: 1, median: 3, p90: 6, max: 9, mode: 1"
+        );
+
+        assert_eq!(percentile(Vec::<u64>::new(), 50.0), None);
+        assert_eq!(mode(Vec::<u64>::new()), None);
+        assert_eq!(Distribution::of(Vec::<u64>::new()), None);
+    }
+}
@@ -0,0 +1,85 @@
+//! Checks of a [`Day`]'s answers to the puzzle's examples, one line each with [`assert_day`](crate::assert_day).
+
+use crate::{
+    day::{parse_file, solve},
+    AError, Answer, Day,
+};
+
+/// The day's answer to the part for the file, which is relative to the day's directory as that's where its
+/// tests are run from
+pub fn answer_for_file<D: Day>(part: u8, file: &str) -> Result<Answer, AError> {
+    solve::<D>(&parse_file::<D>(file)?, part)
+}
+
+/// Assert a [`Day`]'s answer to a part for an input file, e.g. `assert_day!(Day9, part1, "test-input.txt", 114);`
+#[macro_export]
+macro_rules! assert_day {
+    (@part $day:ty, $part:literal, $file:expr, $expected:expr) => {
+        match $crate::testing::answer_for_file::<$day>($part, $file) {
+            Ok(answer) => assert_eq!(
+                answer,
+                $crate::Answer::from($expected),
+                "{} part {} for {}",
+                stringify!($day),
+                $part,
+                $file
+            ),
+            Err(e) => panic!(
+                "{} part {} failed for {}: {}",
+                stringify!($day),
+                $part,
+                $file,
+                e
+            ),
+        }
+    };
+    ($day:ty, part1, $file:expr, $expected:expr) => {
+        $crate::assert_day!(@part $day, 1, $file, $expected)
+    };
+    ($day:ty, part2, $file:expr, $expected:expr) => {
+        $crate::assert_day!(@part $day, 2, $file, $expected)
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{PuzzleMetadata, Solver};
+
+    use super::*;
+
+    struct Words;
+
+    impl Solver for Words {
+        fn metadata() -> PuzzleMetadata {
+            PuzzleMetadata::new(0, "Words")
+        }
+    }
+
+    impl Day for Words {
+        type Parsed = Vec<String>;
+
+        fn parse(lines: impl Iterator<Item = String>) -> Result<Self::Parsed, AError> {
+            Ok(lines.collect())
+        }
+
+        fn part1(lines: &Self::Parsed) -> Result<Answer, AError> {
+            Ok(lines.len().into())
+        }
+
+        fn part2(lines: &Self::Parsed) -> Result<Answer, AError> {
+            Ok(lines.join(" ").into())
+        }
+    }
+
+    #[test]
+    fn answers_asserted() {
+        let file = std::env::temp_dir().join(format!("testing-{}.txt", std::process::id()));
+        std::fs::write(&file, "sand\nslabs\n").unwrap();
+        let file = file.to_str().unwrap();
+        assert_day!(Words, part1, file, 2);
+        assert_day!(Words, part2, file, "sand slabs");
+        assert!(std::panic::catch_unwind(|| assert_day!(Words, part1, file, 3)).is_err());
+        assert!(answer_for_file::<Words>(1, "no-such-file.txt").is_err());
+        std::fs::remove_file(file).unwrap();
+    }
+}
@@ -0,0 +1,228 @@
+//! The tiles that keep turning up in the maze style puzzles, so that days can share them (and how they are
+//! drawn) rather than each declaring their own near identical enum.  Tiles particular to a day go in
+//! [`Tile::Custom`], via the [`CustomTile`] hooks.
+
+use std::fmt::Display;
+
+use crate::{AError, CellChar, Direction, InlineVec};
+
+/// A mirror, named after the character drawing it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Mirror {
+    /// `/`
+    Slash,
+    /// `\`
+    Backslash,
+}
+
+impl Mirror {
+    /// The direction something heading in the given direction leaves the mirror
+    pub fn reflect(&self, heading: Direction) -> Direction {
+        match (self, heading) {
+            (Mirror::Slash, Direction::North) | (Mirror::Backslash, Direction::South) => {
+                Direction::East
+            }
+            (Mirror::Slash, Direction::East) | (Mirror::Backslash, Direction::West) => {
+                Direction::North
+            }
+            (Mirror::Slash, Direction::South) | (Mirror::Backslash, Direction::North) => {
+                Direction::West
+            }
+            (Mirror::Slash, Direction::West) | (Mirror::Backslash, Direction::East) => {
+                Direction::South
+            }
+        }
+    }
+}
+
+/// A splitter, passing through anything heading along it and splitting anything hitting its side
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Splitter {
+    /// `-`
+    Horizontal,
+    /// `|`
+    Vertical,
+}
+
+impl Splitter {
+    /// The directions something heading in the given direction leaves the splitter
+    pub fn split(&self, heading: Direction) -> InlineVec<Direction, 2> {
+        match (self, heading) {
+            (Splitter::Horizontal, Direction::North | Direction::South) => {
+                InlineVec::from_iter([Direction::West, Direction::East])
+            }
+            (Splitter::Vertical, Direction::East | Direction::West) => {
+                InlineVec::from_iter([Direction::North, Direction::South])
+            }
+            _ => InlineVec::from_iter([heading]),
+        }
+    }
+}
+
+/// Hooks for the tiles particular to a day.  [`CustomTile::from_char`] is tried before the shared tiles, so
+/// that a day can take over any character (and map it to any tile, shared or custom).
+pub trait CustomTile: Sized {
+    fn from_char(c: char) -> Option<Tile<Self>>;
+
+    fn to_char(&self) -> char;
+
+    /// Colour to draw the tile in, as RGB
+    fn colour(&self) -> [u8; 3] {
+        [255, 0, 255]
+    }
+}
+
+/// For days that only need the shared tiles
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NoCustom {}
+
+impl CustomTile for NoCustom {
+    fn from_char(_c: char) -> Option<Tile<Self>> {
+        None
+    }
+
+    fn to_char(&self) -> char {
+        match *self {}
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Tile<C = NoCustom> {
+    /// `.`, also `S` as start markers are open ground (and recorded separately by the days)
+    #[default]
+    Empty,
+    /// `#`
+    Wall,
+    /// `^`, `>`, `v` or `<`, only crossed in its direction
+    Slope(Direction),
+    Mirror(Mirror),
+    Splitter(Splitter),
+    Custom(C),
+}
+
+impl<C> Tile<C> {
+    pub fn is_wall(&self) -> bool {
+        matches!(self, Tile::Wall)
+    }
+}
+
+impl<C: CustomTile> Tile<C> {
+    /// Colour to draw the tile in, as RGB, so that every day draws the shared tiles the same way
+    pub fn colour(&self) -> [u8; 3] {
+        match self {
+            Tile::Empty => [0, 0, 0],
+            Tile::Wall => [96, 96, 96],
+            Tile::Slope(_) => [64, 160, 64],
+            Tile::Mirror(_) => [64, 128, 255],
+            Tile::Splitter(_) => [255, 160, 32],
+            Tile::Custom(custom) => custom.colour(),
+        }
+    }
+}
+
+impl<C: CustomTile> CellChar for Tile<C> {
+    fn from_char(c: char) -> Option<Self> {
+        C::from_char(c).or(match c {
+            '.' | 'S' => Some(Tile::Empty),
+            '#' => Some(Tile::Wall),
+            '/' => Some(Tile::Mirror(Mirror::Slash)),
+            '\\' => Some(Tile::Mirror(Mirror::Backslash)),
+            '-' => Some(Tile::Splitter(Splitter::Horizontal)),
+            '|' => Some(Tile::Splitter(Splitter::Vertical)),
+            _ => Direction::from_arrow(c).map(Tile::Slope),
+        })
+    }
+
+    fn to_char(&self) -> char {
+        match self {
+            Tile::Empty => '.',
+            Tile::Wall => '#',
+            Tile::Slope(direction) => direction.to_arrow(),
+            Tile::Mirror(Mirror::Slash) => '/',
+            Tile::Mirror(Mirror::Backslash) => '\\',
+            Tile::Splitter(Splitter::Horizontal) => '-',
+            Tile::Splitter(Splitter::Vertical) => '|',
+            Tile::Custom(custom) => custom.to_char(),
+        }
+    }
+}
+
+/// The tile for the char, as long as it's one of the chars the day's grid is drawn with, for
+/// [`crate::CellsBuilder::add_line_with`].  A day then rejects tiles that belong to other days (e.g. a mirror
+/// in a maze of slopes) rather than accepting all of the shared ones.
+pub fn allowed_tile<C: CustomTile>(c: char, allowed: &str) -> Result<Tile<C>, AError> {
+    if !allowed.contains(c) {
+        return Err(AError::msg(format!("'{c}' is not one of '{allowed}'")));
+    }
+    Tile::from_char(c).ok_or_else(|| AError::msg(format!("No tile for '{c}'")))
+}
+
+impl<C: CustomTile> Display for Tile<C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_char())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Cells;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    struct Door;
+
+    impl CustomTile for Door {
+        fn from_char(c: char) -> Option<Tile<Self>> {
+            match c {
+                'D' => Some(Tile::Custom(Door)),
+                '|' => Some(Tile::Wall),
+                _ => None,
+            }
+        }
+
+        fn to_char(&self) -> char {
+            'D'
+        }
+    }
+
+    #[test]
+    fn shared_and_custom_tiles() {
+        let cells = Cells::<Tile>::parse_lines([r".#/\", "-|>S"]).unwrap();
+        assert_eq!(cells.to_string(), ".#/\\\n-|>.\n");
+        assert_eq!(*cells.get(2, 1).unwrap(), Tile::Slope(Direction::East));
+
+        let slopes = |c| allowed_tile::<NoCustom>(c, ".#^>v<");
+        assert_eq!(
+            Cells::parse([".>", "#v"], slopes).unwrap().to_string(),
+            ".>\n#v\n"
+        );
+        let error = Cells::parse([".>", "#/"], slopes).unwrap_err();
+        assert_eq!(
+            format!("{error:#}"),
+            "Unrecognised cell '/' at (1, 1): '/' is not one of '.#^>v<'"
+        );
+
+        let custom = Cells::<Tile<Door>>::parse_lines(["D|-"]).unwrap();
+        assert_eq!(*custom.get(0, 0).unwrap(), Tile::Custom(Door));
+        assert!(custom.get(1, 0).unwrap().is_wall());
+        assert_eq!(custom.to_string(), "D#-\n");
+        assert_eq!(custom.get(0, 0).unwrap().colour(), [255, 0, 255]);
+
+        assert_eq!(Mirror::Slash.reflect(Direction::East), Direction::North);
+        assert_eq!(Mirror::Backslash.reflect(Direction::East), Direction::South);
+        assert_eq!(
+            Splitter::Vertical
+                .split(Direction::West)
+                .into_iter()
+                .collect::<Vec<_>>(),
+            vec![Direction::North, Direction::South]
+        );
+        assert_eq!(
+            Splitter::Vertical
+                .split(Direction::South)
+                .into_iter()
+                .collect::<Vec<_>>(),
+            vec![Direction::South]
+        );
+    }
+}
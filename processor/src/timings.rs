@@ -0,0 +1,117 @@
+//! Timings of the phases of [`crate::process`] (parsing, finalising, processing and calculating the result),
+//! printed as a `Phases N: ...` line after each run when [`PHASE_TIMINGS_ENV`] is set so that the runner can
+//! pick them up.
+
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, Instant},
+};
+
+use crate::part_requested;
+
+/// Environment variable that turns on printing the phase timings
+pub const PHASE_TIMINGS_ENV: &str = "AOC_PHASE_TIMINGS";
+
+/// Counts the runs of the pipeline so that each is numbered, i.e. by part for days running part 1 then 2
+static RUNS: AtomicUsize = AtomicUsize::new(0);
+
+/// The number of the run after the previous one, passing over any parts that won't be run so that a day
+/// skipping part 1 still numbers its part 2 run as 2
+fn next_run(previous: usize, requested: impl Fn(u8) -> bool) -> usize {
+    (previous + 1..)
+        .find(|run| u8::try_from(*run).map_or(true, &requested))
+        .unwrap()
+}
+
+/// Times consecutive phases
+pub struct PhaseTimer {
+    started_at: Instant,
+    phases: Vec<(&'static str, Duration)>,
+}
+
+impl PhaseTimer {
+    pub fn start() -> Self {
+        PhaseTimer {
+            started_at: Instant::now(),
+            phases: Vec::default(),
+        }
+    }
+
+    /// End the current phase, naming it, and start the next
+    pub fn lap(&mut self, phase: &'static str) {
+        let now = Instant::now();
+        self.phases.push((phase, now - self.started_at));
+        self.started_at = now;
+    }
+
+    pub fn phases(&self) -> &[(&'static str, Duration)] {
+        &self.phases
+    }
+
+    /// Print the phases if they have been asked for, giving the number of the run (see [`part_requested`])
+    pub fn report(&self) -> usize {
+        let next = |previous| next_run(previous, part_requested);
+        let previous = RUNS
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |previous| {
+                Some(next(previous))
+            })
+            .unwrap();
+        self.report_as(next(previous))
+    }
+
+    /// As [`PhaseTimer::report`] but numbered by the caller, e.g. by the part when part 1 may have been skipped
+    pub fn report_as(&self, run: usize) -> usize {
+        if std::env::var_os(PHASE_TIMINGS_ENV).is_some() {
+            println!("{}", format_phases(run, &self.phases));
+        }
+        run
+    }
+}
+
+/// e.g. `Phases 1: parse=0.001200 finalise=0.000010 process=1.500000 result=0.000001`, in seconds
+pub fn format_phases(run: usize, phases: &[(&str, Duration)]) -> String {
+    let timings: Vec<String> = phases
+        .iter()
+        .map(|(phase, took)| format!("{phase}={:.6}", took.as_secs_f64()))
+        .collect();
+    format!("Phases {run}: {}", timings.join(" "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn phases_formatted() {
+        let mut timer = PhaseTimer::start();
+        timer.lap("parse");
+        timer.lap("process");
+        assert_eq!(
+            timer
+                .phases()
+                .iter()
+                .map(|(phase, _)| *phase)
+                .collect::<Vec<_>>(),
+            vec!["parse", "process"]
+        );
+        assert_eq!(timer.report_as(2), 2);
+        assert_eq!(
+            format_phases(
+                2,
+                &[
+                    ("parse", Duration::from_micros(1200)),
+                    ("process", Duration::from_millis(1500))
+                ]
+            ),
+            "Phases 2: parse=0.001200 process=1.500000"
+        );
+    }
+
+    #[test]
+    fn runs_numbered_past_skipped_parts() {
+        assert_eq!(next_run(0, |_| true), 1);
+        assert_eq!(next_run(0, |part| part != 1), 2);
+        assert_eq!(next_run(1, |part| part != 1), 2);
+        assert_eq!(next_run(1, |part| part > 2), 3);
+    }
+}
@@ -0,0 +1,139 @@
+//! Disjoint sets of the elements `0..n`, merged a pair at a time, e.g. the components left joined as Karger's
+//! algorithm contracts edges.  Finding compresses the paths to the roots and unions hang the shallower tree
+//! under the deeper, so both are close to constant time.
+
+use std::{cmp::Ordering, collections::BTreeMap};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnionFind {
+    parents: Vec<usize>,
+    ranks: Vec<usize>,
+    /// Number of elements in the set, only kept up to date for the roots
+    sizes: Vec<usize>,
+    component_count: usize,
+}
+
+impl UnionFind {
+    /// Every element in a set of its own
+    pub fn new(element_count: usize) -> Self {
+        UnionFind {
+            parents: (0..element_count).collect(),
+            ranks: vec![0; element_count],
+            sizes: vec![1; element_count],
+            component_count: element_count,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.parents.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parents.is_empty()
+    }
+
+    /// The root standing for the element's set
+    pub fn find(&mut self, element: usize) -> usize {
+        let mut root = element;
+        while self.parents[root] != root {
+            root = self.parents[root];
+        }
+        let mut current = element;
+        while self.parents[current] != root {
+            let next = self.parents[current];
+            self.parents[current] = root;
+            current = next;
+        }
+        root
+    }
+
+    /// Merge the sets of the two elements, false if they were already in the same set
+    pub fn union(&mut self, x: usize, y: usize) -> bool {
+        let x_root = self.find(x);
+        let y_root = self.find(y);
+        if x_root == y_root {
+            return false;
+        }
+        let (root, child) = match self.ranks[x_root].cmp(&self.ranks[y_root]) {
+            Ordering::Less => (y_root, x_root),
+            Ordering::Greater => (x_root, y_root),
+            Ordering::Equal => {
+                self.ranks[x_root] += 1;
+                (x_root, y_root)
+            }
+        };
+        self.parents[child] = root;
+        self.sizes[root] += self.sizes[child];
+        self.component_count -= 1;
+        true
+    }
+
+    pub fn connected(&mut self, x: usize, y: usize) -> bool {
+        self.find(x) == self.find(y)
+    }
+
+    /// Number of elements in the same set as the element, itself included
+    pub fn size_of(&mut self, element: usize) -> usize {
+        let root = self.find(element);
+        self.sizes[root]
+    }
+
+    /// Number of disjoint sets
+    pub fn component_count(&self) -> usize {
+        self.component_count
+    }
+
+    /// The elements of each set, each in ascending order, the sets ordered by their smallest element
+    pub fn components(&mut self) -> Vec<Vec<usize>> {
+        let mut by_root: BTreeMap<usize, Vec<usize>> = BTreeMap::default();
+        let mut roots: Vec<usize> = vec![];
+        for element in 0..self.len() {
+            let root = self.find(element);
+            by_root
+                .entry(root)
+                .or_insert_with(|| {
+                    roots.push(root);
+                    vec![]
+                })
+                .push(element);
+        }
+        roots
+            .into_iter()
+            .map(|root| by_root.remove(&root).unwrap())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sets_merged_and_counted() {
+        let mut sets = UnionFind::new(7);
+        assert_eq!(sets.component_count(), 7);
+        assert!(sets.union(0, 3));
+        assert!(sets.union(4, 3));
+        assert!(sets.union(5, 6));
+        assert!(!sets.union(0, 4));
+        assert!(sets.connected(4, 0));
+        assert!(!sets.connected(1, 6));
+        assert_eq!(sets.size_of(3), 3);
+        assert_eq!(sets.size_of(6), 2);
+        assert_eq!(sets.size_of(2), 1);
+        assert_eq!(sets.component_count(), 4);
+        assert_eq!(
+            sets.components(),
+            vec![vec![0, 3, 4], vec![1], vec![2], vec![5, 6]]
+        );
+
+        // a long chain, every element ending up under the same root
+        let mut chain = UnionFind::new(10_000);
+        (1..10_000).for_each(|element| {
+            chain.union(element - 1, element);
+        });
+        assert_eq!(chain.component_count(), 1);
+        assert_eq!(chain.size_of(1234), 10_000);
+        assert!(UnionFind::new(0).is_empty());
+    }
+}
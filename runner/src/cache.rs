@@ -0,0 +1,147 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use anyhow::Context;
+use sha2::{Digest, Sha256};
+
+use crate::AError;
+
+/// Hex encoded SHA-256 of the file's contents
+pub fn file_sha(path: &Path) -> Result<String, AError> {
+    let contents = fs::read(path).with_context(|| format!("Failed reading {}", path.display()))?;
+    Ok(Sha256::digest(&contents)
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct CacheKey {
+    pub day: u8,
+    pub part: u8,
+    pub input_sha: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CachedResult {
+    /// Fingerprint of the binary that produced the answer and of any config it read, a rebuilt or reconfigured
+    /// day invalidates its results
+    pub build_fingerprint: String,
+    pub answer: String,
+    pub took: Duration,
+}
+
+/// Answers from previous runs, stored as tab separated lines:
+/// `day  part  input_sha  build_fingerprint  answer  took_micros`
+#[derive(Debug, Default)]
+pub struct ResultCache {
+    path: PathBuf,
+    entries: BTreeMap<CacheKey, CachedResult>,
+}
+
+impl ResultCache {
+    /// Load the cache from the path, starting afresh if it doesn't exist yet
+    pub fn load(path: &Path) -> Result<Self, AError> {
+        let mut cache = ResultCache {
+            path: path.to_path_buf(),
+            entries: BTreeMap::default(),
+        };
+        if !path.exists() {
+            return Ok(cache);
+        }
+        let contents = fs::read_to_string(path)?;
+        for line in contents.lines().filter(|line| !line.is_empty()) {
+            let fields: Vec<&str> = line.split('\t').collect();
+            let [day, part, input_sha, build_fingerprint, answer, took_micros] = fields[..] else {
+                return Err(AError::msg(format!("Malformed cache line: '{line}'")));
+            };
+            cache.entries.insert(
+                CacheKey {
+                    day: day.parse()?,
+                    part: part.parse()?,
+                    input_sha: input_sha.to_string(),
+                },
+                CachedResult {
+                    build_fingerprint: build_fingerprint.to_string(),
+                    answer: answer.to_string(),
+                    took: Duration::from_micros(took_micros.parse()?),
+                },
+            );
+        }
+        Ok(cache)
+    }
+
+    pub fn save(&self) -> Result<(), AError> {
+        let contents: String = self
+            .entries
+            .iter()
+            .map(|(key, result)| {
+                format!(
+                    "{}\t{}\t{}\t{}\t{}\t{}\n",
+                    key.day,
+                    key.part,
+                    key.input_sha,
+                    result.build_fingerprint,
+                    result.answer,
+                    result.took.as_micros()
+                )
+            })
+            .collect();
+        fs::write(&self.path, contents)
+            .with_context(|| format!("Failed writing cache {}", self.path.display()))
+    }
+
+    /// The cached result, provided it was produced by the same build of the day
+    pub fn get(&self, key: &CacheKey, build_fingerprint: &str) -> Option<&CachedResult> {
+        self.entries
+            .get(key)
+            .filter(|result| result.build_fingerprint == build_fingerprint)
+    }
+
+    pub fn insert(&mut self, key: CacheKey, result: CachedResult) {
+        self.entries.insert(key, result);
+    }
+
+    /// Drop everything cached for the day's part
+    pub fn invalidate(&mut self, day: u8, part: u8) {
+        self.entries
+            .retain(|key, _| key.day != day || key.part != part);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cache_round_trip() {
+        let path = std::env::temp_dir().join(format!("aoc-cache-test-{}", std::process::id()));
+        let key = CacheKey {
+            day: 7,
+            part: 2,
+            input_sha: "abc".to_string(),
+        };
+        let result = CachedResult {
+            build_fingerprint: "build1".to_string(),
+            answer: "Ratio { numer: 5, denom: 1 }".to_string(),
+            took: Duration::from_micros(1234),
+        };
+        let mut cache = ResultCache::load(&path).unwrap();
+        assert!(cache.get(&key, "build1").is_none());
+        cache.insert(key.clone(), result.clone());
+        cache.save().unwrap();
+
+        let mut loaded = ResultCache::load(&path).unwrap();
+        assert_eq!(loaded.get(&key, "build1"), Some(&result));
+        assert!(loaded.get(&key, "build2").is_none());
+        loaded.invalidate(7, 1);
+        assert!(loaded.get(&key, "build1").is_some());
+        loaded.invalidate(7, 2);
+        assert!(loaded.get(&key, "build1").is_none());
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,1035 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Display,
+    io::{BufRead, Read, Write},
+    path::{Path, PathBuf},
+    process::{Child, Command, Stdio},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::Context;
+use aoc_client::{ensure_input, AocClient, Submissions, Verdict, SESSION_ENV};
+use cache::{file_sha, CacheKey, CachedResult, ResultCache};
+use processor::{
+    answer::NumberFormat,
+    config::{DAY_CONFIG_FILE, FILE_ENV},
+    diagnostics::DIAGNOSTICS_ENV,
+    solver::{DESCRIBE_ARG, SKIP_PARTS_ENV},
+    timings::PHASE_TIMINGS_ENV,
+    PuzzleMetadata,
+};
+use progress::Progress;
+use report::{
+    explain_mismatch, load_expected, render_json, render_markdown, render_text, DayReport,
+    DaySummary, PartReport, PhaseTiming, RunReport,
+};
+
+mod cache;
+mod progress;
+mod report;
+
+type AError = anyhow::Error;
+
+const CACHE_FILE: &str = "aoc-results-cache.txt";
+const EXPECTED_FILE: &str = "expected-results.txt";
+/// The parts verified against the expected answers, see [`Progress`]
+const PROGRESS_FILE: &str = "progress.json";
+/// Answers submitted to the site and what it made of them, see [`Submissions`]
+const SUBMISSIONS_FILE: &str = "submitted-answers.txt";
+
+/// The workspace holding the dayN directories
+fn workspace_dir() -> PathBuf {
+    let runner_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    runner_dir.parent().unwrap_or(runner_dir).to_path_buf()
+}
+
+/// The day binaries are built alongside the runner, e.g. target/release/day1..day25
+fn binaries_dir() -> Result<PathBuf, AError> {
+    let exe = std::env::current_exe()?;
+    exe.parent()
+        .map(|dir| dir.to_path_buf())
+        .ok_or_else(|| AError::msg("Runner executable has no parent directory"))
+}
+
+/// Finds the built day binaries, ordered by day number
+fn find_day_binaries(dir: &Path) -> Result<Vec<(u8, PathBuf)>, AError> {
+    let mut days: Vec<(u8, PathBuf)> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let path = entry.path();
+            let day = path
+                .file_stem()?
+                .to_str()?
+                .strip_prefix("day")?
+                .parse::<u8>()
+                .ok()?;
+            (path.is_file() && path.extension().is_none_or(|ext| ext == "exe"))
+                .then_some((day, path))
+        })
+        .collect();
+    days.sort();
+    Ok(days)
+}
+
+fn describe(binary: &Path) -> Result<PuzzleMetadata, AError> {
+    let output = Command::new(binary)
+        .arg(DESCRIBE_ARG)
+        .output()
+        .with_context(|| format!("Failed running {}", binary.display()))?;
+    PuzzleMetadata::parse_describe_lines(&String::from_utf8_lossy(&output.stdout))
+        .with_context(|| format!("Failed reading description from {}", binary.display()))
+}
+
+fn list() -> Result<(), AError> {
+    let dir = binaries_dir()?;
+    let binaries = find_day_binaries(&dir)?;
+    if binaries.is_empty() {
+        println!("No day binaries found in {}", dir.display());
+    }
+    for (day, binary) in binaries {
+        match describe(&binary) {
+            Ok(metadata) => println!("{metadata}"),
+            Err(e) => println!("Day {day}: {e:#}"),
+        }
+    }
+    Ok(())
+}
+
+/// Answers are printed by the days as "Result N: answer", possibly followed by " (took: Ns)" giving how long
+/// the part took in seconds
+fn parse_results(output: &str) -> Vec<(u8, String, Option<Duration>)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let rest = line.strip_prefix("Result ")?;
+            let (part, answer) = rest.split_once(": ")?;
+            let (answer, took) = match answer.split_once(" (took: ") {
+                Some((answer, took)) => (
+                    answer,
+                    took.strip_suffix("s)")
+                        .and_then(|secs| secs.parse::<f64>().ok())
+                        .and_then(|secs| Duration::try_from_secs_f64(secs).ok()),
+                ),
+                None => (answer, None),
+            };
+            Some((part.parse::<u8>().ok()?, answer.to_string(), took))
+        })
+        .collect()
+}
+
+/// Phase timings printed by the days (when asked for) as "Phases N: parse=0.001 process=1.5 ...", in seconds
+fn parse_phases(output: &str) -> Vec<(u8, Vec<PhaseTiming>)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (part, timings) = line.strip_prefix("Phases ")?.split_once(": ")?;
+            let phases = timings
+                .split_whitespace()
+                .map(|timing| {
+                    let (phase, secs) = timing.split_once('=')?;
+                    Some(PhaseTiming {
+                        phase: phase.to_string(),
+                        secs: secs.parse().ok()?,
+                    })
+                })
+                .collect::<Option<Vec<_>>>()?;
+            Some((part.parse::<u8>().ok()?, phases))
+        })
+        .collect()
+}
+
+/// Diagnostics recorded by the days (when asked for) as "Diagnostic N: name: value"
+fn parse_diagnostics(output: &str) -> Vec<(u8, String, String)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (part, diagnostic) = line.strip_prefix("Diagnostic ")?.split_once(": ")?;
+            let (name, value) = diagnostic.split_once(": ")?;
+            Some((
+                part.parse::<u8>().ok()?,
+                name.to_string(),
+                value.to_string(),
+            ))
+        })
+        .collect()
+}
+
+/// Parts that failed, printed by the days as "Error on N: error"
+fn parse_errors(output: &str) -> Vec<(u8, String)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (part, error) = line.strip_prefix("Error on ")?.split_once(": ")?;
+            Some((part.parse::<u8>().ok()?, error.to_string()))
+        })
+        .collect()
+}
+
+/// Which parts to run given those marked as slow in the days' metadata
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SlowParts {
+    Include,
+    Skip,
+    Only,
+}
+
+impl SlowParts {
+    fn from_args(args: &[String]) -> Result<Self, AError> {
+        let skip = args.iter().any(|arg| arg == "--skip-slow");
+        let only = args.iter().any(|arg| arg == "--only-slow");
+        match (skip, only) {
+            (false, false) => Ok(SlowParts::Include),
+            (true, false) => Ok(SlowParts::Skip),
+            (false, true) => Ok(SlowParts::Only),
+            (true, true) => Err(AError::msg(
+                "Only one of --skip-slow and --only-slow can be given",
+            )),
+        }
+    }
+
+    fn parts(&self, metadata: &PuzzleMetadata) -> Vec<u8> {
+        [1, 2]
+            .into_iter()
+            .filter(|part| match self {
+                SlowParts::Include => true,
+                SlowParts::Skip => !metadata.is_slow(*part),
+                SlowParts::Only => metadata.is_slow(*part),
+            })
+            .collect()
+    }
+}
+
+/// The days and parts to run, and what on
+#[derive(Debug, Clone, PartialEq)]
+struct Selection {
+    /// Empty meaning all of them
+    days: Vec<u8>,
+    /// Empty meaning both, unless excluded by slow_parts
+    parts: Vec<u8>,
+    slow_parts: SlowParts,
+    /// Instead of the day's own input file
+    input: Option<PathBuf>,
+}
+
+impl Selection {
+    fn from_args(args: &[String]) -> Result<Self, AError> {
+        let days = parse_days(args)?;
+        let input = args
+            .windows(2)
+            .find(|pair| pair[0] == "--input")
+            .map(|pair| std::env::current_dir().map(|dir| dir.join(&pair[1])))
+            .transpose()?;
+        if input.is_some() && days.len() != 1 {
+            return Err(AError::msg("--input needs exactly one --day"));
+        }
+        Ok(Selection {
+            days,
+            parts: parse_parts(args)?,
+            slow_parts: SlowParts::from_args(args)?,
+            input,
+        })
+    }
+
+    /// The answers to check against, which are only known for the days' own inputs
+    fn expected(&self) -> Result<BTreeMap<(u8, u8), String>, AError> {
+        match self.input {
+            Some(_) => Ok(BTreeMap::new()),
+            None => load_expected(&workspace_dir().join(EXPECTED_FILE)),
+        }
+    }
+
+    fn includes_day(&self, day: u8) -> bool {
+        self.days.is_empty() || self.days.contains(&day)
+    }
+
+    /// The parts asked for with `--part`, both if none were
+    fn requested_parts(&self) -> Vec<u8> {
+        match self.parts.is_empty() {
+            true => vec![1, 2],
+            false => self.parts.clone(),
+        }
+    }
+
+    fn parts(&self, metadata: &PuzzleMetadata) -> Vec<u8> {
+        self.slow_parts
+            .parts(metadata)
+            .into_iter()
+            .filter(|part| self.parts.is_empty() || self.parts.contains(part))
+            .collect()
+    }
+}
+
+struct DayRun {
+    day: u8,
+    part: u8,
+    answer: String,
+    took: Duration,
+    peak_memory_kib: Option<u64>,
+    cached: bool,
+    phases: Vec<PhaseTiming>,
+    /// Empty for cached results
+    diagnostics: Vec<(String, String)>,
+}
+
+/// A part that was asked for but gave no answer
+struct FailedPart {
+    day: u8,
+    part: u8,
+    reason: String,
+}
+
+/// Lines of a failed day's stderr kept for its error, enough for a panic message and where it happened
+const STDERR_TAIL_LINES: usize = 10;
+
+/// Read all of the child's stdout and stderr, the stderr on a thread of its own so that neither pipe can fill
+/// up and leave the child blocked
+fn read_output(child: &mut Child) -> Result<(String, String), AError> {
+    let stderr = child.stderr.take().map(|mut err| {
+        std::thread::spawn(move || {
+            let mut stderr = String::new();
+            err.read_to_string(&mut stderr).map(|_| stderr)
+        })
+    });
+    let mut stdout = String::new();
+    if let Some(mut out) = child.stdout.take() {
+        out.read_to_string(&mut stdout)?;
+    }
+    let stderr = match stderr {
+        Some(reader) => reader
+            .join()
+            .map_err(|_| AError::msg("Failed reading stderr"))??,
+        None => String::new(),
+    };
+    Ok((stdout, stderr))
+}
+
+/// The error for a command that didn't exit cleanly, with the tail of what it wrote to stderr
+fn failed_run(exit: impl Display, stderr: &str) -> AError {
+    let lines: Vec<&str> = stderr.lines().collect();
+    let tail = lines[lines.len().saturating_sub(STDERR_TAIL_LINES)..].join("\n");
+    match tail.is_empty() {
+        true => AError::msg(format!("Exited with {exit}")),
+        false => AError::msg(format!("Exited with {exit}:\n{tail}")),
+    }
+}
+
+/// Run the command to completion, giving its stdout and, where it can be measured, its peak resident
+/// memory in KiB.  An error if it doesn't exit successfully.
+#[cfg(target_os = "linux")]
+fn run_measured(command: &mut Command) -> Result<(String, Option<u64>), AError> {
+    let mut child = command
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    let (stdout, stderr) = read_output(&mut child)?;
+    let mut status = 0;
+    // SAFETY: rusage is plain data, and the child is waited on here rather than through `child`
+    let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+    let pid = unsafe { libc::wait4(child.id() as libc::pid_t, &mut status, 0, &mut usage) };
+    if pid < 0 {
+        return Err(std::io::Error::last_os_error().into());
+    }
+    if !libc::WIFEXITED(status) {
+        return Err(failed_run(
+            format_args!("signal {}", libc::WTERMSIG(status)),
+            &stderr,
+        ));
+    }
+    if libc::WEXITSTATUS(status) != 0 {
+        return Err(failed_run(
+            format_args!("exit code {}", libc::WEXITSTATUS(status)),
+            &stderr,
+        ));
+    }
+    // ru_maxrss is in KiB on Linux
+    Ok((stdout, Some(usage.ru_maxrss as u64)))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn run_measured(command: &mut Command) -> Result<(String, Option<u64>), AError> {
+    let mut child = command
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    let (stdout, stderr) = read_output(&mut child)?;
+    let status = child.wait()?;
+    if !status.success() {
+        return Err(failed_run(status, &stderr));
+    }
+    Ok((stdout, None))
+}
+
+/// Run the requested parts of the day's binary in its directory against its input (or the one given), unless
+/// the cache already holds their answers from the same build for the same input.  The day's input is
+/// downloaded first if it's missing.  Each requested part gives either its run or why it has no answer.
+fn run_day(
+    cache: &mut ResultCache,
+    use_cache: bool,
+    day: u8,
+    parts: &[u8],
+    input: Option<&Path>,
+    binary: &Path,
+) -> Result<Vec<Result<DayRun, FailedPart>>, AError> {
+    if parts.is_empty() {
+        return Ok(vec![]);
+    }
+    let day_dir = workspace_dir().join(format!("day{day}"));
+    let input_path = match input {
+        Some(input) => input.to_path_buf(),
+        None => ensure_input(&day_dir, day)?,
+    };
+    let input_sha = file_sha(&input_path)
+        .with_context(|| format!("Failed reading {}", input_path.display()))?;
+    //The day's config can change its answers as much as a rebuild can
+    let config_path = day_dir.join(DAY_CONFIG_FILE);
+    let build_fingerprint = match config_path.is_file() {
+        true => format!("{}+{}", file_sha(binary)?, file_sha(&config_path)?),
+        false => file_sha(binary)?,
+    };
+    let keys: Vec<CacheKey> = parts
+        .iter()
+        .map(|&part| CacheKey {
+            day,
+            part,
+            input_sha: input_sha.clone(),
+        })
+        .collect();
+
+    if use_cache {
+        let cached: Vec<DayRun> = keys
+            .iter()
+            .filter_map(|key| {
+                cache.get(key, &build_fingerprint).map(|result| DayRun {
+                    day,
+                    part: key.part,
+                    answer: result.answer.clone(),
+                    took: result.took,
+                    peak_memory_kib: None,
+                    cached: true,
+                    phases: vec![],
+                    diagnostics: vec![],
+                })
+            })
+            .collect();
+        if cached.len() == keys.len() {
+            return Ok(cached.into_iter().map(Ok).collect());
+        }
+    }
+
+    let started_at = Instant::now();
+    let skipped: Vec<String> = [1, 2]
+        .iter()
+        .filter(|part| !parts.contains(part))
+        .map(|part| part.to_string())
+        .collect();
+    let mut command = Command::new(binary);
+    if let Some(input) = input {
+        command.env(FILE_ENV, input);
+    }
+    let (stdout, peak_memory_kib) = run_measured(
+        command
+            .current_dir(&day_dir)
+            .env(SKIP_PARTS_ENV, skipped.join(","))
+            .env(PHASE_TIMINGS_ENV, "1")
+            .env(DIAGNOSTICS_ENV, "1"),
+    )
+    .with_context(|| format!("Failed running {}", binary.display()))?;
+    let elapsed = started_at.elapsed();
+    let results = parse_results(&stdout);
+    let mut phases = parse_phases(&stdout);
+    let diagnostics = parse_diagnostics(&stdout);
+    let errors = parse_errors(&stdout);
+
+    parts.iter().for_each(|part| cache.invalidate(day, *part));
+    let mut runs: Vec<DayRun> = results
+        .into_iter()
+        .filter(|(part, _, _)| parts.contains(part))
+        .map(|(part, answer, took)| {
+            let phases = phases
+                .iter_mut()
+                .find(|(phases_part, _)| *phases_part == part)
+                .map(|(_, phases)| std::mem::take(phases))
+                .unwrap_or_default();
+            //The part's own time where the day gives it, only falling back on the whole run's time
+            let took = took
+                .or_else(|| {
+                    (!phases.is_empty()).then(|| {
+                        Duration::from_secs_f64(phases.iter().map(|phase| phase.secs).sum())
+                    })
+                })
+                .unwrap_or(elapsed);
+            DayRun {
+                day,
+                part,
+                answer,
+                took,
+                peak_memory_kib,
+                cached: false,
+                phases,
+                diagnostics: diagnostics
+                    .iter()
+                    .filter(|(diagnostic_part, _, _)| *diagnostic_part == part)
+                    .map(|(_, name, value)| (name.clone(), value.clone()))
+                    .collect(),
+            }
+        })
+        .collect();
+    for run in runs.iter() {
+        cache.insert(
+            CacheKey {
+                day,
+                part: run.part,
+                input_sha: input_sha.clone(),
+            },
+            CachedResult {
+                build_fingerprint: build_fingerprint.clone(),
+                answer: run.answer.clone(),
+                took: run.took,
+            },
+        );
+    }
+    Ok(parts
+        .iter()
+        .map(|&part| match runs.iter().position(|run| run.part == part) {
+            Some(index) => Ok(runs.swap_remove(index)),
+            None => Err(FailedPart {
+                day,
+                part,
+                reason: errors
+                    .iter()
+                    .find(|(error_part, _)| *error_part == part)
+                    .map_or("No result printed".to_string(), |(_, error)| error.clone()),
+            }),
+        })
+        .collect())
+}
+
+/// Explain the run's answer if it isn't the expected one
+fn explain_if_wrong(run: &DayRun, expected: &BTreeMap<(u8, u8), String>) {
+    if let Some(wanted) = expected.get(&(run.day, run.part)) {
+        if *wanted != run.answer {
+            print!(
+                "{}",
+                explain_mismatch(run.day, run.part, wanted, &run.answer, &run.diagnostics)
+            );
+        }
+    }
+}
+
+/// Whether the answer to the question read back is yes, anything but `y` or `yes` being taken as no
+fn confirmed(question: &str, input: &mut impl BufRead) -> Result<bool, AError> {
+    print!("{question} [y/N] ");
+    std::io::stdout().flush()?;
+    let mut answer = String::default();
+    input.read_line(&mut answer)?;
+    Ok(matches!(
+        answer.trim().to_ascii_lowercase().as_str(),
+        "y" | "yes"
+    ))
+}
+
+/// Submits the answers of a batch of runs, asking before each one unless told not to.  Once the site says to
+/// wait before submitting again nothing more is submitted, as each further guess would only be turned away.
+struct Submitter {
+    client: AocClient,
+    submissions: Submissions,
+    confirm: bool,
+    /// What the site said about the submission that was turned away for being too soon
+    waiting: Option<Verdict>,
+}
+
+impl Submitter {
+    fn new(confirm: bool) -> Result<Self, AError> {
+        Ok(Submitter {
+            client: AocClient::from_env()?,
+            submissions: Submissions::load(&workspace_dir().join(SUBMISSIONS_FILE))?,
+            confirm,
+            waiting: None,
+        })
+    }
+
+    /// Submit the run's answer unless it's the expected one or it's already known what it would get,
+    /// recording what the site makes of it
+    fn submit(
+        &mut self,
+        expected: &BTreeMap<(u8, u8), String>,
+        run: &DayRun,
+    ) -> Result<(), AError> {
+        let (day, part, answer) = (run.day, run.part, &run.answer);
+        if expected.contains_key(&(day, part)) {
+            println!("Day {day} part {part}: already solved, not submitting {answer}");
+            return Ok(());
+        }
+        if let Some(verdict) = self.submissions.known_verdict(day, part, answer) {
+            println!(
+                "Day {day} part {part}: not submitting {answer}, it's already known to be {verdict}"
+            );
+            return Ok(());
+        }
+        if let Some(waiting) = &self.waiting {
+            println!(
+                "Day {day} part {part}: not submitting {answer}, an earlier answer was {waiting}"
+            );
+            return Ok(());
+        }
+        let question = format!("Day {day} part {part}: submit {answer}?");
+        if self.confirm && !confirmed(&question, &mut std::io::stdin().lock())? {
+            println!("Day {day} part {part}: not submitting {answer}");
+            return Ok(());
+        }
+        let verdict = self.client.submit_answer(day, part, answer)?;
+        self.submissions.record(day, part, answer, &verdict);
+        println!("Day {day} part {part}: submitted {answer}, {verdict}");
+        if matches!(verdict, Verdict::Wait(_)) {
+            self.waiting = Some(verdict);
+        }
+        Ok(())
+    }
+
+    fn save(&self) -> Result<(), AError> {
+        self.submissions.save()
+    }
+}
+
+/// How to submit the answers found, if at all
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Submit {
+    No,
+    Confirming,
+    /// Without asking first, i.e. `--submit --yes`
+    Unconfirmed,
+}
+
+impl Submit {
+    fn from_args(args: &[String]) -> Self {
+        let has = |name: &str| args.iter().any(|arg| arg == name);
+        match (has("--submit"), has("--yes")) {
+            (false, _) => Submit::No,
+            (true, false) => Submit::Confirming,
+            (true, true) => Submit::Unconfirmed,
+        }
+    }
+}
+
+/// Run the days, printing their answers and optionally submitting them.  Verifying records which answers
+/// match the expected ones in the [`PROGRESS_FILE`], failing if any don't or give no answer at all (e.g. as the
+/// day crashed).
+fn run(
+    selection: &Selection,
+    use_cache: bool,
+    submit_answers: Submit,
+    verify: bool,
+) -> Result<(), AError> {
+    let dir = binaries_dir()?;
+    let mut cache = ResultCache::load(&dir.join(CACHE_FILE))?;
+    let expected = selection.expected()?;
+    let mut progress = match verify {
+        false => None,
+        true if selection.input.is_some() => {
+            return Err(AError::msg(
+                "--verify only verifies answers for the days' own inputs",
+            ))
+        }
+        true => Some(Progress::load(&workspace_dir().join(PROGRESS_FILE))?),
+    };
+    let mut failures = 0;
+    let mut submitter = match submit_answers {
+        Submit::No => None,
+        _ if selection.input.is_some() => {
+            return Err(AError::msg(
+                "--submit only submits answers for the days' own inputs",
+            ))
+        }
+        Submit::Confirming => Some(Submitter::new(true)?),
+        Submit::Unconfirmed => Some(Submitter::new(false)?),
+    };
+    let binaries = find_day_binaries(&dir)?;
+    for (day, binary) in binaries
+        .iter()
+        .filter(|(day, _)| selection.includes_day(*day))
+    {
+        //Without its metadata there's no telling which parts the day would have run, so any asked for fail
+        let (parts, runs) = match describe(binary) {
+            Ok(metadata) => {
+                let parts = selection.parts(&metadata);
+                let runs = run_day(
+                    &mut cache,
+                    use_cache,
+                    *day,
+                    &parts,
+                    selection.input.as_deref(),
+                    binary,
+                );
+                (parts, runs)
+            }
+            Err(e) => (selection.requested_parts(), Err(e)),
+        };
+        let failed = match runs {
+            Ok(runs) => runs
+                .into_iter()
+                .filter_map(|run| match run {
+                    Ok(run) => {
+                        println!(
+                            "Day {} part {}: {} (took: {}s){}",
+                            run.day,
+                            run.part,
+                            run.answer,
+                            run.took.as_secs_f32(),
+                            if run.cached { " [cached]" } else { "" }
+                        );
+                        explain_if_wrong(&run, &expected);
+                        let wanted = expected.get(&(run.day, run.part));
+                        if let Some(submitter) = submitter.as_mut() {
+                            if let Err(e) = submitter.submit(&expected, &run) {
+                                println!(
+                                    "Day {} part {}: failed submitting: {e}",
+                                    run.day, run.part
+                                );
+                            }
+                        }
+                        match wanted {
+                            Some(wanted) if *wanted != run.answer => Some(run.part),
+                            _ => {
+                                if let Some(progress) = progress.as_mut() {
+                                    progress.record(run.day, run.part, wanted.is_some());
+                                }
+                                None
+                            }
+                        }
+                    }
+                    Err(failed) => {
+                        println!("Day {} part {}: {}", failed.day, failed.part, failed.reason);
+                        Some(failed.part)
+                    }
+                })
+                .collect(),
+            Err(e) => {
+                println!("Day {day}: {e:#}");
+                parts
+            }
+        };
+        if let Some(progress) = progress.as_mut() {
+            failed
+                .iter()
+                .for_each(|part| progress.record(*day, *part, false));
+            failures += failed.len();
+        }
+    }
+    if let Some(submitter) = submitter {
+        submitter.save()?;
+    }
+    cache.save()?;
+    match progress {
+        Some(progress) => {
+            progress.save()?;
+            match failures {
+                0 => Ok(()),
+                _ => Err(AError::msg(format!("{failures} failed verification"))),
+            }
+        }
+        None => Ok(()),
+    }
+}
+
+/// The short hash of the workspace's checked out revision
+fn git_revision() -> String {
+    Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .current_dir(workspace_dir())
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map_or("unknown".to_string(), |output| {
+            String::from_utf8_lossy(&output.stdout).trim().to_string()
+        })
+}
+
+/// Run the days and print a table of their answers, how long they took, their peak memory and whether
+/// the answers are the expected ones, also writing it as Markdown and the full
+/// report as JSON if files are given
+fn summary(
+    selection: &Selection,
+    use_cache: bool,
+    markdown: Option<&Path>,
+    json: Option<&Path>,
+    number_format: NumberFormat,
+) -> Result<(), AError> {
+    let dir = binaries_dir()?;
+    let mut cache = ResultCache::load(&dir.join(CACHE_FILE))?;
+    let expected = selection.expected()?;
+    let mut summaries = vec![];
+    let mut day_reports = vec![];
+    for (day, binary) in find_day_binaries(&dir)?
+        .iter()
+        .filter(|(day, _)| selection.includes_day(*day))
+    {
+        let metadata = describe(binary);
+        let mut summary = DaySummary {
+            day: *day,
+            title: metadata
+                .as_ref()
+                .map_or("?".to_string(), |metadata| metadata.title.clone()),
+            ..Default::default()
+        };
+        let runs = metadata.and_then(|metadata| {
+            run_day(
+                &mut cache,
+                use_cache,
+                *day,
+                &selection.parts(&metadata),
+                selection.input.as_deref(),
+                binary,
+            )
+        });
+        let mut day_report = DayReport {
+            day: *day,
+            title: summary.title.clone(),
+            parts: vec![],
+        };
+        match runs {
+            Ok(runs) => runs.into_iter().for_each(|run| {
+                let run = match run {
+                    Ok(run) => run,
+                    Err(failed) => {
+                        println!("Day {} part {}: {}", failed.day, failed.part, failed.reason);
+                        return;
+                    }
+                };
+                explain_if_wrong(&run, &expected);
+                day_report.parts.push(PartReport {
+                    part: run.part,
+                    answer: run.answer.clone(),
+                    matches: expected
+                        .get(&(run.day, run.part))
+                        .map(|answer| *answer == run.answer),
+                    took_secs: run.took.as_secs_f64(),
+                    peak_memory_kib: run.peak_memory_kib,
+                    cached: run.cached,
+                    phases: run.phases,
+                });
+                if let Some(answer) = summary.answers.get_mut(run.part as usize - 1) {
+                    *answer = Some(run.answer);
+                }
+                summary.took = summary.took.max(run.took);
+                summary.peak_memory_kib = run.peak_memory_kib;
+            }),
+            Err(e) => println!("Day {day}: {e:#}"),
+        }
+        summary.check(&expected);
+        summaries.push(summary);
+        day_reports.push(day_report);
+    }
+    cache.save()?;
+    print!("{}", render_text(&summaries, number_format));
+    if let Some(path) = markdown {
+        std::fs::write(path, render_markdown(&summaries, number_format))
+            .with_context(|| format!("Failed writing {}", path.display()))?;
+    }
+    if let Some(path) = json {
+        let report = RunReport {
+            revision: git_revision(),
+            timestamp: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+            days: day_reports,
+        };
+        std::fs::write(path, render_json(&report)?)
+            .with_context(|| format!("Failed writing {}", path.display()))?;
+    }
+    Ok(())
+}
+
+fn usage() {
+    println!("Usage: aoc-runner --list");
+    println!(
+        "       aoc-runner [--day N]... [--part N] [--input FILE] [--no-cache] [--skip-slow | --only-slow] [--submit [--yes]] [--verify]"
+    );
+    println!(
+        "       aoc-runner --summary [--markdown FILE] [--json FILE] [--day N]... [--part N] [--input FILE] [--no-cache] [--skip-slow | --only-slow] [--number-format plain|thousands|scientific]"
+    );
+    println!("--number-format shows whole number answers in the summary grouped in thousands or in scientific notation");
+    println!("--input runs a single --day on FILE instead of the day's input.txt");
+    println!("Missing inputs are downloaded as the user whose session cookie is in {SESSION_ENV}");
+    println!("--submit submits the days' answers as that user, asking first unless given --yes and never submitting");
+    println!("         the same guess twice, stopping once the site says to wait");
+    println!("--verify fails unless the answers are the expected ones, recording those that are in {PROGRESS_FILE}");
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let result = if args.iter().any(|arg| arg == "--help") {
+        usage();
+        Ok(())
+    } else if args.iter().any(|arg| arg == "--list") {
+        list()
+    } else {
+        let use_cache = !args.iter().any(|arg| arg == "--no-cache");
+        let path_arg = |name: &str| {
+            args.windows(2)
+                .find(|pair| pair[0] == name)
+                .map(|pair| PathBuf::from(&pair[1]))
+        };
+        let (markdown, json) = (path_arg("--markdown"), path_arg("--json"));
+        Selection::from_args(&args).and_then(|selection| {
+            if markdown.is_some() || json.is_some() || args.iter().any(|arg| arg == "--summary") {
+                let number_format = args
+                    .windows(2)
+                    .find(|pair| pair[0] == "--number-format")
+                    .map_or(Ok(NumberFormat::Plain), |pair| pair[1].parse())?;
+                summary(
+                    &selection,
+                    use_cache,
+                    markdown.as_deref(),
+                    json.as_deref(),
+                    number_format,
+                )
+            } else {
+                run(
+                    &selection,
+                    use_cache,
+                    Submit::from_args(&args),
+                    args.iter().any(|arg| arg == "--verify"),
+                )
+            }
+        })
+    };
+    if let Err(e) = result {
+        println!("Error: {e}");
+        std::process::exit(1);
+    }
+}
+
+/// Days given as `--day N`, empty meaning all of them
+fn parse_days(args: &[String]) -> Result<Vec<u8>, AError> {
+    args.windows(2)
+        .filter(|pair| pair[0] == "--day")
+        .map(|pair| {
+            pair[1]
+                .parse::<u8>()
+                .with_context(|| format!("Invalid day: '{}'", pair[1]))
+        })
+        .collect()
+}
+
+/// Parts given as `--part N`, empty meaning both
+fn parse_parts(args: &[String]) -> Result<Vec<u8>, AError> {
+    args.windows(2)
+        .filter(|pair| pair[0] == "--part")
+        .map(|pair| match pair[1].parse::<u8>() {
+            Ok(part @ (1 | 2)) => Ok(part),
+            _ => Err(AError::msg(format!("Invalid part: '{}'", pair[1]))),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn results_parsed() {
+        let output =
+            "Loaded:\nResult 1: 2370 (took: 0.008s)\nPhases 1: parse=0.25 process=1.5\nDiagnostic 2: finished walks: 6\nResult 2: Ratio { numer: 9, denom: 1 }\n";
+        assert_eq!(
+            parse_results(output),
+            vec![
+                (1, "2370".to_string(), Some(Duration::from_millis(8))),
+                (2, "Ratio { numer: 9, denom: 1 }".to_string(), None)
+            ]
+        );
+        assert_eq!(
+            parse_phases(output),
+            vec![(
+                1,
+                vec![
+                    PhaseTiming {
+                        phase: "parse".to_string(),
+                        secs: 0.25
+                    },
+                    PhaseTiming {
+                        phase: "process".to_string(),
+                        secs: 1.5
+                    }
+                ]
+            )]
+        );
+        assert_eq!(
+            parse_errors("Result 1: 3\nError on 2: No path found\n"),
+            vec![(2, "No path found".to_string())]
+        );
+        assert_eq!(
+            parse_diagnostics(output),
+            vec![(2, "finished walks".to_string(), "6".to_string())]
+        );
+        let args: Vec<String> = ["--day", "3", "--no-cache", "--day", "17"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert_eq!(parse_days(&args).unwrap(), vec![3, 17]);
+    }
+
+    #[test]
+    fn submissions_confirmed() {
+        assert!(confirmed("Submit?", &mut "y\n".as_bytes()).unwrap());
+        assert!(confirmed("Submit?", &mut " YES \n".as_bytes()).unwrap());
+        assert!(!confirmed("Submit?", &mut "n\n".as_bytes()).unwrap());
+        assert!(!confirmed("Submit?", &mut "".as_bytes()).unwrap());
+        let args = |args: &[&str]| args.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(Submit::from_args(&args(&["--yes"])), Submit::No);
+        assert_eq!(Submit::from_args(&args(&["--submit"])), Submit::Confirming);
+        assert_eq!(
+            Submit::from_args(&args(&["--yes", "--submit"])),
+            Submit::Unconfirmed
+        );
+    }
+
+    #[test]
+    fn slow_parts_selected() {
+        let metadata = PuzzleMetadata::new(23, "A Long Walk").with_slow_part(2);
+        let args = |args: &[&str]| args.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let parts = |given: &[&str]| SlowParts::from_args(&args(given)).unwrap().parts(&metadata);
+        assert_eq!(parts(&[]), vec![1, 2]);
+        assert_eq!(parts(&["--skip-slow"]), vec![1]);
+        assert_eq!(parts(&["--only-slow"]), vec![2]);
+        assert!(SlowParts::from_args(&args(&["--skip-slow", "--only-slow"])).is_err());
+    }
+
+    #[test]
+    fn selection_from_args() {
+        let args = |args: &[&str]| args.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let metadata = PuzzleMetadata::new(23, "A Long Walk").with_slow_part(2);
+        let selection =
+            Selection::from_args(&args(&["--day", "23", "--part", "2", "--input", "/tmp/x"]))
+                .unwrap();
+        assert_eq!(selection.days, vec![23]);
+        assert!(selection.includes_day(23) && !selection.includes_day(3));
+        assert_eq!(selection.parts(&metadata), vec![2]);
+        assert_eq!(selection.input, Some(PathBuf::from("/tmp/x")));
+
+        let selection = Selection::from_args(&args(&["--part", "2", "--skip-slow"])).unwrap();
+        assert!(selection.includes_day(3));
+        assert_eq!(selection.parts(&metadata), Vec::<u8>::new());
+
+        assert!(Selection::from_args(&args(&["--part", "3"])).is_err());
+        assert!(Selection::from_args(&args(&["--input", "x.txt"])).is_err());
+        assert!(
+            Selection::from_args(&args(&["--day", "1", "--day", "2", "--input", "x"])).is_err()
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn failed_runs_reported() {
+        let (stdout, _) =
+            run_measured(Command::new("sh").args(["-c", "echo Result 1: 3"])).unwrap();
+        assert_eq!(stdout, "Result 1: 3\n");
+
+        let script = "echo Result 1: 3; for i in $(seq 12); do echo line $i >&2; done; exit 101";
+        let error = run_measured(Command::new("sh").args(["-c", script]))
+            .unwrap_err()
+            .to_string();
+        assert!(error.starts_with("Exited with exit code 101:\nline 3\n"));
+        assert!(error.ends_with("line 12"));
+
+        let error = run_measured(Command::new("sh").args(["-c", "kill -9 $$"])).unwrap_err();
+        assert_eq!(error.to_string(), "Exited with signal 9");
+    }
+}
@@ -0,0 +1,86 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+use crate::AError;
+
+/// The parts whose answers have been verified against the expected ones, by day.  Kept as JSON so that other
+/// tools (e.g. `tools progress`) can read it: `{"verified": {"1": [1, 2], ...}}`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct ProgressState {
+    verified: BTreeMap<u8, BTreeSet<u8>>,
+}
+
+/// The verified parts, loaded from and saved back to a file
+#[derive(Debug, Default)]
+pub struct Progress {
+    path: PathBuf,
+    state: ProgressState,
+}
+
+impl Progress {
+    /// Load the progress from the path, starting afresh if it doesn't exist yet
+    pub fn load(path: &Path) -> Result<Self, AError> {
+        let state = if path.exists() {
+            let json = fs::read_to_string(path)
+                .with_context(|| format!("Failed reading {}", path.display()))?;
+            serde_json::from_str(&json)
+                .with_context(|| format!("Failed parsing {}", path.display()))?
+        } else {
+            ProgressState::default()
+        };
+        Ok(Progress {
+            path: path.to_path_buf(),
+            state,
+        })
+    }
+
+    pub fn save(&self) -> Result<(), AError> {
+        let json = serde_json::to_string_pretty(&self.state)?;
+        fs::write(&self.path, json)
+            .with_context(|| format!("Failed writing {}", self.path.display()))
+    }
+
+    /// Record whether the part's answer matched the expected one, a wrong answer undoing any earlier
+    /// verification
+    pub fn record(&mut self, day: u8, part: u8, verified: bool) {
+        if verified {
+            self.state.verified.entry(day).or_default().insert(part);
+        } else if let Some(parts) = self.state.verified.get_mut(&day) {
+            parts.remove(&part);
+            if parts.is_empty() {
+                self.state.verified.remove(&day);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn progress_round_trip() {
+        let path =
+            std::env::temp_dir().join(format!("aoc-progress-test-{}.json", std::process::id()));
+        let mut progress = Progress::load(&path).unwrap();
+        progress.record(1, 1, true);
+        progress.record(1, 2, true);
+        progress.record(3, 1, true);
+        progress.record(3, 1, false);
+        progress.record(4, 2, false);
+        progress.save().unwrap();
+
+        let loaded = Progress::load(&path).unwrap();
+        assert_eq!(loaded.state, progress.state);
+        let json: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(json, serde_json::json!({"verified": {"1": [1, 2]}}));
+        fs::remove_file(&path).unwrap();
+    }
+}
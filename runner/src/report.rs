@@ -0,0 +1,313 @@
+use std::{collections::BTreeMap, fs, path::Path, time::Duration};
+
+use processor::answer::NumberFormat;
+use serde::Serialize;
+
+use crate::AError;
+
+/// Expected answers keyed by (day, part), read from tab separated lines: `day  part  answer`.
+/// Blank lines and lines starting with `#` are ignored.
+pub fn load_expected(path: &Path) -> Result<BTreeMap<(u8, u8), String>, AError> {
+    if !path.exists() {
+        return Ok(BTreeMap::default());
+    }
+    parse_expected(&fs::read_to_string(path)?)
+}
+
+fn parse_expected(contents: &str) -> Result<BTreeMap<(u8, u8), String>, AError> {
+    contents
+        .lines()
+        .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let fields: Vec<&str> = line.split('\t').collect();
+            let [day, part, answer] = fields[..] else {
+                return Err(AError::msg(format!("Malformed expected line: '{line}'")));
+            };
+            Ok(((day.parse()?, part.parse()?), answer.to_string()))
+        })
+        .collect()
+}
+
+/// One line of the summary, covering both parts of a day
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DaySummary {
+    pub day: u8,
+    pub title: String,
+    pub answers: [Option<String>; 2],
+    pub took: Duration,
+    /// Peak resident memory of the day's process in KiB, None if it wasn't measured (e.g. cached)
+    pub peak_memory_kib: Option<u64>,
+    /// None when there are no expected answers for the day
+    pub matches: Option<bool>,
+}
+
+impl DaySummary {
+    /// Check the answers against any expected for the day
+    pub fn check(&mut self, expected: &BTreeMap<(u8, u8), String>) {
+        let checks: Vec<bool> = (1..=2)
+            .filter_map(|part| {
+                let wanted = expected.get(&(self.day, part))?;
+                Some(self.answers[part as usize - 1].as_ref() == Some(wanted))
+            })
+            .collect();
+        self.matches = (!checks.is_empty()).then(|| checks.iter().all(|ok| *ok));
+    }
+
+    fn cells(&self, number_format: NumberFormat) -> Vec<String> {
+        let answer = |part: usize| {
+            self.answers[part]
+                .as_ref()
+                .map_or("-".to_string(), |answer| number_format.format_text(answer))
+        };
+        vec![
+            self.day.to_string(),
+            self.title.clone(),
+            answer(0),
+            answer(1),
+            format!("{:.3}s", self.took.as_secs_f32()),
+            self.peak_memory_kib.map_or("-".to_string(), |kib| {
+                format!("{:.1} MiB", kib as f32 / 1024.0)
+            }),
+            match self.matches {
+                Some(true) => "yes",
+                Some(false) => "NO",
+                None => "-",
+            }
+            .to_string(),
+        ]
+    }
+}
+
+const HEADINGS: [&str; 7] = [
+    "Day",
+    "Title",
+    "Part 1",
+    "Part 2",
+    "Took",
+    "Peak memory",
+    "Expected",
+];
+
+/// The summary as a plain text table with aligned columns
+pub fn render_text(summaries: &[DaySummary], number_format: NumberFormat) -> String {
+    let rows: Vec<Vec<String>> = std::iter::once(HEADINGS.map(String::from).to_vec())
+        .chain(summaries.iter().map(|summary| summary.cells(number_format)))
+        .collect();
+    let widths: Vec<usize> = (0..HEADINGS.len())
+        .map(|column| {
+            rows.iter()
+                .map(|row| row[column].chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    rows.iter()
+        .map(|row| {
+            let line = row
+                .iter()
+                .zip(widths.iter())
+                .map(|(cell, width)| format!("{cell:width$}"))
+                .collect::<Vec<_>>()
+                .join("  ");
+            format!("{}\n", line.trim_end())
+        })
+        .collect()
+}
+
+/// The summary as a Markdown table
+pub fn render_markdown(summaries: &[DaySummary], number_format: NumberFormat) -> String {
+    let row = |cells: Vec<String>| {
+        let cells: Vec<String> = cells.iter().map(|cell| cell.replace('|', "\\|")).collect();
+        format!("| {} |\n", cells.join(" | "))
+    };
+    let mut markdown = row(HEADINGS.map(String::from).to_vec());
+    markdown.push_str(&row(HEADINGS.map(|_| "---".to_string()).to_vec()));
+    summaries
+        .iter()
+        .for_each(|summary| markdown.push_str(&row(summary.cells(number_format))));
+    markdown
+}
+
+/// How long a phase of a part's pipeline took, e.g. parsing
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PhaseTiming {
+    pub phase: String,
+    pub secs: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PartReport {
+    pub part: u8,
+    pub answer: String,
+    /// None when there is no expected answer for the part
+    pub matches: Option<bool>,
+    pub took_secs: f64,
+    pub peak_memory_kib: Option<u64>,
+    pub cached: bool,
+    /// Empty for cached results
+    pub phases: Vec<PhaseTiming>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DayReport {
+    pub day: u8,
+    pub title: String,
+    pub parts: Vec<PartReport>,
+}
+
+/// Everything from one run of the suite, written as JSON so that runs over several revisions can be
+/// collected in a results directory and compared
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RunReport {
+    pub revision: String,
+    /// Seconds since the Unix epoch
+    pub timestamp: u64,
+    pub days: Vec<DayReport>,
+}
+
+pub fn render_json(report: &RunReport) -> Result<String, AError> {
+    Ok(serde_json::to_string_pretty(report)?)
+}
+
+/// A wrong answer set against the expected one, followed by the diagnostics the day recorded while
+/// working it out
+pub fn explain_mismatch(
+    day: u8,
+    part: u8,
+    expected: &str,
+    actual: &str,
+    diagnostics: &[(String, String)],
+) -> String {
+    let mut lines = vec![
+        format!("Day {day} part {part} is wrong"),
+        format!("  expected: {expected}"),
+        format!("  actual:   {actual}"),
+    ];
+    if diagnostics.is_empty() {
+        lines.push("  no diagnostics recorded".to_string());
+    }
+    lines.extend(
+        diagnostics
+            .iter()
+            .map(|(name, value)| format!("  {name}: {value}")),
+    );
+    lines.join("\n") + "\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mismatch_explained() {
+        assert_eq!(
+            explain_mismatch(
+                19,
+                1,
+                "19114",
+                "19115",
+                &[("accepted parts".to_string(), "3".to_string())]
+            ),
+            "Day 19 part 1 is wrong\n  expected: 19114\n  actual:   19115\n  accepted parts: 3\n"
+        );
+        assert!(explain_mismatch(1, 2, "281", "280", &[]).ends_with("  no diagnostics recorded\n"));
+    }
+
+    #[test]
+    fn summaries_checked_and_rendered() {
+        let expected =
+            parse_expected("# day part answer\n1\t1\t142\n1\t2\t281\n\n2\t1\t8\n").unwrap();
+        assert!(parse_expected("1\t1").is_err());
+
+        let mut day1 = DaySummary {
+            day: 1,
+            title: "Trebuchet?!".to_string(),
+            answers: [Some("142".to_string()), Some("281".to_string())],
+            took: Duration::from_millis(12),
+            peak_memory_kib: Some(2048),
+            matches: None,
+        };
+        day1.check(&expected);
+        assert_eq!(day1.matches, Some(true));
+        let mut day2 = DaySummary {
+            day: 2,
+            title: "Cube | Conundrum".to_string(),
+            answers: [Some("9".to_string()), None],
+            took: Duration::from_secs(1),
+            ..Default::default()
+        };
+        day2.check(&expected);
+        assert_eq!(day2.matches, Some(false));
+        let mut day3 = DaySummary {
+            day: 3,
+            ..Default::default()
+        };
+        day3.check(&expected);
+        assert_eq!(day3.matches, None);
+
+        assert_eq!(
+            render_text(&[day1.clone(), day2.clone()], NumberFormat::Plain),
+            "\
+Day  Title             Part 1  Part 2  Took    Peak memory  Expected
+1    Trebuchet?!       142     281     0.012s  2.0 MiB      yes
+2    Cube | Conundrum  9       -       1.000s  -            NO
+"
+        );
+        assert_eq!(
+            render_markdown(&[day1.clone(), day2], NumberFormat::Plain),
+            "\
+| Day | Title | Part 1 | Part 2 | Took | Peak memory | Expected |
+| --- | --- | --- | --- | --- | --- | --- |
+| 1 | Trebuchet?! | 142 | 281 | 0.012s | 2.0 MiB | yes |
+| 2 | Cube \\| Conundrum | 9 | - | 1.000s | - | NO |
+"
+        );
+        let big = DaySummary {
+            answers: [
+                Some("920630818300104".to_string()),
+                Some("bgpsvlq".to_string()),
+            ],
+            ..day1
+        };
+        assert_eq!(
+            render_text(std::slice::from_ref(&big), NumberFormat::Thousands)
+                .lines()
+                .nth(1),
+            Some("1    Trebuchet?!  920,630,818,300,104  bgpsvlq  0.012s  2.0 MiB      yes")
+        );
+        assert!(
+            render_markdown(&[big], NumberFormat::Scientific).contains("| 9.206e14 | bgpsvlq |")
+        );
+    }
+
+    #[test]
+    fn run_report_as_json() {
+        let report = RunReport {
+            revision: "abc1234".to_string(),
+            timestamp: 1702000000,
+            days: vec![DayReport {
+                day: 1,
+                title: "Trebuchet?!".to_string(),
+                parts: vec![PartReport {
+                    part: 1,
+                    answer: "142".to_string(),
+                    matches: Some(true),
+                    took_secs: 0.5,
+                    peak_memory_kib: None,
+                    cached: false,
+                    phases: vec![PhaseTiming {
+                        phase: "parse".to_string(),
+                        secs: 0.25,
+                    }],
+                }],
+            }],
+        };
+        let json: serde_json::Value = serde_json::from_str(&render_json(&report).unwrap()).unwrap();
+        assert_eq!(json["revision"], "abc1234");
+        let part = &json["days"][0]["parts"][0];
+        assert_eq!(part["answer"], "142");
+        assert_eq!(part["peak_memory_kib"], serde_json::Value::Null);
+        assert_eq!(part["phases"][0]["phase"], "parse");
+        assert_eq!(part["phases"][0]["secs"], 0.25);
+    }
+}
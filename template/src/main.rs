@@ -1,56 +1,45 @@
-use processor::process;
+use processor::{run_day, Answer, Day, PuzzleMetadata, Solver};
 
 type AError = anyhow::Error;
-type InitialState = Vec<String>;
-type LoadedState = InitialState;
-type ProcessedState = LoadedState;
-type FinalResult = ProcessedState;
 
-fn main() {
-    let file = "test-input.txt";
-    //let file = "test-input2.txt";
-    //let file = "input.txt";
-
-    let result1 = process(
-        file,
-        Vec::new(),
-        parse_line,
-        finalise_state,
-        perform_processing,
-        calc_result,
-    );
-    match result1 {
-        Ok(res) => println!("Result 1: {:?}", res),
-        Err(e) => println!("Error on 1: {}", e),
-    }
+struct DayN;
 
-    let result2 = process(
-        file,
-        Vec::new(),
-        parse_line,
-        finalise_state,
-        perform_processing,
-        calc_result,
-    );
-    match result2 {
-        Ok(res) => println!("Result 2: {:?}", res),
-        Err(e) => println!("Error on 2: {}", e),
+impl Solver for DayN {
+    fn metadata() -> PuzzleMetadata {
+        PuzzleMetadata::new(0, "Template")
     }
 }
 
-fn parse_line(mut state: InitialState, line: String) -> Result<InitialState, AError> {
-    state.push(line);
-    Ok(state)
-}
+impl Day for DayN {
+    type Parsed = Vec<String>;
 
-fn finalise_state(state: InitialState) -> Result<LoadedState, AError> {
-    Ok(state)
+    fn parse(lines: impl Iterator<Item = String>) -> Result<Self::Parsed, AError> {
+        Ok(lines.collect())
+    }
+
+    fn part1(lines: &Self::Parsed) -> Result<Answer, AError> {
+        Ok(lines.len().into())
+    }
+
+    fn part2(lines: &Self::Parsed) -> Result<Answer, AError> {
+        Ok(lines.len().into())
+    }
 }
 
-fn perform_processing(state: LoadedState) -> Result<ProcessedState, AError> {
-    Ok(state)
+fn main() {
+    // e.g. file=input.txt on the command line or AOC_FILE=input.txt in the environment to run the real input
+    run_day::<DayN>("test-input.txt");
 }
 
-fn calc_result(state: ProcessedState) -> Result<FinalResult, AError> {
-    Ok(state)
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use processor::assert_day;
+
+    // The puzzle's examples and their answers, which fail until the example is copied in to test-input.txt
+    #[test]
+    fn examples() {
+        assert_day!(DayN, part1, "test-input.txt", 1);
+        assert_day!(DayN, part2, "test-input.txt", 1);
+    }
 }
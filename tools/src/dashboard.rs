@@ -0,0 +1,244 @@
+//! A static HTML performance dashboard built from the JSON run reports written by `aoc-runner --json`.
+//! Each report in the results directory is one run of the suite at some revision.
+
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+use serde::Deserialize;
+
+use crate::AError;
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct PhaseTiming {
+    pub phase: String,
+    pub secs: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct PartReport {
+    pub part: u8,
+    pub answer: String,
+    pub matches: Option<bool>,
+    pub took_secs: f64,
+    pub peak_memory_kib: Option<u64>,
+    pub cached: bool,
+    #[serde(default)]
+    pub phases: Vec<PhaseTiming>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct DayReport {
+    pub day: u8,
+    pub title: String,
+    pub parts: Vec<PartReport>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct RunReport {
+    pub revision: String,
+    pub timestamp: u64,
+    pub days: Vec<DayReport>,
+}
+
+const STYLE: &str = "body { font-family: sans-serif; margin: 2em; }
+table { border-collapse: collapse; margin-bottom: 2em; }
+th, td { border: 1px solid #ccc; padding: 0.3em 0.6em; text-align: left; }
+td.number { text-align: right; }
+.bar { display: flex; width: 30em; height: 1.2em; }
+.bar span { display: block; height: 100%; overflow: hidden; font-size: 0.7em; color: white; }
+.mismatch { color: #c00; }
+";
+
+/// Colours the phases are shown in, cycled through in the order the phases ran
+const PHASE_COLOURS: [&str; 5] = ["#4e79a7", "#f28e2b", "#59a14f", "#e15759", "#76b7b2"];
+
+/// All the run reports in the directory, oldest first
+pub fn load_reports(dir: &Path) -> Result<Vec<RunReport>, AError> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir)
+        .with_context(|| format!("Failed reading {}", dir.display()))?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<_, _>>()?;
+    paths.retain(|path| {
+        path.extension()
+            .is_some_and(|extension| extension == "json")
+    });
+    let mut reports = paths
+        .iter()
+        .map(|path| {
+            let json = fs::read_to_string(path)
+                .with_context(|| format!("Failed reading {}", path.display()))?;
+            serde_json::from_str::<RunReport>(&json)
+                .with_context(|| format!("Failed parsing {}", path.display()))
+        })
+        .collect::<Result<Vec<_>, AError>>()?;
+    reports.sort_by_key(|report| report.timestamp);
+    Ok(reports)
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// The phases as a bar of proportional widths, like one level of a flamegraph
+fn phase_bar(phases: &[PhaseTiming]) -> String {
+    let total: f64 = phases.iter().map(|timing| timing.secs).sum();
+    if total <= 0.0 {
+        return String::new();
+    }
+    let spans = phases
+        .iter()
+        .zip(PHASE_COLOURS.iter().cycle())
+        .map(|(timing, colour)| {
+            format!(
+                "<span style=\"width: {:.2}%; background: {colour}\" title=\"{} {:.6}s\">{}</span>",
+                timing.secs / total * 100.0,
+                escape(&timing.phase),
+                timing.secs,
+                escape(&timing.phase)
+            )
+        })
+        .collect::<String>();
+    format!("<div class=\"bar\">{spans}</div>")
+}
+
+fn latest_table(html: &mut String, report: &RunReport) -> Result<(), AError> {
+    writeln!(
+        html,
+        "<h2>Latest run: {}</h2>\n<table>\n<tr><th>Day</th><th>Title</th><th>Part</th><th>Answer</th><th>Took (s)</th><th>Peak memory (KiB)</th><th>Phases</th></tr>",
+        escape(&report.revision)
+    )?;
+    for day in &report.days {
+        for part in &day.parts {
+            let answer_class = if part.matches == Some(false) {
+                " class=\"mismatch\""
+            } else {
+                ""
+            };
+            writeln!(
+                html,
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td{answer_class}>{}</td><td class=\"number\">{:.3}{}</td><td class=\"number\">{}</td><td>{}</td></tr>",
+                day.day,
+                escape(&day.title),
+                part.part,
+                escape(&part.answer),
+                part.took_secs,
+                if part.cached { " (cached)" } else { "" },
+                part.peak_memory_kib
+                    .map_or(String::new(), |kib| kib.to_string()),
+                phase_bar(&part.phases)
+            )?;
+        }
+    }
+    writeln!(html, "</table>")?;
+    Ok(())
+}
+
+/// How long each day and part took at each revision, cached results left out as their times are stale
+fn history_table(html: &mut String, reports: &[RunReport]) -> Result<(), AError> {
+    let mut took: BTreeMap<(u8, u8), Vec<Option<f64>>> = BTreeMap::new();
+    for (index, report) in reports.iter().enumerate() {
+        for day in &report.days {
+            for part in day.parts.iter().filter(|part| !part.cached) {
+                took.entry((day.day, part.part))
+                    .or_insert_with(|| vec![None; reports.len()])[index] = Some(part.took_secs);
+            }
+        }
+    }
+    write!(
+        html,
+        "<h2>History</h2>\n<table>\n<tr><th>Day</th><th>Part</th>"
+    )?;
+    for report in reports {
+        write!(html, "<th>{}</th>", escape(&report.revision))?;
+    }
+    writeln!(html, "</tr>")?;
+    for ((day, part), times) in took {
+        write!(html, "<tr><td>{day}</td><td>{part}</td>")?;
+        for time in times {
+            match time {
+                Some(secs) => write!(html, "<td class=\"number\">{secs:.3}</td>")?,
+                None => write!(html, "<td></td>")?,
+            }
+        }
+        writeln!(html, "</tr>")?;
+    }
+    writeln!(html, "</table>")?;
+    Ok(())
+}
+
+pub fn render_dashboard(reports: &[RunReport]) -> Result<String, AError> {
+    let mut html = String::new();
+    writeln!(
+        html,
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Advent of Code 2023 performance</title>\n<style>\n{STYLE}</style>\n</head>\n<body>\n<h1>Advent of Code 2023 performance</h1>"
+    )?;
+    match reports.last() {
+        Some(latest) => {
+            latest_table(&mut html, latest)?;
+            history_table(&mut html, reports)?;
+        }
+        None => writeln!(html, "<p>No results yet</p>")?,
+    }
+    writeln!(html, "</body>\n</html>")?;
+    Ok(html)
+}
+
+/// Render the dashboard for the reports in the results directory to the output file
+pub fn dashboard(results_dir: &Path, output: &Path) -> Result<(), AError> {
+    let reports = load_reports(results_dir)?;
+    fs::write(output, render_dashboard(&reports)?)
+        .with_context(|| format!("Failed writing {}", output.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dashboard_rendered_from_results() {
+        let dir = std::env::temp_dir().join(format!("aoc-dashboard-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("b.json"),
+            r#"{"revision": "bbb", "timestamp": 20, "days": [{"day": 17, "title": "Clumsy <Crucible>", "parts": [
+                {"part": 1, "answer": "102", "matches": false, "took_secs": 1.5, "peak_memory_kib": 2048, "cached": false,
+                 "phases": [{"phase": "parse", "secs": 0.5}, {"phase": "process", "secs": 1.5}]}]}]}"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.join("a.json"),
+            r#"{"revision": "aaa", "timestamp": 10, "days": [{"day": 17, "title": "Clumsy Crucible", "parts": [
+                {"part": 1, "answer": "102", "matches": true, "took_secs": 4.25, "peak_memory_kib": null, "cached": false}]}]}"#,
+        )
+        .unwrap();
+        fs::write(dir.join("notes.txt"), "not a report").unwrap();
+
+        let reports = load_reports(&dir).unwrap();
+        assert_eq!(
+            reports
+                .iter()
+                .map(|report| report.revision.as_str())
+                .collect::<Vec<_>>(),
+            vec!["aaa", "bbb"]
+        );
+        assert!(reports[0].days[0].parts[0].phases.is_empty());
+
+        let html = render_dashboard(&reports).unwrap();
+        assert!(html.contains("<h2>Latest run: bbb</h2>"));
+        assert!(html.contains("Clumsy &lt;Crucible&gt;"));
+        assert!(html.contains("<td class=\"mismatch\">102</td>"));
+        assert!(html.contains("width: 25.00%; background: #4e79a7\" title=\"parse 0.500000s\""));
+        assert!(html.contains("<tr><td>17</td><td>1</td><td class=\"number\">4.250</td><td class=\"number\">1.500</td></tr>"));
+        assert!(render_dashboard(&[]).unwrap().contains("No results yet"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
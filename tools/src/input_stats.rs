@@ -0,0 +1,164 @@
+//! A first look at a puzzle input: its size and shape, which chars it uses and the ranges of the numbers
+//! in it, numbers being any tokens (see [`read_token`]) that parse as integers.
+
+use std::{
+    collections::{BTreeMap, HashSet},
+    fmt::Display,
+};
+
+use processor::{read_token, Token};
+
+use crate::AError;
+
+/// Numbers are split out of lines by whitespace and the punctuation that usually separates them
+const DELIMITERS: [char; 8] = [' ', '\t', ',', ':', ';', '=', '@', '|'];
+
+/// The numbers found at one position along the lines
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NumberRange {
+    pub count: usize,
+    pub min: i64,
+    pub max: i64,
+}
+
+impl NumberRange {
+    fn include(range: Option<NumberRange>, number: i64) -> NumberRange {
+        match range {
+            None => NumberRange {
+                count: 1,
+                min: number,
+                max: number,
+            },
+            Some(range) => NumberRange {
+                count: range.count + 1,
+                min: range.min.min(number),
+                max: range.max.max(number),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InputStats {
+    pub lines: usize,
+    pub max_line_length: usize,
+    /// Runs of non-blank lines separated by blank ones
+    pub sections: usize,
+    pub chars: BTreeMap<char, usize>,
+    /// Ranges of the first number on each line, the second, and so on
+    pub numbers: Vec<NumberRange>,
+    /// Ranges of all of the numbers, wherever they are
+    pub all_numbers: Option<NumberRange>,
+}
+
+/// Numbers in the text, looking inside any bracketed groups and quoted strings
+fn numbers(text: &str, delimiters: &HashSet<char>) -> Result<Vec<i64>, AError> {
+    let mut chars = text.chars();
+    let mut found = vec![];
+    while let Some((token, _)) = read_token(&mut chars, delimiters)? {
+        match token {
+            Token::Word(word) => found.extend(word.parse::<i64>().ok()),
+            Token::Group { contents, .. } | Token::Quoted(contents) => {
+                found.extend(numbers(&contents, delimiters)?)
+            }
+        }
+    }
+    Ok(found)
+}
+
+pub fn input_stats(contents: &str) -> Result<InputStats, AError> {
+    let delimiters: HashSet<char> = DELIMITERS.into_iter().collect();
+    let mut stats = InputStats::default();
+    let mut in_section = false;
+    for line in contents.lines() {
+        stats.lines += 1;
+        stats.max_line_length = stats.max_line_length.max(line.chars().count());
+        let blank = line.trim().is_empty();
+        if !blank && !in_section {
+            stats.sections += 1;
+        }
+        in_section = !blank;
+        line.chars()
+            .for_each(|c| *stats.chars.entry(c).or_default() += 1);
+        for (position, number) in numbers(line, &delimiters)?.into_iter().enumerate() {
+            if position == stats.numbers.len() {
+                stats.numbers.push(NumberRange::include(None, number));
+            } else {
+                stats.numbers[position] =
+                    NumberRange::include(Some(stats.numbers[position]), number);
+            }
+            stats.all_numbers = Some(NumberRange::include(stats.all_numbers, number));
+        }
+    }
+    Ok(stats)
+}
+
+impl Display for InputStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Lines: {} (longest {} chars) in {} section(s)",
+            self.lines, self.max_line_length, self.sections
+        )?;
+        let mut chars: Vec<(&char, &usize)> = self.chars.iter().collect();
+        chars.sort_by(|(c1, count1), (c2, count2)| count2.cmp(count1).then(c1.cmp(c2)));
+        let chars: Vec<String> = chars
+            .iter()
+            .map(|(c, count)| format!("{c:?} {count}"))
+            .collect();
+        writeln!(f, "Chars: {}", chars.join(", "))?;
+        match self.all_numbers {
+            None => writeln!(f, "Numbers: none")?,
+            Some(all) => {
+                writeln!(f, "Numbers: {} from {} to {}", all.count, all.min, all.max)?;
+                for (position, range) in self.numbers.iter().enumerate() {
+                    writeln!(
+                        f,
+                        "  #{}: {} from {} to {}",
+                        position + 1,
+                        range.count,
+                        range.min,
+                        range.max
+                    )?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stats_gathered() {
+        let stats = input_stats(
+            "px{a<2006:qkq,m>2090:A}\n\n{x=787,m=2655}\n{x=1679,m=44}\n\n19, 13 @ -2, 1\n",
+        )
+        .unwrap();
+        assert_eq!(stats.lines, 6);
+        assert_eq!(stats.max_line_length, 23);
+        assert_eq!(stats.sections, 3);
+        assert_eq!(stats.chars[&'{'], 3);
+        assert_eq!(
+            stats.numbers[1],
+            NumberRange {
+                count: 3,
+                min: 13,
+                max: 2655
+            }
+        );
+        assert_eq!(
+            stats.all_numbers,
+            Some(NumberRange {
+                count: 8,
+                min: -2,
+                max: 2655
+            })
+        );
+        let text = stats.to_string();
+        assert!(text.starts_with("Lines: 6 (longest 23 chars) in 3 section(s)\nChars: "));
+        assert!(text.contains("\nNumbers: 8 from -2 to 2655\n  #1: 3 from 19 to 1679\n"));
+    }
+}
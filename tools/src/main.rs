@@ -0,0 +1,206 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+
+mod dashboard;
+mod input_stats;
+mod progress;
+
+type AError = anyhow::Error;
+
+const TEMPLATE_DIR: &str = "template";
+const TEMPLATE_SOLVER: &str = "DayN";
+const TEMPLATE_METADATA: &str = "PuzzleMetadata::new(0, \"Template\")";
+/// Created empty in the new day, ready for the puzzle's examples and input to be pasted in
+const INPUT_FILES: [&str; 3] = ["test-input.txt", "test-input2.txt", "input.txt"];
+
+/// The workspace holding the template and dayN directories
+fn workspace_dir() -> PathBuf {
+    let tools_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    tools_dir.parent().unwrap_or(tools_dir).to_path_buf()
+}
+
+fn render_cargo_toml(template: &str, day: u8) -> Result<String, AError> {
+    if !template.contains("name = \"template\"") {
+        return Err(AError::msg(
+            "Template Cargo.toml has no 'name = \"template\"'",
+        ));
+    }
+    Ok(template.replacen("name = \"template\"", &format!("name = \"day{day}\""), 1))
+}
+
+fn render_main(template: &str, day: u8, title: &str) -> Result<String, AError> {
+    if !template.contains(TEMPLATE_METADATA) {
+        return Err(AError::msg(format!(
+            "Template main.rs has no '{TEMPLATE_METADATA}'"
+        )));
+    }
+    let metadata = format!("PuzzleMetadata::new({day}, {title:?})");
+    Ok(template
+        .replace(TEMPLATE_METADATA, &metadata)
+        .replace(TEMPLATE_SOLVER, &format!("Day{day}")))
+}
+
+/// Add the member to the end of the workspace's members list
+fn add_workspace_member(manifest: &str, member: &str) -> Result<String, AError> {
+    let members_start = manifest
+        .find("members = [")
+        .ok_or_else(|| AError::msg("No workspace members in Cargo.toml"))?;
+    let members_end = members_start
+        + manifest[members_start..]
+            .find(']')
+            .ok_or_else(|| AError::msg("Unterminated workspace members in Cargo.toml"))?;
+    let members = &manifest[members_start..members_end];
+    if members.contains(&format!("\"{member}\"")) {
+        return Err(AError::msg(format!(
+            "'{member}' is already a workspace member"
+        )));
+    }
+    let insert_at = members_start + members.trim_end().len();
+    let separator = if members.trim_end().ends_with(',') {
+        ""
+    } else {
+        ","
+    };
+    Ok(format!(
+        "{}{separator}\n    \"{member}\",{}",
+        &manifest[..insert_at],
+        &manifest[insert_at..]
+    ))
+}
+
+/// Create dayN from the template and add it to the workspace, returning the new directory
+fn new_day(workspace: &Path, day: u8, title: &str) -> Result<PathBuf, AError> {
+    let name = format!("day{day}");
+    let day_dir = workspace.join(&name);
+    if day_dir.exists() {
+        return Err(AError::msg(format!("{} already exists", day_dir.display())));
+    }
+    let template_dir = workspace.join(TEMPLATE_DIR);
+    let read = |path: PathBuf| {
+        fs::read_to_string(&path).with_context(|| format!("Failed reading {}", path.display()))
+    };
+    let cargo_toml = render_cargo_toml(&read(template_dir.join("Cargo.toml"))?, day)?;
+    let main = render_main(&read(template_dir.join("src").join("main.rs"))?, day, title)?;
+    let workspace_manifest = workspace.join("Cargo.toml");
+    let manifest = add_workspace_member(&read(workspace_manifest.clone())?, &name)?;
+
+    fs::create_dir_all(day_dir.join("src"))?;
+    fs::write(day_dir.join("Cargo.toml"), cargo_toml)?;
+    fs::write(day_dir.join("src").join("main.rs"), main)?;
+    for input in INPUT_FILES {
+        fs::write(day_dir.join(input), "")?;
+    }
+    fs::write(workspace_manifest, manifest)?;
+    Ok(day_dir)
+}
+
+fn usage() {
+    println!("Usage: tools <command>");
+    println!("  new-day <day> [title]               create dayN from the template and add it to the workspace");
+    println!("  dashboard <results-dir> <out.html>  render the runner's JSON reports as an HTML dashboard");
+    println!(
+        "  stats <file>                        summarise a puzzle input's lines, chars and numbers"
+    );
+    println!(
+        "  progress                            show which days and parts are solved and verified"
+    );
+}
+
+fn run(args: &[String]) -> Result<(), AError> {
+    match args {
+        [command, day, title @ ..] if command == "new-day" => {
+            let day = day
+                .parse::<u8>()
+                .with_context(|| format!("Invalid day: '{day}'"))?;
+            let title = if title.is_empty() {
+                format!("Day {day}")
+            } else {
+                title.join(" ")
+            };
+            let day_dir = new_day(&workspace_dir(), day, &title)?;
+            println!("Created {}", day_dir.display());
+            Ok(())
+        }
+        [command, results_dir, output] if command == "dashboard" => {
+            dashboard::dashboard(Path::new(results_dir), Path::new(output))?;
+            println!("Wrote {output}");
+            Ok(())
+        }
+        [command, file] if command == "stats" => {
+            let contents =
+                fs::read_to_string(file).with_context(|| format!("Failed reading {file}"))?;
+            print!("{}", input_stats::input_stats(&contents)?);
+            Ok(())
+        }
+        [command] if command == "progress" => {
+            print!(
+                "{}",
+                progress::Progress::of_workspace(&workspace_dir())?.render()
+            );
+            Ok(())
+        }
+        _ => {
+            usage();
+            Err(AError::msg("Unrecognised command"))
+        }
+    }
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Err(e) = run(&args) {
+        println!("Error: {e}");
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scaffold_new_day() {
+        let workspace = std::env::temp_dir().join(format!("aoc-tools-test-{}", std::process::id()));
+        fs::create_dir_all(workspace.join("template").join("src")).unwrap();
+        fs::write(
+            workspace.join("Cargo.toml"),
+            "[workspace]\n\nmembers = [\n    \"template\",\n    \"day1\",\n]\n",
+        )
+        .unwrap();
+        fs::write(
+            workspace.join("template").join("Cargo.toml"),
+            "[package]\nname = \"template\"\n",
+        )
+        .unwrap();
+        fs::write(
+            workspace.join("template").join("src").join("main.rs"),
+            "struct DayN;\nfn metadata() -> PuzzleMetadata {\n    PuzzleMetadata::new(0, \"Template\")\n}\nfn main() {\n    describe_if_requested::<DayN>();\n}\n",
+        )
+        .unwrap();
+
+        let day_dir = new_day(&workspace, 26, "Snow \"Day\"").unwrap();
+        let main = fs::read_to_string(day_dir.join("src").join("main.rs")).unwrap();
+        assert!(main.contains("struct Day26;"));
+        assert!(main.contains("describe_if_requested::<Day26>();"));
+        assert!(main.contains("PuzzleMetadata::new(26, \"Snow \\\"Day\\\"\")"));
+        assert_eq!(
+            fs::read_to_string(day_dir.join("Cargo.toml")).unwrap(),
+            "[package]\nname = \"day26\"\n"
+        );
+        assert_eq!(
+            fs::read_to_string(day_dir.join("test-input.txt")).unwrap(),
+            ""
+        );
+        assert_eq!(
+            fs::read_to_string(workspace.join("Cargo.toml")).unwrap(),
+            "[workspace]\n\nmembers = [\n    \"template\",\n    \"day1\",\n    \"day26\",\n]\n"
+        );
+        assert!(new_day(&workspace, 26, "Again").is_err());
+
+        fs::remove_dir_all(&workspace).unwrap();
+    }
+}
@@ -0,0 +1,185 @@
+//! A calendar of how far through the puzzles the workspace is: which days have crates, which parts have
+//! expected answers recorded in `expected-results.txt` and which of those the runner has verified
+//! (`aoc-runner --verify`, which keeps them in `progress.json`).
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Write,
+    fs,
+    path::Path,
+};
+
+use anyhow::Context;
+use serde::Deserialize;
+
+use crate::AError;
+
+const EXPECTED_FILE: &str = "expected-results.txt";
+const PROGRESS_FILE: &str = "progress.json";
+const DAYS: u8 = 25;
+
+/// As written by the runner
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+struct ProgressState {
+    verified: BTreeMap<u8, BTreeSet<u8>>,
+}
+
+/// Where a part of a day has got to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PartProgress {
+    NoCrate,
+    Unsolved,
+    /// The answer's recorded but the runner hasn't checked the day still gives it
+    Recorded,
+    Verified,
+}
+
+impl PartProgress {
+    fn symbol(&self) -> char {
+        match self {
+            PartProgress::NoCrate => ' ',
+            PartProgress::Unsolved => '.',
+            PartProgress::Recorded => '+',
+            PartProgress::Verified => '*',
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Progress {
+    crates: BTreeSet<u8>,
+    /// (day, part) with an expected answer
+    recorded: BTreeSet<(u8, u8)>,
+    verified: BTreeMap<u8, BTreeSet<u8>>,
+}
+
+impl Progress {
+    /// Inspect the workspace's day crates, expected answers and any progress recorded by the runner
+    pub fn of_workspace(workspace: &Path) -> Result<Self, AError> {
+        let crates = (1..=DAYS)
+            .filter(|day| {
+                workspace
+                    .join(format!("day{day}"))
+                    .join("Cargo.toml")
+                    .is_file()
+            })
+            .collect();
+        let read = |name: &str| {
+            let path = workspace.join(name);
+            match path.exists() {
+                true => fs::read_to_string(&path)
+                    .map(Some)
+                    .with_context(|| format!("Failed reading {}", path.display())),
+                false => Ok(None),
+            }
+        };
+        let recorded = match read(EXPECTED_FILE)? {
+            Some(contents) => parse_recorded(&contents)?,
+            None => BTreeSet::default(),
+        };
+        let verified = match read(PROGRESS_FILE)? {
+            Some(json) => {
+                serde_json::from_str::<ProgressState>(&json)
+                    .with_context(|| format!("Failed parsing {PROGRESS_FILE}"))?
+                    .verified
+            }
+            None => BTreeMap::default(),
+        };
+        Ok(Progress {
+            crates,
+            recorded,
+            verified,
+        })
+    }
+
+    fn part(&self, day: u8, part: u8) -> PartProgress {
+        if !self.crates.contains(&day) {
+            PartProgress::NoCrate
+        } else if !self.recorded.contains(&(day, part)) {
+            PartProgress::Unsolved
+        } else if self
+            .verified
+            .get(&day)
+            .is_some_and(|parts| parts.contains(&part))
+        {
+            PartProgress::Verified
+        } else {
+            PartProgress::Recorded
+        }
+    }
+
+    /// A grid of the days 1 to 25 across and the parts down, with a key and totals underneath
+    pub fn render(&self) -> String {
+        let mut grid = String::from("      ");
+        (1..=DAYS).for_each(|day| write!(grid, "{day:>3}").unwrap());
+        grid.push('\n');
+        for part in 1..=2 {
+            write!(grid, "Part {part}").unwrap();
+            (1..=DAYS)
+                .for_each(|day| write!(grid, "{:>3}", self.part(day, part).symbol()).unwrap());
+            grid.push('\n');
+        }
+        let count = |progress: PartProgress| {
+            (1..=DAYS)
+                .flat_map(|day| [(day, 1), (day, 2)])
+                .filter(|(day, part)| self.part(*day, *part) == progress)
+                .count()
+        };
+        let verified = count(PartProgress::Verified);
+        let recorded = verified + count(PartProgress::Recorded);
+        writeln!(grid, "* verified  + recorded, not verified  . not solved").unwrap();
+        writeln!(
+            grid,
+            "{} of {DAYS} days started, {recorded} of {} parts solved, {verified} verified",
+            self.crates.len(),
+            DAYS * 2
+        )
+        .unwrap();
+        grid
+    }
+}
+
+/// The (day, part)s with answers in the tab separated `day  part  answer` lines, skipping blanks and comments
+fn parse_recorded(contents: &str) -> Result<BTreeSet<(u8, u8)>, AError> {
+    contents
+        .lines()
+        .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let fields: Vec<&str> = line.split('\t').collect();
+            let [day, part, _answer] = fields[..] else {
+                return Err(AError::msg(format!("Malformed expected line: '{line}'")));
+            };
+            Ok((day.parse()?, part.parse()?))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn progress_rendered() {
+        let progress = Progress {
+            crates: BTreeSet::from([1, 2, 3]),
+            recorded: parse_recorded("# day part answer\n1\t1\t10\n1\t2\t20\n2\t1\t5\n\n").unwrap(),
+            verified: serde_json::from_str::<ProgressState>(r#"{"verified": {"1": [1, 2]}}"#)
+                .unwrap()
+                .verified,
+        };
+        assert_eq!(progress.part(1, 2), PartProgress::Verified);
+        assert_eq!(progress.part(2, 1), PartProgress::Recorded);
+        assert_eq!(progress.part(3, 1), PartProgress::Unsolved);
+        assert_eq!(progress.part(4, 1), PartProgress::NoCrate);
+        let rendered = progress.render();
+        let lines: Vec<&str> = rendered.lines().collect();
+        assert!(lines[0].starts_with("        1  2  3  4"));
+        assert!(lines[1].starts_with("Part 1  *  +  .   "));
+        assert!(lines[2].starts_with("Part 2  *  .  .   "));
+        assert_eq!(
+            lines[4],
+            "3 of 25 days started, 3 of 50 parts solved, 2 verified"
+        );
+        assert!(parse_recorded("1\t1").is_err());
+    }
+}